@@ -22,11 +22,15 @@
 
 use crate::ast::*;
 use crate::entities::Entities;
-use crate::evaluator::{EvaluationError, Evaluator};
+use crate::evaluator::{
+    EvaluationError, EvaluationErrorKind, EvaluationLimits, EvaluationMetrics, Evaluator,
+    TraceEvent,
+};
 use crate::extensions::Extensions;
 use itertools::Either;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use smol_str::SmolStr;
+use std::collections::{HashMap, HashSet};
 use std::iter::once;
 
 #[cfg(feature = "wasm")]
@@ -97,7 +101,102 @@ impl Authorizer {
     /// The language spec and formal model give a precise definition of how this is
     /// computed.
     pub fn is_authorized(&self, q: Request, pset: &PolicySet, entities: &Entities) -> Response {
-        match self.is_authorized_core(q, pset, entities) {
+        self.concretize(self.is_authorized_core(q, pset, entities), pset)
+    }
+
+    /// Returns an authorization response for `q` as [`Authorizer::is_authorized`]
+    /// does, unless evaluation exceeds `limits`. In that case evaluation is
+    /// aborted, and the error is returned along with the policy being
+    /// evaluated when it occurred.
+    // `AuthorizationError` is as large as the `EvaluationError` it wraps.
+    // Boxing it would save a copy only on the rare path where a limit is
+    // exceeded, at the cost of an API unlike the rest of the authorizer's.
+    #[allow(clippy::result_large_err)]
+    pub fn is_authorized_with_limits(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<Response, AuthorizationError> {
+        let mut results = self.evaluate_policies_core(pset, q, entities, limits);
+        match results.limit_exceeded.take() {
+            Some((id, error)) => Err(AuthorizationError::PolicyEvaluationError { id, error }),
+            None => Ok(self.concretize(self.response_kind(results), pset)),
+        }
+    }
+
+    /// Returns an authorization response for `q` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with a report of
+    /// the evaluation. The report includes a trace of each policy and the
+    /// entities and attributes evaluation found missing only if `details`
+    /// asks for them, since recording them slows evaluation.
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_report(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+        limits: EvaluationLimits<'_>,
+        details: ReportDetails,
+    ) -> Result<(Response, AuthorizationReport), AuthorizationError> {
+        let eval = Evaluator::new(q, entities, &self.extensions)
+            .with_limits(limits)
+            .with_metrics();
+        let eval = if details.trace() {
+            eval.with_trace()
+        } else {
+            eval
+        };
+        let eval = if details.missing_data() {
+            eval.with_missing_data()
+        } else {
+            eval
+        };
+        let mut results = self.evaluate_policies_with(&eval, pset, true);
+        let report = std::mem::take(&mut results.report);
+        match results.limit_exceeded.take() {
+            Some((id, error)) => Err(AuthorizationError::PolicyEvaluationError { id, error }),
+            None => Ok((self.concretize(self.response_kind(results), pset), report)),
+        }
+    }
+
+    /// Returns an authorization response for `q` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with the outcome
+    /// of evaluating every policy in `pset`, not only those determining the
+    /// decision.
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_outcomes(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<(Response, HashMap<PolicyID, PolicyOutcome>), AuthorizationError> {
+        self.is_authorized_with_report(q, pset, entities, limits, ReportDetails::new())
+            .map(|(response, report)| (response, report.outcomes))
+    }
+
+    /// Returns an authorization response for `q` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with a trace of
+    /// the evaluation of each policy, in the order the policies were
+    /// evaluated.
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_trace(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<(Response, Vec<PolicyTrace>), AuthorizationError> {
+        self.is_authorized_with_report(q, pset, entities, limits, ReportDetails::new().with_trace())
+            .map(|(response, report)| (response, report.traces))
+    }
+
+    /// Turns a possibly partial response into a concrete one, treating every
+    /// residual policy as an error
+    fn concretize(&self, response: ResponseKind, pset: &PolicySet) -> Response {
+        match response {
             ResponseKind::FullyEvaluated(response) => response,
             ResponseKind::Partial(partial) => {
                 // If we get a residual, we have to treat every residual policy as an error, and obey the error semantics.
@@ -168,8 +267,11 @@ impl Authorizer {
         pset: &PolicySet,
         entities: &Entities,
     ) -> ResponseKind {
-        let results = self.evaluate_policies_core(pset, q, entities);
+        self.response_kind(self.evaluate_policies_core(pset, q, entities, EvaluationLimits::new()))
+    }
 
+    /// Combines the results of evaluating each policy into a response
+    fn response_kind(&self, results: EvaluationResults<'_>) -> ResponseKind {
         let errors = results
             .errors
             .into_iter()
@@ -288,7 +390,9 @@ impl Authorizer {
             errors,
             permit_residuals,
             forbid_residuals,
-        } = self.evaluate_policies_core(pset, q, entities);
+            limit_exceeded: _,
+            report: _,
+        } = self.evaluate_policies_core(pset, q, entities, EvaluationLimits::new());
 
         let errors = errors
             .into_iter()
@@ -322,13 +426,46 @@ impl Authorizer {
         pset: &'a PolicySet,
         q: Request,
         entities: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> EvaluationResults<'a> {
+        let eval = Evaluator::new(q, entities, &self.extensions).with_limits(limits);
+        self.evaluate_policies_with(&eval, pset, false)
+    }
+
+    /// Evaluate every policy of `pset` with `eval`, recording the outcome of
+    /// each in the report only if `record_outcomes` is `true`
+    fn evaluate_policies_with<'a>(
+        &'a self,
+        eval: &Evaluator<'_>,
+        pset: &'a PolicySet,
+        record_outcomes: bool,
     ) -> EvaluationResults<'a> {
-        let eval = Evaluator::new(q, entities, &self.extensions);
         let mut results = EvaluationResults::default();
         let mut satisfied_policies = vec![];
 
         for p in pset.policies() {
-            match eval.partial_evaluate(p) {
+            let result = eval.partial_evaluate(p);
+            let outcome = match &result {
+                Ok(Either::Left(true)) => PolicyOutcome::Satisfied,
+                Ok(Either::Left(false)) => PolicyOutcome::NotSatisfied,
+                // a concrete response treats a residual as an error
+                Ok(Either::Right(_)) | Err(_) => PolicyOutcome::Error,
+            };
+            if record_outcomes {
+                results.report.outcomes.insert(p.id().clone(), outcome);
+            }
+            if let Some(events) = eval.take_trace() {
+                results.report.traces.push(PolicyTrace {
+                    id: p.id().clone(),
+                    outcome,
+                    events,
+                });
+            }
+            match result {
+                Err(e) if matches!(e.error_kind(), EvaluationErrorKind::LimitExceeded(_)) => {
+                    results.limit_exceeded = Some((p.id().clone(), e));
+                    break;
+                }
                 Ok(Either::Left(response)) => {
                     if response {
                         satisfied_policies.push(p)
@@ -372,6 +509,9 @@ impl Authorizer {
 
         results.satisfied_forbids = satisfied_forbids;
         results.satisfied_permits = satisfied_permits;
+        results.report.missing_entities = eval.take_missing_entities().unwrap_or_default();
+        results.report.missing_attributes = eval.take_missing_attributes().unwrap_or_default();
+        results.report.metrics = eval.take_metrics().unwrap_or_default();
 
         results
     }
@@ -414,6 +554,86 @@ struct EvaluationResults<'a> {
     errors: Vec<(PolicyID, EvaluationError)>,
     permit_residuals: Vec<Policy>,
     forbid_residuals: Vec<Policy>,
+    /// Set if evaluation was aborted for exceeding its limits, in which case
+    /// the other results are incomplete
+    limit_exceeded: Option<(PolicyID, EvaluationError)>,
+    /// What was learned about the evaluation, beyond the response
+    report: AuthorizationReport,
+}
+
+/// What an [`AuthorizationReport`] records beyond the outcome of each policy
+/// and the metrics. Each of these slows evaluation, so none is recorded by
+/// default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportDetails {
+    /// Whether to record a trace of each policy
+    trace: bool,
+    /// Whether to record the entities and attributes evaluation found missing
+    missing_data: bool,
+}
+
+impl ReportDetails {
+    /// Record nothing beyond the outcomes and metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a trace of each policy
+    pub fn with_trace(self) -> Self {
+        Self {
+            trace: true,
+            ..self
+        }
+    }
+
+    /// Record the entities and attributes evaluation found missing
+    pub fn with_missing_data(self) -> Self {
+        Self {
+            missing_data: true,
+            ..self
+        }
+    }
+
+    /// Whether a trace of each policy is recorded
+    pub fn trace(&self) -> bool {
+        self.trace
+    }
+
+    /// Whether the entities and attributes evaluation found missing are
+    /// recorded
+    pub fn missing_data(&self) -> bool {
+        self.missing_data
+    }
+}
+
+/// What was learned evaluating the policies for a request, beyond the
+/// response; see [`Authorizer::is_authorized_with_report`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthorizationReport {
+    /// Outcome of evaluating each policy
+    pub outcomes: HashMap<PolicyID, PolicyOutcome>,
+    /// Traces of the policies evaluated, in order, if tracing was asked for
+    pub traces: Vec<PolicyTrace>,
+    /// Entities that evaluation looked up but are not in the store, if
+    /// missing data was asked for. Adding them may change the response.
+    pub missing_entities: HashSet<EntityUID>,
+    /// Attributes that evaluation read from entities that do not have them,
+    /// if missing data was asked for. Each of these made a policy error.
+    pub missing_attributes: HashSet<(EntityUID, SmolStr)>,
+    /// Counters of the work evaluation did
+    pub metrics: EvaluationMetrics,
+}
+
+/// The steps taken evaluating one policy, as recorded by
+/// [`Authorizer::is_authorized_with_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyTrace {
+    /// Id of the policy
+    pub id: PolicyID,
+    /// Outcome of evaluating the policy
+    pub outcome: PolicyOutcome,
+    /// Events recorded evaluating the policy, in order
+    pub events: Vec<TraceEvent>,
 }
 
 impl std::fmt::Debug for Authorizer {
@@ -436,6 +656,7 @@ impl std::fmt::Debug for Authorizer {
 mod test {
     use super::*;
     use crate::ast::{Annotations, RequestSchemaAllPass};
+    use crate::evaluator::ResourceLimit;
     use crate::parser;
 
     /// Sanity unit test case for is_authorized.
@@ -514,6 +735,357 @@ mod test {
         assert_eq!(ans.decision, Decision::Deny);
     }
 
+    /// Tests that evaluation stops once it exceeds its limits
+    #[test]
+    fn limits_tests() {
+        let a = Authorizer::new();
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        let entities = Entities::new();
+        let src = r#"
+        permit(principal, action, resource) when { 1 + 1 + 1 + 1 + 1 + 1 + 1 == 7 };
+        "#;
+        pset.add_static(parser::parse_policy(Some("1".into()), src).unwrap())
+            .unwrap();
+
+        let ans = a
+            .is_authorized_with_limits(q.clone(), &pset, &entities, EvaluationLimits::new())
+            .unwrap();
+        assert_eq!(ans.decision, Decision::Allow);
+
+        let limits = EvaluationLimits::new().with_max_steps(1000);
+        let ans = a
+            .is_authorized_with_limits(q.clone(), &pset, &entities, limits)
+            .unwrap();
+        assert_eq!(ans.decision, Decision::Allow);
+
+        let limits = EvaluationLimits::new().with_max_steps(5);
+        let err = a
+            .is_authorized_with_limits(q.clone(), &pset, &entities, limits)
+            .unwrap_err();
+        let AuthorizationError::PolicyEvaluationError { id, error } = err;
+        assert_eq!(id, PolicyID::from_string("1"));
+        assert_eq!(
+            error.error_kind(),
+            &EvaluationErrorKind::LimitExceeded(ResourceLimit::Steps(5))
+        );
+
+        // a clock which advances one millisecond each time it is read
+        let now = std::cell::Cell::new(0.0);
+        let clock = || {
+            now.set(now.get() + 1.0);
+            now.get()
+        };
+        let big = format!(
+            "permit(principal, action, resource) when {{ [{}].contains(2) }};",
+            vec!["1"; 200].join(", ")
+        );
+        pset.add_static(parser::parse_policy(Some("2".into()), &big).unwrap())
+            .unwrap();
+        let limits = EvaluationLimits::new().with_time_budget(&clock, 1);
+        let err = a
+            .is_authorized_with_limits(q, &pset, &entities, limits)
+            .unwrap_err();
+        let AuthorizationError::PolicyEvaluationError { error, .. } = err;
+        assert_eq!(
+            error.error_kind(),
+            &EvaluationErrorKind::LimitExceeded(ResourceLimit::TimeBudgetMs(1))
+        );
+    }
+
+    /// Tests that the outcome of every policy is reported, whether or not it
+    /// determined the decision
+    #[test]
+    fn outcomes_tests() {
+        let a = Authorizer::new();
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let entities = Entities::new();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            ("1", "permit(principal, action, resource);"),
+            ("2", "permit(principal, action, resource) when { 1 > 2 };"),
+            (
+                "3",
+                "forbid(principal, action, resource) when { 1 + \"a\" > 0 };",
+            ),
+            ("4", "forbid(principal, action, resource) unless { true };"),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+
+        let (ans, outcomes) = a
+            .is_authorized_with_outcomes(q.clone(), &pset, &entities, EvaluationLimits::new())
+            .unwrap();
+        // only callers that ask for outcomes pay for recording them
+        let plain = a.evaluate_policies_core(&pset, q.clone(), &entities, EvaluationLimits::new());
+        assert!(plain.report.outcomes.is_empty());
+        assert_eq!(ans, a.is_authorized(q, &pset, &entities));
+        assert_eq!(ans.decision, Decision::Allow);
+        assert_eq!(
+            outcomes,
+            HashMap::from([
+                (PolicyID::from_string("1"), PolicyOutcome::Satisfied),
+                (PolicyID::from_string("2"), PolicyOutcome::NotSatisfied),
+                (PolicyID::from_string("3"), PolicyOutcome::Error),
+                (PolicyID::from_string("4"), PolicyOutcome::NotSatisfied),
+            ])
+        );
+    }
+
+    /// Tests that a trace records each policy's evaluation steps and the
+    /// attributes read
+    #[test]
+    fn trace_tests() {
+        let a = Authorizer::new();
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let principal = Entity::new(
+            EntityUID::with_eid("p"),
+            [("level".into(), RestrictedExpr::val(3))]
+                .into_iter()
+                .collect(),
+            HashSet::new(),
+            &Extensions::none(),
+        )
+        .unwrap();
+        let entities = Entities::from_entities(
+            [principal],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        let src = r#"
+        permit(principal, action, resource) when { principal.level > 2 };
+        "#;
+        pset.add_static(parser::parse_policy(Some("1".into()), src).unwrap())
+            .unwrap();
+        let src = r#"
+        forbid(principal, action, resource) when { principal has banned };
+        "#;
+        pset.add_static(parser::parse_policy(Some("2".into()), src).unwrap())
+            .unwrap();
+
+        let (ans, traces) = a
+            .is_authorized_with_trace(q.clone(), &pset, &entities, EvaluationLimits::new())
+            .unwrap();
+        assert_eq!(ans, a.is_authorized(q, &pset, &entities));
+        assert_eq!(ans.decision, Decision::Allow);
+        let trace = |id: &str| {
+            traces
+                .iter()
+                .find(|t| t.id == PolicyID::from_string(id))
+                .map(|t| t.events.clone())
+                .unwrap()
+        };
+
+        let outcomes: Vec<_> = traces.iter().map(|t| (t.id.clone(), t.outcome)).collect();
+        assert!(outcomes.contains(&(PolicyID::from_string("1"), PolicyOutcome::Satisfied)));
+        assert!(outcomes.contains(&(PolicyID::from_string("2"), PolicyOutcome::NotSatisfied)));
+        let permit = trace("1");
+        assert!(permit.contains(&TraceEvent::AttributeRead {
+            entity: EntityUID::with_eid("p"),
+            attr: "level".into(),
+            present: true,
+        }));
+        assert!(permit.contains(&TraceEvent::Evaluated {
+            expr: "principal[\"level\"]".into(),
+            result: Ok("3".into()),
+        }));
+        let read = permit
+            .iter()
+            .position(|e| matches!(e, TraceEvent::AttributeRead { .. }))
+            .unwrap();
+        let compared = permit
+            .iter()
+            .position(|e| matches!(e, TraceEvent::Evaluated { expr, .. } if expr.contains("<=")))
+            .unwrap();
+        assert!(read < compared);
+
+        assert!(trace("2").contains(&TraceEvent::AttributeRead {
+            entity: EntityUID::with_eid("p"),
+            attr: "banned".into(),
+            present: false,
+        }));
+    }
+
+    /// Tests that the entities looked up but not in the store, and the
+    /// attributes read but not on their entity, are reported
+    #[test]
+    fn missing_data_tests() {
+        let a = Authorizer::new();
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let principal = Entity::new(
+            EntityUID::with_eid("p"),
+            HashMap::new(),
+            HashSet::new(),
+            &Extensions::none(),
+        )
+        .unwrap();
+        let entities = Entities::from_entities(
+            [principal],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "1",
+                "permit(principal, action, resource) when { resource.public };",
+            ),
+            (
+                "2",
+                "forbid(principal, action, resource) when { principal has banned };",
+            ),
+            (
+                "3",
+                "permit(principal, action, resource) when { principal.level > 2 };",
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+
+        let (_, report) = a
+            .is_authorized_with_report(
+                q.clone(),
+                &pset,
+                &entities,
+                EvaluationLimits::new(),
+                ReportDetails::new(),
+            )
+            .unwrap();
+        assert!(report.missing_entities.is_empty());
+        assert!(report.missing_attributes.is_empty());
+
+        let (ans, report) = a
+            .is_authorized_with_report(
+                q.clone(),
+                &pset,
+                &entities,
+                EvaluationLimits::new(),
+                ReportDetails::new().with_missing_data(),
+            )
+            .unwrap();
+        assert_eq!(ans, a.is_authorized(q, &pset, &entities));
+        assert_eq!(ans.decision, Decision::Deny);
+        assert!(report.traces.is_empty());
+        assert_eq!(
+            report.missing_entities,
+            HashSet::from([EntityUID::with_eid("r")])
+        );
+        assert_eq!(
+            report.missing_attributes,
+            HashSet::from([(EntityUID::with_eid("p"), "level".into())])
+        );
+    }
+
+    #[test]
+    fn metrics_tests() {
+        let a = Authorizer::new();
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let entity = |eid: &str, parents: &[&str]| {
+            Entity::new(
+                EntityUID::with_eid(eid),
+                HashMap::new(),
+                parents.iter().map(|eid| EntityUID::with_eid(eid)).collect(),
+                &Extensions::none(),
+            )
+            .unwrap()
+        };
+        let entities = Entities::from_entities(
+            [
+                entity("p", &["g1"]),
+                entity("g1", &["g2"]),
+                entity("g2", &[]),
+            ],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "1",
+                r#"permit(principal in test_entity_type::"g2", action, resource);"#,
+            ),
+            (
+                "2",
+                "forbid(principal, action, resource) when { principal has banned };",
+            ),
+            (
+                "3",
+                "permit(principal, action, resource) when { resource.public };",
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+
+        let (ans, report) = a
+            .is_authorized_with_report(
+                q,
+                &pset,
+                &entities,
+                EvaluationLimits::new(),
+                ReportDetails::new(),
+            )
+            .unwrap();
+        assert_eq!(ans.decision, Decision::Allow);
+        assert_eq!(
+            report.metrics,
+            EvaluationMetrics {
+                policies_evaluated: 3,
+                entity_dereferences: 3,
+                max_hierarchy_depth: 2,
+            }
+        );
+    }
+
     fn true_policy(id: &str, e: Effect) -> StaticPolicy {
         let pid = PolicyID::from_string(id);
         StaticPolicy::new(
@@ -973,6 +1545,21 @@ impl Response {
     }
 }
 
+/// Outcome of evaluating a single policy for a request, whether or not the
+/// policy determined the decision
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum PolicyOutcome {
+    /// The policy's scope and conditions all held
+    Satisfied,
+    /// The policy's scope or one of its conditions did not hold
+    NotSatisfied,
+    /// Evaluating the policy raised an error, so it was skipped
+    Error,
+}
+
 /// Decision returned from the `Authorizer`
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
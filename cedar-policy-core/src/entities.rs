@@ -413,7 +413,6 @@ impl Default for Mode {
 
 /// Describes the option for how the TC (transitive closure) of the entity
 /// hierarchy is computed
-#[allow(dead_code)] // only `ComputeNow` is used currently, that's intentional
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TCComputation {
     /// Assume that the TC has already been computed and that the input is a DAG before the call of
@@ -20,19 +20,24 @@ use crate::ast::*;
 use crate::entities::{Dereference, Entities};
 use crate::extensions::Extensions;
 use crate::parser::Loc;
-#[cfg(test)]
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 mod err;
 pub(crate) use err::*;
-pub use err::{EvaluationError, EvaluationErrorKind};
+pub use err::{EvaluationError, EvaluationErrorKind, ResourceLimit};
 use itertools::Either;
 use nonempty::nonempty;
 use smol_str::SmolStr;
 
 const REQUIRED_STACK_SPACE: usize = 1024 * 100;
 
+/// How many evaluation steps pass between checks of the clock when evaluating
+/// with a time budget
+const STEPS_PER_CLOCK_CHECK: u64 = 64;
+
 // PANIC SAFETY `Name`s in here are valid `Name`s
 #[allow(clippy::expect_used)]
 mod names {
@@ -64,6 +69,126 @@ pub struct Evaluator<'e> {
     entities: &'e Entities,
     /// Extensions which are active for this evaluation
     extensions: &'e Extensions<'e>,
+    /// Limits on the work done by this `Evaluator`
+    limits: EvaluationLimits<'e>,
+    /// Time, in the milliseconds of the clock in `limits`, after which
+    /// evaluation is aborted
+    deadline: Option<f64>,
+    /// Number of evaluation steps taken so far, across every call
+    steps: Cell<u64>,
+    /// Events recorded since the trace was last taken, if tracing is enabled
+    trace: Option<RefCell<Vec<TraceEvent>>>,
+    /// Entities looked up since the missing entities were last taken that
+    /// are not in `entities`, if missing data is recorded
+    missing_entities: Option<RefCell<HashSet<EntityUID>>>,
+    /// Attributes read since the missing attributes were last taken that the
+    /// entity read from does not have, if missing data is recorded
+    missing_attributes: Option<RefCell<HashSet<(EntityUID, SmolStr)>>>,
+    /// Counters of the work done since the metrics were last taken, if
+    /// metrics are enabled
+    metrics: Option<Cell<EvaluationMetrics>>,
+    /// The depth of the hierarchy above each entity measured so far, if
+    /// metrics are enabled
+    hierarchy_depths: RefCell<HashMap<EntityUID, u64>>,
+}
+
+/// Counters of the work done by an [`Evaluator`] with metrics enabled, which
+/// show what makes a request expensive to decide
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvaluationMetrics {
+    /// Number of policies evaluated
+    pub policies_evaluated: u64,
+    /// Number of times an entity was looked up in the entities, to read or
+    /// test for an attribute or to test its ancestors with `in`
+    pub entity_dereferences: u64,
+    /// The length of the longest chain of ancestors above the entity on the
+    /// left of an `in`, counting every ancestor on the chain rather than only
+    /// those the `in` had to look at. An entity without ancestors has depth 0.
+    pub max_hierarchy_depth: u64,
+}
+
+/// A step of evaluation recorded by an [`Evaluator`] with tracing enabled.
+///
+/// Events are recorded in the order evaluation completes them, so each
+/// subexpression is recorded before the expression containing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A subexpression other than a literal, variable, or slot was evaluated
+    Evaluated {
+        /// The subexpression, as Cedar text
+        expr: String,
+        /// The value (or residual) it evaluated to, or the error it raised,
+        /// as text
+        result: std::result::Result<String, String>,
+    },
+    /// An attribute of an entity was read, or tested for with `has`
+    AttributeRead {
+        /// The entity
+        entity: EntityUID,
+        /// The attribute
+        attr: SmolStr,
+        /// Whether the entity has the attribute
+        present: bool,
+    },
+}
+
+/// Limits on the work an [`Evaluator`] may do, checked cooperatively as it
+/// interprets expressions. Exceeding a limit aborts evaluation with an
+/// [`EvaluationErrorKind::LimitExceeded`] error.
+///
+/// The default is to have no limits.
+#[derive(Clone, Copy, Default)]
+pub struct EvaluationLimits<'a> {
+    /// Maximum number of evaluation steps, where each subexpression
+    /// interpreted counts as one step
+    max_steps: Option<u64>,
+    /// Clock returning the current time in milliseconds, and the number of
+    /// milliseconds evaluation may take as measured by it
+    time_budget: Option<(&'a dyn Fn() -> f64, u64)>,
+}
+
+impl<'a> EvaluationLimits<'a> {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit evaluation to `max_steps` steps
+    pub fn with_max_steps(self, max_steps: u64) -> Self {
+        Self {
+            max_steps: Some(max_steps),
+            ..self
+        }
+    }
+
+    /// Limit evaluation to `budget_ms` milliseconds, as measured by `clock`,
+    /// which returns the current time in milliseconds. The budget starts when
+    /// the `Evaluator` is created.
+    pub fn with_time_budget(self, clock: &'a dyn Fn() -> f64, budget_ms: u64) -> Self {
+        Self {
+            time_budget: Some((clock, budget_ms)),
+            ..self
+        }
+    }
+
+    /// The maximum number of evaluation steps, if limited
+    pub fn max_steps(&self) -> Option<u64> {
+        self.max_steps
+    }
+
+    /// The time budget in milliseconds, if limited
+    pub fn time_budget_ms(&self) -> Option<u64> {
+        self.time_budget.map(|(_, budget_ms)| budget_ms)
+    }
+}
+
+impl std::fmt::Debug for EvaluationLimits<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvaluationLimits")
+            .field("max_steps", &self.max_steps)
+            .field("time_budget_ms", &self.time_budget_ms())
+            .finish()
+    }
 }
 
 /// Evaluator for "restricted" expressions. See notes on `RestrictedExpr`.
@@ -194,9 +319,194 @@ impl<'e> Evaluator<'e> {
             },
             entities,
             extensions,
+            limits: EvaluationLimits::default(),
+            deadline: None,
+            steps: Cell::new(0),
+            trace: None,
+            missing_entities: None,
+            missing_attributes: None,
+            metrics: None,
+            hierarchy_depths: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Record a trace of the evaluations this `Evaluator` does, to be
+    /// retrieved with [`Evaluator::take_trace`]
+    pub fn with_trace(self) -> Self {
+        Self {
+            trace: Some(RefCell::new(Vec::new())),
+            ..self
+        }
+    }
+
+    /// Record the entities and attributes this `Evaluator` looks for that are
+    /// not there, to be retrieved with [`Evaluator::take_missing_entities`]
+    /// and [`Evaluator::take_missing_attributes`]
+    pub fn with_missing_data(self) -> Self {
+        Self {
+            missing_entities: Some(RefCell::new(HashSet::new())),
+            missing_attributes: Some(RefCell::new(HashSet::new())),
+            ..self
+        }
+    }
+
+    /// Count the work this `Evaluator` does, to be retrieved with
+    /// [`Evaluator::take_metrics`]
+    pub fn with_metrics(self) -> Self {
+        Self {
+            metrics: Some(Cell::new(EvaluationMetrics::default())),
+            ..self
+        }
+    }
+
+    /// The metrics counted since they were last taken, resetting them, or
+    /// `None` if metrics are not enabled
+    pub fn take_metrics(&self) -> Option<EvaluationMetrics> {
+        self.metrics.as_ref().map(Cell::take)
+    }
+
+    /// Update the metrics with `update`, if metrics are enabled
+    fn count(&self, update: impl FnOnce(&mut EvaluationMetrics)) {
+        if let Some(metrics) = &self.metrics {
+            let mut counted = metrics.get();
+            update(&mut counted);
+            metrics.set(counted);
+        }
+    }
+
+    /// The length of the longest chain of ancestors above `uid`, i.e. the
+    /// number of entities on it after `uid`, remembering the length for each
+    /// entity on the way. An entity that is not in the entities has none, and
+    /// an edge back onto the chain being measured, as on a cycle of the
+    /// hierarchy, counts only once.
+    fn hierarchy_depth(&self, uid: &EntityUID) -> u64 {
+        let mut depths = self.hierarchy_depths.borrow_mut();
+        if let Some(depth) = depths.get(uid) {
+            return *depth;
+        }
+        let ancestors = |uid: &EntityUID| -> Vec<EntityUID> {
+            match self.entities.entity(uid) {
+                Dereference::Data(entity) => entity.ancestors().cloned().collect(),
+                Dereference::NoSuchEntity | Dereference::Residual(_) => Vec::new(),
+            }
+        };
+        // the chain being measured, from `uid`, with the ancestors left to
+        // visit of each entity on it and the longest chain found above it so
+        // far. This is a loop rather than a recursion so that a deep hierarchy
+        // cannot overflow the stack. Entities on the chain have depth 0 until
+        // they are measured, which guards against cycles.
+        depths.insert(uid.clone(), 0);
+        let mut chain = vec![(uid.clone(), ancestors(uid).into_iter(), 0)];
+        while let Some((_, left, depth)) = chain.last_mut() {
+            match left.next() {
+                Some(ancestor) => match depths.get(&ancestor) {
+                    Some(known) => *depth = (*depth).max(known + 1),
+                    None => {
+                        depths.insert(ancestor.clone(), 0);
+                        let above = ancestors(&ancestor).into_iter();
+                        chain.push((ancestor, above, 0));
+                    }
+                },
+                None => {
+                    if let Some((measured, _, depth)) = chain.pop() {
+                        if let Some((_, _, below)) = chain.last_mut() {
+                            *below = (*below).max(depth + 1);
+                        }
+                        depths.insert(measured, depth);
+                    }
+                }
+            }
+        }
+        depths.get(uid).copied().unwrap_or(0)
+    }
+
+    /// The events recorded since the trace was last taken, leaving it empty,
+    /// or `None` if tracing is not enabled
+    pub fn take_trace(&self) -> Option<Vec<TraceEvent>> {
+        self.trace.as_ref().map(RefCell::take)
+    }
+
+    /// Record `event`, if tracing is enabled
+    fn record(&self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(trace) = &self.trace {
+            trace.borrow_mut().push(event());
+        }
+    }
+
+    /// Record a read of the attribute `attr` of `uid`, if tracing is enabled
+    fn record_read(&self, uid: &EntityUID, attr: &SmolStr, present: bool) {
+        self.record(|| TraceEvent::AttributeRead {
+            entity: uid.clone(),
+            attr: attr.clone(),
+            present,
+        });
+    }
+
+    /// The entities looked up since this was last called that are not in the
+    /// `Entities` this `Evaluator` was built with, leaving the set empty, or
+    /// `None` if missing data is not recorded
+    pub fn take_missing_entities(&self) -> Option<HashSet<EntityUID>> {
+        self.missing_entities.as_ref().map(RefCell::take)
+    }
+
+    /// The attributes read since this was last called that the entities read
+    /// from do not have, leaving the set empty, or `None` if missing data is
+    /// not recorded. Testing for an attribute with `has` is not a read.
+    pub fn take_missing_attributes(&self) -> Option<HashSet<(EntityUID, SmolStr)>> {
+        self.missing_attributes.as_ref().map(RefCell::take)
+    }
+
+    /// Look up `uid` in the entities, remembering it if it is not there and
+    /// missing data is recorded
+    fn lookup(&self, uid: &EntityUID) -> Dereference<'e, Entity> {
+        self.count(|metrics| metrics.entity_dereferences += 1);
+        let entity = self.entities.entity(uid);
+        if let Some(missing) = &self.missing_entities {
+            if matches!(entity, Dereference::NoSuchEntity)
+                && matches!(uid.entity_type(), EntityType::Specified(_))
+            {
+                missing.borrow_mut().insert(uid.clone());
+            }
+        }
+        entity
+    }
+
+    /// Limit the work done by this `Evaluator` across all of its evaluations.
+    /// A time budget starts counting down now.
+    pub fn with_limits(self, limits: EvaluationLimits<'e>) -> Self {
+        Self {
+            deadline: limits
+                .time_budget
+                .map(|(clock, budget_ms)| clock() + budget_ms as f64),
+            limits,
+            ..self
         }
     }
 
+    /// Count one evaluation step, failing if that exceeds the limits
+    // returns the evaluator's `Result`, like every evaluation it is part of
+    #[allow(clippy::result_large_err)]
+    fn take_step(&self) -> Result<()> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        if let Some(max_steps) = self.limits.max_steps {
+            if steps > max_steps {
+                return Err(EvaluationError::limit_exceeded(ResourceLimit::Steps(
+                    max_steps,
+                )));
+            }
+        }
+        if let (Some((clock, budget_ms)), Some(deadline)) = (self.limits.time_budget, self.deadline)
+        {
+            if steps.is_multiple_of(STEPS_PER_CLOCK_CHECK) && clock() > deadline {
+                return Err(EvaluationError::limit_exceeded(
+                    ResourceLimit::TimeBudgetMs(budget_ms),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Evaluate the given `Policy`, returning either a bool or an error.
     /// The bool indicates whether the policy applies, ie, "is satisfied" for the
     /// current `request`.
@@ -204,6 +514,7 @@ impl<'e> Evaluator<'e> {
     /// it doesn't consider whether we're processing a `Permit` policy or a
     /// `Forbid` policy.
     pub fn evaluate(&self, p: &Policy) -> Result<bool> {
+        self.count(|metrics| metrics.policies_evaluated += 1);
         self.interpret(&p.condition(), p.env())?.get_as_bool()
     }
 
@@ -217,6 +528,7 @@ impl<'e> Evaluator<'e> {
     /// it doesn't consider whether we're processing a `Permit` policy or a
     /// `Forbid` policy.
     pub fn partial_evaluate(&self, p: &Policy) -> Result<Either<bool, Expr>> {
+        self.count(|metrics| metrics.policies_evaluated += 1);
         match self.partial_interpret(&p.condition(), p.env())? {
             PartialValue::Value(v) => v.get_as_bool().map(Either::Left),
             PartialValue::Residual(e) => Ok(Either::Right(e)),
@@ -265,6 +577,7 @@ impl<'e> Evaluator<'e> {
     /// attribute that doesn't exist.
     pub fn partial_interpret(&self, expr: &Expr, slots: &SlotEnv) -> Result<PartialValue> {
         stack_size_check()?;
+        self.take_step()?;
 
         let res = self.partial_interpret_internal(expr, slots);
 
@@ -275,11 +588,25 @@ impl<'e> Evaluator<'e> {
         // also, if there is an error, set its source location to the source
         // location of the input expression as well, unless it already had a
         // more specific location
-        res.map(|pval| pval.with_maybe_source_loc(expr.source_loc().cloned()))
+        let res = res
+            .map(|pval| pval.with_maybe_source_loc(expr.source_loc().cloned()))
             .map_err(|err| match err.source_loc() {
                 None => err.with_maybe_source_loc(expr.source_loc().cloned()),
                 Some(_) => err,
-            })
+            });
+        if !matches!(
+            expr.expr_kind(),
+            ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Slot(_)
+        ) {
+            self.record(|| TraceEvent::Evaluated {
+                expr: expr.to_string(),
+                result: res
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .map_err(ToString::to_string),
+            });
+        }
+        res
     }
 
     /// Internal function to interpret an `Expr`. (External callers, use
@@ -459,7 +786,7 @@ impl<'e> Evaluator<'e> {
                                 };
                                 e
                             })?;
-                        match self.entities.entity(uid1) {
+                        match self.lookup(uid1) {
                             Dereference::Residual(r) => Ok(PartialValue::Residual(
                                 Expr::binary_app(BinaryOp::In, r, arg2.into()),
                             )),
@@ -573,12 +900,16 @@ impl<'e> Evaluator<'e> {
                 PartialValue::Value(Value {
                     value: ValueKind::Lit(Literal::EntityUID(uid)),
                     ..
-                }) => match self.entities.entity(&uid) {
+                }) => match self.lookup(&uid) {
                     Dereference::NoSuchEntity => Ok(false.into()),
                     Dereference::Residual(r) => {
                         Ok(PartialValue::Residual(Expr::has_attr(r, attr.clone())))
                     }
-                    Dereference::Data(e) => Ok(e.get(attr).is_some().into()),
+                    Dereference::Data(e) => {
+                        let present = e.get(attr).is_some();
+                        self.record_read(&uid, attr, present);
+                        Ok(present.into())
+                    }
                 },
                 PartialValue::Value(val) => Err(err::EvaluationError::type_error(
                     nonempty![
@@ -651,6 +982,12 @@ impl<'e> Evaluator<'e> {
         entity1: Option<&Entity>,
         arg2: Value,
     ) -> Result<PartialValue> {
+        if self.metrics.is_some() {
+            let depth = self.hierarchy_depth(uid1);
+            self.count(|metrics| {
+                metrics.max_hierarchy_depth = metrics.max_hierarchy_depth.max(depth);
+            });
+        }
         // `rhs` is a list of all the UIDs for which we need to
         // check if `uid1` is a descendant of
         let rhs = match arg2.value {
@@ -777,7 +1114,7 @@ impl<'e> Evaluator<'e> {
             PartialValue::Value(Value {
                 value: ValueKind::Lit(Literal::EntityUID(uid)),
                 loc,
-            }) => match self.entities.entity(uid.as_ref()) {
+            }) => match self.lookup(uid.as_ref()) {
                 Dereference::NoSuchEntity => Err(match *uid.entity_type() {
                     EntityType::Unspecified => EvaluationError::unspecified_entity_access(
                         attr.clone(),
@@ -791,16 +1128,24 @@ impl<'e> Evaluator<'e> {
                 Dereference::Residual(r) => {
                     Ok(PartialValue::Residual(Expr::get_attr(r, attr.clone())))
                 }
-                Dereference::Data(entity) => entity
-                    .get(attr)
-                    .ok_or_else(|| {
-                        EvaluationError::entity_attr_does_not_exist(
-                            uid,
-                            attr.clone(),
-                            source_loc.cloned(),
-                        )
-                    })
-                    .cloned(),
+                Dereference::Data(entity) => {
+                    let value = entity.get(attr);
+                    self.record_read(&uid, attr, value.is_some());
+                    if let (None, Some(missing)) = (value, &self.missing_attributes) {
+                        missing
+                            .borrow_mut()
+                            .insert((uid.as_ref().clone(), attr.clone()));
+                    }
+                    value
+                        .ok_or_else(|| {
+                            EvaluationError::entity_attr_does_not_exist(
+                                uid,
+                                attr.clone(),
+                                source_loc.cloned(),
+                            )
+                        })
+                        .cloned()
+                }
             },
             PartialValue::Value(v) => {
                 // PANIC SAFETY Entity type name is fully static and a valid unqualified `Name`
@@ -3542,6 +3887,37 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn measures_deep_hierarchies_without_recursing() {
+        let request = basic_request();
+        // a chain of single parents, too long to measure with a recursion
+        let entities = Entities::from_entities(
+            (0..100_000).map(|i| {
+                let mut entity = Entity::with_uid(EntityUID::with_eid(&i.to_string()));
+                entity.add_ancestor(EntityUID::with_eid(&(i + 1).to_string()));
+                entity
+            }),
+            None::<&NoEntitiesSchema>,
+            TCComputation::AssumeAlreadyComputed,
+            Extensions::none(),
+        )
+        .expect("failed to create basic entities");
+        let exts = Extensions::none();
+        let eval = Evaluator::new(request, &entities, &exts).with_metrics();
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::is_in(
+                Expr::val(EntityUID::with_eid("0")),
+                Expr::val(EntityUID::with_eid("1"))
+            )),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            eval.take_metrics()
+                .map(|metrics| metrics.max_hierarchy_depth),
+            Some(100_000)
+        );
+    }
+
     #[test]
     fn interpret_hierarchy_membership_slice() {
         // User::"Alice" in Group::"Friends".
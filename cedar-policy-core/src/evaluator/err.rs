@@ -258,6 +258,15 @@ impl EvaluationError {
         }
     }
 
+    /// Construct a [`LimitExceeded`] error
+    pub(crate) fn limit_exceeded(limit: ResourceLimit) -> Self {
+        Self {
+            error_kind: EvaluationErrorKind::LimitExceeded(limit),
+            advice: None,
+            source_loc: None,
+        }
+    }
+
     pub(crate) fn extension_function_lookup(
         err: crate::extensions::ExtensionFunctionLookupError,
         source_loc: Option<Loc>,
@@ -375,6 +384,31 @@ pub enum EvaluationErrorKind {
     /// Maximum recursion limit reached for expression evaluation
     #[error("recursion limit reached")]
     RecursionLimit,
+
+    /// Evaluation was aborted because it exceeded a limit set by the caller;
+    /// see [`crate::evaluator::EvaluationLimits`]
+    #[error("{0}")]
+    LimitExceeded(ResourceLimit),
+}
+
+/// A limit on evaluation which was exceeded
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResourceLimit {
+    /// The maximum number of evaluation steps
+    Steps(u64),
+    /// The time budget, in milliseconds
+    TimeBudgetMs(u64),
+}
+
+impl std::fmt::Display for ResourceLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Steps(steps) => write!(f, "evaluation exceeded its limit of {steps} steps"),
+            Self::TimeBudgetMs(ms) => {
+                write!(f, "evaluation exceeded its time budget of {ms} ms")
+            }
+        }
+    }
 }
 
 /// helper function for pretty-printing type errors
@@ -137,6 +137,59 @@ where
     Ok(())
 }
 
+/// Find a cycle in the graph given as a map from keys to `TCNode`s, following
+/// its edges as they are, without computing the transitive closure. Returns
+/// the keys along the cycle, starting and ending with the same key, e.g.
+/// `[a, b, a]` for `a -> b -> a`, or `None` if the graph is a DAG. Edges to
+/// keys that are not in the map are ignored. `compute_tc` only reports one
+/// vertex on a cycle, and loses the path once the closure is computed, so
+/// this is meant for explaining that error from the graph as given.
+pub fn find_cycle<K, V>(nodes: &HashMap<K, V>) -> Option<Vec<K>>
+where
+    K: Clone + Eq + Hash,
+    V: TCNode<K>,
+{
+    // keys all of whose ancestors have been searched without finding a cycle
+    let mut done: HashSet<K> = HashSet::new();
+    for start in nodes.values() {
+        if done.contains(&start.get_key()) {
+            continue;
+        }
+        // the path being searched, from `start`, with the edges left to
+        // follow out of each node on it. This is a loop rather than a
+        // recursion so that a deep hierarchy cannot overflow the stack.
+        let mut path = vec![(start.get_key(), start.out_edges())];
+        let mut on_path = HashSet::from([start.get_key()]);
+        while let Some((_, edges)) = path.last_mut() {
+            match edges.next() {
+                Some(next) if on_path.contains(next) => {
+                    let mut cycle: Vec<K> = path
+                        .iter()
+                        .map(|(key, _)| key.clone())
+                        .skip_while(|key| key != next)
+                        .collect();
+                    cycle.push(next.clone());
+                    return Some(cycle);
+                }
+                Some(next) if !done.contains(next) => {
+                    if let Some(node) = nodes.get(next) {
+                        on_path.insert(next.clone());
+                        path.push((next.clone(), node.out_edges()));
+                    }
+                }
+                Some(_) => (),
+                None => {
+                    if let Some((key, _)) = path.pop() {
+                        on_path.remove(&key);
+                        done.insert(key);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 /// For the given `node` in the given `hierarchy`, add all of the `node`'s
 /// transitive ancestors to the given set. Assume that any nodes already in
 /// `ancestors` don't need to be searched -- they have been already handled.
@@ -763,4 +816,45 @@ mod tests {
             Err(_) => panic!("Unexpected error in enforce_dag_from_tc"),
         }
     }
+
+    #[test]
+    fn cycle_path() {
+        // A -> B -> C -> A, with D -> B and C -> E outside the cycle
+        let mut a = Entity::with_uid(EntityUID::with_eid("A"));
+        a.add_ancestor(EntityUID::with_eid("B"));
+        let mut b = Entity::with_uid(EntityUID::with_eid("B"));
+        b.add_ancestor(EntityUID::with_eid("C"));
+        let mut c = Entity::with_uid(EntityUID::with_eid("C"));
+        c.add_ancestor(EntityUID::with_eid("E"));
+        c.add_ancestor(EntityUID::with_eid("A"));
+        let mut d = Entity::with_uid(EntityUID::with_eid("D"));
+        d.add_ancestor(EntityUID::with_eid("B"));
+        let e = Entity::with_uid(EntityUID::with_eid("E"));
+        let mut entities = HashMap::from([
+            (a.uid().clone(), a),
+            (b.uid().clone(), b),
+            (c.uid().clone(), c),
+            (d.uid().clone(), d),
+            (e.uid().clone(), e),
+        ]);
+        let cycle = find_cycle(&entities).expect("should find the cycle");
+        // the cycle may start at any of its vertices
+        let start = ["A", "B", "C"]
+            .iter()
+            .position(|eid| cycle[0] == EntityUID::with_eid(eid))
+            .expect("cycle should start on the cycle");
+        let expected: Vec<_> = ["A", "B", "C", "A", "B", "C"][start..=start + 3]
+            .iter()
+            .map(|eid| EntityUID::with_eid(eid))
+            .collect();
+        assert_eq!(cycle, expected);
+        assert!(compute_tc(&mut entities.clone(), true).is_err());
+
+        // removing C -> A breaks the cycle
+        let mut c = Entity::with_uid(EntityUID::with_eid("C"));
+        c.add_ancestor(EntityUID::with_eid("E"));
+        entities.insert(c.uid().clone(), c);
+        assert_eq!(find_cycle(&entities), None);
+        assert!(compute_tc(&mut entities, true).is_ok());
+    }
 }
@@ -14,21 +14,48 @@
  * limitations under the License.
  */
 
-/// Fuzzy string matching using the Levenshtein distance algorithm
-pub fn fuzzy_search(key: &str, lst: &[impl AsRef<str>]) -> Option<String> {
-    if key.is_empty() || lst.is_empty() {
-        None
-    } else {
-        let t = lst.iter().fold((std::usize::MAX, ""), |acc, word| {
-            let e = levenshtein_distance(key, word.as_ref());
-            if e < acc.0 {
-                (e, word.as_ref())
-            } else {
-                acc
-            }
-        });
-        Some(t.1.to_owned())
+use itertools::Itertools;
+
+/// Maximum number of suggestions `fuzzy_search` returns
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Fuzzy string matching using the Levenshtein distance algorithm. Returns the
+/// distinct words of `lst` at the smallest distance from `key`, sorted so that
+/// the answer does not depend on the order of `lst`, and at most
+/// `MAX_SUGGESTIONS` of them.
+pub fn fuzzy_search(key: &str, lst: &[impl AsRef<str>]) -> Vec<String> {
+    if key.is_empty() {
+        return Vec::new();
     }
+    let distances = lst
+        .iter()
+        .map(|word| (levenshtein_distance(key, word.as_ref()), word.as_ref()))
+        .collect::<Vec<_>>();
+    let Some(nearest) = distances.iter().map(|(e, _)| *e).min() else {
+        return Vec::new();
+    };
+    distances
+        .into_iter()
+        .filter(|(e, _)| *e == nearest)
+        .map(|(_, word)| word.to_owned())
+        .sorted()
+        .dedup()
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// The help text for an error with `suggestions`, e.g. ``did you mean `a` or
+/// `b`?``, if there are any
+pub(crate) fn did_you_mean(suggestions: &[String]) -> Option<String> {
+    let (last, rest) = suggestions.split_last()?;
+    Some(if rest.is_empty() {
+        format!("did you mean `{last}`?")
+    } else {
+        format!(
+            "did you mean {} or `{last}`?",
+            rest.iter().map(|s| format!("`{s}`")).join(", ")
+        )
+    })
 }
 pub fn levenshtein_distance(word1: &str, word2: &str) -> usize {
     let w1 = word1.chars().collect::<Vec<_>>();
@@ -83,7 +110,7 @@ pub mod test {
     fn test_match1() {
         let word1 = "user::Alice";
         let words = vec!["User::Alice", "user::alice", "user", "alice"];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("User::Alice".to_owned()));
     }
 
@@ -98,7 +125,7 @@ pub mod test {
             "prince",
             "principle",
         ];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("principal".to_owned()));
     }
 
@@ -113,7 +140,7 @@ pub mod test {
             "prince",
             "principle",
         ];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("principal".to_owned()));
     }
 
@@ -122,7 +149,7 @@ pub mod test {
     fn test_match4() {
         let word1 = "user::\"Alice\"";
         let words = vec!["User::\"Alice\"", "user::\"alice\"", "user", "alice"];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("User::\"Alice\"".to_owned()));
     }
 
@@ -131,7 +158,7 @@ pub mod test {
     fn test_match5() {
         let word1 = "";
         let words = vec!["User::\"Alice\"", "user::\"alice\"", "user", "alice"];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, None); //Some("user".to_owned()));
     }
 
@@ -148,7 +175,7 @@ pub mod test {
             "principle",
             "principal",
         ];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("principal".to_owned()));
     }
 
@@ -157,7 +184,7 @@ pub mod test {
     fn test_match7() {
         let word1 = "User::\"Alice\"";
         let words = vec!["User::\'Alice\'", "user::\"alice\"", "user", "alice"];
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, Some("User::\'Alice\'".to_owned()));
     }
 
@@ -165,7 +192,22 @@ pub mod test {
     fn test_match_empty() {
         let word1 = "user::Alice";
         let words: Vec<&str> = Vec::new();
-        let x = fuzzy_search(word1, &words);
+        let x = fuzzy_search(word1, &words).into_iter().next();
         assert_eq!(x, None);
     }
+
+    ///every word at the smallest distance is suggested, once and sorted
+    #[test]
+    fn test_match_ties() {
+        let word1 = "UserGroup1";
+        let words = vec!["UserGroups", "User", "UserGroup", "UserGroup", "Group"];
+        assert_eq!(
+            fuzzy_search(word1, &words),
+            vec!["UserGroup".to_owned(), "UserGroups".to_owned()]
+        );
+        assert_eq!(
+            did_you_mean(&fuzzy_search(word1, &words)).as_deref(),
+            Some("did you mean `UserGroup` or `UserGroups`?")
+        );
+    }
 }
@@ -90,6 +90,11 @@ impl Validator {
         Self { schema }
     }
 
+    /// Get the schema this validator validates against
+    pub fn schema(&self) -> &ValidatorSchema {
+        &self.schema
+    }
+
     /// Validate all templates, links, and static policies in a policy set.
     /// Return an iterator of policy notes associated with each policy id.
     pub fn validate<'a>(
@@ -51,11 +51,11 @@ impl Validator {
 
                 if !is_action_entity_type && !is_known_entity_type {
                     let actual_entity_type = name.to_string();
-                    let suggested_entity_type =
+                    let suggested_entity_types =
                         fuzzy_search(&actual_entity_type, known_entity_types.as_slice());
                     Some(ValidationErrorKind::unrecognized_entity_type(
                         actual_entity_type,
-                        suggested_entity_type,
+                        suggested_entity_types,
                     ))
                 } else {
                     None
@@ -133,11 +133,11 @@ impl Validator {
                 cedar_policy_core::ast::EntityType::Specified(name) => {
                     if !self.schema.is_known_entity_type(name) {
                         let actual_entity_type = entity_type.to_string();
-                        let suggested_entity_type =
+                        let suggested_entity_types =
                             fuzzy_search(&actual_entity_type, known_entity_types.as_slice());
                         Some(ValidationErrorKind::unrecognized_entity_type(
                             actual_entity_type,
-                            suggested_entity_type,
+                            suggested_entity_types,
                         ))
                     } else {
                         None
@@ -457,11 +457,11 @@ mod test {
         match notes.first() {
             Some(ValidationErrorKind::UnrecognizedEntityType(UnrecognizedEntityType {
                 actual_entity_type,
-                suggested_entity_type,
+                suggested_entity_types,
             })) => {
                 assert_eq!("foo_type", actual_entity_type);
                 assert!(
-                    suggested_entity_type.is_none(),
+                    suggested_entity_types.is_empty(),
                     "Did not expect suggested entity type."
                 );
             }
@@ -621,13 +621,13 @@ mod test {
         match notes.first() {
             Some(ValidationErrorKind::UnrecognizedEntityType(UnrecognizedEntityType {
                 actual_entity_type,
-                suggested_entity_type,
+                suggested_entity_types,
             })) => {
                 assert_eq!("bar_type", actual_entity_type);
                 assert_eq!(
                     "foo_type",
-                    suggested_entity_type
-                        .as_ref()
+                    suggested_entity_types
+                        .first()
                         .expect("Expected a suggested entity type")
                 );
             }
@@ -658,11 +658,11 @@ mod test {
         match notes.first() {
             Some(ValidationErrorKind::UnrecognizedActionId(UnrecognizedActionId {
                 actual_action_id,
-                suggested_action_id,
+                suggested_action_ids,
             })) => {
                 assert_eq!("Action::\"foo_name\"", actual_action_id);
                 assert!(
-                    suggested_action_id.is_none(),
+                    suggested_action_ids.is_empty(),
                     "Did not expect suggested action id."
                 );
             }
@@ -784,13 +784,13 @@ mod test {
         match notes.first() {
             Some(ValidationErrorKind::UnrecognizedEntityType(UnrecognizedEntityType {
                 actual_entity_type,
-                suggested_entity_type,
+                suggested_entity_types,
             })) => {
                 assert_eq!("Undefined", actual_entity_type);
                 assert_eq!(
                     "User",
-                    suggested_entity_type
-                        .as_ref()
+                    suggested_entity_types
+                        .first()
                         .expect("Expected a suggested entity type")
                 );
             }
@@ -833,13 +833,13 @@ mod test {
         match notes.first() {
             Some(ValidationErrorKind::UnrecognizedActionId(UnrecognizedActionId {
                 actual_action_id,
-                suggested_action_id,
+                suggested_action_ids,
             })) => {
                 assert_eq!("Action::\"bar_name\"", actual_action_id);
                 assert_eq!(
                     "Action::\"foo_name\"",
-                    suggested_action_id
-                        .as_ref()
+                    suggested_action_ids
+                        .first()
                         .expect("Expected suggested action id.")
                 )
             }
@@ -50,6 +50,16 @@ impl ValidatorActionId {
     pub fn context_type(&self) -> Type {
         self.context.clone()
     }
+
+    /// The principal types this action applies to
+    pub fn applies_to_principals(&self) -> impl Iterator<Item = &EntityType> {
+        self.applies_to.applicable_principal_types()
+    }
+
+    /// The resource types this action applies to
+    pub fn applies_to_resources(&self) -> impl Iterator<Item = &EntityType> {
+        self.applies_to.applicable_resource_types()
+    }
 }
 
 impl TCNode<EntityUID> for ValidatorActionId {
@@ -21,6 +21,7 @@ use std::{collections::BTreeSet, fmt::Display};
 use cedar_policy_core::ast::{CallStyle, EntityUID, Expr, ExprKind, Name, Var};
 use cedar_policy_core::parser::Loc;
 
+use crate::fuzzy_match::did_you_mean;
 use crate::types::{EntityLUB, EntityRecordKind, RequestEnv};
 
 use super::types::Type;
@@ -140,7 +141,7 @@ impl TypeError {
     pub(crate) fn unsafe_attribute_access(
         on_expr: Expr,
         attribute_access: AttributeAccess,
-        suggestion: Option<String>,
+        suggestions: impl IntoIterator<Item = String>,
         may_exist: bool,
     ) -> Self {
         Self {
@@ -148,7 +149,7 @@ impl TypeError {
             source_loc: None,
             kind: TypeErrorKind::UnsafeAttributeAccess(UnsafeAttributeAccess {
                 attribute_access,
-                suggestion,
+                suggestions: suggestions.into_iter().collect(),
                 may_exist,
             }),
         }
@@ -345,18 +346,26 @@ pub struct IncompatibleTypes {
 #[error("attribute {attribute_access} not found")]
 pub struct UnsafeAttributeAccess {
     attribute_access: AttributeAccess,
-    suggestion: Option<String>,
+    suggestions: Vec<String>,
     /// When this is true, the attribute might still exist, but the validator
     /// cannot guarantee that it will.
     may_exist: bool,
 }
 
+impl UnsafeAttributeAccess {
+    /// The attributes nearest to the one accessed, which the user might
+    /// reasonably have intended to write
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
+
 impl Diagnostic for UnsafeAttributeAccess {
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
-        match (&self.suggestion, self.may_exist) {
-            (Some(suggestion), false) => Some(Box::new(format!("did you mean `{suggestion}`?"))),
+        match (did_you_mean(&self.suggestions), self.may_exist) {
+            (Some(suggestion), false) => Some(Box::new(suggestion)),
             (None, true) => Some(Box::new("there may be additional attributes that the validator is not able to reason about".to_string())),
-            (Some(suggestion), true) => Some(Box::new(format!("did you mean `{suggestion}`? (there may also be additional attributes that the validator is not able to reason about)"))),
+            (Some(suggestion), true) => Some(Box::new(format!("{suggestion} (there may also be additional attributes that the validator is not able to reason about)"))),
             (None, false) => None,
         }
     }
@@ -1014,11 +1014,11 @@ impl<'a> Typechecker<'a> {
                             None => {
                                 let borrowed =
                                     all_attrs.iter().map(|s| s.as_str()).collect::<Vec<_>>();
-                                let suggestion = fuzzy_search(attr, &borrowed);
+                                let suggestions = fuzzy_search(attr, &borrowed);
                                 type_errors.push(TypeError::unsafe_attribute_access(
                                     e.clone(),
                                     AttributeAccess::from_expr(request_env, &annot_expr),
-                                    suggestion,
+                                    suggestions,
                                     Type::may_have_attr(self.schema, typ_actual, attr),
                                 ));
                                 TypecheckAnswer::fail(annot_expr)
@@ -19,7 +19,7 @@ use cedar_policy_core::parser::Loc;
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::{TypeErrorKind, ValidationWarning};
+use crate::{fuzzy_match::did_you_mean, TypeErrorKind, ValidationWarning};
 
 /// Contains the result of policy validation. The result includes the list of
 /// issues found by validation and whether validation succeeds or fails.
@@ -170,22 +170,22 @@ pub enum ValidationErrorKind {
 impl ValidationErrorKind {
     pub(crate) fn unrecognized_entity_type(
         actual_entity_type: String,
-        suggested_entity_type: Option<String>,
+        suggested_entity_types: impl IntoIterator<Item = String>,
     ) -> ValidationErrorKind {
         UnrecognizedEntityType {
             actual_entity_type,
-            suggested_entity_type,
+            suggested_entity_types: suggested_entity_types.into_iter().collect(),
         }
         .into()
     }
 
     pub(crate) fn unrecognized_action_id(
         actual_action_id: String,
-        suggested_action_id: Option<String>,
+        suggested_action_ids: impl IntoIterator<Item = String>,
     ) -> ValidationErrorKind {
         UnrecognizedActionId {
             actual_action_id,
-            suggested_action_id,
+            suggested_action_ids: suggested_action_ids.into_iter().collect(),
         }
         .into()
     }
@@ -217,17 +217,23 @@ impl ValidationErrorKind {
 pub struct UnrecognizedEntityType {
     /// The entity type seen in the policy.
     pub(crate) actual_entity_type: String,
-    /// An entity type from the schema that the user might reasonably have
+    /// Entity types from the schema that the user might reasonably have
     /// intended to write.
-    pub(crate) suggested_entity_type: Option<String>,
+    pub(crate) suggested_entity_types: Vec<String>,
+}
+
+impl UnrecognizedEntityType {
+    /// The entity types from the schema nearest to the unrecognized one, which
+    /// the user might reasonably have intended to write
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggested_entity_types
+    }
 }
 
 impl Diagnostic for UnrecognizedEntityType {
     fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
-        match &self.suggested_entity_type {
-            Some(s) => Some(Box::new(format!("did you mean `{s}`?"))),
-            None => None,
-        }
+        did_you_mean(&self.suggested_entity_types)
+            .map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
     }
 }
 
@@ -238,17 +244,23 @@ impl Diagnostic for UnrecognizedEntityType {
 pub struct UnrecognizedActionId {
     /// Action Id seen in the policy.
     pub(crate) actual_action_id: String,
-    /// An action id from the schema that the user might reasonably have
+    /// Action ids from the schema that the user might reasonably have
     /// intended to write.
-    pub(crate) suggested_action_id: Option<String>,
+    pub(crate) suggested_action_ids: Vec<String>,
+}
+
+impl UnrecognizedActionId {
+    /// The action ids from the schema nearest to the unrecognized one, which
+    /// the user might reasonably have intended to write
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggested_action_ids
+    }
 }
 
 impl Diagnostic for UnrecognizedActionId {
     fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
-        match &self.suggested_action_id {
-            Some(s) => Some(Box::new(format!("did you mean `{s}`?"))),
-            None => None,
-        }
+        did_you_mean(&self.suggested_action_ids)
+            .map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
     }
 }
 
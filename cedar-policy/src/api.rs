@@ -22,18 +22,22 @@
 )]
 pub use ast::Effect;
 pub use authorizer::Decision;
+pub use authorizer::PolicyOutcome;
 use cedar_policy_core::ast;
 use cedar_policy_core::ast::{
     ContextCreationError, ExprConstructionError, Integer, RestrictedExprParseError,
 }; // `ContextCreationError` is unsuitable for `pub use` because it contains internal types like `RestrictedExpr`
 use cedar_policy_core::authorizer;
+pub use cedar_policy_core::authorizer::ReportDetails;
 use cedar_policy_core::entities::{
     self, ContextJsonDeserializationError, ContextSchema, Dereference, JsonDeserializationError,
     JsonDeserializationErrorContext,
 };
 use cedar_policy_core::est;
 use cedar_policy_core::evaluator::Evaluator;
-pub use cedar_policy_core::evaluator::{EvaluationError, EvaluationErrorKind};
+pub use cedar_policy_core::evaluator::{
+    EvaluationError, EvaluationErrorKind, EvaluationLimits, EvaluationMetrics, ResourceLimit,
+};
 pub use cedar_policy_core::extensions;
 use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::parser;
@@ -258,6 +262,7 @@ impl std::fmt::Display for Entity {
 pub struct Entities(pub(crate) entities::Entities);
 
 pub use entities::EntitiesError;
+pub use entities::TCComputation;
 
 impl Entities {
     /// Create a fresh `Entities` with no entities
@@ -321,6 +326,27 @@ impl Entities {
         .map(Entities)
     }
 
+    /// Create an `Entities` object with the given entities, choosing how the
+    /// transitive closure of the entity hierarchy is obtained
+    ///
+    /// This behaves like [`Entities::from_entities`], which always computes
+    /// the transitive closure (`TCComputation::ComputeNow`).
+    pub fn from_entities_with_tc_computation(
+        entities: impl IntoIterator<Item = Entity>,
+        schema: Option<&Schema>,
+        tc_computation: TCComputation,
+    ) -> Result<Self, entities::EntitiesError> {
+        entities::Entities::from_entities(
+            entities.into_iter().map(|e| e.0),
+            schema
+                .map(|s| cedar_policy_validator::CoreSchema::new(&s.0))
+                .as_ref(),
+            tc_computation,
+            Extensions::all_available(),
+        )
+        .map(Entities)
+    }
+
     /// Add all of the [`Entity`]s in the collection to this [`Entities`]
     /// structure, re-computing the transitive closure.
     ///
@@ -505,6 +531,28 @@ impl Entities {
         eparser.from_json_str(json).map(Entities)
     }
 
+    /// Parse an entities JSON file (in `&str` form) into an `Entities` object,
+    /// choosing how the transitive closure of the entity hierarchy is obtained
+    ///
+    /// This behaves like [`Entities::from_json_str`], which always computes
+    /// the transitive closure (`TCComputation::ComputeNow`). Callers whose
+    /// `parents` already list every ancestor can skip that work with
+    /// `TCComputation::AssumeAlreadyComputed`, or have it checked with
+    /// `TCComputation::EnforceAlreadyComputed`.
+    pub fn from_json_str_with_tc_computation(
+        json: &str,
+        schema: Option<&Schema>,
+        tc_computation: TCComputation,
+    ) -> Result<Self, entities::EntitiesError> {
+        let schema = schema.map(|s| cedar_policy_validator::CoreSchema::new(&s.0));
+        let eparser = entities::EntityJsonParser::new(
+            schema.as_ref(),
+            Extensions::all_available(),
+            tc_computation,
+        );
+        eparser.from_json_str(json).map(Entities)
+    }
+
     /// Parse an entities JSON file (in `serde_json::Value` form) into an
     /// `Entities` object
     ///
@@ -557,6 +605,26 @@ impl Entities {
         eparser.from_json_value(json).map(Entities)
     }
 
+    /// Parse an entities JSON file (in `serde_json::Value` form) into an
+    /// `Entities` object, choosing how the transitive closure of the entity
+    /// hierarchy is obtained
+    ///
+    /// This behaves like [`Entities::from_json_value`], which always computes
+    /// the transitive closure (`TCComputation::ComputeNow`).
+    pub fn from_json_value_with_tc_computation(
+        json: serde_json::Value,
+        schema: Option<&Schema>,
+        tc_computation: TCComputation,
+    ) -> Result<Self, entities::EntitiesError> {
+        let schema = schema.map(|s| cedar_policy_validator::CoreSchema::new(&s.0));
+        let eparser = entities::EntityJsonParser::new(
+            schema.as_ref(),
+            Extensions::all_available(),
+            tc_computation,
+        );
+        eparser.from_json_value(json).map(Entities)
+    }
+
     /// Parse an entities JSON file (in `std::io::Read` form) into an `Entities`
     /// object
     ///
@@ -749,6 +817,123 @@ impl Authorizer {
         self.0.is_authorized(r.0.clone(), &p.ast, &e.0).into()
     }
 
+    /// Returns an authorization response for `r` as [`Authorizer::is_authorized`]
+    /// does, unless evaluating it exceeds `limits`. In that case evaluation is
+    /// aborted and the error is returned, with an error kind of
+    /// [`EvaluationErrorKind::LimitExceeded`].
+    // The error is returned by value, like the other `AuthorizationError`s of
+    // this API, though it is large; it only occurs when a limit is exceeded.
+    #[allow(clippy::result_large_err)]
+    pub fn is_authorized_with_limits(
+        &self,
+        r: &Request,
+        p: &PolicySet,
+        e: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<Response, AuthorizationError> {
+        self.0
+            .is_authorized_with_limits(r.0.clone(), &p.ast, &e.0, limits)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    /// Returns an authorization response for `r` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with the outcome
+    /// of evaluating every policy in `p`. Unlike the response's
+    /// [`Diagnostics::reason`], this includes policies that were satisfied
+    /// without determining the decision, and those that were not satisfied.
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_outcomes(
+        &self,
+        r: &Request,
+        p: &PolicySet,
+        e: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<(Response, HashMap<PolicyId, PolicyOutcome>), AuthorizationError> {
+        self.0
+            .is_authorized_with_outcomes(r.0.clone(), &p.ast, &e.0, limits)
+            .map(|(response, outcomes)| {
+                (
+                    response.into(),
+                    outcomes
+                        .into_iter()
+                        .map(|(id, outcome)| (PolicyId(id), outcome))
+                        .collect(),
+                )
+            })
+            .map_err(Into::into)
+    }
+
+    /// Returns an authorization response for `r` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with a trace of
+    /// the evaluation of each policy. Tracing slows evaluation, so this is
+    /// meant for debugging policies rather than for answering requests.
+    /// ```
+    /// # use cedar_policy::{Authorizer, Context, Entities, EvaluationLimits, PolicySet, Request, TraceEvent};
+    /// let policies: PolicySet = r#"permit(principal, action, resource) when { principal.level > 2 };"#
+    ///     .parse()
+    ///     .unwrap();
+    /// let entities = Entities::from_json_str(
+    ///     r#"[{"uid": {"type": "User", "id": "alice"}, "attrs": {"level": 3}, "parents": []}]"#,
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let request = Request::new(
+    ///     Some(r#"User::"alice""#.parse().unwrap()),
+    ///     Some(r#"Action::"view""#.parse().unwrap()),
+    ///     Some(r#"Photo::"vacation.jpg""#.parse().unwrap()),
+    ///     Context::empty(),
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let (_, traces) = Authorizer::new()
+    ///     .is_authorized_with_trace(&request, &policies, &entities, EvaluationLimits::new())
+    ///     .unwrap();
+    /// assert!(traces[0].events().any(|event| matches!(
+    ///     event,
+    ///     TraceEvent::AttributeRead { attr, present: true, .. } if attr == "level"
+    /// )));
+    /// ```
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_trace(
+        &self,
+        r: &Request,
+        p: &PolicySet,
+        e: &Entities,
+        limits: EvaluationLimits<'_>,
+    ) -> Result<(Response, Vec<PolicyTrace>), AuthorizationError> {
+        self.0
+            .is_authorized_with_trace(r.0.clone(), &p.ast, &e.0, limits)
+            .map(|(response, traces)| {
+                (
+                    response.into(),
+                    traces.into_iter().map(Into::into).collect(),
+                )
+            })
+            .map_err(Into::into)
+    }
+
+    /// Returns an authorization response for `r` as
+    /// [`Authorizer::is_authorized_with_limits`] does, along with a report of
+    /// the evaluation: the outcome of every policy, the entities evaluation
+    /// looked up that are not in `e` and the entity attributes it read that
+    /// are not there, and a trace of each policy, the last two only if
+    /// `details` asks for them.
+    #[allow(clippy::result_large_err)] // see `is_authorized_with_limits`
+    pub fn is_authorized_with_report(
+        &self,
+        r: &Request,
+        p: &PolicySet,
+        e: &Entities,
+        limits: EvaluationLimits<'_>,
+        details: ReportDetails,
+    ) -> Result<(Response, AuthorizationReport), AuthorizationError> {
+        self.0
+            .is_authorized_with_report(r.0.clone(), &p.ast, &e.0, limits, details)
+            .map(|(response, report)| (response.into(), report.into()))
+            .map_err(Into::into)
+    }
+
     /// A partially evaluated authorization request.
     /// The Authorizer will attempt to make as much progress as possible in the presence of unknowns.
     /// If the Authorizer can reach a response, it will return that response.
@@ -1057,6 +1242,155 @@ impl From<authorizer::Response> for Response {
     }
 }
 
+/// What was learned evaluating the policies for a request, beyond the
+/// response; see [`Authorizer::is_authorized_with_report`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthorizationReport {
+    /// Outcome of evaluating each policy
+    outcomes: HashMap<PolicyId, PolicyOutcome>,
+    /// Traces of the policies evaluated, in order, if tracing was asked for
+    traces: Vec<PolicyTrace>,
+    /// Entities that evaluation looked up but are not in the store
+    missing_entities: HashSet<EntityUid>,
+    /// Attributes that evaluation read from entities that do not have them
+    missing_attributes: HashSet<(EntityUid, String)>,
+    /// Counters of the work evaluation did
+    metrics: EvaluationMetrics,
+}
+
+impl AuthorizationReport {
+    /// Get the outcome of evaluating each policy
+    pub fn outcomes(&self) -> impl Iterator<Item = (&PolicyId, PolicyOutcome)> {
+        self.outcomes.iter().map(|(id, outcome)| (id, *outcome))
+    }
+
+    /// Get the trace of each policy evaluated, in order. This is empty unless
+    /// tracing was asked for.
+    pub fn traces(&self) -> impl Iterator<Item = &PolicyTrace> {
+        self.traces.iter()
+    }
+
+    /// Get the entities that evaluation looked up but are not in the store.
+    /// Adding them may change the response.
+    pub fn missing_entities(&self) -> impl Iterator<Item = &EntityUid> {
+        self.missing_entities.iter()
+    }
+
+    /// Get the attributes that evaluation read from entities that do not have
+    /// them, each with the entity. Each of these made a policy error.
+    pub fn missing_attributes(&self) -> impl Iterator<Item = (&EntityUid, &str)> {
+        self.missing_attributes
+            .iter()
+            .map(|(entity, attr)| (entity, attr.as_str()))
+    }
+
+    /// Get counters of the work evaluation did, such as the number of entity
+    /// lookups, which show what makes a request expensive to decide
+    pub fn metrics(&self) -> EvaluationMetrics {
+        self.metrics
+    }
+}
+
+impl From<authorizer::AuthorizationReport> for AuthorizationReport {
+    fn from(report: authorizer::AuthorizationReport) -> Self {
+        Self {
+            outcomes: report
+                .outcomes
+                .into_iter()
+                .map(|(id, outcome)| (PolicyId(id), outcome))
+                .collect(),
+            traces: report.traces.into_iter().map(Into::into).collect(),
+            missing_entities: report.missing_entities.into_iter().map(EntityUid).collect(),
+            missing_attributes: report
+                .missing_attributes
+                .into_iter()
+                .map(|(entity, attr)| (EntityUid(entity), attr.to_string()))
+                .collect(),
+            metrics: report.metrics,
+        }
+    }
+}
+
+/// The steps taken evaluating one policy; see
+/// [`Authorizer::is_authorized_with_trace`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PolicyTrace {
+    /// Id of the policy
+    policy_id: PolicyId,
+    /// Outcome of evaluating the policy
+    outcome: PolicyOutcome,
+    /// Events recorded evaluating the policy, in order
+    events: Vec<TraceEvent>,
+}
+
+impl PolicyTrace {
+    /// Get the id of the policy
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// Get the outcome of evaluating the policy
+    pub fn outcome(&self) -> PolicyOutcome {
+        self.outcome
+    }
+
+    /// Get the events recorded evaluating the policy. Each subexpression is
+    /// recorded before the expression containing it.
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+}
+
+impl From<authorizer::PolicyTrace> for PolicyTrace {
+    fn from(trace: authorizer::PolicyTrace) -> Self {
+        Self {
+            policy_id: PolicyId(trace.id),
+            outcome: trace.outcome,
+            events: trace.events.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A step of evaluating a policy, as recorded in a [`PolicyTrace`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TraceEvent {
+    /// A subexpression other than a literal, variable, or slot was evaluated
+    Evaluated {
+        /// The subexpression, as Cedar text
+        expr: String,
+        /// The value it evaluated to, or the error it raised, as text
+        result: Result<String, String>,
+    },
+    /// An attribute of an entity was read, or tested for with `has`
+    AttributeRead {
+        /// The entity
+        entity: EntityUid,
+        /// The attribute
+        attr: String,
+        /// Whether the entity has the attribute
+        present: bool,
+    },
+}
+
+impl From<cedar_policy_core::evaluator::TraceEvent> for TraceEvent {
+    fn from(event: cedar_policy_core::evaluator::TraceEvent) -> Self {
+        match event {
+            cedar_policy_core::evaluator::TraceEvent::Evaluated { expr, result } => {
+                Self::Evaluated { expr, result }
+            }
+            cedar_policy_core::evaluator::TraceEvent::AttributeRead {
+                entity,
+                attr,
+                present,
+            } => Self::AttributeRead {
+                entity: EntityUid(entity),
+                attr: attr.to_string(),
+                present,
+            },
+        }
+    }
+}
+
 #[cfg(feature = "partial-eval")]
 impl ResidualResponse {
     /// Create a new `ResidualResponse`
@@ -1385,6 +1719,15 @@ impl Schema {
     pub fn action_entities(&self) -> Result<Entities, EntitiesError> {
         Ok(Entities(self.0.action_entities()?))
     }
+
+    /// The type the schema declares for the context of `action`, if it
+    /// declares the action
+    pub(crate) fn context_type(
+        &self,
+        action: &EntityUid,
+    ) -> Option<cedar_policy_validator::types::Type> {
+        self.0.context_type(&action.0)
+    }
 }
 
 /// Errors encountered during construction of a Validation Schema
@@ -0,0 +1,198 @@
+/*
+ * Copyright 2022-2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module exposes a JSON-based function for evaluating a single Cedar
+//! expression, used by other language FFI's, e.g. for playgrounds and for
+//! testing policy conditions in isolation
+use super::utils::{ContextRecord, InterfaceResult};
+use crate::{Context, Entities, EntityUid, Request};
+use cedar_policy_core::{
+    ast::{Expr, SlotEnv},
+    entities::CedarValueJson,
+    evaluator::Evaluator,
+    extensions::Extensions,
+    jsonvalue::JsonValueWithNoDuplicateKeys,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[cfg(feature = "wasm")]
+extern crate tsify;
+
+/// public string-based JSON interface to evaluate the `expression` of a call,
+/// with `principal`, `action`, `resource`, and `context` bound as given and
+/// the call's `entities` to look entities up in
+///
+/// Any of the bindings may be left out, in which case the expression sees an
+/// unspecified entity or an empty context. The result holds the `value` in the
+/// JSON format of Cedar values, e.g. `{"__entity": {"type": "User", "id":
+/// "alice"}}` for an entity. An expression that fails to evaluate fails the
+/// call with the evaluation error.
+pub fn json_evaluate(input: &str) -> InterfaceResult {
+    serde_json::from_str::<EvaluationCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| match call.evaluate() {
+            Ok(value) => InterfaceResult::succeed(EvaluationAnswer { value }),
+            Err(errors) => InterfaceResult::fail_bad_request(errors),
+        },
+    )
+}
+
+/// An expression to evaluate, with what its variables are bound to
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct EvaluationCall {
+    /// The expression, as Cedar text
+    expression: String,
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "string|{type: string, id: string}")
+    )]
+    principal: Option<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "string|{type: string, id: string}")
+    )]
+    action: Option<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "string|{type: string, id: string}")
+    )]
+    resource: Option<JsonValueWithNoDuplicateKeys>,
+    #[serde(default)]
+    #[serde_as(as = "ContextRecord")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, any> | null | \"\"")
+    )]
+    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    /// Entities in their JSON format, which the expression may refer to
+    #[cfg_attr(feature = "wasm", tsify(optional, type = "Array<EntityJson>"))]
+    entities: Option<JsonValueWithNoDuplicateKeys>,
+}
+
+/// The value of an evaluated expression
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct EvaluationAnswer {
+    /// The value, in the JSON format of Cedar values
+    #[cfg_attr(feature = "wasm", tsify(type = "any"))]
+    value: serde_json::Value,
+}
+
+impl EvaluationCall {
+    fn evaluate(self) -> Result<serde_json::Value, Vec<String>> {
+        let uid = |json: Option<JsonValueWithNoDuplicateKeys>, var: &str| {
+            json.map(|json| {
+                EntityUid::from_json(json.into())
+                    .map_err(|e| vec![format!("error parsing {var}: {e}")])
+            })
+            .transpose()
+        };
+        let principal = uid(self.principal, "principal")?;
+        let action = uid(self.action, "action")?;
+        let resource = uid(self.resource, "resource")?;
+        let context = serde_json::to_value(self.context)
+            .map_err(|e| vec![format!("error parsing context: {e}")])
+            .and_then(|context| {
+                Context::from_json_value(context, None)
+                    .map_err(|e| vec![format!("error parsing context: {e}")])
+            })?;
+        let request = Request::new(principal, action, resource, context, None)
+            .map_err(|e| vec![e.to_string()])?;
+        let entities = match self.entities {
+            Some(entities) => Entities::from_json_value(entities.into(), None)
+                .map_err(|e| vec![format!("error parsing entities: {e}")])?,
+            None => Entities::empty(),
+        };
+        let expr = Expr::from_str(&self.expression).map_err(|errs| {
+            errs.errors_as_strings()
+                .into_iter()
+                .map(|e| format!("error parsing expression: {e}"))
+                .collect::<Vec<_>>()
+        })?;
+
+        let extensions = Extensions::all_available();
+        let evaluator = Evaluator::new(request.0, &entities.0, &extensions);
+        let value = evaluator
+            .interpret(&expr, &SlotEnv::new())
+            .map_err(|e| vec![e.to_string()])?;
+        CedarValueJson::from_value(value)
+            .and_then(|json| serde_json::to_value(json).map_err(Into::into))
+            .map_err(|e| vec![format!("error serializing value: {e}")])
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frontend::utils::assert_is_failure;
+    use cool_asserts::assert_matches;
+
+    fn value(call: serde_json::Value) -> serde_json::Value {
+        assert_matches!(json_evaluate(&call.to_string()), InterfaceResult::Success { result, .. } => {
+            let answer: EvaluationAnswer = serde_json::from_str(&result).unwrap();
+            answer.value
+        })
+    }
+
+    #[test]
+    fn evaluates_expressions_with_bindings() {
+        let call = |expression: &str| {
+            serde_json::json!({
+                "expression": expression,
+                "principal": { "type": "User", "id": "alice" },
+                "context": { "ip": { "__extn": { "fn": "ip", "arg": "10.1.2.3" } } },
+                "entities": [
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": { "level": 3 }, "parents": [] }
+                ]
+            })
+        };
+        assert_eq!(value(call("principal.level + 1")), serde_json::json!(4));
+        assert_eq!(
+            value(call("[principal, context.ip]")),
+            serde_json::json!([
+                { "__entity": { "type": "User", "id": "alice" } },
+                { "__extn": { "fn": "ip", "arg": "10.1.2.3" } }
+            ])
+        );
+        assert_eq!(
+            value(serde_json::json!({ "expression": "{ a: \"b\" }" })),
+            serde_json::json!({ "a": "b" })
+        );
+    }
+
+    #[test]
+    fn fails_on_errors() {
+        assert_is_failure(
+            &json_evaluate(&serde_json::json!({ "expression": "1 +" }).to_string()),
+            false,
+            "error parsing expression",
+        );
+        assert_is_failure(
+            &json_evaluate(&serde_json::json!({ "expression": "principal.level" }).to_string()),
+            false,
+            "unspecified entity",
+        );
+    }
+}
@@ -17,23 +17,28 @@
 //! This module contains the `json_is_authorized` entry point that other language
 //! FFI's can call in order to use Cedar functionality
 #![allow(clippy::module_name_repetitions)]
-use super::utils::{InterfaceResult, PolicySpecification};
+use super::utils::{
+    ContextRecord, InterfaceResult, PolicyJsonWithId, PolicySpecification, Stopwatch,
+};
 use crate::api::EntityId;
 use crate::api::EntityTypeName;
 #[cfg(feature = "partial-eval")]
 use crate::api::PartialResponse;
 use crate::PolicyId;
 use crate::{
-    Authorizer, Context, Decision, Entities, EntityUid, Policy, PolicySet, Request, Response,
-    Schema, SlotId, Template,
+    AuthorizationError, Authorizer, Context, Decision, Entities, EntityUid, EvaluationErrorKind,
+    EvaluationLimits, EvaluationMetrics, Policy, PolicyOutcome, PolicySet, PolicyTrace,
+    PrincipalConstraint, ReportDetails, Request, Response, Schema, SlotId, Template, TraceEvent,
 };
+use cedar_policy_core::est::FromJsonError;
 use cedar_policy_core::jsonvalue::JsonValueWithNoDuplicateKeys;
+use cedar_policy_validator::types::{EntityRecordKind, Primitive, Type};
 use itertools::Itertools;
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use serde_with::MapPreventDuplicates;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
 use thiserror::Error;
 
@@ -45,17 +50,333 @@ thread_local!(
     static AUTHORIZER: Authorizer = Authorizer::new();
 );
 
-/// Construct and ask the authorizer the request.
-fn is_authorized(call: AuthorizationCall) -> AuthorizationAnswer {
-    match call.get_components() {
-        Ok((request, policies, entities)) => {
-            AUTHORIZER.with(|authorizer| AuthorizationAnswer::Success {
-                response: authorizer
-                    .is_authorized(&request, &policies, &entities)
-                    .into(),
+/// Construct and ask the authorizer the request. If the call asks for timings
+/// and a `clock` is given, the phases of the call are measured with it.
+fn is_authorized(call: AuthorizationCall, clock: Option<&dyn Fn() -> f64>) -> AuthorizationAnswer {
+    let limits = match call
+        .limits
+        .map(|limits| limits.with_clock(clock))
+        .transpose()
+    {
+        Ok(limits) => limits,
+        Err(errors) => return AuthorizationAnswer::ParseFailed { errors },
+    };
+    let details = call.details;
+    let mut stopwatch = Stopwatch::start(clock.filter(|_| call.include_timings));
+    let mut timings = AuthorizationTimings::default();
+    match call.get_components(&mut stopwatch, &mut timings) {
+        Ok((request, policies, entities, duplicate_entities)) => {
+            let mut answer = authorize(&request, &policies, &entities, limits, details);
+            timings.eval_ms = stopwatch.lap();
+            if let AuthorizationAnswer::Success {
+                timings: answer_timings,
+                ..
+            } = &mut answer
+            {
+                *answer_timings = stopwatch.is_running().then_some(timings);
+            }
+            answer.with_duplicate_entities(duplicate_entities)
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Ask the authorizer the request, within `limits` if there are any, with the
+/// `details` the call asks for
+fn authorize(
+    request: &Request,
+    policies: &PolicySet,
+    entities: &Entities,
+    limits: Option<EvaluationLimits<'_>>,
+    details: AnswerDetails,
+) -> AuthorizationAnswer {
+    AUTHORIZER.with(|authorizer| {
+        let answer = if details.wants_report() {
+            authorizer
+                .is_authorized_with_report(
+                    request,
+                    policies,
+                    entities,
+                    limits.unwrap_or_default(),
+                    details.report_details(),
+                )
+                .map(|(response, report)| (response, Some(report)))
+        } else {
+            limits.map_or_else(
+                || Ok((authorizer.is_authorized(request, policies, entities), None)),
+                |limits| {
+                    authorizer
+                        .is_authorized_with_limits(request, policies, entities, limits)
+                        .map(|response| (response, None))
+                },
+            )
+        };
+        match answer {
+            Ok((response, report)) => {
+                let report = report.as_ref();
+                let determining_policies = details
+                    .include_determining_policies
+                    .map(|format| determining_policies(&response, policies, format));
+                let obligations = annotated_policies(&response, policies, "obligation");
+                let advice = annotated_policies(&response, policies, "advice");
+                let membership_paths = if details.include_membership_paths {
+                    membership_paths(&response, policies, entities, request)
+                } else {
+                    Vec::new()
+                };
+                let mut response = InterfaceResponse {
+                    obligations,
+                    advice,
+                    ..response.into()
+                };
+                response.diagnostics.membership_paths = membership_paths;
+                AuthorizationAnswer::Success {
+                    response: Box::new(response),
+                    timings: None,
+                    duplicate_entities: Vec::new(),
+                    policy_outcomes: report.filter(|_| details.include_policy_outcomes).map(
+                        |report| {
+                            report
+                                .outcomes()
+                                .map(|(id, outcome)| (id.to_string(), outcome))
+                                .collect()
+                        },
+                    ),
+                    trace: report
+                        .filter(|_| details.trace)
+                        .map(|report| report.traces().cloned().map(Into::into).collect()),
+                    missing_entities: report.filter(|_| details.strict).map(|report| {
+                        let mut uids: Vec<_> =
+                            report.missing_entities().map(ToString::to_string).collect();
+                        uids.sort();
+                        uids
+                    }),
+                    missing_attributes: report.filter(|_| details.strict).map(|report| {
+                        let mut attrs: Vec<_> = report
+                            .missing_attributes()
+                            .map(|(entity, attr)| InterfaceMissingAttribute {
+                                entity: entity.to_string(),
+                                attr: attr.to_string(),
+                            })
+                            .collect();
+                        attrs.sort();
+                        attrs
+                    }),
+                    determining_policies,
+                    metrics: report
+                        .filter(|_| details.include_metrics)
+                        .map(|report| Box::new(report.metrics().into())),
+                    error_mode: None,
+                    error_mode_applied: false,
+                }
+            }
+            Err(e) => AuthorizationAnswer::LimitExceeded {
+                errors: vec![e.to_string()],
+            },
+        }
+    })
+}
+
+/// The value of the annotation `key` on each of the policies that determined
+/// `response` which has it, in order of policy id
+fn annotated_policies(
+    response: &Response,
+    policies: &PolicySet,
+    key: &str,
+) -> Vec<InterfacePolicyAnnotation> {
+    let mut annotations: Vec<_> = response
+        .diagnostics()
+        .reason()
+        .filter_map(|id| {
+            let value = policies.policy(id)?.annotation(key)?;
+            Some(InterfacePolicyAnnotation {
+                policy: id.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect();
+    annotations.sort();
+    annotations
+}
+
+/// How the principal of `request` is a member of each group that a policy
+/// determining `response` requires it to be `in`, by its scope or conditions
+fn membership_paths(
+    response: &Response,
+    policies: &PolicySet,
+    entities: &Entities,
+    request: &Request,
+) -> Vec<InterfaceMembershipPath> {
+    let Some(principal) = request.principal() else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = response
+        .diagnostics()
+        .reason()
+        .filter_map(|id| Some((id, policies.policy(id)?)))
+        .flat_map(|(id, policy)| {
+            principal_groups(policy)
+                .into_iter()
+                .filter_map(move |group| {
+                    let path = membership_path(principal, &group, entities)?;
+                    Some(InterfaceMembershipPath {
+                        policy: id.to_string(),
+                        path: path.iter().map(ToString::to_string).collect(),
+                    })
+                })
+        })
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// The groups `policy` requires the principal to be `in`, by a `principal in`
+/// scope constraint or a `principal in` expression in its conditions
+fn principal_groups(policy: &Policy) -> Vec<EntityUid> {
+    let mut groups = match policy.principal_constraint() {
+        PrincipalConstraint::In(group) | PrincipalConstraint::IsIn(_, group) => vec![group],
+        PrincipalConstraint::Any | PrincipalConstraint::Eq(_) | PrincipalConstraint::Is(_) => {
+            Vec::new()
+        }
+    };
+    if let Ok(est) = policy.to_json() {
+        let conditions = est.get("conditions").and_then(serde_json::Value::as_array);
+        for body in conditions
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.get("body"))
+        {
+            principal_in_groups(body, &mut groups);
+        }
+    }
+    groups
+}
+
+/// Add to `groups` the entities that the JSON policy format expression `expr`
+/// tests the principal to be `in`, anywhere within it
+fn principal_in_groups(expr: &serde_json::Value, groups: &mut Vec<EntityUid>) {
+    let principal = serde_json::json!({ "Var": "principal" });
+    match expr {
+        serde_json::Value::Object(expr) => {
+            let tested = match (expr.get("in"), expr.get("is")) {
+                (Some(test), _) => Some((test.get("left"), test.get("right"))),
+                (None, Some(test)) => Some((test.get("left"), test.get("in"))),
+                (None, None) => None,
+            };
+            if let Some((Some(left), Some(right))) = tested {
+                if *left == principal {
+                    let literals = right
+                        .get("Set")
+                        .and_then(serde_json::Value::as_array)
+                        .map_or_else(|| vec![right], |elements| elements.iter().collect());
+                    groups.extend(
+                        literals
+                            .into_iter()
+                            .filter_map(|literal| literal.get("Value"))
+                            .filter_map(|value| EntityUid::from_json(value.clone()).ok()),
+                    );
+                }
+            }
+            for value in expr.values() {
+                principal_in_groups(value, groups);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                principal_in_groups(value, groups);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The chain of entities from `member` to `group` in the entity hierarchy,
+/// each a member of the next, or `None` if `member` is not in `group`. Where
+/// the hierarchy gives the ancestors of an entity rather than only its
+/// parents, the chain goes through every group on the way.
+fn membership_path(
+    member: &EntityUid,
+    group: &EntityUid,
+    entities: &Entities,
+) -> Option<Vec<EntityUid>> {
+    let ancestors = |uid: &EntityUid| -> Vec<EntityUid> {
+        entities
+            .ancestors(uid)
+            .map(|ancestors| ancestors.cloned().collect())
+            .unwrap_or_default()
+    };
+    let reaches_group = |start: &EntityUid| {
+        let mut seen = HashSet::new();
+        let mut queue = vec![start.clone()];
+        while let Some(uid) = queue.pop() {
+            if uid == *group {
+                return true;
+            }
+            if seen.insert(uid.clone()) {
+                queue.extend(ancestors(&uid));
+            }
+        }
+        false
+    };
+    if member == group {
+        return None;
+    }
+    let mut path = vec![member.clone()];
+    while path.last() != Some(group) {
+        let last = path.last()?;
+        let candidates: Vec<_> = ancestors(last)
+            .into_iter()
+            .filter(|uid| !path.contains(uid) && reaches_group(uid))
+            .collect();
+        // the nearest candidate is not itself an ancestor of another
+        let next = candidates
+            .iter()
+            .filter(|uid| {
+                !candidates
+                    .iter()
+                    .any(|other| other != *uid && ancestors(other).contains(uid))
             })
+            .min_by_key(ToString::to_string)?
+            .clone();
+        path.push(next);
+    }
+    Some(path)
+}
+
+/// The policies that determined `response`, by id, in `format`. A policy
+/// without a JSON form is given as text.
+fn determining_policies(
+    response: &Response,
+    policies: &PolicySet,
+    format: PolicyFormat,
+) -> BTreeMap<String, serde_json::Value> {
+    response
+        .diagnostics()
+        .reason()
+        .filter_map(|id| {
+            let policy = policies.policy(id)?;
+            let policy = match format {
+                PolicyFormat::Json => policy.to_json().ok(),
+                PolicyFormat::Text => None,
+            }
+            .unwrap_or_else(|| serde_json::Value::String(policy.to_string()));
+            Some((id.to_string(), policy))
+        })
+        .collect()
+}
+
+/// Turn the answer to a call into the result returned to the caller
+fn answer_to_result(answer: AuthorizationAnswer) -> InterfaceResult {
+    match answer {
+        answer @ AuthorizationAnswer::Success { .. } => InterfaceResult::succeed(answer),
+        AuthorizationAnswer::ParseFailed { errors } => InterfaceResult::fail_bad_request(errors),
+        AuthorizationAnswer::LimitExceeded { errors } => {
+            InterfaceResult::fail_limit_exceeded(errors)
+        }
+        AuthorizationAnswer::RequestValidationFailed { errors } => {
+            InterfaceResult::fail_request_validation(errors)
         }
-        Err(errors) => AuthorizationAnswer::ParseFailed { errors },
     }
 }
 
@@ -64,17 +385,152 @@ fn is_authorized(call: AuthorizationCall) -> AuthorizationAnswer {
 /// or a single String which is a concatenation of multiple policies. If you choose the latter,
 /// policy id's will be auto-generated for you in the format `policyX` where X is a Natural Number (zero or a positive int)
 pub fn json_is_authorized(input: &str) -> InterfaceResult {
+    authorize_call(input, None)
+}
+
+/// public string-based JSON interface like [`json_is_authorized`], which
+/// measures the phases of the call with `clock` when the call sets
+/// `include_timings`
+///
+/// `clock` returns the current time in milliseconds, e.g. `performance.now()`
+/// in a browser. The response then includes `timings` with the time spent
+/// parsing policies, parsing entities, and evaluating the request.
+pub fn json_is_authorized_with_clock(input: &str, clock: &dyn Fn() -> f64) -> InterfaceResult {
+    authorize_call(input, Some(clock))
+}
+
+fn authorize_call(input: &str, clock: Option<&dyn Fn() -> f64>) -> InterfaceResult {
     serde_json::from_str::<AuthorizationCall>(input).map_or_else(
         |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
-        |call| match is_authorized(call) {
-            answer @ AuthorizationAnswer::Success { .. } => InterfaceResult::succeed(answer),
-            AuthorizationAnswer::ParseFailed { errors } => {
-                InterfaceResult::fail_bad_request(errors)
+        |call| answer_to_result(is_authorized(call, clock)),
+    )
+}
+
+/// public string-based JSON interface for answering a request against a
+/// policy set that the caller has already parsed
+///
+/// This is useful for callers holding a long-lived parsed policy set. The call
+/// has the same fields as the one accepted by [`json_is_authorized`], except
+/// that `entities` is given directly in place of the `slice`. A time budget in
+/// the call's `limits` is measured with `clock`, and is an error without one.
+pub fn json_is_authorized_with_policy_set(
+    input: &str,
+    policies: &PolicySet,
+    clock: Option<&dyn Fn() -> f64>,
+) -> InterfaceResult {
+    serde_json::from_str::<PolicySetAuthorizationCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| {
+            let limits = call.request.limits;
+            let details = call.request.details;
+            match call
+                .get_components()
+                .and_then(|(request, entities, duplicates)| {
+                    let limits = limits.map(|limits| limits.with_clock(clock)).transpose()?;
+                    Ok((request, entities, duplicates, limits))
+                }) {
+                Ok((request, entities, duplicates, limits)) => answer_to_result(
+                    authorize(&request, policies, &entities, limits, details)
+                        .with_duplicate_entities(duplicates),
+                ),
+                Err(error) => answer_to_result(error.into()),
+            }
+        },
+    )
+}
+
+/// public string-based JSON interface for answering a request against a
+/// policy set and entities that the caller has already parsed
+///
+/// The call has the same fields as the one accepted by [`json_is_authorized`],
+/// minus the `slice`. An already-parsed `schema` may be passed instead of
+/// giving one in the call; giving both is an error. A time budget in the
+/// call's `limits` is measured with `clock`, and is an error without one.
+pub fn json_is_authorized_with(
+    input: &str,
+    policies: &PolicySet,
+    entities: &Entities,
+    schema: Option<&Schema>,
+    clock: Option<&dyn Fn() -> f64>,
+) -> InterfaceResult {
+    json_is_authorized_with_error_mode(input, policies, entities, schema, clock, None, &|_, _| None)
+        .0
+}
+
+/// public string-based JSON interface like [`json_is_authorized_with`], for a
+/// caller that decides requests with an [`ErrorMode`] of its own
+///
+/// Once the request is answered, `decide` is given it with the response, and
+/// returns the decision of `error_mode` if that differs. The answer then
+/// reports that decision with `error_mode_applied`, and echoes the mode as
+/// `error_mode` if one is given or it changed the decision. The decision
+/// answered is returned alongside the result if the call succeeded, so that
+/// the caller need not parse it out again.
+pub fn json_is_authorized_with_error_mode(
+    input: &str,
+    policies: &PolicySet,
+    entities: &Entities,
+    schema: Option<&Schema>,
+    clock: Option<&dyn Fn() -> f64>,
+    error_mode: Option<ErrorMode>,
+    decide: &dyn Fn(&Request, &InterfaceResponse) -> Option<Decision>,
+) -> (InterfaceResult, Option<Decision>) {
+    serde_json::from_str::<AuthorizationRequestCall>(input).map_or_else(
+        |e| {
+            (
+                InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+                None,
+            )
+        },
+        |call| {
+            let limits = match call
+                .limits
+                .map(|limits| limits.with_clock(clock))
+                .transpose()
+            {
+                Ok(limits) => limits,
+                Err(errors) => return (InterfaceResult::fail_bad_request(errors), None),
+            };
+            let details = call.details;
+            let request = match schema {
+                Some(_) if call.schema.is_some() => Err(CallError::Parse(vec![
+                    "a schema was given both in the call and already parsed".to_string(),
+                ])),
+                Some(schema) => call.get_request_with_schema(schema),
+                None => call.get_request().map(|(request, _)| request),
+            };
+            match request {
+                Ok(request) => {
+                    let answer = authorize(&request, policies, entities, limits, details)
+                        .with_error_mode(&request, error_mode, decide);
+                    let decision = answer.decision();
+                    (answer_to_result(answer), decision)
+                }
+                Err(error) => (answer_to_result(error.into()), None),
             }
         },
     )
 }
 
+/// public string-based JSON interface for deciding one request for each of
+/// several actions, parsing the policies and entities only once
+///
+/// The call has the same fields as the one accepted by [`json_is_authorized`],
+/// except that it takes a list of `actions` in place of the `action`, and does
+/// not report diagnostics. The answer maps each action's UID to its decision.
+/// Actions for which no valid request can be made, e.g. because the action
+/// does not apply to the principal or resource types in the schema, are
+/// reported in `errors` instead of failing the whole call.
+pub fn json_is_authorized_actions(input: &str) -> InterfaceResult {
+    serde_json::from_str::<ActionsAuthorizationCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| match call.decide() {
+            Ok(answer) => InterfaceResult::succeed(answer),
+            Err(errors) => InterfaceResult::fail_bad_request(errors),
+        },
+    )
+}
+
 #[cfg(feature = "partial-eval")]
 fn is_authorized_partial(call: AuthorizationCall) -> PartialAuthorizationAnswer {
     match call.get_components_partial() {
@@ -126,9 +582,19 @@ pub struct InterfaceResponse {
     decision: Decision,
     /// Diagnostics providing more information on how this decision was reached
     diagnostics: InterfaceDiagnostics,
+    /// The `@obligation` annotations of the policies that determined the
+    /// decision, which the caller is expected to carry out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    obligations: Vec<InterfacePolicyAnnotation>,
+    /// The `@advice` annotations of the policies that determined the
+    /// decision, which the caller may act on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    advice: Vec<InterfacePolicyAnnotation>,
 }
 
-/// Interface version of `Diagnostics` that stores error messages as strings for simpler (de)serialization
+/// Interface version of `Diagnostics` that stores errors as `InterfaceAuthorizationError`s for simpler (de)serialization
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
@@ -137,16 +603,170 @@ pub struct InterfaceDiagnostics {
     /// If no policies applied to the request, this set will be empty.
     #[cfg_attr(feature = "wasm", tsify(type = "Set<String>"))]
     reason: HashSet<PolicyId>,
-    /// Set of error messages that occurred
-    errors: HashSet<String>,
+    /// The `permit` policies in `reason`, which allowed the request
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(type = "Set<String>"))]
+    determining_permits: HashSet<PolicyId>,
+    /// The `forbid` policies in `reason`, which explicitly denied the request
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(type = "Set<String>"))]
+    determining_forbids: HashSet<PolicyId>,
+    /// Whether the request was denied by default, because no `permit` policy
+    /// was satisfied and no `forbid` policy was either
+    #[serde(default)]
+    denied_by_default: bool,
+    /// Set of errors that occurred, keyed by the id of the erroring policy
+    errors: HashSet<InterfaceAuthorizationError>,
+    /// How the principal is a member of each group that a determining policy
+    /// requires it to be `in`, if the call set `include_membership_paths`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    membership_paths: Vec<InterfaceMembershipPath>,
+}
+
+/// How the principal is a member of a group that a policy requires it to be
+/// `in`, according to the entity hierarchy
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct InterfaceMembershipPath {
+    /// Id of the policy
+    policy: String,
+    /// UIDs from the principal to the group, each a member of the next, e.g.
+    /// `["User::\"alice\"", "Group::\"staff\"", "Group::\"everyone\""]`
+    path: Vec<String>,
+}
+
+/// Interface version of an `AuthorizationError`, recording which policy
+/// errored and what kind of error occurred alongside the error message
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct InterfaceAuthorizationError {
+    /// Id of the policy that errored during evaluation
+    #[cfg_attr(feature = "wasm", tsify(type = "string"))]
+    policy_id: PolicyId,
+    /// Kind of error that occurred
+    kind: AuthorizationErrorKind,
+    /// Error message
+    message: String,
+}
+
+/// Classification of the errors that can occur while evaluating a policy.
+/// Mirrors `EvaluationErrorKind`, without the payloads.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum AuthorizationErrorKind {
+    /// Tried to lookup an entity that didn't exist in the provided entities
+    EntityDoesNotExist,
+    /// Tried to get an attribute that the entity didn't have
+    EntityAttrDoesNotExist,
+    /// Tried to access an attribute of an unspecified entity
+    UnspecifiedEntityAccess,
+    /// Tried to get an attribute that the (non-entity) record didn't have
+    RecordAttrDoesNotExist,
+    /// An error occurred when looking up an extension function
+    FailedExtensionFunctionLookup,
+    /// Operation applied to values with incorrect types
+    TypeError,
+    /// Wrong number of arguments provided to an extension function
+    WrongNumArguments,
+    /// Overflow during an integer operation
+    IntegerOverflow,
+    /// Error with the use of "restricted" expressions
+    InvalidRestrictedExpression,
+    /// Policy evaluated with a slot that is not linked
+    UnlinkedSlot,
+    /// Evaluation error thrown by an extension function
+    FailedExtensionFunctionApplication,
+    /// Expression contains unknowns and cannot be reduced to a value
+    NonValue,
+    /// Maximum recursion limit reached for expression evaluation
+    RecursionLimit,
+    /// Evaluation exceeded a limit set by the caller
+    LimitExceeded,
+}
+
+impl From<&EvaluationErrorKind> for AuthorizationErrorKind {
+    fn from(kind: &EvaluationErrorKind) -> Self {
+        match kind {
+            EvaluationErrorKind::EntityDoesNotExist(_) => Self::EntityDoesNotExist,
+            EvaluationErrorKind::EntityAttrDoesNotExist { .. } => Self::EntityAttrDoesNotExist,
+            EvaluationErrorKind::UnspecifiedEntityAccess(_) => Self::UnspecifiedEntityAccess,
+            EvaluationErrorKind::RecordAttrDoesNotExist(_, _) => Self::RecordAttrDoesNotExist,
+            EvaluationErrorKind::FailedExtensionFunctionLookup(_) => {
+                Self::FailedExtensionFunctionLookup
+            }
+            EvaluationErrorKind::TypeError { .. } => Self::TypeError,
+            EvaluationErrorKind::WrongNumArguments { .. } => Self::WrongNumArguments,
+            EvaluationErrorKind::IntegerOverflow(_) => Self::IntegerOverflow,
+            EvaluationErrorKind::InvalidRestrictedExpression(_) => {
+                Self::InvalidRestrictedExpression
+            }
+            EvaluationErrorKind::UnlinkedSlot(_) => Self::UnlinkedSlot,
+            EvaluationErrorKind::FailedExtensionFunctionApplication { .. } => {
+                Self::FailedExtensionFunctionApplication
+            }
+            EvaluationErrorKind::NonValue(_) => Self::NonValue,
+            EvaluationErrorKind::RecursionLimit => Self::RecursionLimit,
+            EvaluationErrorKind::LimitExceeded(_) => Self::LimitExceeded,
+        }
+    }
+}
+
+impl InterfaceAuthorizationError {
+    /// Construct an `InterfaceAuthorizationError`
+    pub fn new(policy_id: PolicyId, kind: AuthorizationErrorKind, message: String) -> Self {
+        Self {
+            policy_id,
+            kind,
+            message,
+        }
+    }
+
+    /// Get the id of the policy that errored
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// Get the kind of error that occurred
+    pub fn kind(&self) -> AuthorizationErrorKind {
+        self.kind
+    }
+
+    /// Get the error message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<&AuthorizationError> for InterfaceAuthorizationError {
+    fn from(err: &AuthorizationError) -> Self {
+        match err {
+            AuthorizationError::PolicyEvaluationError { error, .. } => Self::new(
+                err.id().clone(),
+                error.error_kind().into(),
+                error.to_string(),
+            ),
+        }
+    }
 }
 
 impl InterfaceResponse {
     /// Construct an `InterfaceResponse`
-    pub fn new(decision: Decision, reason: HashSet<PolicyId>, errors: HashSet<String>) -> Self {
+    pub fn new(
+        decision: Decision,
+        reason: HashSet<PolicyId>,
+        errors: HashSet<InterfaceAuthorizationError>,
+    ) -> Self {
         Self {
             decision,
-            diagnostics: InterfaceDiagnostics { reason, errors },
+            diagnostics: InterfaceDiagnostics::new(Some(decision), reason, errors),
+            obligations: Vec::new(),
+            advice: Vec::new(),
         }
     }
 
@@ -159,6 +779,25 @@ impl InterfaceResponse {
     pub fn diagnostics(&self) -> &InterfaceDiagnostics {
         &self.diagnostics
     }
+
+    /// Get the `@obligation` annotations of the determining policies
+    pub fn obligations(&self) -> &[InterfacePolicyAnnotation] {
+        &self.obligations
+    }
+
+    /// Get the `@advice` annotations of the determining policies
+    pub fn advice(&self) -> &[InterfacePolicyAnnotation] {
+        &self.advice
+    }
+
+    /// Report `decision` in place of the one the authorizer made, as an
+    /// [`ErrorMode`] may
+    fn override_decision(&mut self, decision: Decision) {
+        self.decision = decision;
+        if decision == Decision::Allow {
+            self.diagnostics.denied_by_default = false;
+        }
+    }
 }
 
 impl From<Response> for InterfaceResponse {
@@ -169,7 +808,7 @@ impl From<Response> for InterfaceResponse {
             response
                 .diagnostics()
                 .errors()
-                .map(ToString::to_string)
+                .map(InterfaceAuthorizationError::from)
                 .collect(),
         )
     }
@@ -187,7 +826,7 @@ impl TryFrom<PartialResponse> for InterfaceResponse {
                 concrete
                     .diagnostics()
                     .errors()
-                    .map(ToString::to_string)
+                    .map(InterfaceAuthorizationError::from)
                     .collect(),
             )),
             PartialResponse::Residual(_) => Err(vec!["unsupported".into()]),
@@ -196,14 +835,59 @@ impl TryFrom<PartialResponse> for InterfaceResponse {
 }
 
 impl InterfaceDiagnostics {
+    /// Diagnostics for a response with `decision`, or for a residual response
+    /// if there is none. The policies determining a decision all have the
+    /// same effect: `permit` for `Allow`, and `forbid` for `Deny`.
+    fn new(
+        decision: Option<Decision>,
+        reason: HashSet<PolicyId>,
+        errors: HashSet<InterfaceAuthorizationError>,
+    ) -> Self {
+        let (determining_permits, determining_forbids) = match decision {
+            Some(Decision::Allow) => (reason.clone(), HashSet::new()),
+            Some(Decision::Deny) => (HashSet::new(), reason.clone()),
+            None => (HashSet::new(), HashSet::new()),
+        };
+        Self {
+            denied_by_default: decision == Some(Decision::Deny) && reason.is_empty(),
+            reason,
+            determining_permits,
+            determining_forbids,
+            errors,
+            membership_paths: Vec::new(),
+        }
+    }
+
     /// Get the policies that contributed to the decision
     pub fn reason(&self) -> impl Iterator<Item = &PolicyId> {
         self.reason.iter()
     }
 
+    /// Get the `permit` policies that contributed to the decision
+    pub fn determining_permits(&self) -> impl Iterator<Item = &PolicyId> {
+        self.determining_permits.iter()
+    }
+
+    /// Get the `forbid` policies that contributed to the decision
+    pub fn determining_forbids(&self) -> impl Iterator<Item = &PolicyId> {
+        self.determining_forbids.iter()
+    }
+
+    /// Whether the request was denied because no policy was satisfied, rather
+    /// than by a `forbid` policy
+    pub fn denied_by_default(&self) -> bool {
+        self.denied_by_default
+    }
+
     /// Get the errors
-    pub fn errors(&self) -> impl Iterator<Item = &str> + '_ {
-        self.errors.iter().map(String::as_str)
+    pub fn errors(&self) -> impl Iterator<Item = &InterfaceAuthorizationError> {
+        self.errors.iter()
+    }
+
+    /// Get the membership paths of the principal in the groups determining
+    /// policies require it to be `in`
+    pub fn membership_paths(&self) -> &[InterfaceMembershipPath] {
+        &self.membership_paths
     }
 }
 
@@ -224,11 +908,11 @@ impl InterfaceResidualResponse {
     pub fn new(
         residuals: HashMap<PolicyId, serde_json::Value>,
         reason: HashSet<PolicyId>,
-        errors: HashSet<String>,
+        errors: HashSet<InterfaceAuthorizationError>,
     ) -> Self {
         Self {
             residuals,
-            diagnostics: InterfaceDiagnostics { reason, errors },
+            diagnostics: InterfaceDiagnostics::new(None, reason, errors),
         }
     }
 }
@@ -254,7 +938,7 @@ impl TryFrom<PartialResponse> for InterfaceResidualResponse {
                 residual
                     .diagnostics()
                     .errors()
-                    .map(ToString::to_string)
+                    .map(InterfaceAuthorizationError::from)
                     .collect(),
             )),
             PartialResponse::Concrete(_) => Err(vec!["unsupported".into()]),
@@ -267,570 +951,2398 @@ impl TryFrom<PartialResponse> for InterfaceResidualResponse {
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 enum AuthorizationAnswer {
-    ParseFailed { errors: Vec<String> },
-    Success { response: InterfaceResponse },
-}
-
-#[cfg(feature = "partial-eval")]
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-enum PartialAuthorizationAnswer {
-    ParseFailed { errors: Vec<String> },
-    Concrete { response: InterfaceResponse },
-    Residuals { response: InterfaceResidualResponse },
+    ParseFailed {
+        errors: Vec<String>,
+    },
+    Success {
+        response: Box<InterfaceResponse>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        timings: Option<AuthorizationTimings>,
+        /// Entities given more than once in the call, and deduplicated
+        /// according to its `duplicate_entities` policy
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        duplicate_entities: Vec<DuplicateEntity>,
+        /// The outcome of evaluating each policy, if the call set
+        /// `include_policy_outcomes`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(
+            feature = "wasm",
+            tsify(optional, type = "Record<string, PolicyOutcome>")
+        )]
+        policy_outcomes: Option<BTreeMap<String, PolicyOutcome>>,
+        /// The evaluation of each policy, if the call asked for a `trace`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        trace: Option<Vec<InterfacePolicyTrace>>,
+        /// The entities evaluation looked up that are not in the call's
+        /// entities, if the call is `strict`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        missing_entities: Option<Vec<String>>,
+        /// The attributes evaluation read from entities that do not have
+        /// them, if the call is `strict`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        missing_attributes: Option<Vec<InterfaceMissingAttribute>>,
+        /// The policies that determined the decision, by id, if the call set
+        /// `include_determining_policies`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional, type = "Record<string, any>"))]
+        determining_policies: Option<BTreeMap<String, serde_json::Value>>,
+        /// Counters of the work evaluation did, if the call set
+        /// `include_metrics`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        metrics: Option<Box<AuthorizationMetrics>>,
+        /// The error mode the caller decided the request with, if it gave one
+        /// or the mode changed the decision
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        error_mode: Option<ErrorMode>,
+        /// Whether the error mode changed the decision the authorizer made
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        error_mode_applied: bool,
+    },
+    LimitExceeded {
+        errors: Vec<String>,
+    },
+    RequestValidationFailed {
+        errors: Vec<String>,
+    },
 }
 
-#[serde_as]
-#[derive(Debug, Serialize, Deserialize)]
+/// Limits on evaluating the request of a call
+///
+/// These let pathological policies or entity graphs fail the call instead of
+/// hanging the caller. Evaluation is aborted once it exceeds a limit, and the
+/// call fails with the `limitExceeded` code. Limits do not apply to partial
+/// evaluation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct AuthorizationCall {
-    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
-    principal: Option<JsonValueWithNoDuplicateKeys>,
-    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
-    action: JsonValueWithNoDuplicateKeys,
-    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
-    resource: Option<JsonValueWithNoDuplicateKeys>,
-    #[serde_as(as = "MapPreventDuplicates<_, _>")]
-    #[cfg_attr(feature = "wasm", tsify(optional, type = "Record<string, any>"))]
-    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
-    /// Optional schema in JSON format.
-    /// If present, this will inform the parsing: for instance, it will allow
-    /// `__entity` and `__extn` escapes to be implicit, and it will error if
-    /// attributes have the wrong types (e.g., string instead of integer).
-    #[serde(rename = "schema")]
-    #[cfg_attr(feature = "wasm", tsify(type = "Schema"))]
-    schema: Option<JsonValueWithNoDuplicateKeys>,
-    /// If this is `true` and a schema is provided, perform request validation.
-    /// If this is `false`, the schema will only be used for schema-based
-    /// parsing of `context`, and not for request validation.
-    /// If a schema is not provided, this option has no effect.
-    #[serde(default = "constant_true")]
-    enable_request_validation: bool,
-    slice: RecvdSlice,
+pub struct CallLimits {
+    /// Maximum number of evaluation steps, where each subexpression evaluated
+    /// counts as one step
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub max_steps: Option<u64>,
+    /// Milliseconds evaluation may take, checked periodically as it proceeds.
+    /// This requires the call to be made with a clock.
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub time_budget_ms: Option<u64>,
 }
 
-fn constant_true() -> bool {
-    true
+impl CallLimits {
+    /// The limits to evaluate with, measuring a time budget with `clock`
+    fn with_clock(
+        self,
+        clock: Option<&dyn Fn() -> f64>,
+    ) -> Result<EvaluationLimits<'_>, Vec<String>> {
+        let mut limits = EvaluationLimits::new();
+        if let Some(max_steps) = self.max_steps {
+            limits = limits.with_max_steps(max_steps);
+        }
+        if let Some(budget_ms) = self.time_budget_ms {
+            match clock {
+                Some(clock) => limits = limits.with_time_budget(clock, budget_ms),
+                None => {
+                    return Err(vec![
+                        "a time budget can only be given to a call made with a clock".to_string(),
+                    ])
+                }
+            }
+        }
+        Ok(limits)
+    }
 }
 
-fn parse_schema(
-    schema_json: Option<JsonValueWithNoDuplicateKeys>,
-) -> Result<Option<Schema>, Vec<String>> {
-    schema_json
-        .map(|v| Schema::from_json_value(v.into()))
-        .transpose()
-        .map_err(|e| vec![e.to_string()])
+impl AuthorizationAnswer {
+    /// Report `duplicates` in a successful answer
+    fn with_duplicate_entities(mut self, duplicates: Vec<DuplicateEntity>) -> Self {
+        if let Self::Success {
+            duplicate_entities, ..
+        } = &mut self
+        {
+            *duplicate_entities = duplicates;
+        }
+        self
+    }
+
+    /// Decide the request of a successful answer with the caller's
+    /// `error_mode`, as [`json_is_authorized_with_error_mode`] describes
+    fn with_error_mode(
+        mut self,
+        request: &Request,
+        error_mode: Option<ErrorMode>,
+        decide: &dyn Fn(&Request, &InterfaceResponse) -> Option<Decision>,
+    ) -> Self {
+        if let Self::Success {
+            response,
+            error_mode: answer_mode,
+            error_mode_applied,
+            ..
+        } = &mut self
+        {
+            let changed = decide(request, response);
+            if let Some(decision) = changed {
+                response.override_decision(decision);
+                *error_mode_applied = true;
+            }
+            if error_mode.is_some() || changed.is_some() {
+                *answer_mode = Some(error_mode.unwrap_or_default());
+            }
+        }
+        self
+    }
+
+    /// The decision of a successful answer
+    fn decision(&self) -> Option<Decision> {
+        match self {
+            Self::Success { response, .. } => Some(response.decision()),
+            _ => None,
+        }
+    }
 }
 
-fn parse_entity_uid(
-    entity_uid_json: Option<JsonValueWithNoDuplicateKeys>,
-    category: &str,
-) -> Result<Option<EntityUid>, Vec<String>> {
-    entity_uid_json
-        .map(|v| EntityUid::from_json(v.into()))
-        .transpose()
-        .map_err(|e| vec![format!("Failed to parse {category}"), e.to_string()])
+/// The decision a caller reports for a request whose answer may be wrong,
+/// because evaluating a policy failed, or because the caller left an entity
+/// of the request out of the entities; see
+/// [`json_is_authorized_with_error_mode`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum ErrorMode {
+    /// Deny a request on an entity the caller left out, and otherwise decide
+    /// as the authorizer does, denying a request that no policy determined
+    #[default]
+    FailClosed,
+    /// Allow a request that no policy determined while evaluating some
+    /// `permit` policy failed; a failing `forbid` policy never allows a
+    /// request
+    FailOpen,
 }
 
-fn parse_action(entity_uid_json: JsonValueWithNoDuplicateKeys) -> Result<EntityUid, Vec<String>> {
-    parse_entity_uid(Some(entity_uid_json), "action")?
-        .map_or_else(|| Err(vec!["parsing action return none".into()]), Ok)
+/// What to do when the entities of a call contain more than one record with
+/// the same UID
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum DuplicateEntityPolicy {
+    /// Fail the call, with an error for each duplicated UID
+    #[default]
+    Error,
+    /// Keep the first record for each UID and drop the rest
+    KeepFirst,
+    /// Merge the records for each UID into the first: attributes of later
+    /// records replace those of the same name, and parents are combined
+    Merge,
 }
 
-fn parse_context(
-    context_map: HashMap<String, JsonValueWithNoDuplicateKeys>,
-    schema_ref: Option<&Schema>,
-    action_ref: &EntityUid,
-) -> Result<Context, Vec<String>> {
-    let context = serde_json::to_value(context_map)
-        .map_err(|e| vec!["Failed to parse context".into(), e.to_string()])?;
-    Context::from_json_value(context, schema_ref.map(|s| (s, action_ref)))
-        .map_err(|e| vec![e.to_string()])
+/// An entity given more than once in a call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DuplicateEntity {
+    /// UID of the entity, e.g. `User::"alice"`
+    pub uid: String,
+    /// Number of records given for it
+    pub records: usize,
 }
 
-impl AuthorizationCall {
-    fn get_components(self) -> Result<(Request, PolicySet, Entities), Vec<String>> {
-        let schema = parse_schema(self.schema)?;
-        let principal = parse_entity_uid(self.principal, "principal")?;
-        let action = parse_action(self.action)?;
-        let resource = parse_entity_uid(self.resource, "resource")?;
-        let context = parse_context(self.context, schema.as_ref(), &action)?;
-        let q = Request::new(
-            principal,
-            Some(action),
-            resource,
-            context,
-            if self.enable_request_validation {
-                schema.as_ref()
-            } else {
-                None
-            },
-        )
-        .map_err(|e| [e.to_string()])?;
-        let (policies, entities) = self.slice.try_into(schema.as_ref())?;
-        Ok((q, policies, entities))
+/// Apply `policy` to any entity records in `entities` sharing a UID, returning
+/// the deduplicated entities and the UIDs that were duplicated. Records whose
+/// UID doesn't parse are passed through for the entity parser to report.
+fn deduplicate_entities(
+    entities: serde_json::Value,
+    policy: DuplicateEntityPolicy,
+) -> Result<(serde_json::Value, Vec<DuplicateEntity>), Vec<String>> {
+    let serde_json::Value::Array(records) = entities else {
+        return Ok((entities, Vec::new()));
+    };
+    let uids = records
+        .iter()
+        .map(|record| {
+            record
+                .get("uid")
+                .and_then(|uid| EntityUid::from_json(uid.clone()).ok())
+        })
+        .collect::<Vec<_>>();
+    let mut counts: HashMap<&EntityUid, usize> = HashMap::new();
+    let mut first_seen = Vec::new();
+    for uid in uids.iter().flatten() {
+        let count = counts.entry(uid).or_default();
+        if *count == 0 {
+            first_seen.push(uid);
+        }
+        *count += 1;
+    }
+    let duplicates = first_seen
+        .into_iter()
+        .filter_map(|uid| {
+            let records = counts.get(uid).copied().unwrap_or_default();
+            (records > 1).then(|| DuplicateEntity {
+                uid: uid.to_string(),
+                records,
+            })
+        })
+        .collect::<Vec<_>>();
+    if duplicates.is_empty() {
+        return Ok((serde_json::Value::Array(records), duplicates));
+    }
+    if policy == DuplicateEntityPolicy::Error {
+        return Err(duplicates
+            .iter()
+            .map(|d| format!("duplicate entity entry `{}` ({} records)", d.uid, d.records))
+            .collect());
     }
 
-    #[cfg(feature = "partial-eval")]
-    fn get_components_partial(self) -> Result<(Request, PolicySet, Entities), Vec<String>> {
-        let schema = parse_schema(self.schema)?;
-        let principal = parse_entity_uid(self.principal, "principal")?;
-        let action = parse_action(self.action)?;
-        let resource = parse_entity_uid(self.resource, "resource")?;
-        let context = parse_context(self.context, schema.as_ref(), &action)?;
-        let mut b = Request::builder().action(Some(action)).context(context);
-        if principal.is_some() {
-            b = b.principal(principal);
+    let mut kept: Vec<serde_json::Value> = Vec::with_capacity(records.len());
+    let mut positions: HashMap<&EntityUid, usize> = HashMap::new();
+    for (record, uid) in records.into_iter().zip(&uids) {
+        if let Some(&position) = uid.as_ref().and_then(|uid| positions.get(uid)) {
+            if policy == DuplicateEntityPolicy::Merge {
+                if let Some(first) = kept.get_mut(position) {
+                    merge_entity_records(first, record);
+                }
+            }
+        } else {
+            if let Some(uid) = uid {
+                positions.insert(uid, kept.len());
+            }
+            kept.push(record);
         }
-        if resource.is_some() {
-            b = b.resource(resource);
+    }
+    Ok((serde_json::Value::Array(kept), duplicates))
+}
+
+/// Merge the entity record `other` into `record`
+fn merge_entity_records(record: &mut serde_json::Value, other: serde_json::Value) {
+    let (Some(record), serde_json::Value::Object(mut other)) = (record.as_object_mut(), other)
+    else {
+        return;
+    };
+    if let Some(serde_json::Value::Object(other_attrs)) = other.remove("attrs") {
+        match record.get_mut("attrs") {
+            Some(serde_json::Value::Object(attrs)) => attrs.extend(other_attrs),
+            _ => {
+                record.insert("attrs".into(), serde_json::Value::Object(other_attrs));
+            }
         }
-        let q = if self.enable_request_validation {
-            match schema.as_ref() {
-                Some(schema_ref) => b.schema(schema_ref).build().map_err(|e| [e.to_string()])?,
-                None => b.build(),
+    }
+    if let Some(serde_json::Value::Array(other_parents)) = other.remove("parents") {
+        match record.get_mut("parents") {
+            Some(serde_json::Value::Array(parents)) => {
+                for parent in other_parents {
+                    if !parents.contains(&parent) {
+                        parents.push(parent);
+                    }
+                }
             }
-        } else {
-            b.build()
-        };
-        let (policies, entities) = self.slice.try_into(schema.as_ref())?;
-        Ok((q, policies, entities.partial()))
+            _ => {
+                record.insert("parents".into(), serde_json::Value::Array(other_parents));
+            }
+        }
     }
 }
 
-///
-/// Entity UID as strings.
-///
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An attribute that evaluation read from an entity that does not have it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct EntityUIDStrings {
-    ty: String,
-    eid: String,
+pub struct InterfaceMissingAttribute {
+    /// UID of the entity, e.g. `User::"alice"`
+    entity: String,
+    /// The attribute
+    attr: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The value of an annotation of a policy, e.g. of `@obligation("mfa")`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct Link {
-    slot: String,
-    value: EntityUIDStrings,
+pub struct InterfacePolicyAnnotation {
+    /// Id of the policy
+    policy: String,
+    /// The value of the annotation
+    value: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Interface version of a `PolicyTrace`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct TemplateLink {
-    /// Template ID to fill in
-    template_id: String,
+pub struct InterfacePolicyTrace {
+    /// Id of the policy
+    #[cfg_attr(feature = "wasm", tsify(type = "string"))]
+    policy_id: PolicyId,
+    /// Outcome of evaluating the policy
+    outcome: PolicyOutcome,
+    /// Steps of evaluating the policy, in the order they completed, so each
+    /// subexpression comes before the expression containing it
+    events: Vec<InterfaceTraceEvent>,
+}
 
-    /// Policy id for resulting concrete policy instance
-    result_policy_id: String,
+/// Interface version of a `TraceEvent`, tagged with its `kind`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum InterfaceTraceEvent {
+    /// A subexpression was evaluated to `value`, or raised `error`
+    Evaluated {
+        /// The subexpression, as Cedar text
+        expr: String,
+        /// The value (or residual) it evaluated to, as Cedar text
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        value: Option<String>,
+        /// The error it raised
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        error: Option<String>,
+    },
+    /// An attribute of an entity was read, or tested for with `has`
+    AttributeRead {
+        /// UID of the entity, e.g. `User::"alice"`
+        entity: String,
+        /// The attribute
+        attr: String,
+        /// Whether the entity has the attribute
+        present: bool,
+    },
+}
 
-    /// List of strings to fill in all slots in policy template "template_id".
-    /// (slot, String)
-    instantiations: Links,
+impl From<PolicyTrace> for InterfacePolicyTrace {
+    fn from(trace: PolicyTrace) -> Self {
+        Self {
+            policy_id: trace.policy_id().clone(),
+            outcome: trace.outcome(),
+            events: trace.events().cloned().map(Into::into).collect(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(try_from = "Vec<Link>")]
-#[serde(into = "Vec<Link>")]
+impl From<TraceEvent> for InterfaceTraceEvent {
+    fn from(event: TraceEvent) -> Self {
+        match event {
+            TraceEvent::Evaluated { expr, result } => Self::Evaluated {
+                expr,
+                value: result.as_ref().ok().cloned(),
+                error: result.err(),
+            },
+            TraceEvent::AttributeRead {
+                entity,
+                attr,
+                present,
+            } => Self::AttributeRead {
+                entity: entity.to_string(),
+                attr,
+                present,
+            },
+        }
+    }
+}
+
+/// Time spent in each phase of an authorization call, in milliseconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct Links(Vec<Link>);
+pub struct AuthorizationTimings {
+    /// Parsing policies and templates, including linking templates
+    pub parse_policies_ms: f64,
+    /// Parsing entities
+    pub parse_entities_ms: f64,
+    /// Evaluating the request
+    pub eval_ms: f64,
+}
 
-/// Error returned for duplicate link ids in a template instantiation
-#[derive(Debug, Clone, Diagnostic, Error)]
-pub enum DuplicateLinkError {
-    /// Duplicate instantiations for the same slot
-    #[error("duplicate instantiations of the slot(s): {}", .0.iter().map(|s| format!("`{s}`")).join(", "))]
-    Duplicates(Vec<String>),
+/// Counters of the work done evaluating the request of an authorization
+/// call, to spot policies that make a decision unexpectedly expensive
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct AuthorizationMetrics {
+    /// Number of policies evaluated
+    pub policies_evaluated: u64,
+    /// Number of times an entity was looked up, to read or test for an
+    /// attribute or to test its ancestors with `in`
+    pub entity_dereferences: u64,
+    /// The length of the longest chain of ancestors above the entity on the
+    /// left of an `in`, counting every ancestor on the chain
+    pub max_hierarchy_depth: u64,
 }
 
-impl TryFrom<Vec<Link>> for Links {
-    type Error = DuplicateLinkError;
+impl From<EvaluationMetrics> for AuthorizationMetrics {
+    fn from(metrics: EvaluationMetrics) -> Self {
+        Self {
+            policies_evaluated: metrics.policies_evaluated,
+            entity_dereferences: metrics.entity_dereferences,
+            max_hierarchy_depth: metrics.max_hierarchy_depth,
+        }
+    }
+}
 
-    fn try_from(links: Vec<Link>) -> Result<Self, Self::Error> {
-        let mut slots = links.iter().map(|link| &link.slot).collect::<Vec<_>>();
-        slots.sort();
-        let duplicates = slots
-            .into_iter()
-            .dedup_with_count()
-            .filter_map(|(count, slot)| if count == 1 { None } else { Some(slot) })
-            .cloned()
-            .collect::<Vec<_>>();
-        if duplicates.is_empty() {
-            Ok(Self(links))
+#[cfg(feature = "partial-eval")]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum PartialAuthorizationAnswer {
+    ParseFailed { errors: Vec<String> },
+    Concrete { response: InterfaceResponse },
+    Residuals { response: InterfaceResidualResponse },
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct AuthorizationCall {
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    principal: Option<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    action: JsonValueWithNoDuplicateKeys,
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    resource: Option<JsonValueWithNoDuplicateKeys>,
+    #[serde(default)]
+    #[serde_as(as = "ContextRecord")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, any> | null | \"\"")
+    )]
+    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    /// Values for context attributes that `context` leaves out. If this is
+    /// given along with a schema, optional attributes of the action's context
+    /// without a value here are also filled in, with a zero value for their
+    /// type: `false`, `0`, `""`, an empty set, or a record of zero values for
+    /// its required attributes. Entities and extension values have no zero
+    /// value, so those attributes are left out.
+    #[serde(default)]
+    #[serde_as(as = "Option<MapPreventDuplicates<_, _>>")]
+    #[cfg_attr(feature = "wasm", tsify(optional, type = "Record<string, any>"))]
+    context_defaults: Option<HashMap<String, JsonValueWithNoDuplicateKeys>>,
+    /// Optional schema in JSON format.
+    /// If present, this will inform the parsing: for instance, it will allow
+    /// `__entity` and `__extn` escapes to be implicit, and it will error if
+    /// attributes have the wrong types (e.g., string instead of integer).
+    #[serde(rename = "schema")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Schema"))]
+    schema: Option<JsonValueWithNoDuplicateKeys>,
+    /// If this is `true` and a schema is provided, perform request validation.
+    /// If this is `false`, the schema will only be used for schema-based
+    /// parsing of `context`, and not for request validation.
+    /// If a schema is not provided, this option has no effect.
+    #[serde(default = "constant_true")]
+    enable_request_validation: bool,
+    /// If this is `true`, the response includes `timings` for the phases of
+    /// the call. This requires a clock; see [`json_is_authorized_with_clock`].
+    #[serde(default)]
+    include_timings: bool,
+    /// Optional limits on evaluating the request
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    limits: Option<CallLimits>,
+    /// What to report about evaluating the policies, beyond the response
+    #[serde(flatten)]
+    details: AnswerDetails,
+    slice: RecvdSlice,
+}
+
+/// An authorization request on its own, for evaluating against a policy set
+/// and entities the caller has already parsed; see [`json_is_authorized_with`]
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct AuthorizationRequestCall {
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    principal: Option<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    action: JsonValueWithNoDuplicateKeys,
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    resource: Option<JsonValueWithNoDuplicateKeys>,
+    #[serde(default)]
+    #[serde_as(as = "ContextRecord")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, any> | null | \"\"")
+    )]
+    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    /// Values for context attributes that `context` leaves out, used as in
+    /// [`AuthorizationCall`]
+    #[serde(default)]
+    #[serde_as(as = "Option<MapPreventDuplicates<_, _>>")]
+    #[cfg_attr(feature = "wasm", tsify(optional, type = "Record<string, any>"))]
+    context_defaults: Option<HashMap<String, JsonValueWithNoDuplicateKeys>>,
+    /// Optional schema in JSON format, used as in [`AuthorizationCall`]
+    #[serde(rename = "schema")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Schema"))]
+    schema: Option<JsonValueWithNoDuplicateKeys>,
+    #[serde(default = "constant_true")]
+    enable_request_validation: bool,
+    /// Optional limits on evaluating the request, as in [`AuthorizationCall`]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    limits: Option<CallLimits>,
+    /// What to report about evaluating the policies, as in
+    /// [`AuthorizationCall`]
+    #[serde(flatten)]
+    details: AnswerDetails,
+}
+
+/// What a call asks to be told about evaluating the policies, beyond the
+/// response
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+#[allow(clippy::struct_excessive_bools)]
+struct AnswerDetails {
+    /// If this is `true`, the response includes a `trace` of the evaluation of
+    /// each policy: the subexpressions evaluated with their results, and the
+    /// entity attributes read, in order
+    #[serde(default)]
+    trace: bool,
+    /// If this is `true`, the response includes `policy_outcomes`, giving for
+    /// every policy whether it was satisfied, not satisfied, or errored, and
+    /// not only the policies that determined the decision
+    #[serde(default)]
+    include_policy_outcomes: bool,
+    /// If this is `true`, the response includes `missing_entities`, the
+    /// entities evaluation looked up that are not in the call's entities, and
+    /// `missing_attributes`, the attributes it read from entities that do not
+    /// have them. Policies error on missing data, which can make the decision
+    /// `Deny`; these tell such a denial apart from one the data supports.
+    #[serde(default)]
+    strict: bool,
+    /// If this is set, the response includes `determining_policies`, the
+    /// policies that determined the decision by id, as Cedar text or in the
+    /// JSON policy format, so they need not be looked up by id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    include_determining_policies: Option<PolicyFormat>,
+    /// If this is `true`, the diagnostics include `membership_paths`: for
+    /// each group a determining policy requires the principal to be `in`, the
+    /// chain of groups through which the principal is a member of it
+    #[serde(default)]
+    include_membership_paths: bool,
+    /// If this is `true`, the response includes `metrics`: the number of
+    /// policies evaluated, of entity lookups, and the deepest entity
+    /// hierarchy tested with `in`
+    #[serde(default)]
+    include_metrics: bool,
+}
+
+/// The form in which an answer gives policies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum PolicyFormat {
+    /// Cedar policy text, as given
+    Text,
+    /// The JSON policy format
+    Json,
+}
+
+impl AnswerDetails {
+    /// Whether the call asks for anything beyond the response
+    fn wants_report(self) -> bool {
+        self.trace || self.include_policy_outcomes || self.strict || self.include_metrics
+    }
+
+    /// What the report must record beyond the outcomes and metrics. Only
+    /// `strict` calls need the missing entities and attributes.
+    fn report_details(self) -> ReportDetails {
+        let details = ReportDetails::new();
+        let details = if self.trace {
+            details.with_trace()
         } else {
-            Err(DuplicateLinkError::Duplicates(duplicates))
+            details
+        };
+        if self.strict {
+            details.with_missing_data()
+        } else {
+            details
         }
     }
 }
 
-impl From<Links> for Vec<Link> {
-    fn from(value: Links) -> Self {
-        value.0
+impl AuthorizationRequestCall {
+    fn get_request(self) -> Result<(Request, Option<Schema>), CallError> {
+        let schema = parse_schema(self.schema)?;
+        let q = parse_request(
+            self.principal,
+            self.action,
+            self.resource,
+            self.context,
+            self.context_defaults,
+            schema.as_ref(),
+            self.enable_request_validation,
+        )?;
+        Ok((q, schema))
+    }
+
+    fn get_request_with_schema(self, schema: &Schema) -> Result<Request, CallError> {
+        parse_request(
+            self.principal,
+            self.action,
+            self.resource,
+            self.context,
+            self.context_defaults,
+            Some(schema),
+            self.enable_request_validation,
+        )
     }
 }
 
-/// policies must either be a single policy per entry, or only one entry with more than one policy
-#[serde_as]
+/// An authorization call against an already-parsed policy set; see
+/// [`json_is_authorized_with_policy_set`]
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-struct RecvdSlice {
-    policies: PolicySpecification,
-    /// JSON object containing the entities data, in "natural JSON" form -- same
-    /// format as expected by EntityJsonParser
+struct PolicySetAuthorizationCall {
+    #[serde(flatten)]
+    request: AuthorizationRequestCall,
+    /// Entities in the same format as the `entities` of a [`RecvdSlice`]
     #[cfg_attr(feature = "wasm", tsify(type = "Array<EntityJson>"))]
     entities: JsonValueWithNoDuplicateKeys,
+    /// What to do with entities given more than once, as in a [`RecvdSlice`]
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    duplicate_entities: DuplicateEntityPolicy,
+}
 
-    /// Optional template policies.
-    #[serde_as(as = "Option<MapPreventDuplicates<_, _>>")]
-    templates: Option<HashMap<String, String>>,
+impl PolicySetAuthorizationCall {
+    fn get_components(self) -> Result<(Request, Entities, Vec<DuplicateEntity>), CallError> {
+        let (q, schema) = self.request.get_request()?;
+        let (entities, duplicates) =
+            deduplicate_entities(self.entities.into(), self.duplicate_entities)?;
+        let entities = Entities::from_json_value(entities, schema.as_ref())
+            .map_err(|e| vec![e.to_string()])?;
+        Ok((q, entities, duplicates))
+    }
+}
 
-    /// Optional template instantiations.
-    /// List of instantiations, one per
-    /// If present, instantiate policies
-    template_instantiations: Option<Vec<TemplateLink>>,
+/// Why the request, policies, or entities of a call could not be built
+#[derive(Debug)]
+enum CallError {
+    /// Part of the call could not be parsed
+    Parse(Vec<String>),
+    /// The request is not valid according to the schema, e.g. because its
+    /// action does not apply to its principal or resource types
+    RequestValidation(Vec<String>),
 }
 
-fn parse_instantiation(v: &Link) -> Result<(SlotId, EntityUid), Vec<String>> {
-    let slot = match v.slot.as_str() {
-        "?principal" => SlotId::principal(),
-        "?resource" => SlotId::resource(),
-        _ => {
-            return Err(vec![
-                "Slot must by \"?principal\" or \"?resource\"".to_string()
-            ]);
-        }
-    };
-    let type_name = EntityTypeName::from_str(v.value.ty.as_str());
-    let eid = match EntityId::from_str(v.value.eid.as_str()) {
-        Ok(eid) => eid,
-        Err(err) => match err {},
-    };
-    match type_name {
-        Ok(type_name) => {
-            let entity_uid = EntityUid::from_type_name_and_id(type_name, eid);
-            Ok((slot, entity_uid))
-        }
-        Err(e) => Err(e.errors_as_strings()),
+impl From<Vec<String>> for CallError {
+    fn from(errors: Vec<String>) -> Self {
+        Self::Parse(errors)
     }
 }
 
-fn parse_instantiations(
-    policies: &mut PolicySet,
-    instantiation: TemplateLink,
-) -> Result<(), Vec<String>> {
-    let template_id = PolicyId::from_str(instantiation.template_id.as_str());
-    let instance_id = PolicyId::from_str(instantiation.result_policy_id.as_str());
-    match (template_id, instance_id) {
-        (Err(never), _) | (_, Err(never)) => match never {},
-        (Ok(template_id), Ok(instance_id)) => {
-            let mut vals = HashMap::new();
-            for i in instantiation.instantiations.0 {
-                match parse_instantiation(&i) {
-                    Err(e) => return Err(e),
-                    Ok(val) => vals.insert(val.0, val.1),
-                };
-            }
-            match policies.link(template_id, instance_id, vals) {
-                Ok(()) => Ok(()),
-                Err(e) => Err(vec![format!("Error instantiating template: {e}")]),
-            }
+impl From<CallError> for AuthorizationAnswer {
+    fn from(error: CallError) -> Self {
+        match error {
+            CallError::Parse(errors) => Self::ParseFailed { errors },
+            CallError::RequestValidation(errors) => Self::RequestValidationFailed { errors },
         }
     }
 }
 
-impl RecvdSlice {
-    #[allow(clippy::too_many_lines)]
-    fn try_into(self, schema: Option<&Schema>) -> Result<(PolicySet, Entities), Vec<String>> {
-        let Self {
-            policies,
-            entities,
-            templates,
-            template_instantiations,
-        } = self;
-
-        let policy_set = match policies {
-            PolicySpecification::Concatenated(policies) => match PolicySet::from_str(&policies) {
-                Ok(ps) => Ok(ps),
-                Err(parse_errors) => Err(std::iter::once(
-                    "couldn't parse concatenated policies string".to_string(),
-                )
-                .chain(parse_errors.errors_as_strings())
-                .collect()),
-            },
-            PolicySpecification::Map(policies) => {
-                parse_policy_set_from_individual_policies(&policies, templates)
-            }
-        };
-
-        let mut errs = Vec::new();
+fn constant_true() -> bool {
+    true
+}
 
-        let (mut policies, entities) = match (
-            Entities::from_json_value(entities.into(), schema),
-            policy_set,
-        ) {
-            (Ok(entities), Ok(policies)) => (policies, entities),
+fn parse_schema(
+    schema_json: Option<JsonValueWithNoDuplicateKeys>,
+) -> Result<Option<Schema>, Vec<String>> {
+    schema_json
+        .map(|v| Schema::from_json_value(v.into()))
+        .transpose()
+        .map_err(|e| vec![e.to_string()])
+}
+
+fn parse_entity_uid(
+    entity_uid_json: Option<JsonValueWithNoDuplicateKeys>,
+    category: &str,
+) -> Result<Option<EntityUid>, Vec<String>> {
+    entity_uid_json
+        .map(|v| EntityUid::from_json(v.into()))
+        .transpose()
+        .map_err(|e| vec![format!("Failed to parse {category}"), e.to_string()])
+}
+
+fn parse_action(entity_uid_json: JsonValueWithNoDuplicateKeys) -> Result<EntityUid, Vec<String>> {
+    parse_entity_uid(Some(entity_uid_json), "action")?
+        .map_or_else(|| Err(vec!["parsing action return none".into()]), Ok)
+}
+
+fn parse_context(
+    mut context_map: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    context_defaults: Option<HashMap<String, JsonValueWithNoDuplicateKeys>>,
+    schema_ref: Option<&Schema>,
+    action_ref: &EntityUid,
+) -> Result<Context, Vec<String>> {
+    if let Some(defaults) = context_defaults {
+        fill_context_defaults(&mut context_map, defaults, schema_ref, action_ref);
+    }
+    if context_map.is_empty() {
+        let required = required_context_attrs(schema_ref, action_ref);
+        if !required.is_empty() {
+            return Err(vec![format!(
+                "no context was given, but the context of `{action_ref}` requires the attributes {}",
+                required.iter().map(|attr| format!("`{attr}`")).join(", ")
+            )]);
+        }
+    }
+    let context = serde_json::to_value(context_map)
+        .map_err(|e| vec!["Failed to parse context".into(), e.to_string()])?;
+    Context::from_json_value(context, schema_ref.map(|s| (s, action_ref)))
+        .map_err(|e| vec![e.to_string()])
+}
+
+/// The attributes `schema` requires in the context of `action`, if any
+fn required_context_attrs(schema: Option<&Schema>, action: &EntityUid) -> Vec<String> {
+    match schema.and_then(|schema| schema.context_type(action)) {
+        Some(Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. })) => attrs
+            .iter()
+            .filter(|(_, attr_type)| attr_type.is_required)
+            .map(|(attr, _)| attr.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fill in the attributes `context` leaves out with their value in `defaults`
+/// or, for the optional attributes `schema` declares for the context of
+/// `action`, with a zero value of their type
+fn fill_context_defaults(
+    context: &mut HashMap<String, JsonValueWithNoDuplicateKeys>,
+    defaults: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    schema: Option<&Schema>,
+    action: &EntityUid,
+) {
+    for (attr, value) in defaults {
+        context.entry(attr).or_insert(value);
+    }
+    if let Some(Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. })) =
+        schema.and_then(|schema| schema.context_type(action))
+    {
+        for (attr, attr_type) in attrs.iter().filter(|(_, attr_type)| !attr_type.is_required) {
+            if !context.contains_key(attr.as_str()) {
+                if let Some(value) = zero_value(&attr_type.attr_type) {
+                    context.insert(attr.to_string(), value.into());
+                }
+            }
+        }
+    }
+}
+
+/// A zero value of `ty` in the JSON format of Cedar values, if it has one
+fn zero_value(ty: &Type) -> Option<serde_json::Value> {
+    match ty {
+        Type::True => Some(serde_json::Value::Bool(true)),
+        Type::False
+        | Type::Primitive {
+            primitive_type: Primitive::Bool,
+        } => Some(serde_json::Value::Bool(false)),
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => Some(serde_json::Value::from(0)),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        } => Some(serde_json::Value::String(String::new())),
+        Type::Set { .. } => Some(serde_json::Value::Array(Vec::new())),
+        Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. }) => attrs
+            .iter()
+            .filter(|(_, attr_type)| attr_type.is_required)
+            .map(|(attr, attr_type)| Some((attr.to_string(), zero_value(&attr_type.attr_type)?)))
+            .collect::<Option<serde_json::Map<_, _>>>()
+            .map(serde_json::Value::Object),
+        Type::Never | Type::EntityOrRecord(_) | Type::ExtensionType { .. } => None,
+    }
+}
+
+fn parse_request(
+    principal: Option<JsonValueWithNoDuplicateKeys>,
+    action: JsonValueWithNoDuplicateKeys,
+    resource: Option<JsonValueWithNoDuplicateKeys>,
+    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    context_defaults: Option<HashMap<String, JsonValueWithNoDuplicateKeys>>,
+    schema: Option<&Schema>,
+    enable_request_validation: bool,
+) -> Result<Request, CallError> {
+    let principal = parse_entity_uid(principal, "principal")?;
+    let action = parse_action(action)?;
+    let resource = parse_entity_uid(resource, "resource")?;
+    let context = parse_context(context, context_defaults, schema, &action)?;
+    Request::new(
+        principal,
+        Some(action),
+        resource,
+        context,
+        if enable_request_validation {
+            schema
+        } else {
+            None
+        },
+    )
+    .map_err(|e| CallError::RequestValidation(vec![e.to_string()]))
+}
+
+impl AuthorizationCall {
+    fn get_components(
+        self,
+        stopwatch: &mut Stopwatch<'_>,
+        timings: &mut AuthorizationTimings,
+    ) -> Result<(Request, PolicySet, Entities, Vec<DuplicateEntity>), CallError> {
+        let schema = parse_schema(self.schema)?;
+        let q = parse_request(
+            self.principal,
+            self.action,
+            self.resource,
+            self.context,
+            self.context_defaults,
+            schema.as_ref(),
+            self.enable_request_validation,
+        )?;
+        // parsing the request is not one of the measured phases
+        stopwatch.lap();
+        let (policies, entities, duplicates) =
+            self.slice.try_into(schema.as_ref(), stopwatch, timings)?;
+        Ok((q, policies, entities, duplicates))
+    }
+
+    #[cfg(feature = "partial-eval")]
+    fn get_components_partial(self) -> Result<(Request, PolicySet, Entities), Vec<String>> {
+        let schema = parse_schema(self.schema)?;
+        let principal = parse_entity_uid(self.principal, "principal")?;
+        let action = parse_action(self.action)?;
+        let resource = parse_entity_uid(self.resource, "resource")?;
+        let context = parse_context(
+            self.context,
+            self.context_defaults,
+            schema.as_ref(),
+            &action,
+        )?;
+        let mut b = Request::builder().action(Some(action)).context(context);
+        if principal.is_some() {
+            b = b.principal(principal);
+        }
+        if resource.is_some() {
+            b = b.resource(resource);
+        }
+        let q = if self.enable_request_validation {
+            match schema.as_ref() {
+                Some(schema_ref) => b.schema(schema_ref).build().map_err(|e| [e.to_string()])?,
+                None => b.build(),
+            }
+        } else {
+            b.build()
+        };
+        let (policies, entities, _) = self.slice.try_into(
+            schema.as_ref(),
+            &mut Stopwatch::start(None),
+            &mut AuthorizationTimings::default(),
+        )?;
+        Ok((q, policies, entities.partial()))
+    }
+}
+
+/// A request for each of several actions, decided against one slice; see
+/// [`json_is_authorized_actions`]
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct ActionsAuthorizationCall {
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    principal: Option<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(type = "Array<string|{type: string, id: string}>")
+    )]
+    actions: Vec<JsonValueWithNoDuplicateKeys>,
+    #[cfg_attr(feature = "wasm", tsify(type = "string|{type: string, id: string}"))]
+    resource: Option<JsonValueWithNoDuplicateKeys>,
+    #[serde(default)]
+    #[serde_as(as = "ContextRecord")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, any> | null | \"\"")
+    )]
+    context: HashMap<String, JsonValueWithNoDuplicateKeys>,
+    /// Values for context attributes that `context` leaves out, used as in
+    /// [`AuthorizationCall`]
+    #[serde(default)]
+    #[serde_as(as = "Option<MapPreventDuplicates<_, _>>")]
+    #[cfg_attr(feature = "wasm", tsify(optional, type = "Record<string, any>"))]
+    context_defaults: Option<HashMap<String, JsonValueWithNoDuplicateKeys>>,
+    /// Optional schema in JSON format, used as in [`AuthorizationCall`]
+    #[serde(rename = "schema")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Schema"))]
+    schema: Option<JsonValueWithNoDuplicateKeys>,
+    /// Whether to validate each request against the schema, as in
+    /// [`AuthorizationCall`]
+    #[serde(default = "constant_true")]
+    enable_request_validation: bool,
+    slice: RecvdSlice,
+}
+
+impl ActionsAuthorizationCall {
+    fn decide(self) -> Result<ActionsAuthorizationAnswer, Vec<String>> {
+        let schema = parse_schema(self.schema)?;
+        let (policies, entities, _) = self.slice.try_into(
+            schema.as_ref(),
+            &mut Stopwatch::start(None),
+            &mut AuthorizationTimings::default(),
+        )?;
+        let mut answer = ActionsAuthorizationAnswer::default();
+        for action_json in self.actions {
+            let action = parse_action(action_json.clone())?.to_string();
+            match parse_request(
+                self.principal.clone(),
+                action_json,
+                self.resource.clone(),
+                self.context.clone(),
+                self.context_defaults.clone(),
+                schema.as_ref(),
+                self.enable_request_validation,
+            ) {
+                Ok(request) => {
+                    let response = AUTHORIZER.with(|authorizer| {
+                        authorizer.is_authorized(&request, &policies, &entities)
+                    });
+                    answer.decisions.insert(action, response.decision());
+                }
+                Err(CallError::Parse(errors) | CallError::RequestValidation(errors)) => {
+                    answer.errors.insert(action, errors);
+                }
+            }
+        }
+        Ok(answer)
+    }
+}
+
+/// The decision for each action of an [`ActionsAuthorizationCall`], keyed by
+/// the action's UID, e.g. `Action::"view"`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct ActionsAuthorizationAnswer {
+    decisions: BTreeMap<String, Decision>,
+    /// Why no request could be made for the remaining actions
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    errors: BTreeMap<String, Vec<String>>,
+}
+
+///
+/// Entity UID as strings.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct EntityUIDStrings {
+    ty: String,
+    eid: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct Link {
+    slot: String,
+    value: EntityUIDStrings,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct TemplateLink {
+    /// Template ID to fill in
+    template_id: String,
+
+    /// Policy id for resulting concrete policy instance
+    result_policy_id: String,
+
+    /// List of strings to fill in all slots in policy template "template_id".
+    /// (slot, String)
+    instantiations: Links,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Link>")]
+#[serde(into = "Vec<Link>")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct Links(Vec<Link>);
+
+/// Error returned for duplicate link ids in a template instantiation
+#[derive(Debug, Clone, Diagnostic, Error)]
+pub enum DuplicateLinkError {
+    /// Duplicate instantiations for the same slot
+    #[error("duplicate instantiations of the slot(s): {}", .0.iter().map(|s| format!("`{s}`")).join(", "))]
+    Duplicates(Vec<String>),
+}
+
+impl TryFrom<Vec<Link>> for Links {
+    type Error = DuplicateLinkError;
+
+    fn try_from(links: Vec<Link>) -> Result<Self, Self::Error> {
+        let mut slots = links.iter().map(|link| &link.slot).collect::<Vec<_>>();
+        slots.sort();
+        let duplicates = slots
+            .into_iter()
+            .dedup_with_count()
+            .filter_map(|(count, slot)| if count == 1 { None } else { Some(slot) })
+            .cloned()
+            .collect::<Vec<_>>();
+        if duplicates.is_empty() {
+            Ok(Self(links))
+        } else {
+            Err(DuplicateLinkError::Duplicates(duplicates))
+        }
+    }
+}
+
+impl From<Links> for Vec<Link> {
+    fn from(value: Links) -> Self {
+        value.0
+    }
+}
+
+/// A link of the template `template_id`, producing the policy `new_id`, in
+/// the format used for template links in policy set JSON
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct TemplateLinkValues {
+    template_id: String,
+    new_id: String,
+    /// Values for the slots, keyed by `?principal` or `?resource`
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(type = "Record<string, {type: string, id: string}>")
+    )]
+    values: HashMap<String, JsonValueWithNoDuplicateKeys>,
+}
+
+/// policies must either be a single policy per entry, or only one entry with more than one policy
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct RecvdSlice {
+    policies: PolicySpecification,
+    /// JSON object containing the entities data, in "natural JSON" form -- same
+    /// format as expected by EntityJsonParser
+    #[cfg_attr(feature = "wasm", tsify(type = "Array<EntityJson>"))]
+    entities: JsonValueWithNoDuplicateKeys,
+
+    /// Optional template policies.
+    #[serde_as(as = "Option<MapPreventDuplicates<_, _>>")]
+    templates: Option<HashMap<String, String>>,
+
+    /// Optional template instantiations.
+    /// List of instantiations, one per
+    /// If present, instantiate policies
+    template_instantiations: Option<Vec<TemplateLink>>,
+
+    /// Optional links of templates, each giving the values for the slots of
+    /// its template by slot. These are linked after `template_instantiations`.
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    template_links: Option<Vec<TemplateLinkValues>>,
+
+    /// What to do with entities given more than once. By default the call
+    /// fails, naming each duplicated UID.
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    duplicate_entities: DuplicateEntityPolicy,
+}
+
+fn parse_instantiation(v: &Link) -> Result<(SlotId, EntityUid), Vec<String>> {
+    let slot = match v.slot.as_str() {
+        "?principal" => SlotId::principal(),
+        "?resource" => SlotId::resource(),
+        _ => {
+            return Err(vec![
+                "Slot must by \"?principal\" or \"?resource\"".to_string()
+            ]);
+        }
+    };
+    let type_name = EntityTypeName::from_str(v.value.ty.as_str());
+    let eid = match EntityId::from_str(v.value.eid.as_str()) {
+        Ok(eid) => eid,
+        Err(err) => match err {},
+    };
+    match type_name {
+        Ok(type_name) => {
+            let entity_uid = EntityUid::from_type_name_and_id(type_name, eid);
+            Ok((slot, entity_uid))
+        }
+        Err(e) => Err(e.errors_as_strings()),
+    }
+}
+
+fn link_template(policies: &mut PolicySet, link: TemplateLinkValues) -> Result<(), Vec<String>> {
+    let values = link
+        .values
+        .into_iter()
+        .map(|(slot, value)| {
+            let slot = match slot.as_str() {
+                "?principal" => SlotId::principal(),
+                "?resource" => SlotId::resource(),
+                _ => {
+                    return Err(vec![format!(
+                        "in link `{}`: slot must be \"?principal\" or \"?resource\", not `{slot}`",
+                        link.new_id
+                    )])
+                }
+            };
+            EntityUid::from_json(value.into())
+                .map(|uid| (slot, uid))
+                .map_err(|e| vec![format!("in link `{}`: {e}", link.new_id)])
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    match (
+        PolicyId::from_str(&link.template_id),
+        PolicyId::from_str(&link.new_id),
+    ) {
+        (Err(never), _) | (_, Err(never)) => match never {},
+        (Ok(template_id), Ok(new_id)) => policies
+            .link(template_id, new_id, values)
+            .map_err(|e| vec![format!("Error instantiating template: {e}")]),
+    }
+}
+
+fn parse_instantiations(
+    policies: &mut PolicySet,
+    instantiation: TemplateLink,
+) -> Result<(), Vec<String>> {
+    let template_id = PolicyId::from_str(instantiation.template_id.as_str());
+    let instance_id = PolicyId::from_str(instantiation.result_policy_id.as_str());
+    match (template_id, instance_id) {
+        (Err(never), _) | (_, Err(never)) => match never {},
+        (Ok(template_id), Ok(instance_id)) => {
+            let mut vals = HashMap::new();
+            for i in instantiation.instantiations.0 {
+                match parse_instantiation(&i) {
+                    Err(e) => return Err(e),
+                    Ok(val) => vals.insert(val.0, val.1),
+                };
+            }
+            match policies.link(template_id, instance_id, vals) {
+                Ok(()) => Ok(()),
+                Err(e) => Err(vec![format!("Error instantiating template: {e}")]),
+            }
+        }
+    }
+}
+
+impl RecvdSlice {
+    #[allow(clippy::too_many_lines)]
+    fn try_into(
+        self,
+        schema: Option<&Schema>,
+        stopwatch: &mut Stopwatch<'_>,
+        timings: &mut AuthorizationTimings,
+    ) -> Result<(PolicySet, Entities, Vec<DuplicateEntity>), Vec<String>> {
+        let Self {
+            policies,
+            entities,
+            templates,
+            template_instantiations,
+            template_links,
+            duplicate_entities,
+        } = self;
+
+        let policy_set = match policies {
+            PolicySpecification::Concatenated(policies) => match PolicySet::from_str(&policies) {
+                Ok(ps) => Ok(ps),
+                Err(parse_errors) => Err(std::iter::once(
+                    "couldn't parse concatenated policies string".to_string(),
+                )
+                .chain(parse_errors.errors_as_strings())
+                .collect()),
+            },
+            PolicySpecification::Map(policies) => {
+                parse_policy_set_from_individual_policies(&policies, templates)
+            }
+            PolicySpecification::Json(policies) => parse_policy_set_from_json(policies, templates),
+        };
+        timings.parse_policies_ms = stopwatch.lap();
+        let (entities, duplicates) = match deduplicate_entities(entities.into(), duplicate_entities)
+        {
+            Ok((entities, duplicates)) => (
+                Entities::from_json_value(entities, schema).map_err(|e| vec![e.to_string()]),
+                duplicates,
+            ),
+            Err(errors) => (Err(errors), Vec::new()),
+        };
+        timings.parse_entities_ms = stopwatch.lap();
+
+        let mut errs = Vec::new();
+
+        let (mut policies, entities) = match (entities, policy_set) {
+            (Ok(entities), Ok(policies)) => (policies, entities),
             (Ok(_), Err(policy_parse_errors)) => {
                 errs.extend(policy_parse_errors);
                 (PolicySet::new(), Entities::empty())
             }
             (Err(e), Ok(_)) => {
-                errs.push(e.to_string());
+                errs.extend(e);
+                (PolicySet::new(), Entities::empty())
+            }
+            (Err(e), Err(policy_parse_errors)) => {
+                errs.extend(e);
+                errs.extend(policy_parse_errors);
                 (PolicySet::new(), Entities::empty())
             }
-            (Err(e), Err(policy_parse_errors)) => {
-                errs.push(e.to_string());
-                errs.extend(policy_parse_errors);
-                (PolicySet::new(), Entities::empty())
+        };
+
+        if let Some(t_inst_list) = template_instantiations {
+            for instantiation in t_inst_list {
+                match parse_instantiations(&mut policies, instantiation) {
+                    Ok(()) => (),
+                    Err(err) => errs.extend(err),
+                }
+            }
+        }
+        for link in template_links.into_iter().flatten() {
+            if let Err(err) = link_template(&mut policies, link) {
+                errs.extend(err);
+            }
+        }
+        timings.parse_policies_ms += stopwatch.lap();
+
+        if errs.is_empty() {
+            Ok((policies, entities, duplicates))
+        } else {
+            Err(errs)
+        }
+    }
+}
+
+fn parse_policy_set_from_individual_policies(
+    policies: &HashMap<String, String>,
+    templates: Option<HashMap<String, String>>,
+) -> Result<PolicySet, Vec<String>> {
+    let mut policy_set = PolicySet::new();
+    let mut errs = Vec::new();
+    for (id, policy_src) in policies {
+        match Policy::parse(Some(id.clone()), policy_src) {
+            Ok(p) => match policy_set.add(p) {
+                Ok(()) => {}
+                Err(err) => {
+                    errs.push(format!("couldn't add policy to set due to error: {err}"));
+                }
+            },
+            Err(pes) => errs.extend(
+                std::iter::once(format!("couldn't parse policy with id `{id}`"))
+                    .chain(pes.errors_as_strings().into_iter()),
+            ),
+        }
+    }
+
+    add_templates(&mut policy_set, templates, &mut errs);
+
+    if errs.is_empty() {
+        Ok(policy_set)
+    } else {
+        Err(errs)
+    }
+}
+
+fn parse_policy_set_from_json(
+    policies: Vec<PolicyJsonWithId>,
+    templates: Option<HashMap<String, String>>,
+) -> Result<PolicySet, Vec<String>> {
+    let mut policy_set = PolicySet::new();
+    let mut errs = Vec::new();
+    for PolicyJsonWithId { id, policy } in policies {
+        let policy_id = match PolicyId::from_str(&id) {
+            Ok(policy_id) => policy_id,
+            Err(never) => match never {},
+        };
+        let policy: serde_json::Value = policy.into();
+        let added = match Policy::from_json(Some(policy_id.clone()), policy.clone()) {
+            Ok(p) => policy_set
+                .add(p)
+                .map_err(|err| format!("couldn't add policy to set due to error: {err}")),
+            Err(FromJsonError::TemplateToPolicy(_)) => {
+                match Template::from_json(Some(policy_id), policy) {
+                    Ok(t) => policy_set
+                        .add_template(t)
+                        .map_err(|err| format!("couldn't add policy to set due to error: {err}")),
+                    Err(err) => Err(format!(
+                        "couldn't parse JSON of policy with id `{id}`: {err}"
+                    )),
+                }
+            }
+            Err(err) => Err(format!(
+                "couldn't parse JSON of policy with id `{id}`: {err}"
+            )),
+        };
+        if let Err(err) = added {
+            errs.push(err);
+        }
+    }
+
+    add_templates(&mut policy_set, templates, &mut errs);
+
+    if errs.is_empty() {
+        Ok(policy_set)
+    } else {
+        Err(errs)
+    }
+}
+
+fn add_templates(
+    policy_set: &mut PolicySet,
+    templates: Option<HashMap<String, String>>,
+    errs: &mut Vec<String>,
+) {
+    for (id, policy_src) in templates.into_iter().flatten() {
+        match Template::parse(Some(id.clone()), policy_src) {
+            Ok(p) => match policy_set.add_template(p) {
+                Ok(()) => {}
+                Err(err) => {
+                    errs.push(format!("couldn't add policy to set due to error: {err}"));
+                }
+            },
+            Err(pes) => errs.extend(
+                std::iter::once(format!("couldn't parse policy with id `{id}`"))
+                    .chain(pes.errors_as_strings().into_iter()),
+            ),
+        }
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        frontend::utils::{assert_is_failure, FailureCode},
+        EntityUid,
+    };
+    use cool_asserts::assert_matches;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_slice_convert() {
+        let entities = serde_json::json!(
+            [
+                {
+                    "uid" : {
+                        "type" : "user",
+                        "id" : "alice"
+                    },
+                    "attrs": { "foo": "bar" },
+                    "parents" : [
+                        {
+                            "type" : "user",
+                            "id" : "bob"
+                        }
+                    ]
+                },
+                {
+                    "uid" : {
+                        "type" : "user",
+                        "id" : "bob"
+                    },
+                    "attrs": {},
+                    "parents": []
+                }
+            ]
+        );
+        let rslice = RecvdSlice {
+            policies: PolicySpecification::Map(HashMap::new()),
+            entities: entities.into(),
+            templates: None,
+            template_instantiations: None,
+            template_links: None,
+            duplicate_entities: DuplicateEntityPolicy::default(),
+        };
+        let (policies, entities, _) = rslice
+            .try_into(
+                None,
+                &mut Stopwatch::start(None),
+                &mut AuthorizationTimings::default(),
+            )
+            .expect("parse failed");
+        assert!(policies.is_empty());
+        entities
+            .get(&EntityUid::from_type_name_and_id(
+                "user".parse().unwrap(),
+                "alice".parse().unwrap(),
+            ))
+            .map_or_else(
+                || panic!("Missing user::alice Entity"),
+                |alice| {
+                    assert!(entities.is_ancestor_of(
+                        &EntityUid::from_type_name_and_id(
+                            "user".parse().unwrap(),
+                            "bob".parse().unwrap()
+                        ),
+                        &alice.uid()
+                    ));
+                },
+            );
+    }
+
+    #[test]
+    fn test_failure_on_invalid_syntax() {
+        assert_is_failure(
+            &json_is_authorized("iefjieoafiaeosij"),
+            true,
+            "expected value",
+        );
+    }
+
+    #[test]
+    fn test_not_authorized_on_empty_slice() {
+        let call = r#"
+        {
+            "principal": {
+             "type": "User",
+             "id": "alice"
+            },
+            "action": {
+             "type": "Photo",
+             "id": "view"
+            },
+            "resource": {
+             "type": "Photo",
+             "id": "door"
+            },
+            "context": {},
+            "slice": {
+             "policies": {},
+             "entities": []
+            }
+           }
+        "#;
+
+        assert_is_not_authorized(json_is_authorized(call));
+    }
+
+    #[test]
+    fn test_not_authorized_on_unspecified() {
+        let call = r#"
+        {
+            "principal": null,
+            "action": {
+             "type": "Photo",
+             "id": "view"
+            },
+            "resource": {
+             "type": "Photo",
+             "id": "door"
+            },
+            "context": {},
+            "slice": {
+             "policies": {
+              "ID1": "permit(principal == User::\"alice\", action, resource);"
+             },
+             "entities": []
+            }
+           }
+        "#;
+
+        assert_is_not_authorized(json_is_authorized(call));
+    }
+
+    #[test]
+    fn test_authorized_on_simple_slice() {
+        let call = r#"
+        {
+            "principal": {
+             "type": "User",
+             "id": "alice"
+            },
+            "action": {
+             "type": "Photo",
+             "id": "view"
+            },
+            "resource": {
+             "type": "Photo",
+             "id": "door"
+            },
+            "context": {},
+            "slice": {
+             "policies": {
+              "ID1": "permit(principal == User::\"alice\", action, resource);"
+             },
+             "entities": []
+            }
+           }
+        "#;
+
+        assert_is_authorized(json_is_authorized(call));
+    }
+
+    #[test]
+    fn test_authorized_on_simple_slice_with_string_policies() {
+        let call = r#"
+        {
+            "principal": {
+             "type": "User",
+             "id": "alice"
+            },
+            "action": {
+             "type": "Photo",
+             "id": "view"
+            },
+            "resource": {
+             "type": "Photo",
+             "id": "door"
+            },
+            "context": {},
+            "slice": {
+             "policies": "permit(principal == User::\"alice\", action, resource);",
+             "entities": []
+            }
+           }
+	         "#;
+
+        assert_is_authorized(json_is_authorized(call));
+    }
+
+    #[test]
+    fn test_authorized_reports_structured_errors() {
+        let call = r#"
+        {
+            "principal": {
+             "type": "User",
+             "id": "alice"
+            },
+            "action": {
+             "type": "Photo",
+             "id": "view"
+            },
+            "resource": {
+             "type": "Photo",
+             "id": "door"
+            },
+            "context": {},
+            "slice": {
+             "policies": {
+              "ID1": "permit(principal == User::\"alice\", action, resource);",
+              "ID2": "forbid(principal, action, resource) when { principal.is_banned };"
+             },
+             "entities": []
             }
-        };
+           }
+        "#;
 
-        if let Some(t_inst_list) = template_instantiations {
-            for instantiation in t_inst_list {
-                match parse_instantiations(&mut policies, instantiation) {
-                    Ok(()) => (),
-                    Err(err) => errs.extend(err),
+        assert_matches!(json_is_authorized(call), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer =
+                serde_json::from_str(result.as_str()).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
+                assert_eq!(response.decision(), Decision::Allow);
+                let error = response.diagnostics().errors.iter().exactly_one().unwrap();
+                assert_eq!(error.policy_id(), &PolicyId::from_str("ID2").unwrap());
+                assert_eq!(error.kind(), AuthorizationErrorKind::EntityDoesNotExist);
+                assert_eq!(error.message(), "entity `User::\"alice\"` does not exist");
+            });
+        });
+    }
+
+    #[test]
+    fn test_authorized_with_timings() {
+        let call = |include_timings: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "include_timings": include_timings,
+                "slice": {
+                    "policies": "permit(principal, action, resource);",
+                    "entities": []
                 }
-            }
-        }
+            })
+            .to_string()
+        };
+        let ticks = std::cell::Cell::new(0.0);
+        let clock = || {
+            ticks.set(ticks.get() + 1.5);
+            ticks.get()
+        };
 
-        if errs.is_empty() {
-            Ok((policies, entities))
-        } else {
-            Err(errs)
+        assert_matches!(json_is_authorized_with_clock(&call(true), &clock), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { timings: Some(timings), .. } => {
+                assert_eq!(timings, AuthorizationTimings {
+                    parse_policies_ms: 3.0,
+                    parse_entities_ms: 1.5,
+                    eval_ms: 1.5,
+                });
+            });
+        });
+        for result in [
+            json_is_authorized_with_clock(&call(false), &clock),
+            json_is_authorized(&call(true)),
+        ] {
+            assert_matches!(result, InterfaceResult::Success { result, .. } => {
+                assert!(!result.contains("timings"));
+            });
         }
     }
-}
 
-fn parse_policy_set_from_individual_policies(
-    policies: &HashMap<String, String>,
-    templates: Option<HashMap<String, String>>,
-) -> Result<PolicySet, Vec<String>> {
-    let mut policy_set = PolicySet::new();
-    let mut errs = Vec::new();
-    for (id, policy_src) in policies {
-        match Policy::parse(Some(id.clone()), policy_src) {
-            Ok(p) => match policy_set.add(p) {
-                Ok(()) => {}
-                Err(err) => {
-                    errs.push(format!("couldn't add policy to set due to error: {err}"));
+    #[test]
+    fn test_authorized_with_policy_outcomes() {
+        let call = |include_policy_outcomes: bool, trace: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "include_policy_outcomes": include_policy_outcomes,
+                "trace": trace,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal, action, resource);",
+                        "ID2": "permit(principal, action, resource) when { principal.level > 2 };",
+                        "ID3": "forbid(principal, action, resource) when { principal.banned };",
+                        "ID4": "permit(principal == User::\"bob\", action, resource);"
+                    },
+                    "entities": [
+                        {
+                            "uid": { "type": "User", "id": "alice" },
+                            "attrs": { "level": 3 },
+                            "parents": []
+                        }
+                    ]
                 }
-            },
-            Err(pes) => errs.extend(
-                std::iter::once(format!("couldn't parse policy with id `{id}`"))
-                    .chain(pes.errors_as_strings().into_iter()),
-            ),
+            })
+            .to_string()
+        };
+        let expected = BTreeMap::from([
+            ("ID1".to_string(), PolicyOutcome::Satisfied),
+            ("ID2".to_string(), PolicyOutcome::Satisfied),
+            ("ID3".to_string(), PolicyOutcome::Error),
+            ("ID4".to_string(), PolicyOutcome::NotSatisfied),
+        ]);
+
+        for trace in [false, true] {
+            assert_matches!(json_is_authorized(&call(true, trace)), InterfaceResult::Success { result, .. } => {
+                let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+                assert_matches!(parsed_result, AuthorizationAnswer::Success { response, policy_outcomes: Some(outcomes), .. } => {
+                    assert_eq!(response.decision, Decision::Allow);
+                    assert_eq!(outcomes, expected);
+                });
+                assert!(result.contains(r#""ID4":"notSatisfied""#));
+            });
         }
+        assert_matches!(json_is_authorized(&call(false, false)), InterfaceResult::Success { result, .. } => {
+            assert!(!result.contains("policy_outcomes"));
+        });
     }
 
-    if let Some(templates) = templates {
-        for (id, policy_src) in templates {
-            match Template::parse(Some(id.clone()), policy_src) {
-                Ok(p) => match policy_set.add_template(p) {
-                    Ok(()) => {}
-                    Err(err) => {
-                        errs.push(format!("couldn't add policy to set due to error: {err}"));
-                    }
+    #[test]
+    fn test_authorized_in_strict_mode() {
+        let call = |strict: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "strict": strict,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal, action, resource) when { resource.owner == principal };",
+                        "ID2": "forbid(principal, action, resource) when { principal in Group::\"banned\" };",
+                        "ID3": "permit(principal, action, resource) when { principal.level > 2 };"
+                    },
+                    "entities": [
+                        {
+                            "uid": { "type": "User", "id": "alice" },
+                            "attrs": {},
+                            "parents": []
+                        }
+                    ]
+                }
+            })
+            .to_string()
+        };
+
+        assert_matches!(json_is_authorized(&call(true)), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, missing_entities: Some(entities), missing_attributes: Some(attrs), .. } => {
+                assert_eq!(response.decision, Decision::Deny);
+                assert_eq!(entities, vec![r#"Photo::"door""#.to_string()]);
+                assert_eq!(
+                    attrs,
+                    vec![InterfaceMissingAttribute {
+                        entity: r#"User::"alice""#.to_string(),
+                        attr: "level".to_string(),
+                    }]
+                );
+            });
+        });
+        assert_matches!(json_is_authorized(&call(false)), InterfaceResult::Success { result, .. } => {
+            assert!(!result.contains("missing_entities"));
+            assert!(!result.contains("missing_attributes"));
+        });
+    }
+
+    #[test]
+    fn test_determining_permits_and_forbids() {
+        let call = |principal: &str| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": principal },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal in Group::\"staff\", action, resource);",
+                        "ID2": "forbid(principal == User::\"mallory\", action, resource);"
+                    },
+                    "entities": [
+                        { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "staff" }] },
+                        { "uid": { "type": "User", "id": "mallory" }, "attrs": {}, "parents": [{ "type": "Group", "id": "staff" }] }
+                    ]
+                }
+            })
+            .to_string()
+        };
+        let diagnostics = |principal: &str| {
+            assert_matches!(json_is_authorized(&call(principal)), InterfaceResult::Success { result, .. } => {
+                let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+                assert_matches!(parsed_result, AuthorizationAnswer::Success { response, .. } => response.diagnostics)
+            })
+        };
+        let ids = |ids: &[&str]| -> HashSet<PolicyId> {
+            ids.iter().map(|id| id.parse().unwrap()).collect()
+        };
+
+        let allowed = diagnostics("alice");
+        assert_eq!(allowed.determining_permits, ids(&["ID1"]));
+        assert!(allowed.determining_forbids.is_empty());
+        assert!(!allowed.denied_by_default);
+
+        let forbidden = diagnostics("mallory");
+        assert!(forbidden.determining_permits.is_empty());
+        assert_eq!(forbidden.determining_forbids, ids(&["ID2"]));
+        assert!(!forbidden.denied_by_default);
+
+        let defaulted = diagnostics("bob");
+        assert!(defaulted.reason.is_empty());
+        assert!(defaulted.determining_forbids.is_empty());
+        assert!(defaulted.denied_by_default);
+    }
+
+    #[test]
+    fn test_authorized_with_metrics() {
+        let call = |include_metrics: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "include_metrics": include_metrics,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal in Group::\"everyone\", action, resource);",
+                        "ID2": "forbid(principal, action, resource) when { principal has banned };"
+                    },
+                    "entities": [
+                        { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "staff" }] },
+                        { "uid": { "type": "Group", "id": "staff" }, "attrs": {}, "parents": [{ "type": "Group", "id": "employees" }] },
+                        { "uid": { "type": "Group", "id": "employees" }, "attrs": {}, "parents": [{ "type": "Group", "id": "everyone" }] },
+                        { "uid": { "type": "Group", "id": "everyone" }, "attrs": {}, "parents": [] }
+                    ]
+                }
+            })
+            .to_string()
+        };
+        assert_matches!(json_is_authorized(&call(true)), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, metrics, .. } => {
+                assert_eq!(response.decision(), Decision::Allow);
+                assert_eq!(
+                    metrics,
+                    Some(Box::new(AuthorizationMetrics {
+                        policies_evaluated: 2,
+                        entity_dereferences: 2,
+                        max_hierarchy_depth: 3,
+                    }))
+                );
+            });
+        });
+        assert_matches!(json_is_authorized(&call(false)), InterfaceResult::Success { result, .. } => {
+            assert!(!result.contains("metrics"));
+        });
+    }
+
+    #[test]
+    fn test_membership_paths() {
+        let call = serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Photo", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
+            "context": {},
+            "include_membership_paths": true,
+            "slice": {
+                "policies": {
+                    "ID1": "permit(principal in Group::\"everyone\", action, resource);",
+                    "ID2": "permit(principal, action, resource) when { principal in [Group::\"admins\", Group::\"staff\"] };",
+                    "ID3": "permit(principal is User in Group::\"nobody\", action, resource) unless { principal in Group::\"nobody\" };"
                 },
-                Err(pes) => errs.extend(
-                    std::iter::once(format!("couldn't parse policy with id `{id}`"))
-                        .chain(pes.errors_as_strings().into_iter()),
-                ),
+                "entities": [
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "staff" }] },
+                    { "uid": { "type": "Group", "id": "staff" }, "attrs": {}, "parents": [{ "type": "Group", "id": "employees" }] },
+                    { "uid": { "type": "Group", "id": "employees" }, "attrs": {}, "parents": [{ "type": "Group", "id": "everyone" }] },
+                    { "uid": { "type": "Group", "id": "everyone" }, "attrs": {}, "parents": [] }
+                ]
             }
-        }
+        });
+        let paths = assert_matches!(json_is_authorized(&call.to_string()), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, .. } => response.diagnostics.membership_paths)
+        });
+        let path = |policy: &str, path: &[&str]| InterfaceMembershipPath {
+            policy: policy.to_string(),
+            path: path.iter().map(ToString::to_string).collect(),
+        };
+        assert_eq!(
+            paths,
+            vec![
+                path(
+                    "ID1",
+                    &[
+                        r#"User::"alice""#,
+                        r#"Group::"staff""#,
+                        r#"Group::"employees""#,
+                        r#"Group::"everyone""#
+                    ]
+                ),
+                path("ID2", &[r#"User::"alice""#, r#"Group::"staff""#]),
+            ]
+        );
     }
 
-    if errs.is_empty() {
-        Ok(policy_set)
-    } else {
-        Err(errs)
+    #[test]
+    fn test_authorized_with_determining_policies() {
+        let call = |format: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "include_determining_policies": format,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal == User::\"alice\", action, resource);",
+                        "ID2": "permit(principal == User::\"bob\", action, resource);"
+                    },
+                    "entities": []
+                }
+            })
+            .to_string()
+        };
+        let determining = |format: serde_json::Value| {
+            assert_matches!(json_is_authorized(&call(format)), InterfaceResult::Success { result, .. } => {
+                let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+                assert_matches!(parsed_result, AuthorizationAnswer::Success { determining_policies, .. } => determining_policies)
+            })
+        };
+
+        assert_eq!(
+            determining("text".into()),
+            Some(BTreeMap::from([(
+                "ID1".to_string(),
+                serde_json::json!("permit(principal == User::\"alice\", action, resource);")
+            )]))
+        );
+        let json = determining("json".into()).unwrap();
+        assert_eq!(json.keys().collect::<Vec<_>>(), vec!["ID1"]);
+        assert_eq!(json["ID1"]["effect"], "permit");
+        assert_eq!(determining(serde_json::Value::Null), None);
     }
-}
 
-// PANIC SAFETY unit tests
-#[allow(clippy::panic)]
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::{frontend::utils::assert_is_failure, EntityUid};
-    use cool_asserts::assert_matches;
-    use std::collections::HashMap;
+    #[test]
+    fn test_authorized_with_obligations_and_advice() {
+        let answer = |principal: &str| {
+            let call = serde_json::json!({
+                "principal": { "type": "User", "id": principal },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "slice": {
+                    "policies": {
+                        "ID1": "@obligation(\"mfa\") @advice(\"log\") permit(principal, action, resource);",
+                        "ID2": "@advice(\"notify\") permit(principal == User::\"alice\", action, resource);",
+                        "ID3": "@obligation(\"audit\") forbid(principal == User::\"bob\", action, resource);"
+                    },
+                    "entities": []
+                }
+            });
+            assert_matches!(json_is_authorized(&call.to_string()), InterfaceResult::Success { result, .. } => {
+                let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+                assert_matches!(parsed_result, AuthorizationAnswer::Success { response, .. } => (response.obligations, response.advice))
+            })
+        };
+        let annotation = |policy: &str, value: &str| InterfacePolicyAnnotation {
+            policy: policy.to_string(),
+            value: value.to_string(),
+        };
+
+        let (obligations, advice) = answer("alice");
+        assert_eq!(obligations, vec![annotation("ID1", "mfa")]);
+        assert_eq!(
+            advice,
+            vec![annotation("ID1", "log"), annotation("ID2", "notify")]
+        );
+        let (obligations, advice) = answer("bob");
+        assert_eq!(obligations, vec![annotation("ID3", "audit")]);
+        assert!(advice.is_empty());
+    }
 
     #[test]
-    fn test_slice_convert() {
-        let entities = serde_json::json!(
-            [
-                {
-                    "uid" : {
-                        "type" : "user",
-                        "id" : "alice"
+    fn test_authorized_with_trace() {
+        let call = |trace: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "trace": trace,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal, action, resource) when { principal.level > 2 };"
                     },
-                    "attrs": { "foo": "bar" },
-                    "parents" : [
+                    "entities": [
                         {
-                            "type" : "user",
-                            "id" : "bob"
+                            "uid": { "type": "User", "id": "alice" },
+                            "attrs": { "level": 3 },
+                            "parents": []
                         }
                     ]
-                },
-                {
-                    "uid" : {
-                        "type" : "user",
-                        "id" : "bob"
+                }
+            })
+            .to_string()
+        };
+
+        assert_matches!(json_is_authorized(&call(true)), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, trace: Some(traces), .. } => {
+                assert_eq!(response.decision, Decision::Allow);
+                assert_eq!(traces.len(), 1);
+                assert_eq!(traces[0].policy_id, PolicyId::from_str("ID1").unwrap());
+                assert!(traces[0].events.contains(&InterfaceTraceEvent::AttributeRead {
+                    entity: r#"User::"alice""#.to_string(),
+                    attr: "level".to_string(),
+                    present: true,
+                }));
+                assert!(traces[0].events.contains(&InterfaceTraceEvent::Evaluated {
+                    expr: r#"principal["level"]"#.to_string(),
+                    value: Some("3".to_string()),
+                    error: None,
+                }));
+            });
+            assert!(result.contains(r#""kind":"attributeRead""#));
+        });
+        assert_matches!(json_is_authorized(&call(false)), InterfaceResult::Success { result, .. } => {
+            assert!(!result.contains("trace"));
+        });
+    }
+
+    #[test]
+    fn test_authorized_with_limits() {
+        let call = |limits: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "limits": limits,
+                "slice": {
+                    "policies": {
+                        "ID0": format!("permit(principal, action, resource) when {{ [{}].contains(2) }};", vec!["1"; 200].join(", "))
                     },
-                    "attrs": {},
-                    "parents": []
+                    "entities": []
                 }
-            ]
+            })
+            .to_string()
+        };
+        let ticks = std::cell::Cell::new(0.0);
+        let clock = || {
+            ticks.set(ticks.get() + 1.0);
+            ticks.get()
+        };
+
+        assert_matches!(json_is_authorized(&call(serde_json::json!({ "max_steps": 1000 }))), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, .. } => {
+                assert_eq!(response.decision(), Decision::Deny);
+            });
+        });
+        for (result, err) in [
+            (
+                json_is_authorized(&call(serde_json::json!({ "max_steps": 10 }))),
+                "evaluation exceeded its limit of 10 steps",
+            ),
+            (
+                json_is_authorized_with_clock(
+                    &call(serde_json::json!({ "time_budget_ms": 1 })),
+                    &clock,
+                ),
+                "evaluation exceeded its time budget of 1 ms",
+            ),
+        ] {
+            assert_matches!(result, InterfaceResult::Failure { is_internal: false, errors, code: Some(FailureCode::LimitExceeded), .. } => {
+                assert_eq!(errors, vec![format!("while evaluating policy `ID0`: {err}")]);
+            });
+        }
+        assert_is_failure(
+            &json_is_authorized(&call(serde_json::json!({ "time_budget_ms": 5 }))),
+            false,
+            "a time budget can only be given to a call made with a clock",
         );
-        let rslice = RecvdSlice {
-            policies: PolicySpecification::Map(HashMap::new()),
-            entities: entities.into(),
-            templates: None,
-            template_instantiations: None,
-        };
-        let (policies, entities) = rslice.try_into(None).expect("parse failed");
-        assert!(policies.is_empty());
-        entities
-            .get(&EntityUid::from_type_name_and_id(
-                "user".parse().unwrap(),
-                "alice".parse().unwrap(),
-            ))
-            .map_or_else(
-                || panic!("Missing user::alice Entity"),
-                |alice| {
-                    assert!(entities.is_ancestor_of(
-                        &EntityUid::from_type_name_and_id(
-                            "user".parse().unwrap(),
-                            "bob".parse().unwrap()
-                        ),
-                        &alice.uid()
-                    ));
-                },
-            );
     }
 
     #[test]
-    fn test_failure_on_invalid_syntax() {
+    fn test_authorized_with_duplicate_entities() {
+        let call = |policy: &str| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "slice": {
+                    "policies": {
+                        "ID0": "permit(principal, action, resource in Album::\"trip\") when { resource.private == false };",
+                        "ID1": "permit(principal, action, resource in Album::\"work\");"
+                    },
+                    "entities": [
+                        { "uid": { "type": "Photo", "id": "door" }, "attrs": { "private": true }, "parents": [{ "type": "Album", "id": "trip" }] },
+                        { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] },
+                        { "uid": { "type": "Photo", "id": "door" }, "attrs": { "private": false }, "parents": [{ "type": "Album", "id": "trip" }] },
+                        { "uid": { "__entity": { "type": "Photo", "id": "door" } }, "attrs": {}, "parents": [{ "type": "Album", "id": "work" }] }
+                    ],
+                    "duplicate_entities": policy
+                }
+            })
+            .to_string()
+        };
+        let answer = |policy: &str| {
+            assert_matches!(json_is_authorized(&call(policy)), InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<AuthorizationAnswer>(&result).unwrap()
+            })
+        };
+
         assert_is_failure(
-            &json_is_authorized("iefjieoafiaeosij"),
-            true,
-            "expected value",
+            &json_is_authorized(&call("error")),
+            false,
+            r#"duplicate entity entry `Photo::"door"` (3 records)"#,
         );
+        let duplicates = vec![DuplicateEntity {
+            uid: r#"Photo::"door""#.to_string(),
+            records: 3,
+        }];
+        assert_matches!(answer("keepFirst"), AuthorizationAnswer::Success { response, duplicate_entities, .. } => {
+            assert_eq!(response.decision(), Decision::Deny);
+            assert_eq!(duplicate_entities, duplicates);
+        });
+        assert_matches!(answer("merge"), AuthorizationAnswer::Success { response, duplicate_entities, .. } => {
+            assert_eq!(response.decision(), Decision::Allow);
+            assert_eq!(response.diagnostics().reason().cloned().collect::<HashSet<_>>(), HashSet::from(["ID0".parse().unwrap(), "ID1".parse().unwrap()]));
+            assert_eq!(duplicate_entities, duplicates);
+        });
     }
 
     #[test]
-    fn test_not_authorized_on_empty_slice() {
+    fn test_authorized_with_parsed_policy_set() {
+        let policies = PolicySet::from_str(
+            r#"permit(principal == User::"alice", action, resource in Folder::"shared");"#,
+        )
+        .unwrap();
         let call = r#"
         {
-            "principal": {
-             "type": "User",
-             "id": "alice"
-            },
-            "action": {
-             "type": "Photo",
-             "id": "view"
-            },
-            "resource": {
-             "type": "Photo",
-             "id": "door"
-            },
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Photo", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
             "context": {},
-            "slice": {
-             "policies": {},
-             "entities": []
-            }
-           }
+            "entities": [
+                {
+                    "uid": { "type": "Photo", "id": "door" },
+                    "attrs": {},
+                    "parents": [{ "type": "Folder", "id": "shared" }]
+                }
+            ]
+        }
         "#;
 
-        assert_is_not_authorized(json_is_authorized(call));
+        assert_matches!(json_is_authorized_with_policy_set(call, &policies, None), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer =
+                serde_json::from_str(result.as_str()).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
+                assert_eq!(response.decision(), Decision::Allow);
+            });
+        });
+
+        let bad_entities = r#"
+        {
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Photo", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
+            "context": {},
+            "entities": [{ "uid": { "type": "Photo", "id": "door" } }]
+        }
+        "#;
+        assert_is_failure(
+            &json_is_authorized_with_policy_set(bad_entities, &policies, None),
+            false,
+            "attrs",
+        );
     }
 
     #[test]
-    fn test_not_authorized_on_unspecified() {
+    fn test_authorized_with_parsed_policy_set_and_entities() {
+        let policies = PolicySet::from_str(
+            r#"permit(principal == User::"alice", action, resource in Folder::"shared");"#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_str(
+            r#"[
+                { "uid": { "type": "Photo", "id": "door" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "album" }] },
+                { "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "shared" }] }
+            ]"#,
+            None,
+        )
+        .unwrap();
         let call = r#"
         {
-            "principal": null,
-            "action": {
-             "type": "Photo",
-             "id": "view"
-            },
-            "resource": {
-             "type": "Photo",
-             "id": "door"
-            },
-            "context": {},
-            "slice": {
-             "policies": {
-              "ID1": "permit(principal == User::\"alice\", action, resource);"
-             },
-             "entities": []
-            }
-           }
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Photo", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
+            "context": {}
+        }
         "#;
 
-        assert_is_not_authorized(json_is_authorized(call));
+        assert_matches!(json_is_authorized_with(call, &policies, &entities, None, None), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer =
+                serde_json::from_str(result.as_str()).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
+                assert_eq!(response.decision(), Decision::Allow);
+            });
+        });
+
+        assert_matches!(
+            json_is_authorized_with(r#"{ "action": "view", "context": {} }"#, &policies, &entities, None, None),
+            InterfaceResult::Failure { is_internal: false, errors, .. } => {
+                assert_eq!(errors.first().map(String::as_str), Some("Failed to parse action"));
+            }
+        );
     }
 
     #[test]
-    fn test_authorized_on_simple_slice() {
+    fn test_authorized_with_parsed_schema() {
+        let schema = Schema::from_str(
+            r#"{ "": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo"],
+                            "context": { "type": "Record", "attributes": { "ip": { "type": "Extension", "name": "ipaddr" } } }
+                        }
+                    }
+                }
+            }}"#,
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.ip.isLoopback() };"#,
+        )
+        .unwrap();
         let call = r#"
         {
-            "principal": {
-             "type": "User",
-             "id": "alice"
-            },
-            "action": {
-             "type": "Photo",
-             "id": "view"
-            },
-            "resource": {
-             "type": "Photo",
-             "id": "door"
-            },
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Action", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
+            "context": { "ip": "127.0.0.1" }
+        }
+        "#;
+
+        assert_matches!(json_is_authorized_with(call, &policies, &Entities::empty(), Some(&schema), None), InterfaceResult::Success { result, .. } => {
+            let parsed_result: AuthorizationAnswer =
+                serde_json::from_str(result.as_str()).unwrap();
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
+                assert_eq!(response.decision(), Decision::Allow);
+            });
+        });
+
+        let call_with_schema = r#"
+        {
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Action", "id": "view" },
+            "resource": { "type": "Photo", "id": "door" },
             "context": {},
-            "slice": {
-             "policies": {
-              "ID1": "permit(principal == User::\"alice\", action, resource);"
-             },
-             "entities": []
-            }
-           }
+            "schema": {}
+        }
         "#;
+        assert_is_failure(
+            &json_is_authorized_with(
+                call_with_schema,
+                &policies,
+                &Entities::empty(),
+                Some(&schema),
+                None,
+            ),
+            false,
+            "a schema was given both in the call and already parsed",
+        );
+    }
 
-        assert_is_authorized(json_is_authorized(call));
+    #[test]
+    fn test_request_failing_validation_has_code() {
+        let call = |principal_type: &str, enable_request_validation: bool| {
+            serde_json::json!({
+                "principal": { "type": principal_type, "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "schema": { "": {
+                    "entityTypes": { "User": {}, "Photo": {} },
+                    "actions": {
+                        "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+                    }
+                }},
+                "enable_request_validation": enable_request_validation,
+                "slice": {
+                    "policies": "permit(principal, action, resource);",
+                    "entities": []
+                }
+            })
+            .to_string()
+        };
+
+        assert_matches!(json_is_authorized(&call("Photo", true)), InterfaceResult::Failure { is_internal: false, errors, code: Some(FailureCode::RequestValidationErr), .. } => {
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].contains("principal type `Photo` is not valid for `Action::\"view\"`"), "unexpected error: {}", errors[0]);
+        });
+        assert_matches!(
+            json_is_authorized(&call("Photo", false)),
+            InterfaceResult::Success { .. }
+        );
+        assert_matches!(
+            json_is_authorized(&call("User", true)),
+            InterfaceResult::Success { .. }
+        );
     }
 
     #[test]
-    fn test_authorized_on_simple_slice_with_string_policies() {
-        let call = r#"
-        {
-            "principal": {
-             "type": "User",
-             "id": "alice"
-            },
-            "action": {
-             "type": "Photo",
-             "id": "view"
-            },
-            "resource": {
-             "type": "Photo",
-             "id": "door"
-            },
-            "context": {},
-            "slice": {
-             "policies": "permit(principal == User::\"alice\", action, resource);",
-             "entities": []
-            }
-           }
-	         "#;
+    fn test_authorized_with_context_defaults() {
+        let call = |context_defaults: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": { "mfa": true },
+                "context_defaults": context_defaults,
+                "schema": { "": {
+                    "entityTypes": { "User": {}, "Photo": {} },
+                    "actions": {
+                        "view": { "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo"],
+                            "context": { "type": "Record", "attributes": {
+                                "mfa": { "type": "Boolean", "required": false },
+                                "level": { "type": "Long", "required": false },
+                                "tags": { "type": "Set", "element": { "type": "String" }, "required": false },
+                                "device": { "type": "Record", "required": false, "attributes": {
+                                    "name": { "type": "String" },
+                                    "trusted": { "type": "Boolean", "required": false }
+                                }},
+                                "ip": { "type": "Extension", "name": "ipaddr", "required": false }
+                            }}
+                        }}
+                    }
+                }},
+                "slice": {
+                    "policies": r#"permit(principal, action, resource) when {
+                        context.mfa && context.level == 3 && context.tags == [] &&
+                        context.device == { name: "" } && !(context has ip)
+                    };"#,
+                    "entities": []
+                }
+            })
+            .to_string()
+        };
+        let decision = |call: String| {
+            assert_matches!(json_is_authorized(&call), InterfaceResult::Success { result, .. } => {
+                let answer: AuthorizationAnswer = serde_json::from_str(&result).unwrap();
+                assert_matches!(answer, AuthorizationAnswer::Success { response, .. } => response.decision())
+            })
+        };
 
-        assert_is_authorized(json_is_authorized(call));
+        assert_eq!(
+            decision(call(serde_json::json!({ "level": 3, "mfa": false }))),
+            Decision::Allow
+        );
+        assert_eq!(decision(call(serde_json::json!({}))), Decision::Deny);
+        assert_eq!(decision(call(serde_json::Value::Null)), Decision::Deny);
+    }
+
+    #[test]
+    fn test_authorized_with_null_or_absent_context() {
+        let call = |context: Option<serde_json::Value>, required: bool| {
+            let mut call = serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "schema": { "": {
+                    "entityTypes": { "User": {}, "Photo": {} },
+                    "actions": {
+                        "view": { "appliesTo": {
+                            "principalTypes": ["User"],
+                            "resourceTypes": ["Photo"],
+                            "context": { "type": "Record", "attributes": {
+                                "mfa": { "type": "Boolean", "required": required }
+                            }}
+                        }}
+                    }
+                }},
+                "slice": {
+                    "policies": "permit(principal, action, resource) unless { context has mfa };",
+                    "entities": []
+                }
+            });
+            if let (Some(context), Some(call)) = (context, call.as_object_mut()) {
+                call.insert("context".to_string(), context);
+            }
+            call.to_string()
+        };
+        for context in [
+            None,
+            Some(serde_json::Value::Null),
+            Some(serde_json::json!("")),
+        ] {
+            assert_is_authorized(json_is_authorized(&call(context.clone(), false)));
+            assert_is_failure(
+                &json_is_authorized(&call(context, true)),
+                false,
+                "no context was given, but the context of `Action::\"view\"` requires the attributes `mfa`",
+            );
+        }
+        assert_is_failure(
+            &json_is_authorized(&call(Some(serde_json::json!("mfa")), false)),
+            true,
+            "expected a context record, `null`, or the empty string",
+        );
     }
 
     #[test]
@@ -1194,6 +3706,156 @@ mod test {
         assert_is_authorized(json_is_authorized(call));
     }
 
+    #[test]
+    fn test_authorized_with_template_links() {
+        let call = |links: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "slice": {
+                    "policies": "permit(principal == ?principal, action, resource in ?resource);",
+                    "entities": [],
+                    "template_links": links
+                }
+            })
+            .to_string()
+        };
+        assert_is_authorized(json_is_authorized(&call(serde_json::json!([
+            {
+                "templateId": "policy0",
+                "newId": "alice_door",
+                "values": {
+                    "?principal": { "type": "User", "id": "alice" },
+                    "?resource": { "type": "Photo", "id": "door" }
+                }
+            }
+        ]))));
+        assert_is_not_authorized(json_is_authorized(&call(serde_json::json!([]))));
+        assert_is_failure(
+            &json_is_authorized(&call(serde_json::json!([
+                {
+                    "templateId": "policy0",
+                    "newId": "alice_door",
+                    "values": { "?principal": { "type": "User", "id": "alice" } }
+                }
+            ]))),
+            false,
+            "Error instantiating template",
+        );
+    }
+
+    #[test]
+    fn test_authorized_actions() {
+        let call = serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "actions": [
+                { "type": "Action", "id": "view" },
+                { "type": "Action", "id": "edit" },
+                { "type": "Action", "id": "delete" }
+            ],
+            "resource": { "type": "Photo", "id": "door" },
+            "context": {},
+            "schema": {
+                "": {
+                    "entityTypes": { "User": {}, "Photo": {} },
+                    "actions": {
+                        "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                        "edit": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                        "delete": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["User"] } }
+                    }
+                }
+            },
+            "slice": {
+                "policies": r#"permit(principal, action == Action::"view", resource);"#,
+                "entities": []
+            }
+        });
+        let result = json_is_authorized_actions(&call.to_string());
+        assert_matches!(result, InterfaceResult::Success { result, .. } => {
+            let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+            assert_eq!(
+                answer["decisions"],
+                serde_json::json!({ "Action::\"view\"": "Allow", "Action::\"edit\"": "Deny" })
+            );
+            assert_eq!(answer["errors"].as_object().map(|errors| errors.len()), Some(1));
+            assert!(answer["errors"].get("Action::\"delete\"").is_some());
+        });
+        let call = serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "actions": [{ "type": "Action", "id": "view" }],
+            "resource": { "type": "Photo", "id": "door" },
+            "context": {},
+            "slice": { "policies": "permit(principal, action, resource", "entities": [] }
+        });
+        assert_matches!(
+            json_is_authorized_actions(&call.to_string()),
+            InterfaceResult::Failure { is_internal: false, errors, .. } => {
+                assert!(errors.iter().any(|e| e.contains("couldn't parse concatenated policies")));
+            }
+        );
+    }
+
+    #[test]
+    fn test_authorized_with_policies_in_json_format() {
+        let call = |policies: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "slice": {
+                    "policies": policies,
+                    "entities": [],
+                    "template_links": [
+                        {
+                            "templateId": "owner",
+                            "newId": "alice_owner",
+                            "values": { "?principal": { "type": "User", "id": "alice" } }
+                        }
+                    ]
+                }
+            })
+            .to_string()
+        };
+        let owner = serde_json::json!({
+            "id": "owner",
+            "policy": {
+                "effect": "permit",
+                "principal": { "op": "==", "slot": "?principal" },
+                "action": { "op": "All" },
+                "resource": { "op": "All" },
+                "conditions": []
+            }
+        });
+        assert_is_authorized(json_is_authorized(&call(serde_json::json!([owner]))));
+        assert_is_not_authorized(json_is_authorized(&call(serde_json::json!([
+            owner,
+            {
+                "id": "forbid_doors",
+                "policy": {
+                    "effect": "forbid",
+                    "principal": { "op": "All" },
+                    "action": { "op": "All" },
+                    "resource": { "op": "==", "entity": { "type": "Photo", "id": "door" } },
+                    "conditions": []
+                }
+            }
+        ]))));
+        assert_matches!(
+            json_is_authorized(&call(serde_json::json!([
+                owner,
+                { "id": "bad", "policy": { "effect": "allow" } }
+            ]))),
+            InterfaceResult::Failure { is_internal: false, errors, .. } => {
+                assert!(errors
+                    .iter()
+                    .any(|e| e.starts_with("couldn't parse JSON of policy with id `bad`")));
+            }
+        );
+    }
+
     #[test]
     fn test_authorized_fails_on_policy_collision_with_template() {
         let call = r#"{
@@ -1359,10 +4021,10 @@ mod test {
 
     #[track_caller] // report the caller's location as the location of the panic, not the location in this function
     fn assert_is_authorized(result: InterfaceResult) {
-        assert_matches!(result, InterfaceResult::Success { result } => {
+        assert_matches!(result, InterfaceResult::Success { result, .. } => {
             let parsed_result: AuthorizationAnswer =
                 serde_json::from_str(result.as_str()).unwrap();
-            assert_matches!(parsed_result, AuthorizationAnswer::Success { response } => {
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
                 assert_eq!(response.decision(), Decision::Allow);
                 assert_eq!(response.diagnostics().errors.len(), 0);
             });
@@ -1371,10 +4033,10 @@ mod test {
 
     #[track_caller] // report the caller's location as the location of the panic, not the location in this function
     fn assert_is_not_authorized(result: InterfaceResult) {
-        assert_matches!(result, InterfaceResult::Success { result } => {
+        assert_matches!(result, InterfaceResult::Success { result, .. } => {
             let parsed_result: AuthorizationAnswer =
                 serde_json::from_str(result.as_str()).unwrap();
-            assert_matches!(parsed_result, AuthorizationAnswer::Success { response } => {
+            assert_matches!(parsed_result, AuthorizationAnswer::Success { response, timings: None, .. } => {
                 assert_eq!(response.decision(), Decision::Deny);
                 assert_eq!(response.diagnostics().errors.len(), 0);
             });
@@ -1756,7 +4418,7 @@ mod test {
 
         #[track_caller] // report the caller's location as the location of the panic, not the location in this function
         fn assert_is_authorized(result: InterfaceResult) {
-            assert_matches!(result, InterfaceResult::Success { result } => {
+            assert_matches!(result, InterfaceResult::Success { result, .. } => {
                 let parsed_result: PartialAuthorizationAnswer = serde_json::from_str(result.as_str()).unwrap();
                 assert_matches!(parsed_result, PartialAuthorizationAnswer::Concrete { response } => {
                     assert_eq!(response.decision(), Decision::Allow);
@@ -1767,7 +4429,7 @@ mod test {
 
         #[track_caller] // report the caller's location as the location of the panic, not the location in this function
         fn assert_is_not_authorized(result: InterfaceResult) {
-            assert_matches!(result, InterfaceResult::Success { result } => {
+            assert_matches!(result, InterfaceResult::Success { result, .. } => {
                 let parsed_result: PartialAuthorizationAnswer = serde_json::from_str(result.as_str()).unwrap();
                 assert_matches!(parsed_result, PartialAuthorizationAnswer::Concrete { response } => {
                     assert_eq!(response.decision(), Decision::Deny);
@@ -1778,7 +4440,7 @@ mod test {
 
         #[track_caller] // report the caller's location as the location of the panic, not the location in this function
         fn assert_is_residual(result: InterfaceResult, residual_ids: HashSet<&str>) {
-            assert_matches!(result, InterfaceResult::Success { result } => {
+            assert_matches!(result, InterfaceResult::Success { result, .. } => {
                 let parsed_result: PartialAuthorizationAnswer = serde_json::from_str(result.as_str()).unwrap();
                 assert_matches!(parsed_result, PartialAuthorizationAnswer::Residuals { response } => {
                     let num_errors = response.diagnostics.errors().count();
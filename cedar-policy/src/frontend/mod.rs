@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+pub mod evaluate;
 pub mod is_authorized;
 pub mod utils;
 pub mod validate;
@@ -15,29 +15,63 @@
  */
 
 //! Utility functions and types for JSON interface
-use serde::{Deserialize, Serialize};
+use cedar_policy_core::jsonvalue::JsonValueWithNoDuplicateKeys;
+use serde::de::{value::MapAccessDeserializer, MapAccess, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{DeserializeAs, MapPreventDuplicates, Same, SerializeAs};
 use std::collections::HashMap;
 
 #[cfg(feature = "wasm")]
 extern crate tsify;
 
+/// Version of the response format produced by the JSON interface.
+///
+/// This is bumped whenever the shape of a response changes, so that consumers
+/// can detect which format they are receiving. It is reported in the
+/// `apiVersion` field of every [`InterfaceResult`]. Versions:
+///
+/// 1. the first versioned format
+/// 2. validation answers report each error and warning as a structured note
+///    with `policyId`, `severity`, `message`, and `span`, in place of a
+///    formatted string
+/// 3. validation answers report `passed`, `errors`, and `warnings` in place
+///    of `notes`
+/// 4. validation answers report policies that can never apply in
+///    `impossiblePolicies` rather than among the `errors`
+pub const RESPONSE_VERSION: u32 = 4;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 #[serde(
-    expecting = "policies as a concatenated string or multiple policies as a hashmap where the policy Id is the key with no duplicate IDs"
+    expecting = "policies as a concatenated string, multiple policies as a hashmap where the policy Id is the key with no duplicate IDs, or an array of policies in JSON format with their IDs"
 )]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
-/// Struct defining the two possible ways to pass a set of policies to `json_is_authorized` and `json_validate`
+/// Struct defining the three possible ways to pass a set of policies to `json_is_authorized` and `json_validate`
 pub enum PolicySpecification {
     /// provides multiple policies as a concatenated string
     Concatenated(String),
     /// provides multiple policies as a hashmap where the policyId is the key
     #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
     Map(HashMap<String, String>),
+    /// provides multiple policies in their JSON (EST) format, each with its
+    /// policyId. Policies with slots are added as templates.
+    Json(Vec<PolicyJsonWithId>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+/// A policy in its JSON (EST) format, with its policyId
+pub struct PolicyJsonWithId {
+    /// The policyId
+    pub id: String,
+    /// The policy in its JSON (EST) format
+    #[cfg_attr(feature = "wasm", tsify(type = "Policy"))]
+    pub policy: JsonValueWithNoDuplicateKeys,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "success")]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
@@ -46,12 +80,18 @@ pub enum InterfaceResult {
     /// The call succeeded
     #[serde(rename = "true")]
     Success {
+        /// Version of the response format; see [`RESPONSE_VERSION`]
+        #[serde(rename = "apiVersion")]
+        api_version: u32,
         /// JSON containing the result of the call
         result: String,
     },
     #[serde(rename = "false")]
     /// The call failed
     Failure {
+        /// Version of the response format; see [`RESPONSE_VERSION`]
+        #[serde(rename = "apiVersion")]
+        api_version: u32,
         /// Whether the failure is "internal".
         ///
         /// An "internal failure" is returned when there is a fault in the
@@ -66,23 +106,53 @@ pub enum InterfaceResult {
         is_internal: bool,
         /// String description of the error(s) that led to the failure
         errors: Vec<String>,
+        /// Code identifying failures that callers may want to handle
+        /// specially, if the failure is one of those
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "wasm", tsify(optional))]
+        code: Option<FailureCode>,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+/// Code identifying a kind of failure of a call to a JSON interface
+pub enum FailureCode {
+    /// Evaluation was aborted because it exceeded the limits set in the call
+    LimitExceeded,
+    /// The request is not valid according to the schema given in the call,
+    /// e.g. because its action does not apply to its principal or resource
+    /// types
+    RequestValidationErr,
+    /// The policies or request use a Cedar extension, e.g. `ipaddr`, that is
+    /// disabled for the caller
+    DisabledExtension,
+    /// The validation settings name a validation mode that does not exist, or
+    /// that this build does not support
+    InvalidValidationMode,
+}
+
 impl InterfaceResult {
     /// A successful result
     pub fn succeed<T: Serialize>(value: T) -> Self {
         serde_json::to_string(&value).map_or_else(
             |e| Self::fail_internally(format!("error serializing result: {e:}")),
-            |result| Self::Success { result },
+            |result| Self::Success {
+                api_version: RESPONSE_VERSION,
+                result,
+            },
         )
     }
 
     /// An "internal failure" result; see docs on [`InterfaceResult::Failure`]
     pub fn fail_internally(message: String) -> Self {
         Self::Failure {
+            api_version: RESPONSE_VERSION,
             is_internal: true,
             errors: vec![message],
+            code: None,
         }
     }
 
@@ -90,10 +160,183 @@ impl InterfaceResult {
     /// `InterfaceResult::Failure`
     pub fn fail_bad_request(errors: Vec<String>) -> Self {
         Self::Failure {
+            api_version: RESPONSE_VERSION,
+            is_internal: false,
+            errors,
+            code: None,
+        }
+    }
+
+    /// A failure result for a call which exceeded its limits. This is not an
+    /// internal failure.
+    pub fn fail_limit_exceeded(errors: Vec<String>) -> Self {
+        Self::Failure {
+            api_version: RESPONSE_VERSION,
+            is_internal: false,
+            errors,
+            code: Some(FailureCode::LimitExceeded),
+        }
+    }
+
+    /// A failure result for a call whose request is not valid according to
+    /// its schema. This is not an internal failure.
+    pub fn fail_request_validation(errors: Vec<String>) -> Self {
+        Self::Failure {
+            api_version: RESPONSE_VERSION,
+            is_internal: false,
+            errors,
+            code: Some(FailureCode::RequestValidationErr),
+        }
+    }
+
+    /// A failure result for a call whose policies or request use a disabled
+    /// extension. This is not an internal failure.
+    pub fn fail_disabled_extension(errors: Vec<String>) -> Self {
+        Self::Failure {
+            api_version: RESPONSE_VERSION,
+            is_internal: false,
+            errors,
+            code: Some(FailureCode::DisabledExtension),
+        }
+    }
+
+    /// A failure result for a call whose validation settings name an invalid
+    /// validation mode. This is not an internal failure.
+    pub fn fail_invalid_validation_mode(errors: Vec<String>) -> Self {
+        Self::Failure {
+            api_version: RESPONSE_VERSION,
             is_internal: false,
             errors,
+            code: Some(FailureCode::InvalidValidationMode),
+        }
+    }
+}
+
+/// Measures the phases of a call in milliseconds with a clock supplied by the
+/// caller, since `std::time::Instant` is unavailable on some Wasm targets.
+/// Without a clock, every phase measures as zero.
+pub(crate) struct Stopwatch<'a> {
+    clock: Option<&'a dyn Fn() -> f64>,
+    last: f64,
+}
+
+impl<'a> Stopwatch<'a> {
+    /// Start measuring with `clock`, which returns the current time in
+    /// milliseconds
+    pub(crate) fn start(clock: Option<&'a dyn Fn() -> f64>) -> Self {
+        Self {
+            clock,
+            last: clock.map_or(0.0, |clock| clock()),
         }
     }
+
+    /// Milliseconds since the last lap, or since the start for the first lap
+    pub(crate) fn lap(&mut self) -> f64 {
+        match self.clock {
+            Some(clock) => {
+                let now = clock();
+                let elapsed = now - self.last;
+                self.last = now;
+                elapsed
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Whether the stopwatch has a clock to measure with
+    pub(crate) fn is_running(&self) -> bool {
+        self.clock.is_some()
+    }
+}
+
+/// The line and column, both counting from 1, of the byte `offset` into
+/// `src`. Columns count characters rather than bytes. An offset inside a
+/// character is taken to be at the start of that character, and one past the
+/// end of `src` at its end.
+pub fn line_and_column(src: &str, offset: usize) -> (usize, usize) {
+    let end = (0..=offset.min(src.len()))
+        .rev()
+        .find(|end| src.is_char_boundary(*end))
+        .unwrap_or(0);
+    let before = src.get(..end).unwrap_or_default();
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .get(line_start..)
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+/// `serde_as` adapter for the `context` of a call: a JSON record with no
+/// duplicate keys, where `null` and the empty string stand for the empty
+/// context, as does leaving the context out of a call
+pub(crate) struct ContextRecord;
+
+impl<'de> DeserializeAs<'de, HashMap<String, JsonValueWithNoDuplicateKeys>> for ContextRecord {
+    fn deserialize_as<D>(
+        deserializer: D,
+    ) -> Result<HashMap<String, JsonValueWithNoDuplicateKeys>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ContextVisitor;
+
+        impl<'de> Visitor<'de> for ContextVisitor {
+            type Value = HashMap<String, JsonValueWithNoDuplicateKeys>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a context record, `null`, or the empty string")
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(HashMap::new())
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E> {
+                Ok(HashMap::new())
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if value.is_empty() {
+                    Ok(HashMap::new())
+                } else {
+                    Err(E::invalid_value(Unexpected::Str(value), &self))
+                }
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                MapPreventDuplicates::<Same, Same>::deserialize_as(MapAccessDeserializer::new(map))
+            }
+        }
+
+        deserializer.deserialize_any(ContextVisitor)
+    }
+}
+
+impl SerializeAs<HashMap<String, JsonValueWithNoDuplicateKeys>> for ContextRecord {
+    fn serialize_as<S>(
+        context: &HashMap<String, JsonValueWithNoDuplicateKeys>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        context.serialize(serializer)
+    }
 }
 
 #[cfg(test)]
@@ -102,7 +345,7 @@ pub(crate) fn assert_is_failure(result: &InterfaceResult, internal: bool, err: &
     use cool_asserts::assert_matches;
     use itertools::Itertools;
 
-    assert_matches!(result, InterfaceResult::Failure { is_internal, errors } => {
+    assert_matches!(result, InterfaceResult::Failure { is_internal, errors, .. } => {
         assert!(
             errors.iter().exactly_one().unwrap().contains(err),
             "Expected to see error containing `{err}`, but saw {errors:?}");
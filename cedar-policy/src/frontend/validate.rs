@@ -17,22 +17,247 @@
 //! This module exposes a JSON-based validate function used by other language FFI's
 //!
 #![allow(clippy::module_name_repetitions)]
-use super::utils::{InterfaceResult, PolicySpecification};
+use super::utils::{
+    line_and_column, InterfaceResult, PolicyJsonWithId, PolicySpecification, Stopwatch,
+};
 use cedar_policy_core::{
-    ast::PolicySet,
-    parser::{parse_policy, parse_policyset},
+    ast::{AnyId, Expr, ExprKind, Policy, PolicyID, PolicySet, Template},
+    est,
+    extensions::Extensions,
+    jsonvalue::JsonValueWithNoDuplicateKeys,
+    parser::{parse_policy, parse_policyset, Loc},
+};
+use cedar_policy_validator::{
+    typecheck::{PolicyCheck, Typechecker},
+    types::Type,
+    SchemaFragment, SourceLocation, TypeErrorKind, ValidationErrorKind, ValidationWarningKind,
+    Validator, ValidatorSchema,
 };
-use cedar_policy_validator::Validator;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::cell::RefCell;
+use std::collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
 
 #[cfg(feature = "wasm")]
 extern crate tsify;
 
-fn validate(call: &ValidateCall) -> Result<ValidateAnswer, String> {
+/// Maximum number of parsed schemas kept in the schema cache
+const SCHEMA_CACHE_CAPACITY: usize = 8;
+
+/// Maximum number of answers kept in the answer cache
+const ANSWER_CACHE_CAPACITY: usize = 32;
+
+/// The annotation that overrides the validation mode of a policy, e.g.
+/// `@validationMode("permissive")` on a legacy policy in a strict policy set
+const VALIDATION_MODE_ANNOTATION: &str = "validationMode";
+
+thread_local!(
+    /// Per-thread cache of the schemas parsed by validation calls, least
+    /// recently used first, so that repeated calls with the same schema skip
+    /// parsing it
+    static SCHEMA_CACHE: RefCell<Vec<CachedSchema>> = const { RefCell::new(Vec::new()) };
+
+    /// Per-thread cache of the answers to [`json_validate`] calls, least
+    /// recently used first, so that repeating a call with an unchanged schema,
+    /// policy set, and settings answers at once
+    static ANSWER_CACHE: RefCell<Vec<CachedAnswer>> = const { RefCell::new(Vec::new()) };
+);
+
+/// A schema parsed by a validation call
+struct CachedSchema {
+    /// Hash of `json`
+    hash: u64,
+    /// The schema JSON, as given in the call
+    json: String,
+    schema: ValidatorSchema,
+}
+
+/// The answer to a validation call
+struct CachedAnswer {
+    /// Hash of `schema`
+    schema_hash: u64,
+    /// Hash of `policy_set`
+    policy_set_hash: u64,
+    /// The schema JSON, as given in the call
+    schema: String,
+    /// The policy set of the call, as JSON with policies given as a map in
+    /// order of id
+    policy_set: String,
+    /// The validation settings of the call, as JSON
+    settings: String,
+    answer: InterfaceResult,
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parse the schema of a call, reusing the result of parsing the same schema
+/// text in an earlier call if it is still cached. A schema given as a string
+/// is in the Cedar schema syntax; otherwise it is in the JSON format.
+fn parse_schema(schema: &RawValue) -> Result<ValidatorSchema, String> {
+    let text = schema.get();
+    let hash = text_hash(text);
+    SCHEMA_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(i) = cache
+            .iter()
+            .position(|cached| cached.hash == hash && cached.json == text)
+        {
+            let cached = cache.remove(i);
+            let schema = cached.schema.clone();
+            cache.push(cached);
+            return Ok(schema);
+        }
+        let schema: ValidatorSchema = match serde_json::from_str::<String>(text) {
+            Ok(src) => ValidatorSchema::from_str_natural(&src, Extensions::all_available())
+                .map(|(schema, _)| schema)
+                .map_err(|e| format!("could not construct schema: {e}"))?,
+            Err(_) => serde_json::from_str::<SchemaFragment>(text)
+                .map_err(|e| format!("error parsing call: {e}"))?
+                .try_into()
+                .map_err(|e| format!("could not construct schema: {e}"))?,
+        };
+        if cache.len() >= SCHEMA_CACHE_CAPACITY {
+            cache.remove(0);
+        }
+        cache.push(CachedSchema {
+            hash,
+            json: text.to_string(),
+            schema: schema.clone(),
+        });
+        Ok(schema)
+    })
+}
+
+/// Drop the schemas cached by earlier validation calls on this thread
+pub fn clear_schema_cache() {
+    SCHEMA_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+/// The answer to `call`, reusing the answer to an earlier call with the same
+/// schema, policy set, and settings if it is still cached. Calls that ask for
+/// timings are always answered afresh.
+fn cached_answer(call: &ValidateCall, answer: impl FnOnce() -> InterfaceResult) -> InterfaceResult {
+    if call.include_timings {
+        return answer();
+    }
+    let policy_set = match &call.policy_set {
+        PolicySpecification::Map(policies) => {
+            serde_json::to_string(&policies.iter().collect::<BTreeMap<_, _>>())
+        }
+        policies => serde_json::to_string(policies),
+    };
+    let (Ok(policy_set), Ok(settings)) =
+        (policy_set, serde_json::to_string(&call.validation_settings))
+    else {
+        return answer();
+    };
+    let schema = call.schema.get();
+    let schema_hash = text_hash(schema);
+    let policy_set_hash = text_hash(&policy_set);
+    let cached = ANSWER_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let i = cache.iter().position(|cached| {
+            cached.schema_hash == schema_hash
+                && cached.policy_set_hash == policy_set_hash
+                && cached.schema == schema
+                && cached.policy_set == policy_set
+                && cached.settings == settings
+        })?;
+        let cached = cache.remove(i);
+        let answer = cached.answer.clone();
+        cache.push(cached);
+        Some(answer)
+    });
+    if let Some(answer) = cached {
+        return answer;
+    }
+    let answer = answer();
+    ANSWER_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= ANSWER_CACHE_CAPACITY {
+            cache.remove(0);
+        }
+        cache.push(CachedAnswer {
+            schema_hash,
+            policy_set_hash,
+            schema: schema.to_string(),
+            policy_set,
+            settings,
+            answer: answer.clone(),
+        });
+    });
+    answer
+}
+
+/// Drop the answers cached by earlier [`json_validate`] calls on this thread,
+/// e.g. to free the memory they hold once an editing session ends
+pub fn clear_answer_cache() {
+    ANSWER_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+/// Validate the call's policies. If the call asks for timings and a `clock`
+/// is given, the phases of the call are measured with it.
+fn validate(
+    call: &ValidateCall,
+    clock: Option<&dyn Fn() -> f64>,
+) -> Result<ValidateAnswer, String> {
+    let mut stopwatch = Stopwatch::start(clock.filter(|_| call.include_timings));
+    let policy_set = match parse_policies(&call.policy_set) {
+        Ok(policy_set) => policy_set,
+        Err(errors) => return Ok(ValidateAnswer::ParseFailed { errors }),
+    };
+    let parse_policies_ms = stopwatch.lap();
+    let mode = match validator_mode(&call.validation_settings) {
+        Ok(mode) => mode,
+        Err(answer) => return Ok(*answer),
+    };
+    let schema = parse_schema(&call.schema)?;
+    let parse_schema_ms = stopwatch.lap();
+    let answer = run_validator(
+        schema,
+        mode,
+        call.validation_settings.include_typed_ast,
+        &policy_set,
+    );
+    let validate_ms = stopwatch.lap();
+    Ok(match answer {
+        ValidateAnswer::Success {
+            passed,
+            errors,
+            warnings,
+            impossible_policies,
+            summary,
+            policy_modes,
+            typed_policies,
+            ..
+        } if stopwatch.is_running() => ValidateAnswer::Success {
+            passed,
+            errors,
+            warnings,
+            impossible_policies,
+            summary,
+            policy_modes,
+            typed_policies,
+            timings: Some(ValidationTimings {
+                parse_policies_ms,
+                parse_schema_ms,
+                validate_ms,
+            }),
+        },
+        answer => answer,
+    })
+}
+
+fn parse_policies(policies: &PolicySpecification) -> Result<PolicySet, Vec<String>> {
     let mut policy_set = PolicySet::new();
     let mut parse_errors: Vec<String> = vec![];
 
-    match &call.policy_set {
+    match policies {
         PolicySpecification::Concatenated(policies_str) => match parse_policyset(policies_str) {
             Ok(parsed_policy_set) => {
                 policy_set = parsed_policy_set;
@@ -59,6 +284,61 @@ fn validate(call: &ValidateCall) -> Result<ValidateAnswer, String> {
                 };
             }
         }
+        PolicySpecification::Json(policies) => {
+            for policy in policies {
+                if let Err(error) = add_policy_json(&mut policy_set, policy) {
+                    parse_errors.push(format!("error in JSON of policy {}: {error}", policy.id));
+                }
+            }
+        }
+    }
+
+    if parse_errors.is_empty() {
+        Ok(policy_set)
+    } else {
+        Err(parse_errors)
+    }
+}
+
+/// Add `policy` to `policy_set`, as a template if it has slots
+fn add_policy_json(policy_set: &mut PolicySet, policy: &PolicyJsonWithId) -> Result<(), String> {
+    let id = PolicyID::from_string(&policy.id);
+    let est = serde_json::from_value::<est::Policy>(policy.policy.clone().into())
+        .map_err(|e| e.to_string())?;
+    match est.clone().try_into_ast_policy(Some(id.clone())) {
+        Ok(p) => policy_set.add(p).map_err(|e| e.to_string()),
+        Err(est::FromJsonError::TemplateToPolicy(_)) => {
+            let template = est
+                .try_into_ast_template(Some(id))
+                .map_err(|e| e.to_string())?;
+            policy_set.add_template(template).map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn validate_policy_json(call: &ValidatePolicyJsonCall) -> Result<ValidateAnswer, String> {
+    let mut policy_set = PolicySet::new();
+    let mut parse_errors: Vec<String> = vec![];
+
+    // ids are assigned in the same `policyX` format used for concatenated policies
+    for (i, policy_json) in call.policies.iter().enumerate() {
+        let id = PolicyID::from_string(format!("policy{i}"));
+        match serde_json::from_value::<est::Policy>(policy_json.clone().into())
+            .map_err(|e| e.to_string())
+            .and_then(|est| {
+                est.try_into_ast_policy(Some(id.clone()))
+                    .map_err(|e| e.to_string())
+            }) {
+            Ok(policy) => {
+                if let Err(error) = policy_set.add(policy) {
+                    parse_errors.push(format!("error adding policy {id}: {error}"));
+                }
+            }
+            Err(error) => {
+                parse_errors.push(format!("error in JSON of policy {id}: {error}"));
+            }
+        }
     }
 
     if !parse_errors.is_empty() {
@@ -67,36 +347,375 @@ fn validate(call: &ValidateCall) -> Result<ValidateAnswer, String> {
         });
     }
 
-    let schema = call
-        .schema
-        .clone()
-        .try_into()
-        .map_err(|e| format!("could not construct schema: {e}"))?;
+    validate_policy_set(&call.validation_settings, &call.schema, &policy_set)
+}
+
+fn validate_policy_set(
+    settings: &ValidationSettings,
+    schema: &RawValue,
+    policy_set: &PolicySet,
+) -> Result<ValidateAnswer, String> {
+    let mode = match validator_mode(settings) {
+        Ok(mode) => mode,
+        Err(answer) => return Ok(*answer),
+    };
+    let schema = parse_schema(schema)?;
+    Ok(run_validator(
+        schema,
+        mode,
+        settings.include_typed_ast,
+        policy_set,
+    ))
+}
+
+fn validate_policy_set_with_schema(
+    settings: &ValidationSettings,
+    schema: &ValidatorSchema,
+    policy_set: &PolicySet,
+) -> ValidateAnswer {
+    match validator_mode(settings) {
+        Ok(mode) => run_validator(schema.clone(), mode, settings.include_typed_ast, policy_set),
+        Err(answer) => *answer,
+    }
+}
+
+/// The validation mode to use for `settings`, or the answer to give without
+/// running the validator at all
+fn validator_mode(settings: &ValidationSettings) -> Result<ValidationMode, Box<ValidateAnswer>> {
+    if let Some(level) = settings.max_deref_level {
+        return Err(Box::new(ValidateAnswer::ParseFailed {
+            errors: vec![format!(
+                "cannot validate with `maxDerefLevel` {level}: level-based validation is not supported by this version of Cedar"
+            )],
+        }));
+    }
+
+    match &settings.mode {
+        RequestedMode::Invalid(mode) => Err(Box::new(ValidateAnswer::InvalidMode {
+            errors: vec![format!("invalid validation mode `{mode}`")],
+        })),
+        RequestedMode::Valid(ValidationMode::Off) => Err(Box::new(ValidateAnswer::Success {
+            passed: true,
+            errors: vec![],
+            warnings: vec![],
+            impossible_policies: vec![],
+            summary: Box::default(),
+            policy_modes: BTreeMap::new(),
+            typed_policies: None,
+            timings: None,
+        })),
+        RequestedMode::Valid(mode) => Ok(*mode),
+    }
+}
+
+/// Run the validator on `policy_set`, validating each policy in `mode` unless
+/// its `@validationMode` annotation names another mode. If
+/// `include_typed_ast` is set and validation passes, the answer includes the
+/// typed AST of each policy.
+fn run_validator(
+    schema: ValidatorSchema,
+    mode: ValidationMode,
+    include_typed_ast: bool,
+    policy_set: &PolicySet,
+) -> ValidateAnswer {
     let validator = Validator::new(schema);
+    let (annotated_modes, mode_errors) = annotated_modes(policy_set);
+    let mode_of = |id: &PolicyID| annotated_modes.get(id).copied().unwrap_or(mode);
 
-    let notes: Vec<ValidationNote> = validator
-        .validate(
-            &policy_set,
-            cedar_policy_validator::ValidationMode::default(),
+    // the policy set is validated once in each mode some policy is in, and the
+    // errors of each policy are taken from the run in its mode
+    let mut modes = vec![mode];
+    for annotated_mode in annotated_modes.values() {
+        if !modes.contains(annotated_mode) {
+            modes.push(*annotated_mode);
+        }
+    }
+    let results: Vec<_> = modes
+        .iter()
+        .filter_map(|mode| {
+            Some((
+                *mode,
+                validator.validate(policy_set, mode.validator_mode()?),
+            ))
+        })
+        .collect();
+    let (impossible, errors): (Vec<_>, Vec<_>) = results
+        .iter()
+        .flat_map(|(mode, result)| {
+            result
+                .validation_errors()
+                .filter(move |error| mode_of(error.location().policy_id()) == *mode)
+        })
+        .partition(|error| {
+            matches!(
+                error.error_kind(),
+                ValidationErrorKind::TypeError(TypeErrorKind::ImpossiblePolicy)
+            )
+        });
+    let impossible_policies: Vec<_> = impossible
+        .into_iter()
+        .map(|error| ImpossiblePolicyNote {
+            note: ValidationNote::new(
+                error.location(),
+                error_code(error.error_kind()),
+                ValidationSeverity::Warning,
+                error.error_kind().to_string(),
+            ),
+            facts: impossible_policy_facts(
+                validator.schema(),
+                policy_set
+                    .get_template(error.location().policy_id())
+                    .as_deref(),
+            ),
+        })
+        .collect();
+    let errors: Vec<_> = mode_errors
+        .into_iter()
+        .chain(errors.into_iter().map(|error| ValidationNote {
+            suggestions: suggestions(error.error_kind()).to_vec(),
+            ..ValidationNote::new(
+                error.location(),
+                error_code(error.error_kind()),
+                ValidationSeverity::Error,
+                error.error_kind().to_string(),
+            )
+        }))
+        .collect();
+    // warnings do not depend on the mode
+    let warnings: Vec<_> = results
+        .iter()
+        .take(1)
+        .flat_map(|(_, result)| result.validation_warnings())
+        .map(|warning| {
+            ValidationNote::new(
+                warning.location(),
+                warning_code(warning.kind()),
+                ValidationSeverity::Warning,
+                warning.kind().to_string(),
+            )
+        })
+        .collect();
+    let typed_policies = (include_typed_ast && errors.is_empty())
+        .then(|| typed_policies(validator.schema(), mode_of, policy_set));
+    let policy_modes = policy_set
+        .all_templates()
+        .map(Template::id)
+        .chain(
+            policy_set
+                .policies()
+                .filter(|policy| !policy.is_static())
+                .map(Policy::id),
         )
-        .validation_errors()
-        .map(|error| ValidationNote {
-            policy_id: error.location().policy_id().to_string(),
-            note: format!("{}", error.error_kind()),
+        .map(|id| (id.to_string(), mode_of(id)))
+        .collect();
+
+    ValidateAnswer::Success {
+        passed: errors.is_empty(),
+        summary: Box::new(ValidationSummary::new(
+            &errors,
+            &warnings,
+            &impossible_policies,
+        )),
+        errors,
+        warnings,
+        impossible_policies,
+        policy_modes,
+        typed_policies,
+        timings: None,
+    }
+}
+
+/// The validation modes named by the `@validationMode` annotations of the
+/// static policies and templates in `policy_set`, by id, including for each
+/// policy linked from an annotated template, with an error for each
+/// annotation that does not name a mode
+fn annotated_modes(
+    policy_set: &PolicySet,
+) -> (HashMap<PolicyID, ValidationMode>, Vec<ValidationNote>) {
+    let mut modes = HashMap::new();
+    let mut errors = Vec::new();
+    let Ok(key) = VALIDATION_MODE_ANNOTATION.parse::<AnyId>() else {
+        return (modes, errors);
+    };
+    for template in policy_set.all_templates() {
+        let Some(annotation) = template.annotation(&key) else {
+            continue;
+        };
+        match annotation.val.as_str() {
+            "strict" => {
+                modes.insert(template.id().clone(), ValidationMode::Strict);
+            }
+            #[cfg(feature = "permissive-validate")]
+            "permissive" => {
+                modes.insert(template.id().clone(), ValidationMode::Permissive);
+            }
+            value => errors.push(ValidationNote {
+                policy_id: template.id().to_string(),
+                code: UNKNOWN_VALIDATION_MODE_CODE.to_string(),
+                severity: ValidationSeverity::Error,
+                message: format!(
+                    "unknown validation mode `{value}` in `@{VALIDATION_MODE_ANNOTATION}`"
+                ),
+                span: annotation.loc.as_ref().map(SourceSpan::new),
+                suggestions: Vec::new(),
+            }),
+        }
+    }
+    for policy in policy_set.policies().filter(|policy| !policy.is_static()) {
+        if let Some(mode) = modes.get(policy.template().id()).copied() {
+            modes.insert(policy.id().clone(), mode);
+        }
+    }
+    (modes, errors)
+}
+
+/// The typed AST of each static policy and template in `policy_set`, once for
+/// each request environment of `schema` it typechecks in, sorted by policy id
+/// and then by environment
+fn typed_policies(
+    schema: &ValidatorSchema,
+    mode_of: impl Fn(&PolicyID) -> ValidationMode,
+    policy_set: &PolicySet,
+) -> Vec<TypedPolicy> {
+    let mut typed_policies: Vec<TypedPolicy> = policy_set
+        .all_templates()
+        .flat_map(|template| {
+            let Some(mode) = mode_of(template.id()).validator_mode() else {
+                return vec![];
+            };
+            Typechecker::new(schema, mode)
+                .typecheck_by_request_env(template)
+                .into_iter()
+                .filter_map(|(env, check)| match check {
+                    PolicyCheck::Success(condition) => Some(TypedPolicy {
+                        policy_id: template.id().to_string(),
+                        principal_type: env.principal_entity_type().map(ToString::to_string),
+                        action: env.action_entity_uid().map(ToString::to_string),
+                        resource_type: env.resource_entity_type().map(ToString::to_string),
+                        condition: TypedExpr::new(&condition),
+                    }),
+                    PolicyCheck::Irrelevant(_) | PolicyCheck::Fail(_) => None,
+                })
+                .collect::<Vec<_>>()
         })
         .collect();
+    typed_policies.sort_by(|a, b| {
+        (&a.policy_id, &a.action, &a.principal_type, &a.resource_type).cmp(&(
+            &b.policy_id,
+            &b.action,
+            &b.principal_type,
+            &b.resource_type,
+        ))
+    });
+    typed_policies
+}
+
+/// The facts from `schema` that bear on why `template` can never apply: what
+/// each action in its scope applies to, and that its condition is false for
+/// every request the schema declares
+fn impossible_policy_facts(schema: &ValidatorSchema, template: Option<&Template>) -> Vec<String> {
+    let type_list = |types: Vec<String>| {
+        if types.is_empty() {
+            "no types".to_string()
+        } else {
+            types.join(", ")
+        }
+    };
+    let mut facts: Vec<String> = template
+        .into_iter()
+        .flat_map(|template| template.action_constraint().iter_euids())
+        .map(|action| {
+            schema.get_action_id(action).map_or_else(
+                || format!("action `{action}` is not declared in the schema"),
+                |declared| {
+                    format!(
+                        "action `{action}` applies to principals of type {} and resources of type {}",
+                        type_list(
+                            declared
+                                .applies_to_principals()
+                                .map(|ty| format!("`{ty}`"))
+                                .collect()
+                        ),
+                        type_list(
+                            declared
+                                .applies_to_resources()
+                                .map(|ty| format!("`{ty}`"))
+                                .collect()
+                        ),
+                    )
+                },
+            )
+        })
+        .collect();
+    facts.push(
+        "the policy evaluates to false for every principal, action, and resource type the schema declares"
+            .to_string(),
+    );
+    facts
+}
 
-    Ok(ValidateAnswer::Success { notes })
+fn answer_to_result(answer: Result<ValidateAnswer, String>) -> InterfaceResult {
+    match answer {
+        Ok(answer @ ValidateAnswer::Success { .. }) => InterfaceResult::succeed(answer),
+        Ok(ValidateAnswer::ParseFailed { errors }) => InterfaceResult::fail_bad_request(errors),
+        Ok(ValidateAnswer::InvalidMode { errors }) => {
+            InterfaceResult::fail_invalid_validation_mode(errors)
+        }
+        Err(e) => InterfaceResult::fail_internally(e),
+    }
 }
 
 /// public string-based validation function
+///
+/// Answers are cached per thread, keyed by the schema, policy set, and
+/// validation settings of the call; see [`clear_answer_cache`].
 pub fn json_validate(input: &str) -> InterfaceResult {
+    validate_call(input, None)
+}
+
+/// public string-based validation function like [`json_validate`], which
+/// measures the phases of the call with `clock` when the call sets
+/// `includeTimings`
+///
+/// `clock` returns the current time in milliseconds, e.g. `performance.now()`
+/// in a browser. The response then includes `timings` with the time spent
+/// parsing policies, parsing the schema, and validating.
+pub fn json_validate_with_clock(input: &str, clock: &dyn Fn() -> f64) -> InterfaceResult {
+    validate_call(input, Some(clock))
+}
+
+fn validate_call(input: &str, clock: Option<&dyn Fn() -> f64>) -> InterfaceResult {
     serde_json::from_str::<ValidateCall>(input).map_or_else(
         |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
-        |call| match validate(&call) {
-            Ok(answer @ ValidateAnswer::Success { .. }) => InterfaceResult::succeed(answer),
-            Ok(ValidateAnswer::ParseFailed { errors }) => InterfaceResult::fail_bad_request(errors),
-            Err(e) => InterfaceResult::fail_internally(e),
+        |call| cached_answer(&call, || answer_to_result(validate(&call, clock))),
+    )
+}
+
+/// public string-based validation function for policies in JSON (EST) format
+///
+/// Policies are expected in the format produced by `Policy::to_json`, and are
+/// assigned ids `policy0`, `policy1`, ... in the order they are given.
+pub fn json_validate_policy_json(input: &str) -> InterfaceResult {
+    serde_json::from_str::<ValidatePolicyJsonCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| answer_to_result(validate_policy_json(&call)),
+    )
+}
+
+/// public string-based validation function for a policy set the caller has
+/// already parsed
+///
+/// The call has the same fields as the one accepted by [`json_validate`],
+/// minus the `policySet`.
+pub fn json_validate_with_policy_set(input: &str, policies: &crate::PolicySet) -> InterfaceResult {
+    serde_json::from_str::<PolicySetValidateCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| {
+            answer_to_result(validate_policy_set(
+                &call.validation_settings,
+                &call.schema,
+                &policies.ast,
+            ))
         },
     )
 }
@@ -108,46 +727,545 @@ struct ValidateCall {
     #[serde(default)]
     #[serde(rename = "validationSettings")]
     validation_settings: ValidationSettings,
-    schema: cedar_policy_validator::SchemaFragment,
+    /// The schema, in the JSON format or as a string in the Cedar schema
+    /// syntax, e.g. `entity User; action view appliesTo { ... };`
+    #[cfg_attr(feature = "wasm", tsify(type = "SchemaFragment | string"))]
+    schema: Box<RawValue>,
     #[serde(rename = "policySet")]
     policy_set: PolicySpecification,
+    /// If this is `true`, the response includes `timings` for the phases of
+    /// the call. This requires a clock; see [`json_validate_with_clock`].
+    #[serde(default)]
+    #[serde(rename = "includeTimings")]
+    include_timings: bool,
+}
+
+/// public string-based validation function against a schema the caller has
+/// already parsed
+///
+/// The call has the same fields as the one accepted by [`json_validate`],
+/// minus the `schema`.
+pub fn json_validate_with_schema(input: &str, schema: &crate::Schema) -> InterfaceResult {
+    serde_json::from_str::<SchemaValidateCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| {
+            answer_to_result(Ok(match parse_policies(&call.policy_set) {
+                Ok(policy_set) => validate_policy_set_with_schema(
+                    &call.validation_settings,
+                    &schema.0,
+                    &policy_set,
+                ),
+                Err(errors) => ValidateAnswer::ParseFailed { errors },
+            }))
+        },
+    )
+}
+
+/// public string-based validation function for a policy set and schema the
+/// caller has already parsed
+///
+/// The call contains only the optional `validationSettings`.
+pub fn json_validate_with(
+    input: &str,
+    schema: &crate::Schema,
+    policies: &crate::PolicySet,
+) -> InterfaceResult {
+    serde_json::from_str::<SettingsValidateCall>(input).map_or_else(
+        |e| InterfaceResult::fail_internally(format!("error parsing call: {e:}")),
+        |call| {
+            answer_to_result(Ok(validate_policy_set_with_schema(
+                &call.validation_settings,
+                &schema.0,
+                &policies.ast,
+            )))
+        },
+    )
+}
+
+/// Validation call against an already-parsed schema; see
+/// [`json_validate_with_schema`]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct SchemaValidateCall {
+    #[serde(default)]
+    #[serde(rename = "validationSettings")]
+    validation_settings: ValidationSettings,
+    #[serde(rename = "policySet")]
+    policy_set: PolicySpecification,
+}
+
+/// Validation call against an already-parsed schema and policy set; see
+/// [`json_validate_with`]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct SettingsValidateCall {
+    #[serde(default)]
+    #[serde(rename = "validationSettings")]
+    validation_settings: ValidationSettings,
+}
+
+/// Validation call for a policy set the caller has already parsed; see
+/// [`json_validate_with_policy_set`]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct PolicySetValidateCall {
+    #[serde(default)]
+    #[serde(rename = "validationSettings")]
+    validation_settings: ValidationSettings,
+    #[cfg_attr(feature = "wasm", tsify(type = "SchemaFragment"))]
+    schema: Box<RawValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+struct ValidatePolicyJsonCall {
+    #[serde(default)]
+    #[serde(rename = "validationSettings")]
+    validation_settings: ValidationSettings,
+    #[cfg_attr(feature = "wasm", tsify(type = "SchemaFragment"))]
+    schema: Box<RawValue>,
+    /// Policies in their JSON (EST) format
+    #[cfg_attr(feature = "wasm", tsify(type = "Array<Policy>"))]
+    policies: Vec<JsonValueWithNoDuplicateKeys>,
 }
 
 #[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 struct ValidationSettings {
-    mode: ValidationMode,
+    #[cfg_attr(feature = "wasm", tsify(type = "ValidationMode"))]
+    mode: RequestedMode,
+    /// Maximum level of entity dereferencing allowed in policies. Level-based
+    /// validation is not available in this version of Cedar, so validation
+    /// fails with an error if this is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    max_deref_level: Option<u32>,
+    /// If this is `true` and validation passes, the answer includes the typed
+    /// AST of each policy as `typedPolicies`, e.g. for tooling that needs the
+    /// types the validator inferred without parsing Cedar itself
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    include_typed_ast: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+/// The `mode` of `ValidationSettings`. A value naming no mode is kept, rather
+/// than failing to parse the call, so that it can be reported as a bad
+/// request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RequestedMode {
+    Valid(ValidationMode),
+    Invalid(String),
+}
+
+impl Default for RequestedMode {
+    fn default() -> Self {
+        Self::Valid(ValidationMode::default())
+    }
+}
+
+/// Validation mode requested in `ValidationSettings`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 enum ValidationMode {
-    #[serde(rename = "regular")]
-    Regular,
+    /// Strict validation; `regular` is accepted for backwards compatibility
+    #[default]
+    #[serde(rename = "strict", alias = "regular")]
+    Strict,
+    /// Permissive validation, which allows some policies that strict
+    /// validation rejects
+    #[doc = include_str!("../../experimental_warning.md")]
+    #[cfg(feature = "permissive-validate")]
+    #[serde(rename = "permissive")]
+    Permissive,
+    /// Parse the policies but skip validation
     #[serde(rename = "off")]
     Off,
 }
 
-impl Default for ValidationMode {
-    fn default() -> Self {
-        Self::Regular
+impl ValidationMode {
+    /// The mode to run the validator in, or `None` to skip validation
+    fn validator_mode(self) -> Option<cedar_policy_validator::ValidationMode> {
+        match self {
+            Self::Strict => Some(cedar_policy_validator::ValidationMode::Strict),
+            #[cfg(feature = "permissive-validate")]
+            Self::Permissive => Some(cedar_policy_validator::ValidationMode::Permissive),
+            Self::Off => None,
+        }
     }
 }
 
+/// A single validation error or warning, attributed to the policy it was
+/// found in
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct ValidationNote {
-    #[serde(rename = "policyId")]
     policy_id: String,
-    note: String,
+    /// Stable code of the kind of issue, e.g. `V001_UNRECOGNIZED_ENTITY_TYPE`,
+    /// for tooling to match on instead of the message
+    code: String,
+    severity: ValidationSeverity,
+    message: String,
+    /// Location of the issue in the policy source, if known. Offsets are into
+    /// the text of the individual policy when policies are passed as a map,
+    /// and into the whole string when they are passed concatenated.
+    span: Option<SourceSpan>,
+    /// For an unrecognized entity type, action, or attribute, the names from
+    /// the schema nearest to it, which the policy may have meant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+}
+
+impl ValidationNote {
+    fn new(
+        location: &SourceLocation<'_>,
+        code: &str,
+        severity: ValidationSeverity,
+        message: String,
+    ) -> Self {
+        Self {
+            policy_id: location.policy_id().to_string(),
+            code: code.to_string(),
+            severity,
+            message,
+            span: location.source_loc().map(SourceSpan::new),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+/// Code of a `@validationMode` annotation that does not name a mode
+const UNKNOWN_VALIDATION_MODE_CODE: &str = "V030_UNKNOWN_VALIDATION_MODE";
+
+/// Code of an error or warning of a kind added to the validator after the
+/// codes below were assigned
+const OTHER_CODE: &str = "V999_OTHER";
+
+/// The stable code of validation errors of the kind of `kind`. Codes are never
+/// reused or renumbered, so a new kind of error gets a new code.
+fn error_code(kind: &ValidationErrorKind) -> &'static str {
+    match kind {
+        ValidationErrorKind::UnrecognizedEntityType(_) => "V001_UNRECOGNIZED_ENTITY_TYPE",
+        ValidationErrorKind::UnrecognizedActionId(_) => "V002_UNRECOGNIZED_ACTION_ID",
+        ValidationErrorKind::InvalidActionApplication(_) => "V003_INVALID_ACTION_APPLICATION",
+        ValidationErrorKind::UnspecifiedEntity(_) => "V004_UNSPECIFIED_ENTITY",
+        ValidationErrorKind::TypeError(kind) => match kind {
+            TypeErrorKind::UnexpectedType(_) => "V010_UNEXPECTED_TYPE",
+            TypeErrorKind::IncompatibleTypes(_) => "V011_INCOMPATIBLE_TYPES",
+            TypeErrorKind::UnsafeAttributeAccess(_) => "V012_UNSAFE_ATTRIBUTE_ACCESS",
+            TypeErrorKind::UnsafeOptionalAttributeAccess(_) => {
+                "V013_UNSAFE_OPTIONAL_ATTRIBUTE_ACCESS"
+            }
+            TypeErrorKind::ImpossiblePolicy => "V014_IMPOSSIBLE_POLICY",
+            TypeErrorKind::UndefinedFunction(_) => "V015_UNDEFINED_FUNCTION",
+            TypeErrorKind::MultiplyDefinedFunction(_) => "V016_MULTIPLY_DEFINED_FUNCTION",
+            TypeErrorKind::WrongNumberArguments(_) => "V017_WRONG_NUMBER_ARGUMENTS",
+            TypeErrorKind::WrongCallStyle(_) => "V018_WRONG_CALL_STYLE",
+            TypeErrorKind::FunctionArgumentValidationError(_) => "V019_INVALID_FUNCTION_ARGUMENT",
+            TypeErrorKind::EmptySetForbidden => "V020_EMPTY_SET_FORBIDDEN",
+            TypeErrorKind::NonLitExtConstructor => "V021_NON_LITERAL_EXTENSION_CONSTRUCTOR",
+            TypeErrorKind::HierarchyNotRespected(_) => "V022_HIERARCHY_NOT_RESPECTED",
+            _ => OTHER_CODE,
+        },
+        _ => OTHER_CODE,
+    }
+}
+
+/// The names from the schema nearest to the unrecognized name a validation
+/// error of the kind of `kind` is about, if it is about one
+fn suggestions(kind: &ValidationErrorKind) -> &[String] {
+    match kind {
+        ValidationErrorKind::UnrecognizedEntityType(error) => error.suggestions(),
+        ValidationErrorKind::UnrecognizedActionId(error) => error.suggestions(),
+        ValidationErrorKind::TypeError(TypeErrorKind::UnsafeAttributeAccess(error)) => {
+            error.suggestions()
+        }
+        _ => &[],
+    }
+}
+
+/// The stable code of validation warnings of the kind of `kind`, numbered
+/// from `V101` to keep them apart from errors
+fn warning_code(kind: &ValidationWarningKind) -> &'static str {
+    match kind {
+        ValidationWarningKind::MixedScriptString(_) => "V101_MIXED_SCRIPT_STRING",
+        ValidationWarningKind::BidiCharsInString(_) => "V102_BIDI_CHARS_IN_STRING",
+        ValidationWarningKind::BidiCharsInIdentifier(_) => "V103_BIDI_CHARS_IN_IDENTIFIER",
+        ValidationWarningKind::MixedScriptIdentifier(_) => "V104_MIXED_SCRIPT_IDENTIFIER",
+        ValidationWarningKind::ConfusableIdentifier(_) => "V105_CONFUSABLE_IDENTIFIER",
+        _ => OTHER_CODE,
+    }
+}
+
+/// A policy that can never apply, with the facts from the schema that bear on
+/// why
+#[derive(Debug, Serialize, Deserialize)]
+struct ImpossiblePolicyNote {
+    #[serde(flatten)]
+    note: ValidationNote,
+    facts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ValidationSeverity {
+    Error,
+    Warning,
+}
+
+/// A range of policy source, as byte offsets and as the line and column at
+/// each end, so an editor can underline it. The end is exclusive: it is the
+/// position just after the last character of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceSpan {
+    start: usize,
+    end: usize,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl SourceSpan {
+    fn new(loc: &Loc) -> Self {
+        let start = loc.span.offset();
+        let end = start + loc.span.len();
+        let (start_line, start_column) = line_and_column(&loc.src, start);
+        let (end_line, end_column) = line_and_column(&loc.src, end);
+        Self {
+            start,
+            end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+/// The typed AST of a policy in one request environment: its condition, with
+/// the scope constraints conjoined, as the validator typechecked it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypedPolicy {
+    policy_id: String,
+    /// The principal type of the environment, or `None` if its action is not
+    /// declared in the schema
+    principal_type: Option<String>,
+    /// The action of the environment, or `None` if it is not declared in the
+    /// schema
+    action: Option<String>,
+    /// The resource type of the environment, or `None` if its action is not
+    /// declared in the schema
+    resource_type: Option<String>,
+    condition: TypedExpr,
 }
 
+/// A node of the typed AST of a policy
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypedExpr {
+    /// The kind of expression, named after the variants of the core
+    /// `ExprKind`, e.g. `getAttr` or `binaryApp`
+    kind: String,
+    /// The literal, variable, slot, operator, constant, function, attribute,
+    /// pattern, or entity type of the node, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// For the elements of a record, the attribute they are the value of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    /// The type the validator gave the expression, if it gave one
+    #[serde(rename = "type")]
+    ty: Option<String>,
+    /// Location of the expression in the policy source, if known. Offsets are
+    /// as for the `span` of validation notes.
+    span: Option<SourceSpan>,
+    /// The subexpressions, in source order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Self>,
+}
+
+impl TypedExpr {
+    fn new(expr: &Expr<Option<Type>>) -> Self {
+        let children = |exprs: &[&Expr<Option<Type>>]| -> Vec<Self> {
+            exprs.iter().map(|expr| Self::new(expr)).collect()
+        };
+        let (kind, value, children) = match expr.expr_kind() {
+            ExprKind::Lit(lit) => ("lit", Some(lit.to_string()), vec![]),
+            ExprKind::Var(var) => ("var", Some(var.to_string()), vec![]),
+            ExprKind::Slot(slot) => ("slot", Some(slot.to_string()), vec![]),
+            ExprKind::Unknown(unknown) => ("unknown", Some(unknown.to_string()), vec![]),
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => ("if", None, children(&[test_expr, then_expr, else_expr])),
+            ExprKind::And { left, right } => ("and", None, children(&[left, right])),
+            ExprKind::Or { left, right } => ("or", None, children(&[left, right])),
+            ExprKind::UnaryApp { op, arg } => ("unaryApp", Some(op.to_string()), children(&[arg])),
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                ("binaryApp", Some(op.to_string()), children(&[arg1, arg2]))
+            }
+            ExprKind::MulByConst { arg, constant } => {
+                ("mulByConst", Some(constant.to_string()), children(&[arg]))
+            }
+            ExprKind::ExtensionFunctionApp { fn_name, args } => (
+                "extensionFunctionApp",
+                Some(fn_name.to_string()),
+                args.iter().map(Self::new).collect(),
+            ),
+            ExprKind::GetAttr { expr, attr } => {
+                ("getAttr", Some(attr.to_string()), children(&[expr]))
+            }
+            ExprKind::HasAttr { expr, attr } => {
+                ("hasAttr", Some(attr.to_string()), children(&[expr]))
+            }
+            ExprKind::Like { expr, pattern } => {
+                ("like", Some(pattern.to_string()), children(&[expr]))
+            }
+            ExprKind::Is { expr, entity_type } => {
+                ("is", Some(entity_type.to_string()), children(&[expr]))
+            }
+            ExprKind::Set(elements) => ("set", None, elements.iter().map(Self::new).collect()),
+            ExprKind::Record(attrs) => (
+                "record",
+                None,
+                attrs
+                    .iter()
+                    .map(|(attr, value)| Self {
+                        key: Some(attr.to_string()),
+                        ..Self::new(value)
+                    })
+                    .collect(),
+            ),
+        };
+        Self {
+            kind: kind.to_string(),
+            value,
+            key: None,
+            ty: expr.data().as_ref().map(ToString::to_string),
+            span: expr.source_loc().map(SourceSpan::new),
+            children,
+        }
+    }
+}
+
+// `Success` comes first so that a successful answer with no errors is not
+// mistaken for `ParseFailed` when deserializing
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum ValidateAnswer {
-    ParseFailed { errors: Vec<String> },
-    Success { notes: Vec<ValidationNote> },
+    Success {
+        /// Whether validation passed, i.e., there were no errors. There may
+        /// still be warnings.
+        passed: bool,
+        errors: Vec<ValidationNote>,
+        warnings: Vec<ValidationNote>,
+        /// Policies that can never apply to a request the schema declares.
+        /// These are warnings, so they do not fail validation.
+        #[serde(default, rename = "impossiblePolicies")]
+        impossible_policies: Vec<ImpossiblePolicyNote>,
+        /// The notes above tallied, e.g. for a CI gate or a dashboard
+        #[serde(default)]
+        summary: Box<ValidationSummary>,
+        /// The mode each static policy, template, and template-linked policy
+        /// was validated in, by id
+        #[serde(default, rename = "policyModes")]
+        policy_modes: BTreeMap<String, ValidationMode>,
+        /// The typed AST of each policy, if the call set `includeTypedAst`
+        /// and validation passed
+        #[serde(
+            default,
+            rename = "typedPolicies",
+            skip_serializing_if = "Option::is_none"
+        )]
+        typed_policies: Option<Vec<TypedPolicy>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timings: Option<ValidationTimings>,
+    },
+    ParseFailed {
+        errors: Vec<String>,
+    },
+    /// The validation settings name an invalid mode
+    InvalidMode {
+        errors: Vec<String>,
+    },
+}
+
+/// A tally of the notes of a validation answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationSummary {
+    /// Whether validation passed, as in the answer
+    passed: bool,
+    /// The number of errors
+    error_count: usize,
+    /// The number of warnings, including impossible policies
+    warning_count: usize,
+    /// The ids of the policies with errors, sorted
+    policies_with_errors: Vec<String>,
+    /// The ids of the policies with warnings but no errors, sorted
+    policies_with_warnings: Vec<String>,
+}
+
+impl Default for ValidationSummary {
+    fn default() -> Self {
+        Self::new(&[], &[], &[])
+    }
+}
+
+impl ValidationSummary {
+    fn new(
+        errors: &[ValidationNote],
+        warnings: &[ValidationNote],
+        impossible_policies: &[ImpossiblePolicyNote],
+    ) -> Self {
+        let policies_with_errors: BTreeSet<&str> =
+            errors.iter().map(|note| note.policy_id.as_str()).collect();
+        let policies_with_warnings: BTreeSet<&str> = warnings
+            .iter()
+            .chain(
+                impossible_policies
+                    .iter()
+                    .map(|impossible| &impossible.note),
+            )
+            .map(|note| note.policy_id.as_str())
+            .filter(|id| !policies_with_errors.contains(id))
+            .collect();
+        Self {
+            passed: errors.is_empty(),
+            error_count: errors.len(),
+            warning_count: warnings.len() + impossible_policies.len(),
+            policies_with_errors: policies_with_errors.into_iter().map(String::from).collect(),
+            policies_with_warnings: policies_with_warnings
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+/// Time spent in each phase of a validation call, in milliseconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct ValidationTimings {
+    /// Parsing policies
+    pub parse_policies_ms: f64,
+    /// Parsing the schema
+    pub parse_schema_ms: f64,
+    /// Validating the policies against the schema
+    pub validate_ms: f64,
 }
 
 // PANIC SAFETY unit tests
@@ -155,18 +1273,21 @@ enum ValidateAnswer {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::frontend::utils::assert_is_failure;
+    use crate::frontend::utils::{assert_is_failure, FailureCode, RESPONSE_VERSION};
     use cool_asserts::assert_matches;
+    use itertools::Itertools;
     use std::collections::HashMap;
+    use std::str::FromStr;
 
     #[test]
     fn test_validate_empty_policy_directly() {
-        let schema = cedar_policy_validator::SchemaFragment(HashMap::new());
+        let schema = RawValue::from_string("{}".to_string()).unwrap();
 
         let call = ValidateCall {
             validation_settings: ValidationSettings::default(),
             schema,
             policy_set: PolicySpecification::Map(HashMap::new()),
+            include_timings: false,
         };
 
         let call_json: String = serde_json::to_string(&call).expect("could not serialise call");
@@ -293,7 +1414,143 @@ mod test {
 "#.to_string();
 
         let result = json_validate(&call_json);
-        assert_validates_with_notes(result, 4);
+        assert_validates_with_notes(result, 2);
+    }
+
+    #[test]
+    fn test_validation_notes_are_structured_per_policy() {
+        let call_json = r#"{
+  "schema": {"": {
+    "entityTypes": {
+      "User": {
+        "memberOfTypes": [ ],
+        "shape": {
+          "type": "Record",
+          "attributes": { "name": { "type": "String" } }
+        }
+      },
+      "Photo": { "memberOfTypes": [ ] }
+    },
+    "actions": {
+      "viewPhoto": {
+        "appliesTo": {
+          "resourceTypes": [ "Photo" ],
+          "principalTypes": [ "User" ]
+        }
+      }
+    }
+  }},
+  "policySet": {
+    "policy0": "permit(principal, action, resource == Album::\"a\");",
+    "policy1": "permit(principal, action, resource) when { principal.name == \"\u0440aypal\" };"
+  }
+}
+"#;
+        let (errors, warnings) =
+            assert_validates_with_errors_and_warnings(json_validate(call_json), 2, 1);
+        assert!(errors
+            .iter()
+            .all(|note| note.severity == ValidationSeverity::Error));
+        assert!(errors.iter().all(|note| note.policy_id == "policy0"));
+        assert!(errors
+            .iter()
+            .any(|note| note.message == "unrecognized entity type `Album`"
+                && note.code == "V001_UNRECOGNIZED_ENTITY_TYPE"));
+        let warning = warnings.into_iter().exactly_one().unwrap();
+        assert_eq!(warning.policy_id, "policy1");
+        assert_eq!(warning.code, "V101_MIXED_SCRIPT_STRING");
+        assert_eq!(
+            warning.message,
+            "string `\"\u{0440}aypal\"` contains mixed scripts"
+        );
+        assert_eq!(
+            warning.span,
+            Some(SourceSpan {
+                start: 61,
+                end: 70,
+                start_line: 1,
+                start_column: 62,
+                end_line: 1,
+                end_column: 70,
+            })
+        );
+    }
+
+    #[test]
+    fn test_line_and_column_clamps_to_char_boundaries() {
+        let src = "ab\nré\nx";
+        assert_eq!(line_and_column(src, 0), (1, 1));
+        assert_eq!(line_and_column(src, 3), (2, 1));
+        // `é` takes bytes 4 and 5, so offset 5 is inside it
+        assert_eq!(line_and_column(src, 4), (2, 2));
+        assert_eq!(line_and_column(src, 5), (2, 2));
+        assert_eq!(line_and_column(src, 6), (2, 3));
+        assert_eq!(line_and_column(src, 100), (3, 2));
+    }
+
+    #[test]
+    fn test_validation_notes_locate_issues_by_line_and_column() {
+        let call_json = serde_json::json!({
+            "schema": { "": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+                }
+            }},
+            "policySet": "permit(principal, action, resource);\n// the résumé check\npermit(principal, action, resource)\n  when { \"résumé\" && true };"
+        })
+        .to_string();
+        let (errors, _) =
+            assert_validates_with_errors_and_warnings(json_validate(&call_json), 1, 0);
+        let error = errors.into_iter().exactly_one().unwrap();
+        assert_eq!(error.policy_id, "policy1");
+        // the string literal `"résumé"`, of 8 characters but 10 bytes
+        assert_eq!(
+            error.span,
+            Some(SourceSpan {
+                start: 104,
+                end: 114,
+                start_line: 4,
+                start_column: 10,
+                end_line: 4,
+                end_column: 18,
+            })
+        );
+    }
+
+    #[test]
+    fn test_validation_notes_suggest_nearest_names() {
+        let call_json = serde_json::json!({
+            "schema": "entity User { name: String, nick: String }; entity UserGroup; entity UserGroups; action view appliesTo { principal: [User], resource: [UserGroup] };",
+            "policySet": {
+                "types": "permit(principal, action, resource is UserGroup1);",
+                "actions": "permit(principal, action == Action::\"veiw\", resource);",
+                "attrs": "permit(principal is User, action, resource) when { principal.nime == \"a\" };"
+            }
+        })
+        .to_string();
+        // the unrecognized entity type and action also make the scopes of
+        // their policies apply to no action
+        let errors = assert_validates_with_notes(json_validate(&call_json), 5);
+        let suggestions = |code: &str| {
+            errors
+                .iter()
+                .find(|note| note.code == code)
+                .map(|note| note.suggestions.clone())
+        };
+        assert_eq!(
+            suggestions("V001_UNRECOGNIZED_ENTITY_TYPE"),
+            Some(vec!["UserGroup".to_string(), "UserGroups".to_string()])
+        );
+        assert_eq!(
+            suggestions("V002_UNRECOGNIZED_ACTION_ID"),
+            Some(vec![r#"Action::"view""#.to_string()])
+        );
+        assert_eq!(
+            suggestions("V012_UNSAFE_ATTRIBUTE_ACCESS"),
+            Some(vec!["name".to_string()])
+        );
+        assert_eq!(suggestions("V003_INVALID_ACTION_APPLICATION"), Some(vec![]));
     }
 
     #[test]
@@ -397,7 +1654,7 @@ mod test {
 "#.to_string();
 
         let result = json_validate(&call_json);
-        assert_validates_with_notes(result, 2);
+        assert_validates_with_notes(result, 1);
     }
 
     #[test]
@@ -418,12 +1675,459 @@ mod test {
         );
     }
 
+    const MIXED_SET_CALL: &str = r#"{
+  "validationSettings": { "mode": "MODE" },
+  "schema": {"": {
+    "entityTypes": {
+      "User": { "memberOfTypes": [ ] },
+      "Photo": { "memberOfTypes": [ ] }
+    },
+    "actions": {
+      "viewPhoto": {
+        "appliesTo": {
+          "resourceTypes": [ "Photo" ],
+          "principalTypes": [ "User" ]
+        }
+      }
+    }
+  }},
+  "policySet": {
+    "policy0": "permit(principal, action == Action::\"viewPhoto\", resource) when { [User::\"alice\", Photo::\"photo.jpg\"].contains(resource) };"
+  }
+}"#;
+
+    #[test]
+    fn test_strict_mode_rejects_mixed_entity_set() {
+        let result = json_validate(&MIXED_SET_CALL.replace("MODE", "strict"));
+        assert_validates_with_notes(result, 1);
+        let result = json_validate(&MIXED_SET_CALL.replace("MODE", "regular"));
+        assert_validates_with_notes(result, 1);
+    }
+
+    #[cfg(feature = "permissive-validate")]
+    #[test]
+    fn test_permissive_mode_accepts_mixed_entity_set() {
+        let result = json_validate(&MIXED_SET_CALL.replace("MODE", "permissive"));
+        assert_validates_without_notes(result);
+    }
+
+    #[test]
+    fn test_off_mode_skips_validation() {
+        let result = json_validate(&MIXED_SET_CALL.replace("MODE", "off"));
+        assert_validates_without_notes(result);
+    }
+
+    #[test]
+    fn test_invalid_mode_fails() {
+        let result = json_validate(&MIXED_SET_CALL.replace("MODE", "lenient"));
+        assert_is_failure(&result, false, "invalid validation mode `lenient`");
+        assert_matches!(
+            result,
+            InterfaceResult::Failure {
+                code: Some(FailureCode::InvalidValidationMode),
+                ..
+            }
+        );
+    }
+
+    /// `MIXED_SET_CALL` in `mode` with `policy0` annotated with `annotation`
+    /// and a second, unannotated copy of it, `policy1`
+    fn annotated_mixed_set_call(mode: &str, annotation: &str) -> String {
+        let mut call: serde_json::Value =
+            serde_json::from_str(&MIXED_SET_CALL.replace("MODE", mode)).unwrap();
+        let policy = call["policySet"]["policy0"].as_str().unwrap().to_string();
+        call["policySet"]["policy0"] = format!("{annotation} {policy}").into();
+        call["policySet"]["policy1"] = policy.into();
+        call.to_string()
+    }
+
+    fn answer(call: &str) -> serde_json::Value {
+        assert_matches!(json_validate(call), InterfaceResult::Success { result, .. } => {
+            serde_json::from_str(&result).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_validation_mode_annotation_reports_policy_modes() {
+        let answer = answer(&annotated_mixed_set_call(
+            "strict",
+            r#"@validationMode("strict")"#,
+        ));
+        assert_eq!(
+            answer["policyModes"],
+            serde_json::json!({ "policy0": "strict", "policy1": "strict" })
+        );
+        assert_eq!(answer["errors"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_validation_mode_annotation_fails() {
+        let call = annotated_mixed_set_call("off", r#"@validationMode("lenient")"#);
+        assert_validates_without_notes(json_validate(&call));
+
+        let call = annotated_mixed_set_call("strict", r#"@validationMode("lenient")"#);
+        let answer = answer(&call);
+        assert_eq!(answer["policyModes"]["policy0"], "strict");
+        let errors = answer["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0]["policyId"], "policy0");
+        assert_eq!(
+            errors[0]["message"],
+            "unknown validation mode `lenient` in `@validationMode`"
+        );
+        assert_eq!(errors[0]["code"], "V030_UNKNOWN_VALIDATION_MODE");
+        assert_eq!(errors[0]["span"]["start"], 0);
+    }
+
+    #[cfg(feature = "permissive-validate")]
+    #[test]
+    fn test_validation_mode_annotation_overrides_mode_per_policy() {
+        let call = annotated_mixed_set_call("strict", r#"@validationMode("permissive")"#);
+        let answer = answer(&call);
+        assert_eq!(
+            answer["policyModes"],
+            serde_json::json!({ "policy0": "permissive", "policy1": "strict" })
+        );
+        let errors = answer["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["policyId"], "policy1");
+
+        let call = annotated_mixed_set_call("permissive", r#"@validationMode("strict")"#);
+        let errors = &self::answer(&call)["errors"];
+        assert_eq!(errors.as_array().unwrap().len(), 1);
+        assert_eq!(errors[0]["policyId"], "policy0");
+    }
+
+    #[test]
+    fn test_policy_json_validates_with_generated_ids() {
+        let call_json = r#"{
+            "schema": { "": { "entityTypes": { "User": {} }, "actions": {} } },
+            "policies": [
+                {
+                    "effect": "permit",
+                    "principal": { "op": "All" },
+                    "action": { "op": "All" },
+                    "resource": { "op": "==", "entity": { "type": "Photo", "id": "p" } },
+                    "conditions": []
+                }
+            ]
+        }"#;
+        let notes = assert_validates_with_notes(json_validate_policy_json(call_json), 2);
+        assert!(notes.iter().all(|note| note.policy_id == "policy0"));
+        assert!(notes
+            .iter()
+            .any(|note| note.message == "unrecognized entity type `Photo`"));
+    }
+
+    #[test]
+    fn test_policy_json_with_template_fails() {
+        let call_json = r#"{
+            "schema": { "": { "entityTypes": {}, "actions": {} } },
+            "policies": [
+                {
+                    "effect": "permit",
+                    "principal": { "op": "==", "slot": "?principal" },
+                    "action": { "op": "All" },
+                    "resource": { "op": "All" },
+                    "conditions": []
+                }
+            ]
+        }"#;
+        assert_is_failure(
+            &json_validate_policy_json(call_json),
+            false,
+            "error in JSON of policy policy0",
+        );
+    }
+
+    #[test]
+    fn test_validate_policy_set_in_json_format() {
+        let call_json = r#"{
+            "schema": { "": { "entityTypes": { "User": {} }, "actions": {} } },
+            "policySet": [
+                {
+                    "id": "photo",
+                    "policy": {
+                        "effect": "permit",
+                        "principal": { "op": "All" },
+                        "action": { "op": "All" },
+                        "resource": { "op": "==", "entity": { "type": "Photo", "id": "p" } },
+                        "conditions": []
+                    }
+                },
+                {
+                    "id": "user",
+                    "policy": {
+                        "effect": "permit",
+                        "principal": { "op": "==", "slot": "?principal" },
+                        "action": { "op": "All" },
+                        "resource": { "op": "All" },
+                        "conditions": []
+                    }
+                }
+            ]
+        }"#;
+        let notes = assert_validates_with_notes(json_validate(call_json), 3);
+        assert!(notes
+            .iter()
+            .any(|note| note.policy_id == "photo"
+                && note.message == "unrecognized entity type `Photo`"));
+        assert!(notes.iter().any(|note| note.policy_id == "user"));
+    }
+
+    #[test]
+    fn test_validate_with_schema_in_cedar_syntax() {
+        let call = |policy: &str| {
+            serde_json::json!({
+                "schema": "entity User; entity Photo; action view appliesTo { principal: [User], resource: [Photo] };",
+                "policySet": { "policy0": policy }
+            })
+            .to_string()
+        };
+        assert_validates_without_notes(json_validate(&call(
+            r#"permit(principal == User::"alice", action == Action::"view", resource);"#,
+        )));
+        let (errors, _) = assert_validates_with_errors_and_warnings(
+            json_validate(&call(
+                r#"permit(principal == Album::"a", action, resource);"#,
+            )),
+            2,
+            0,
+        );
+        assert!(errors
+            .iter()
+            .any(|note| note.message == "unrecognized entity type `Album`"));
+        assert_matches!(json_validate(&call("permit(principal, action, resource);").replace("entity User;", "entity User")), InterfaceResult::Failure { is_internal: true, errors, .. } => {
+            assert!(errors.iter().all(|e| e.starts_with("could not construct schema")), "{errors:?}");
+        });
+    }
+
+    #[test]
+    fn test_validate_reports_impossible_policies_as_warnings() {
+        let call = serde_json::json!({
+            "schema": "entity User; entity Photo; action view appliesTo { principal: [User], resource: [Photo] };",
+            "policySet": {
+                "dead": r#"permit(principal, action == Action::"view", resource) when { false };"#,
+                "live": r#"permit(principal, action == Action::"view", resource);"#
+            }
+        })
+        .to_string();
+        assert_matches!(json_validate(&call), InterfaceResult::Success { result, .. } => {
+            let parsed_result: ValidateAnswer = serde_json::from_str(result.as_str()).unwrap();
+            assert_matches!(parsed_result, ValidateAnswer::Success { passed, errors, impossible_policies, .. } => {
+                assert!(passed);
+                assert_eq!(errors.len(), 0, "Unexpected validation errors: {errors:?}");
+                assert_matches!(impossible_policies.as_slice(), [impossible] => {
+                    assert_eq!(impossible.note.policy_id, "dead");
+                    assert_eq!(impossible.note.severity, ValidationSeverity::Warning);
+                    assert_eq!(impossible.note.code, "V014_IMPOSSIBLE_POLICY");
+                    assert_eq!(
+                        impossible.facts.first().map(String::as_str),
+                        Some(r#"action `Action::"view"` applies to principals of type `User` and resources of type `Photo`"#)
+                    );
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn test_validation_summary_tallies_notes() {
+        let call = serde_json::json!({
+            "schema": "entity User; entity Photo; action view appliesTo { principal: [User], resource: [Photo] };",
+            "policySet": {
+                "bad": r#"permit(principal == Album::"a", action == Action::"view", resource);"#,
+                "dead": r#"permit(principal, action == Action::"view", resource) when { false };"#,
+                "ok": r#"permit(principal, action == Action::"view", resource);"#
+            }
+        })
+        .to_string();
+        assert_matches!(json_validate(&call), InterfaceResult::Success { result, .. } => {
+            let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+            assert_eq!(
+                answer["summary"],
+                serde_json::json!({
+                    "passed": false,
+                    "errorCount": 2,
+                    "warningCount": 1,
+                    "policiesWithErrors": ["bad"],
+                    "policiesWithWarnings": ["dead"],
+                })
+            );
+        });
+
+        let off = serde_json::json!({
+            "schema": {},
+            "policySet": { "policy0": "permit(principal, action, resource);" },
+            "validationSettings": { "mode": "off" }
+        })
+        .to_string();
+        assert_matches!(json_validate(&off), InterfaceResult::Success { result, .. } => {
+            let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+            assert_eq!(answer["summary"]["passed"], true);
+            assert_eq!(answer["summary"]["errorCount"], 0);
+        });
+    }
+
+    #[test]
+    fn test_validate_includes_typed_ast() {
+        let call = |policy: &str, include_typed_ast: bool| {
+            serde_json::json!({
+                "schema": "entity User { age: Long }; entity Photo; action view appliesTo { principal: [User], resource: [Photo] };",
+                "policySet": { "p": policy },
+                "validationSettings": { "includeTypedAst": include_typed_ast }
+            })
+            .to_string()
+        };
+        let answer = |call: String| {
+            assert_matches!(json_validate(&call), InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<serde_json::Value>(&result).unwrap()
+            })
+        };
+        fn find<'a>(node: &'a serde_json::Value, kind: &str) -> Option<&'a serde_json::Value> {
+            if node["kind"] == kind {
+                return Some(node);
+            }
+            node["children"]
+                .as_array()
+                .and_then(|children| children.iter().find_map(|child| find(child, kind)))
+        }
+
+        let policy =
+            r#"permit(principal, action == Action::"view", resource) when { principal.age > 3 };"#;
+        let typed = answer(call(policy, true));
+        let typed_policies = typed["typedPolicies"].as_array().unwrap();
+        assert_eq!(typed_policies.len(), 1);
+        assert_eq!(typed_policies[0]["policyId"], "p");
+        assert_eq!(typed_policies[0]["principalType"], "User");
+        assert_eq!(typed_policies[0]["action"], r#"Action::"view""#);
+        assert_eq!(typed_policies[0]["resourceType"], "Photo");
+        let get_attr = find(&typed_policies[0]["condition"], "getAttr").unwrap();
+        assert_eq!(get_attr["value"], "age");
+        assert_eq!(get_attr["type"], "Long");
+        assert_eq!(
+            get_attr["span"]["start"],
+            policy.find("principal.age").unwrap()
+        );
+        assert_eq!(get_attr["children"][0]["kind"], "var");
+        assert_eq!(get_attr["children"][0]["type"], "User");
+
+        assert!(answer(call(policy, false)).get("typedPolicies").is_none());
+        let failing = r#"permit(principal, action == Action::"view", resource) when { principal.name == "a" };"#;
+        assert!(answer(call(failing, true)).get("typedPolicies").is_none());
+    }
+
+    #[test]
+    fn test_validate_with_parsed_policy_set() {
+        let policies = crate::PolicySet::from_str(
+            r#"permit(principal == User::"alice", action == Action::"view", resource);"#,
+        )
+        .unwrap();
+        let call_json = r#"{
+            "schema": { "": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+                }
+            }}
+        }"#;
+        assert_validates_without_notes(json_validate_with_policy_set(call_json, &policies));
+
+        let empty_schema = r#"{ "schema": {} }"#;
+        let notes =
+            assert_validates_with_notes(json_validate_with_policy_set(empty_schema, &policies), 3);
+        assert!(notes.iter().all(|note| note.policy_id == "policy0"));
+    }
+
+    #[test]
+    fn test_validate_with_parsed_schema() {
+        let schema = crate::Schema::from_str(
+            r#"{ "": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+                }
+            }}"#,
+        )
+        .unwrap();
+        let call_json = r#"{
+            "policySet": {
+                "ok": "permit(principal == User::\"alice\", action == Action::\"view\", resource);",
+                "bad": "permit(principal == Photo::\"p\", action == Action::\"view\", resource);"
+            }
+        }"#;
+        let notes = assert_validates_with_notes(json_validate_with_schema(call_json, &schema), 1);
+        assert!(notes.iter().all(|note| note.policy_id == "bad"));
+
+        let policies = crate::PolicySet::from_str(
+            r#"permit(principal == User::"alice", action == Action::"view", resource);"#,
+        )
+        .unwrap();
+        assert_validates_without_notes(json_validate_with("{}", &schema, &policies));
+        assert_validates_without_notes(json_validate_with(
+            r#"{ "validationSettings": { "mode": "off" } }"#,
+            &schema,
+            &crate::PolicySet::from_str(r#"permit(principal == Photo::"p", action, resource);"#)
+                .unwrap(),
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_timings() {
+        let call_json = r#"{
+            "schema": {},
+            "policySet": "permit(principal, action, resource);",
+            "includeTimings": true
+        }"#;
+        let ticks = std::cell::Cell::new(0.0);
+        let clock = || {
+            ticks.set(ticks.get() + 2.0);
+            ticks.get()
+        };
+        assert_matches!(json_validate_with_clock(call_json, &clock), InterfaceResult::Success { result, .. } => {
+            let parsed_result: ValidateAnswer = serde_json::from_str(&result).unwrap();
+            assert_matches!(parsed_result, ValidateAnswer::Success { timings: Some(timings), .. } => {
+                assert_eq!(timings, ValidationTimings {
+                    parse_policies_ms: 2.0,
+                    parse_schema_ms: 2.0,
+                    validate_ms: 2.0,
+                });
+            });
+        });
+        assert_matches!(json_validate(call_json), InterfaceResult::Success { result, .. } => {
+            assert!(!result.contains("timings"));
+        });
+    }
+
+    #[test]
+    fn test_max_deref_level_is_unsupported() {
+        let call_json = r#"{
+            "validationSettings": { "maxDerefLevel": 1 },
+            "schema": {},
+            "policySet": {}
+        }"#;
+        assert_is_failure(
+            &json_validate(call_json),
+            false,
+            "level-based validation is not supported",
+        );
+    }
+
     #[test]
     fn test_bad_call_format_fails() {
         let result = json_validate("uerfheriufheiurfghtrg");
         assert_is_failure(&result, true, "error parsing call: expected value");
     }
 
+    #[test]
+    fn test_response_reports_api_version() {
+        let success = json_validate(r#"{ "schema": {}, "policySet": {} }"#);
+        let failure = json_validate("uerfheriufheiurfghtrg");
+        for result in [success, failure] {
+            let json = serde_json::to_value(result).unwrap();
+            assert_eq!(json["apiVersion"], serde_json::json!(RESPONSE_VERSION));
+        }
+    }
+
     #[test]
     fn test_validate_fails_on_duplicate_namespace() {
         let call_json = r#"{
@@ -442,24 +2146,134 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_validate_caches_schemas() {
+        let call = |namespace: &str| {
+            serde_json::json!({
+                "schema": { namespace: { "entityTypes": {}, "actions": {} } },
+                "policySet": ""
+            })
+            .to_string()
+        };
+        let cached = || SCHEMA_CACHE.with(|cache| cache.borrow().len());
+
+        clear_schema_cache();
+        assert_validates_without_notes(json_validate(&call("foo")));
+        assert_validates_without_notes(json_validate(&call("foo")));
+        assert_eq!(cached(), 1);
+        assert_validates_without_notes(json_validate(&call("bar")));
+        assert_eq!(cached(), 2);
+
+        // schemas that fail to parse are not cached
+        let bad_call = serde_json::json!({
+            "schema": { "": {
+                "entityTypes": { "User": { "memberOfTypes": ["Group"] } },
+                "actions": {}
+            } },
+            "policySet": ""
+        })
+        .to_string();
+        assert_is_failure(
+            &json_validate(&bad_call),
+            true,
+            "could not construct schema",
+        );
+        assert_eq!(cached(), 2);
+
+        for i in 0..SCHEMA_CACHE_CAPACITY {
+            assert_validates_without_notes(json_validate(&call(&format!("ns{i}"))));
+        }
+        assert_eq!(cached(), SCHEMA_CACHE_CAPACITY);
+
+        clear_schema_cache();
+        assert_eq!(cached(), 0);
+    }
+
+    #[test]
+    fn test_validate_caches_answers() {
+        let call = |policies: serde_json::Value, mode: &str| {
+            serde_json::json!({
+                "schema": "entity User; action view appliesTo { principal: [User], resource: [User] };",
+                "policySet": policies,
+                "validationSettings": { "mode": mode }
+            })
+            .to_string()
+        };
+        let cached = || ANSWER_CACHE.with(|cache| cache.borrow().len());
+        let result = |result: InterfaceResult| serde_json::to_value(result).unwrap();
+
+        clear_answer_cache();
+        let bad = call(
+            serde_json::json!({ "a": "permit(principal, action, resource) when { principal.name == \"x\" };" }),
+            "strict",
+        );
+        let first = result(json_validate(&bad));
+        assert_eq!(result(json_validate(&bad)), first);
+        assert_eq!(cached(), 1);
+
+        // maps of policies are keyed by their contents, not their order
+        let two = |first: &str, second: &str| {
+            let mut policies = serde_json::Map::new();
+            for id in [first, second] {
+                policies.insert(
+                    id.to_string(),
+                    "permit(principal, action, resource);".into(),
+                );
+            }
+            call(serde_json::Value::Object(policies), "strict")
+        };
+        json_validate(&two("a", "b"));
+        json_validate(&two("b", "a"));
+        assert_eq!(cached(), 2);
+
+        // the settings are part of the key
+        assert_validates_without_notes(json_validate(&call(
+            serde_json::json!({ "a": "permit(principal, action, resource) when { principal.name == \"x\" };" }),
+            "off",
+        )));
+        assert_eq!(cached(), 3);
+
+        // calls asking for timings are not cached
+        let timed: serde_json::Value = serde_json::from_str(&bad).unwrap();
+        let mut timed = timed.as_object().unwrap().clone();
+        timed.insert("includeTimings".to_string(), true.into());
+        json_validate(&serde_json::Value::Object(timed).to_string());
+        assert_eq!(cached(), 3);
+
+        clear_answer_cache();
+        assert_eq!(cached(), 0);
+        assert_eq!(result(json_validate(&bad)), first);
+    }
+
     #[track_caller] // report the caller's location as the location of the panic, not the location in this function
     fn assert_validates_without_notes(result: InterfaceResult) {
-        assert_matches!(result, InterfaceResult::Success { result } => {
-            let parsed_result: ValidateAnswer = serde_json::from_str(result.as_str()).unwrap();
-            assert_matches!(parsed_result, ValidateAnswer::Success { notes, .. } => {
-                assert_eq!(notes.len(), 0, "Unexpected validation notes: {notes:?}");
-            });
-        });
+        assert_validates_with_errors_and_warnings(result, 0, 0);
+    }
+
+    #[track_caller] // report the caller's location as the location of the panic, not the location in this function
+    fn assert_validates_with_notes(
+        result: InterfaceResult,
+        expected_num_errors: usize,
+    ) -> Vec<ValidationNote> {
+        let (errors, _) = assert_validates_with_errors_and_warnings(result, expected_num_errors, 0);
+        errors
     }
 
     #[track_caller] // report the caller's location as the location of the panic, not the location in this function
-    fn assert_validates_with_notes(result: InterfaceResult, expected_num_notes: usize) {
-        assert_matches!(result, InterfaceResult::Success { result } => {
+    fn assert_validates_with_errors_and_warnings(
+        result: InterfaceResult,
+        expected_num_errors: usize,
+        expected_num_warnings: usize,
+    ) -> (Vec<ValidationNote>, Vec<ValidationNote>) {
+        assert_matches!(result, InterfaceResult::Success { result, .. } => {
             let parsed_result: ValidateAnswer = serde_json::from_str(result.as_str()).unwrap();
-            assert_matches!(parsed_result, ValidateAnswer::Success { notes, .. } => {
-                assert_eq!(notes.len(), expected_num_notes);
-            });
-        });
+            assert_matches!(parsed_result, ValidateAnswer::Success { passed, errors, warnings, .. } => {
+                assert_eq!(errors.len(), expected_num_errors, "Unexpected validation errors: {errors:?}");
+                assert_eq!(warnings.len(), expected_num_warnings, "Unexpected validation warnings: {warnings:?}");
+                assert_eq!(passed, errors.is_empty());
+                (errors, warnings)
+            })
+        })
     }
 
     #[test]
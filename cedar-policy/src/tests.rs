@@ -1472,6 +1472,35 @@ mod ancestors_tests {
         assert!(ans.contains(&b_euid));
         assert!(ans.contains(&a_euid));
     }
+
+    #[test]
+    fn test_ancestors_with_tc_computation() {
+        let json = r#"[
+            { "uid": { "type": "test", "id": "C" }, "attrs": {}, "parents": [{ "type": "test", "id": "b" }] },
+            { "uid": { "type": "test", "id": "b" }, "attrs": {}, "parents": [{ "type": "test", "id": "A" }] },
+            { "uid": { "type": "test", "id": "A" }, "attrs": {}, "parents": [] }
+        ]"#;
+        let c_euid: EntityUid = EntityUid::from_strs("test", "C");
+
+        let es = Entities::from_json_str_with_tc_computation(
+            json,
+            None,
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .unwrap();
+        assert_eq!(es.ancestors(&c_euid).unwrap().count(), 1);
+
+        assert!(Entities::from_json_str_with_tc_computation(
+            json,
+            None,
+            TCComputation::EnforceAlreadyComputed
+        )
+        .is_err());
+
+        let es = Entities::from_json_str_with_tc_computation(json, None, TCComputation::ComputeNow)
+            .unwrap();
+        assert_eq!(es.ancestors(&c_euid).unwrap().count(), 2);
+    }
 }
 
 /// A few tests of validating entities.
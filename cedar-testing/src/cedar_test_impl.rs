@@ -17,7 +17,7 @@
 //! Definition of a `CedarTestImplementation` trait that describes an
 //! implementation of Cedar to use during testing.
 
-pub use cedar_policy::frontend::is_authorized::InterfaceResponse;
+pub use cedar_policy::frontend::is_authorized::{InterfaceAuthorizationError, InterfaceResponse};
 use cedar_policy_core::ast::{Expr, PolicySet, Request, Value};
 use cedar_policy_core::authorizer::Authorizer;
 use cedar_policy_core::entities::Entities;
@@ -128,10 +128,9 @@ pub enum ErrorComparisonMode {
     /// In fact, the `CedarTestImplementation` will be expected to never report
     /// errors.
     Ignore,
-    /// The `CedarTestImplementation` is expected to produce "error messages" that
-    /// are actually just the id of the erroring policy. This will be compared to
-    /// ensure that the `CedarTestImplementation` agrees with the Rust
-    /// implementation on which policies produce errors.
+    /// Only the ids of the erroring policies are compared, to ensure that the
+    /// `CedarTestImplementation` agrees with the Rust implementation on which
+    /// policies produce errors.
     PolicyIds,
     /// The `CedarTestImplementation` is expected to produce error messages that
     /// exactly match the Rust implementation's error messages' `Display` text.
@@ -171,8 +170,6 @@ impl CedarTestImplementation for RustEngine {
         let authorizer = Authorizer::new();
         let (response, duration) =
             time_function(|| authorizer.is_authorized(request.clone(), policies, entities));
-        // Error messages should only include the policy id to use the
-        // `ErrorComparisonMode::PolicyIds` mode.
         let response = cedar_policy::Response::from(response);
         let response = InterfaceResponse::new(
             response.decision(),
@@ -180,8 +177,7 @@ impl CedarTestImplementation for RustEngine {
             response
                 .diagnostics()
                 .errors()
-                .map(cedar_policy::AuthorizationError::id)
-                .map(ToString::to_string)
+                .map(InterfaceAuthorizationError::from)
                 .collect(),
         );
         let response = TestResponse {
@@ -349,7 +349,7 @@ pub fn perform_integration_test_from_json_custom(
                 .response
                 .diagnostics()
                 .errors()
-                .map(|err| PolicyId::from_str(err).unwrap())
+                .map(|err| err.policy_id().clone())
                 .collect();
             assert_eq!(
                 errors,
@@ -0,0 +1,183 @@
+//! This module contains `validateAnnotations`, which checks the annotations of
+//! the policies in a policy set against a description of the annotations they
+//! must or may have, e.g. that every policy has an `@owner`.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{PolicyMetadata, WasmPolicySet};
+
+#[derive(Tsify, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The annotations that the policies of a policy set must or may have
+pub struct AnnotationSchema {
+    /// the annotations every policy must have, without the `@`
+    #[serde(default)]
+    pub required: Vec<String>,
+    /// if given, the only annotations a policy may have other than the
+    /// required ones
+    #[serde(default)]
+    #[tsify(optional)]
+    pub allowed: Option<Vec<String>>,
+    /// the values an annotation may have, by annotation
+    #[serde(default)]
+    #[tsify(optional)]
+    pub values: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How a policy breaks an annotation schema
+pub enum AnnotationViolationKind {
+    /// a required annotation is missing
+    Missing,
+    /// an annotation is neither required nor allowed
+    NotAllowed,
+    /// an annotation has a value that is not one of its allowed values
+    InvalidValue,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An annotation of a policy that breaks an annotation schema
+pub struct AnnotationViolation {
+    /// the annotation, without the `@`
+    pub annotation: String,
+    pub kind: AnnotationViolationKind,
+    pub message: String,
+}
+
+#[derive(Tsify, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The policies of a policy set that break an annotation schema
+pub struct AnnotationValidationResult {
+    /// whether every policy meets the annotation schema
+    pub passed: bool,
+    /// the violations of each policy or template that has any, by id
+    pub violations: BTreeMap<String, Vec<AnnotationViolation>>,
+}
+
+/// Check the annotations of each static policy and template of `policies`
+/// against `annotationSchema`. Policies linked from a template have its
+/// annotations, so they are checked through the template.
+#[wasm_bindgen(js_name = "validateAnnotations")]
+pub fn validate_annotations(
+    policies: &WasmPolicySet,
+    annotation_schema: AnnotationSchema,
+) -> AnnotationValidationResult {
+    let metadata = policies.metadata();
+    let violations: BTreeMap<_, _> = metadata
+        .policies
+        .iter()
+        .filter(|policy| policy.template_id.is_none())
+        .chain(metadata.templates.iter())
+        .filter_map(|policy| {
+            let violations = violations(policy, &annotation_schema);
+            (!violations.is_empty()).then(|| (policy.id.clone(), violations))
+        })
+        .collect();
+    AnnotationValidationResult {
+        passed: violations.is_empty(),
+        violations,
+    }
+}
+
+/// The ways `policy` breaks `schema`
+fn violations(policy: &PolicyMetadata, schema: &AnnotationSchema) -> Vec<AnnotationViolation> {
+    let missing = schema
+        .required
+        .iter()
+        .filter(|annotation| !policy.annotations.contains_key(*annotation))
+        .map(|annotation| AnnotationViolation {
+            annotation: annotation.clone(),
+            kind: AnnotationViolationKind::Missing,
+            message: format!("required annotation `@{annotation}` is missing"),
+        });
+    let present = policy.annotations.iter().filter_map(|(annotation, value)| {
+        let allowed = schema.required.contains(annotation)
+            || !matches!(&schema.allowed, Some(allowed) if !allowed.contains(annotation));
+        if !allowed {
+            return Some(AnnotationViolation {
+                annotation: annotation.clone(),
+                kind: AnnotationViolationKind::NotAllowed,
+                message: format!("annotation `@{annotation}` is not allowed"),
+            });
+        }
+        let values = schema.values.get(annotation)?;
+        (!values.contains(value)).then(|| AnnotationViolation {
+            annotation: annotation.clone(),
+            kind: AnnotationViolationKind::InvalidValue,
+            message: format!(
+                "annotation `@{annotation}` has the value `{value}`, which is not one of {}",
+                values
+                    .iter()
+                    .map(|value| format!("`{value}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        })
+    });
+    missing.chain(present).collect()
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validates_annotations_per_policy() {
+        let policies = WasmPolicySet::parse(
+            r#"
+            @owner("alice") @ticket("T-1") @env("prod")
+            permit(principal, action, resource);
+            @owner("bob") @env("staging") @note("temporary")
+            forbid(principal, action, resource);
+            @ticket("T-2")
+            permit(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let schema = AnnotationSchema {
+            required: vec!["owner".to_string(), "ticket".to_string()],
+            allowed: Some(vec!["env".to_string()]),
+            values: BTreeMap::from([(
+                "env".to_string(),
+                vec!["prod".to_string(), "dev".to_string()],
+            )]),
+        };
+        let result = validate_annotations(&policies, schema);
+        assert!(!result.passed);
+        let kinds = |id: &str| {
+            result.violations.get(id).map(|violations| {
+                violations
+                    .iter()
+                    .map(|violation| (violation.annotation.as_str(), violation.kind))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(kinds("policy0"), None);
+        assert_eq!(
+            kinds("policy1"),
+            Some(vec![
+                ("ticket", AnnotationViolationKind::Missing),
+                ("env", AnnotationViolationKind::InvalidValue),
+                ("note", AnnotationViolationKind::NotAllowed),
+            ])
+        );
+        assert_eq!(
+            kinds("policy2"),
+            Some(vec![("owner", AnnotationViolationKind::Missing)])
+        );
+
+        let result = validate_annotations(&policies, AnnotationSchema::default());
+        assert!(result.passed);
+    }
+}
@@ -1,9 +1,107 @@
 //! This module contains the entry point to the wasm isAuthorized functionality.
-use cedar_policy::frontend::{is_authorized::json_is_authorized, utils::InterfaceResult};
+use cedar_policy::frontend::{
+    is_authorized::{
+        json_is_authorized_actions, json_is_authorized_with, json_is_authorized_with_clock,
+        json_is_authorized_with_policy_set,
+    },
+    utils::InterfaceResult,
+};
 
 use wasm_bindgen::prelude::*;
 
+use crate::{now_ms, with_cbor_context, WasmEntities, WasmEntityShards, WasmPolicySet, WasmSchema};
+
+/// Answer an authorization call. If the call sets `include_timings`, the
+/// response includes `timings` measured with `performance.now()`, which also
+/// measures any `time_budget_ms` in the call's `limits`.
 #[wasm_bindgen(js_name = isAuthorized)]
 pub fn wasm_is_authorized(input: &str) -> InterfaceResult {
-    json_is_authorized(input)
+    json_is_authorized_with_clock(input, &now_ms)
+}
+
+/// Decide a request for each of several actions in one call, parsing the
+/// policies and entities once. The call takes `actions` in place of the
+/// `action`, and answers with the decision for each action by its UID.
+#[wasm_bindgen(js_name = isAuthorizedActions)]
+pub fn wasm_is_authorized_actions(input: &str) -> InterfaceResult {
+    json_is_authorized_actions(input)
+}
+
+/// Like `isAuthorized`, but against a policy set parsed ahead of time. The
+/// call takes `entities` directly in place of the `slice`.
+#[wasm_bindgen(js_name = isAuthorizedWithPolicySet)]
+pub fn wasm_is_authorized_with_policy_set(
+    policy_set: &WasmPolicySet,
+    input: &str,
+) -> InterfaceResult {
+    json_is_authorized_with_policy_set(input, policy_set.policy_set(), Some(&now_ms))
+}
+
+/// Like `isAuthorized`, but against a policy set and entities parsed ahead of
+/// time, so that neither is re-parsed (nor the entity hierarchy recomputed) on
+/// each call. The call takes the request fields only, without the `slice`.
+#[wasm_bindgen(js_name = isAuthorizedWith)]
+pub fn wasm_is_authorized_with(
+    policy_set: &WasmPolicySet,
+    entities: &WasmEntities,
+    input: &str,
+) -> InterfaceResult {
+    json_is_authorized_with(
+        input,
+        policy_set.policy_set(),
+        entities.entities(),
+        None,
+        Some(&now_ms),
+    )
+}
+
+/// Like `isAuthorizedWith`, but against the entities of several shards viewed
+/// as one store
+#[wasm_bindgen(js_name = isAuthorizedWithShards)]
+pub fn wasm_is_authorized_with_shards(
+    policy_set: &WasmPolicySet,
+    shards: &WasmEntityShards,
+    input: &str,
+) -> InterfaceResult {
+    json_is_authorized_with(
+        input,
+        policy_set.policy_set(),
+        shards.merged(),
+        None,
+        Some(&now_ms),
+    )
+}
+
+/// Like `isAuthorizedWith`, but with a schema parsed ahead of time, used as
+/// the `schema` of an `isAuthorized` call would be. The call itself must not
+/// include a `schema`.
+#[wasm_bindgen(js_name = isAuthorizedWithSchema)]
+pub fn wasm_is_authorized_with_schema(
+    schema: &WasmSchema,
+    policy_set: &WasmPolicySet,
+    entities: &WasmEntities,
+    input: &str,
+) -> InterfaceResult {
+    json_is_authorized_with(
+        input,
+        policy_set.policy_set(),
+        entities.entities(),
+        Some(schema.schema()),
+        Some(&now_ms),
+    )
+}
+
+/// Like `isAuthorizedWith`, but with the request's context encoded as CBOR,
+/// in place of any `context` in the call
+#[wasm_bindgen(js_name = isAuthorizedWithCborContext)]
+pub fn wasm_is_authorized_with_cbor_context(
+    policy_set: &WasmPolicySet,
+    entities: &WasmEntities,
+    input: &str,
+    context: &[u8],
+) -> InterfaceResult {
+    match with_cbor_context(input, context) {
+        Ok(input) => wasm_is_authorized_with(policy_set, entities, &input),
+        Err(errors) => InterfaceResult::fail_bad_request(errors),
+    }
 }
@@ -0,0 +1,859 @@
+//! This module contains a helper for assembling a Cedar context from plain
+//! key/value pairs, applying schema-driven coercions, one for checking a
+//! context against the context type of an action attribute by attribute, and
+//! one describing the context type of an action as a JSON Schema.
+use std::str::FromStr;
+
+use cedar_policy::{Context, EntityUid};
+use cedar_policy_core::ast::EntityUID;
+use cedar_policy_validator::types::{Attributes, EntityRecordKind, OpenTag, Primitive, Type};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{EntityUidInput, WasmSchema};
+
+#[derive(Tsify, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An error building the context, attributed to the key it was found at
+pub struct ContextKeyError {
+    /// the key the error was found at, or `None` for errors about the whole context
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl ContextKeyError {
+    fn at_key(key: &str, message: String) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            message,
+        }
+    }
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum BuildContextResult {
+    /// the assembled context, in the JSON format expected by `isAuthorized`
+    Success {
+        #[tsify(type = "Record<string, any>")]
+        context: Value,
+    },
+    Error {
+        errors: Vec<ContextKeyError>,
+    },
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How an attribute of a context fails to match the context type of an action
+pub enum ContextErrorKind {
+    /// a required attribute is missing
+    MissingAttribute,
+    /// the value of the attribute is not of its declared type
+    WrongType,
+    /// the attribute is not declared
+    UnknownAttribute,
+    /// the value of the attribute is of its declared type, but is not a valid
+    /// value of it, e.g. an `ipaddr` of a malformed address
+    InvalidValue,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An attribute of a context that does not match the context type of an action
+pub struct ContextAttributeError {
+    /// the path of the attribute, e.g. `address.city` or `tags[0]`, or `None`
+    /// for errors about the whole context
+    pub attribute: Option<String>,
+    pub kind: ContextErrorKind,
+    pub message: String,
+}
+
+impl ContextAttributeError {
+    fn at(path: &str, kind: ContextErrorKind, message: String) -> Self {
+        Self {
+            attribute: Some(path.to_string()),
+            kind,
+            message,
+        }
+    }
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of checking a context against a schema
+pub enum ValidateContextResult {
+    /// the context matches the context type of the action
+    Success {},
+    /// the context does not match the context type of the action
+    Invalid { errors: Vec<ContextAttributeError> },
+    /// the action or the context do not parse, or the action is not declared
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `contextJsonSchema`
+pub enum ContextJsonSchemaResult {
+    /// the JSON Schema, in draft-07, of contexts of the action
+    Success {
+        #[tsify(type = "Record<string, any>")]
+        schema: Value,
+    },
+    /// the action does not parse or is not declared
+    Error { errors: Vec<String> },
+}
+
+/// Assemble a Cedar context from a JSON object of plain values.
+///
+/// When a schema and action are given, each value is coerced to the type the
+/// schema declares for that context attribute: numeric strings become `Long`s,
+/// scalars become `String`s, strings become `ipaddr`/`decimal` values or
+/// entity references (written as `Type::"id"`), and so on. Without a schema the
+/// values are passed through unchanged.
+#[wasm_bindgen(js_name = "buildContext")]
+pub fn build_context(
+    pairs_str: &str,
+    schema_str: Option<String>,
+    action_str: Option<String>,
+) -> BuildContextResult {
+    let pairs = match serde_json::from_str::<Value>(pairs_str) {
+        Ok(Value::Object(pairs)) => pairs,
+        Ok(_) => return whole_context_error("context pairs must be a JSON object".to_string()),
+        Err(e) => return whole_context_error(format!("error parsing context pairs: {e}")),
+    };
+    match (schema_str, action_str) {
+        (Some(schema_str), Some(action_str)) => {
+            build_context_with_schema(pairs, &schema_str, &action_str)
+        }
+        (None, None) => build_context_without_schema(pairs),
+        _ => whole_context_error(
+            "a schema and an action must be provided together to build a context".to_string(),
+        ),
+    }
+}
+
+/// Check a context in its JSON format against the context type a schema handle
+/// declares for `action`, reporting each attribute that is missing, unknown,
+/// or of the wrong type. Unlike `buildContext`, values are not coerced, though
+/// `__entity` and `__extn` escapes may be left implicit as in `isAuthorized`
+/// with a schema.
+#[wasm_bindgen(js_name = "validateContext")]
+pub fn validate_context(
+    schema: &WasmSchema,
+    action: EntityUidInput,
+    context_json: &str,
+) -> ValidateContextResult {
+    match check_context(schema, &action, context_json) {
+        Ok(errors) if errors.is_empty() => ValidateContextResult::Success {},
+        Ok(errors) => ValidateContextResult::Invalid { errors },
+        Err(errors) => ValidateContextResult::Error { errors },
+    }
+}
+
+/// Describe the context type a schema handle declares for `action` as a
+/// draft-07 JSON Schema, so that contexts can be checked with any JSON Schema
+/// validator. It accepts the JSON format of contexts with `__entity` and
+/// `__extn` escapes either written out or left implicit, as `isAuthorized`
+/// with a schema does; the values of extension types are not checked beyond
+/// being strings.
+#[wasm_bindgen(js_name = "contextJsonSchema")]
+pub fn context_json_schema(schema: &WasmSchema, action: EntityUidInput) -> ContextJsonSchemaResult {
+    let action = match action.to_uid() {
+        Ok(action) => action,
+        Err(e) => {
+            return ContextJsonSchemaResult::Error {
+                errors: vec![format!("error parsing action uid {e}")],
+            }
+        }
+    };
+    let context_type = EntityUID::from_str(&action.to_string())
+        .ok()
+        .and_then(|core_action| schema.validator_schema().context_type(&core_action));
+    let Some(context_type) = context_type else {
+        return ContextJsonSchemaResult::Error {
+            errors: vec![format!("action `{action}` is not declared in the schema")],
+        };
+    };
+    let mut json_schema = match json_schema(&context_type) {
+        Value::Object(json_schema) => json_schema,
+        _ => Map::new(),
+    };
+    json_schema.insert(
+        "$schema".to_string(),
+        Value::from("http://json-schema.org/draft-07/schema#"),
+    );
+    json_schema.insert(
+        "title".to_string(),
+        Value::from(format!("Context of the `{action}` action")),
+    );
+    ContextJsonSchemaResult::Success {
+        schema: Value::Object(json_schema),
+    }
+}
+
+fn whole_context_error(message: String) -> BuildContextResult {
+    BuildContextResult::Error {
+        errors: vec![ContextKeyError { key: None, message }],
+    }
+}
+
+fn build_context_without_schema(pairs: Map<String, Value>) -> BuildContextResult {
+    let errors: Vec<ContextKeyError> = pairs
+        .iter()
+        .filter_map(|(key, value)| {
+            let single = Value::Object(Map::from_iter([(key.clone(), value.clone())]));
+            Context::from_json_value(single, None)
+                .err()
+                .map(|e| ContextKeyError::at_key(key, e.to_string()))
+        })
+        .collect();
+    if errors.is_empty() {
+        BuildContextResult::Success {
+            context: Value::Object(pairs),
+        }
+    } else {
+        BuildContextResult::Error { errors }
+    }
+}
+
+fn build_context_with_schema(
+    pairs: Map<String, Value>,
+    schema_str: &str,
+    action_str: &str,
+) -> BuildContextResult {
+    let parsed = match WasmSchema::parse_in_format(schema_str, None) {
+        Ok(schema) => schema,
+        Err(errors) => {
+            return BuildContextResult::Error {
+                errors: errors
+                    .into_iter()
+                    .map(|message| ContextKeyError { key: None, message })
+                    .collect(),
+            }
+        }
+    };
+    let validator_schema = parsed.validator_schema();
+    let schema = parsed.schema();
+    let (action, core_action) = match (
+        EntityUid::from_str(action_str),
+        EntityUID::from_str(action_str),
+    ) {
+        (Ok(action), Ok(core_action)) => (action, core_action),
+        (Err(e), _) | (_, Err(e)) => {
+            return whole_context_error(format!("error parsing action: {e}"))
+        }
+    };
+    let Some(Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. })) =
+        validator_schema.context_type(&core_action)
+    else {
+        return whole_context_error(format!(
+            "action `{action_str}` is not declared in the schema"
+        ));
+    };
+
+    let mut errors = Vec::new();
+    let mut context = Map::new();
+    for (key, value) in pairs {
+        match attrs.attrs.get(key.as_str()) {
+            Some(attr_type) => match coerce(value, &attr_type.attr_type) {
+                Ok(value) => {
+                    context.insert(key, value);
+                }
+                Err(message) => errors.push(ContextKeyError::at_key(&key, message)),
+            },
+            None => errors.push(ContextKeyError::at_key(
+                &key,
+                format!("attribute `{key}` is not declared in the context of `{action_str}`"),
+            )),
+        }
+    }
+    for (key, attr_type) in attrs.iter() {
+        if attr_type.is_required && !context.contains_key(key.as_str()) && !errors_at(&errors, key)
+        {
+            errors.push(ContextKeyError::at_key(
+                key,
+                format!("required attribute `{key}` is missing"),
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return BuildContextResult::Error { errors };
+    }
+
+    let context = Value::Object(context);
+    match Context::from_json_value(context.clone(), Some((schema, &action))) {
+        Ok(_) => BuildContextResult::Success { context },
+        Err(e) => whole_context_error(e.to_string()),
+    }
+}
+
+fn errors_at(errors: &[ContextKeyError], key: &str) -> bool {
+    errors.iter().any(|e| e.key.as_deref() == Some(key))
+}
+
+/// Coerce a plain JSON value to the Cedar JSON representation of `expected`
+fn coerce(value: Value, expected: &Type) -> Result<Value, String> {
+    match (expected, value) {
+        (
+            Type::Primitive {
+                primitive_type: Primitive::Long,
+            },
+            Value::String(s),
+        ) => s
+            .trim()
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("expected a Long, but `{s}` is not an integer")),
+        (
+            Type::Primitive {
+                primitive_type: Primitive::String,
+            },
+            value @ (Value::Number(_) | Value::Bool(_)),
+        ) => Ok(Value::String(value.to_string())),
+        (
+            Type::Primitive {
+                primitive_type: Primitive::Bool,
+            },
+            Value::String(s),
+        ) => match s.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(format!("expected a Bool, but got `{s}`")),
+        },
+        (Type::ExtensionType { name }, Value::String(s)) => {
+            let name = name.to_string();
+            let constructor = extension_constructor(&name)
+                .ok_or_else(|| format!("unsupported extension type `{name}`"))?;
+            Ok(serde_json::json!({ "__extn": { "fn": constructor, "arg": s } }))
+        }
+        (Type::EntityOrRecord(EntityRecordKind::Entity(_)), Value::String(s)) => {
+            let uid = EntityUID::from_str(&s)
+                .map_err(|e| format!("expected an entity reference like `Type::\"id\"`: {e}"))?;
+            Ok(serde_json::json!({
+                "__entity": { "type": uid.entity_type().to_string(), "id": AsRef::<str>::as_ref(uid.eid()) }
+            }))
+        }
+        (
+            Type::Set {
+                element_type: Some(element_type),
+            },
+            Value::Array(elements),
+        ) => elements
+            .into_iter()
+            .map(|element| coerce(element, element_type))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        (Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. }), Value::Object(record)) => {
+            record
+                .into_iter()
+                .map(|(key, value)| match attrs.attrs.get(key.as_str()) {
+                    Some(attr_type) => match coerce(value, &attr_type.attr_type) {
+                        Ok(value) => Ok((key, value)),
+                        Err(e) => Err(format!("in attribute `{key}`: {e}")),
+                    },
+                    None => Ok((key, value)),
+                })
+                .collect::<Result<Map<_, _>, _>>()
+                .map(Value::Object)
+        }
+        // anything else is passed through as-is and checked against the schema
+        // when the context is constructed
+        (_, value) => Ok(value),
+    }
+}
+
+/// The function constructing values of the extension type `name` from strings
+fn extension_constructor(name: &str) -> Option<&'static str> {
+    match name {
+        "ipaddr" => Some("ip"),
+        "decimal" => Some("decimal"),
+        _ => None,
+    }
+}
+
+/// The attribute errors of the context `context_json` for `action`. Errors are
+/// for input that does not parse and for an undeclared action.
+fn check_context(
+    schema: &WasmSchema,
+    action: &EntityUidInput,
+    context_json: &str,
+) -> Result<Vec<ContextAttributeError>, Vec<String>> {
+    let action = action
+        .to_uid()
+        .map_err(|e| vec![format!("error parsing action uid {e}")])?;
+    let core_action = EntityUID::from_str(&action.to_string())
+        .map_err(|e| vec![format!("error parsing action uid `{action}`: {e}")])?;
+    let context = match serde_json::from_str::<Value>(context_json) {
+        Ok(Value::Object(context)) => context,
+        Ok(_) => return Err(vec!["context must be a JSON object".to_string()]),
+        Err(e) => return Err(vec![format!("error parsing context: {e}")]),
+    };
+    let Some(Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. })) =
+        schema.validator_schema().context_type(&core_action)
+    else {
+        return Err(vec![format!(
+            "action `{action}` is not declared in the schema"
+        )]);
+    };
+
+    let mut errors = Vec::new();
+    check_record(&context, &attrs, OpenTag::ClosedAttributes, "", &mut errors);
+    if errors.is_empty() {
+        // anything the checks above do not cover is reported for the whole
+        // context
+        if let Err(e) =
+            Context::from_json_value(Value::Object(context), Some((schema.schema(), &action)))
+        {
+            errors.push(ContextAttributeError {
+                attribute: None,
+                kind: ContextErrorKind::InvalidValue,
+                message: e.to_string(),
+            });
+        }
+    }
+    Ok(errors)
+}
+
+/// Check the attributes of `record`, at `prefix`, against `attrs`
+fn check_record(
+    record: &Map<String, Value>,
+    attrs: &Attributes,
+    open_attributes: OpenTag,
+    prefix: &str,
+    errors: &mut Vec<ContextAttributeError>,
+) {
+    let path = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+    for (key, value) in record {
+        match attrs.attrs.get(key.as_str()) {
+            Some(attr_type) => check_value(value, &attr_type.attr_type, &path(key), errors),
+            None if matches!(open_attributes, OpenTag::OpenAttributes) => (),
+            None => errors.push(ContextAttributeError::at(
+                &path(key),
+                ContextErrorKind::UnknownAttribute,
+                format!("attribute `{}` is not declared", path(key)),
+            )),
+        }
+    }
+    for (key, attr_type) in attrs.iter() {
+        if attr_type.is_required && !record.contains_key(key.as_str()) {
+            errors.push(ContextAttributeError::at(
+                &path(key),
+                ContextErrorKind::MissingAttribute,
+                format!("required attribute `{}` is missing", path(key)),
+            ));
+        }
+    }
+}
+
+/// Check `value`, the value of the attribute at `path`, against `expected`
+fn check_value(
+    value: &Value,
+    expected: &Type,
+    path: &str,
+    errors: &mut Vec<ContextAttributeError>,
+) {
+    let matches_type = match expected {
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => value.is_i64(),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        } => value.is_string(),
+        Type::Primitive {
+            primitive_type: Primitive::Bool,
+        }
+        | Type::True
+        | Type::False => value.is_boolean(),
+        Type::Set { element_type } => match value {
+            Value::Array(elements) => {
+                if let Some(element_type) = element_type {
+                    for (i, element) in elements.iter().enumerate() {
+                        check_value(element, element_type, &format!("{path}[{i}]"), errors);
+                    }
+                }
+                true
+            }
+            _ => false,
+        },
+        Type::EntityOrRecord(EntityRecordKind::Record {
+            attrs,
+            open_attributes,
+        }) => match value {
+            Value::Object(record) => {
+                check_record(record, attrs, *open_attributes, path, errors);
+                true
+            }
+            _ => false,
+        },
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+            match (entity_type(value), lub.get_single_entity()) {
+                (Some(entity_type), Some(name)) => entity_type == name.to_string(),
+                (found, _) => found.is_some(),
+            }
+        }
+        Type::ExtensionType { name } => {
+            return check_extension_value(value, &name.to_string(), path, errors)
+        }
+        _ => true,
+    };
+    if !matches_type {
+        errors.push(ContextAttributeError::at(
+            path,
+            ContextErrorKind::WrongType,
+            format!(
+                "attribute `{path}` should be of type `{}`, but is `{value}`",
+                type_name(expected)
+            ),
+        ));
+    }
+}
+
+/// Check `value`, the value of the attribute at `path`, against the extension
+/// type `name`, by constructing it
+fn check_extension_value(
+    value: &Value,
+    name: &str,
+    path: &str,
+    errors: &mut Vec<ContextAttributeError>,
+) {
+    let escape = value.get("__extn").unwrap_or(value);
+    let call = match (value, escape.get("fn").and_then(Value::as_str)) {
+        (Value::String(arg), _) => {
+            extension_constructor(name).map(|constructor| (constructor.to_string(), arg.clone()))
+        }
+        (_, Some(constructor)) if extension_constructor(name) == Some(constructor) => escape
+            .get("arg")
+            .and_then(Value::as_str)
+            .map(|arg| (constructor.to_string(), arg.to_string())),
+        _ => None,
+    };
+    let Some((constructor, arg)) = call else {
+        errors.push(ContextAttributeError::at(
+            path,
+            ContextErrorKind::WrongType,
+            format!("attribute `{path}` should be of type `{name}`, but is `{value}`"),
+        ));
+        return;
+    };
+    let single = serde_json::json!({ "value": { "__extn": { "fn": constructor, "arg": arg } } });
+    if let Err(e) = Context::from_json_value(single, None) {
+        errors.push(ContextAttributeError::at(
+            path,
+            ContextErrorKind::InvalidValue,
+            format!("attribute `{path}` is not a valid `{name}`: {e}"),
+        ));
+    }
+}
+
+/// The type of the entity `value` refers to, with or without an `__entity`
+/// escape
+fn entity_type(value: &Value) -> Option<String> {
+    let uid = value.get("__entity").unwrap_or(value);
+    uid.get("id")
+        .and_then(Value::as_str)
+        .and(uid.get("type").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// `expected` as written in a schema
+fn type_name(expected: &Type) -> String {
+    match expected {
+        Type::EntityOrRecord(EntityRecordKind::Record { .. }) => "Record".to_string(),
+        Type::True | Type::False => "Bool".to_string(),
+        _ => expected.to_string(),
+    }
+}
+
+/// The JSON Schema of values of type `expected` in the JSON format of contexts
+fn json_schema(expected: &Type) -> Value {
+    match expected {
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => json!({ "type": "integer" }),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        } => json!({ "type": "string" }),
+        Type::Primitive {
+            primitive_type: Primitive::Bool,
+        } => json!({ "type": "boolean" }),
+        Type::True => json!({ "const": true }),
+        Type::False => json!({ "const": false }),
+        Type::Set {
+            element_type: Some(element_type),
+        } => json!({ "type": "array", "items": json_schema(element_type) }),
+        Type::Set { element_type: None } => json!({ "type": "array", "maxItems": 0 }),
+        Type::EntityOrRecord(EntityRecordKind::Record {
+            attrs,
+            open_attributes,
+        }) => {
+            let properties: Map<_, _> = attrs
+                .iter()
+                .map(|(name, attr)| (name.to_string(), json_schema(&attr.attr_type)))
+                .collect();
+            let required: Vec<_> = attrs
+                .iter()
+                .filter(|(_, attr)| attr.is_required)
+                .map(|(name, _)| name.to_string())
+                .collect();
+            let mut record = Map::new();
+            record.insert("type".to_string(), json!("object"));
+            record.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                record.insert("required".to_string(), json!(required));
+            }
+            record.insert(
+                "additionalProperties".to_string(),
+                json!(*open_attributes == OpenTag::OpenAttributes),
+            );
+            Value::Object(record)
+        }
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+            let mut types: Vec<_> = lub.iter().map(ToString::to_string).collect();
+            types.sort();
+            let uid = json!({
+                "type": "object",
+                "properties": {
+                    "type": { "enum": types },
+                    "id": { "type": "string" },
+                },
+                "required": ["type", "id"],
+                "additionalProperties": false,
+            });
+            json!({ "anyOf": [uid, escaped("__entity", uid.clone())] })
+        }
+        Type::ExtensionType { name } => {
+            let Some(constructor) = extension_constructor(&name.to_string()) else {
+                return json!({});
+            };
+            let call = json!({
+                "type": "object",
+                "properties": {
+                    "fn": { "const": constructor },
+                    "arg": { "type": "string" },
+                },
+                "required": ["fn", "arg"],
+                "additionalProperties": false,
+            });
+            json!({ "anyOf": [{ "type": "string" }, escaped("__extn", call)] })
+        }
+        _ => json!({}),
+    }
+}
+
+/// The JSON Schema of an object whose only property is `escape`, of the JSON
+/// Schema `inner`
+fn escaped(escape: &str, inner: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": { escape: inner },
+        "required": [escape],
+        "additionalProperties": false,
+    })
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = r#"{ "": {
+        "entityTypes": { "User": {}, "Photo": {} },
+        "actions": {
+            "view": {
+                "appliesTo": {
+                    "principalTypes": ["User"],
+                    "resourceTypes": ["Photo"],
+                    "context": {
+                        "type": "Record",
+                        "attributes": {
+                            "age": { "type": "Long" },
+                            "name": { "type": "String" },
+                            "ip": { "type": "Extension", "name": "ipaddr" },
+                            "owner": { "type": "Entity", "name": "User", "required": false }
+                        }
+                    }
+                }
+            }
+        }
+    }}"#;
+
+    fn build(pairs: &str) -> BuildContextResult {
+        build_context(
+            pairs,
+            Some(SCHEMA.to_string()),
+            Some(r#"Action::"view""#.to_string()),
+        )
+    }
+
+    #[test]
+    fn coerces_values_to_schema_types() {
+        match build(r#"{ "age": "42", "name": 7, "ip": "10.0.0.1", "owner": "User::\"alice\"" }"#) {
+            BuildContextResult::Success { context } => assert_eq!(
+                context,
+                serde_json::json!({
+                    "age": 42,
+                    "name": "7",
+                    "ip": { "__extn": { "fn": "ip", "arg": "10.0.0.1" } },
+                    "owner": { "__entity": { "type": "User", "id": "alice" } },
+                })
+            ),
+            BuildContextResult::Error { errors } => panic!("unexpected errors: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_errors_per_key() {
+        match build(r#"{ "age": "forty", "name": "bob", "extra": true }"#) {
+            BuildContextResult::Success { context } => panic!("unexpected success: {context}"),
+            BuildContextResult::Error { errors } => {
+                let keys: Vec<_> = errors.iter().map(|e| e.key.as_deref()).collect();
+                assert_eq!(keys, vec![Some("age"), Some("extra"), Some("ip")]);
+            }
+        }
+    }
+
+    fn validate(context: &str) -> ValidateContextResult {
+        let schema = WasmSchema::parse_in_format(
+            r#"
+            entity User;
+            entity Photo;
+            action view appliesTo {
+                principal: [User],
+                resource: [Photo],
+                context: {
+                    age: Long,
+                    ip: ipaddr,
+                    owner?: User,
+                    address: { city: String, zip?: String },
+                    tags: Set<String>,
+                }
+            };
+            "#,
+            None,
+        )
+        .unwrap();
+        validate_context(
+            &schema,
+            EntityUidInput::Text(r#"Action::"view""#.to_string()),
+            context,
+        )
+    }
+
+    #[test]
+    fn validates_context_against_schema() {
+        assert!(matches!(
+            validate(
+                r#"{ "age": 42, "ip": "10.0.0.1", "owner": { "type": "User", "id": "alice" },
+                     "address": { "city": "Paris" }, "tags": ["a"] }"#
+            ),
+            ValidateContextResult::Success {}
+        ));
+        match validate(
+            r#"{ "age": "42", "ip": "nope", "owner": { "__entity": { "type": "Photo", "id": "p" } },
+                 "address": { "zip": 7, "street": "Main" }, "tags": ["a", 1], "extra": true }"#,
+        ) {
+            ValidateContextResult::Invalid { errors } => {
+                let errors: Vec<_> = errors
+                    .iter()
+                    .map(|e| (e.attribute.as_deref().unwrap_or_default(), e.kind))
+                    .collect();
+                assert_eq!(
+                    errors,
+                    vec![
+                        ("age", ContextErrorKind::WrongType),
+                        ("ip", ContextErrorKind::InvalidValue),
+                        ("owner", ContextErrorKind::WrongType),
+                        ("address.zip", ContextErrorKind::WrongType),
+                        ("address.street", ContextErrorKind::UnknownAttribute),
+                        ("address.city", ContextErrorKind::MissingAttribute),
+                        ("tags[1]", ContextErrorKind::WrongType),
+                        ("extra", ContextErrorKind::UnknownAttribute),
+                    ]
+                );
+            }
+            result => panic!("expected the context to be invalid: {result:?}"),
+        }
+        assert!(matches!(
+            validate("[]"),
+            ValidateContextResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn passes_values_through_without_schema() {
+        match build_context(r#"{ "age": "42" }"#, None, None) {
+            BuildContextResult::Success { context } => {
+                assert_eq!(context, serde_json::json!({ "age": "42" }));
+            }
+            BuildContextResult::Error { errors } => panic!("unexpected errors: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn describes_context_as_json_schema() {
+        let schema = WasmSchema::parse(SCHEMA).unwrap();
+        let action = EntityUidInput::Text(r#"Action::"view""#.to_string());
+        let ContextJsonSchemaResult::Success {
+            schema: json_schema,
+        } = context_json_schema(&schema, action)
+        else {
+            panic!("expected a JSON Schema");
+        };
+        let uid = json!({
+            "type": "object",
+            "properties": {
+                "type": { "enum": ["User"] },
+                "id": { "type": "string" },
+            },
+            "required": ["type", "id"],
+            "additionalProperties": false,
+        });
+        let ip = json!({
+            "type": "object",
+            "properties": {
+                "fn": { "const": "ip" },
+                "arg": { "type": "string" },
+            },
+            "required": ["fn", "arg"],
+            "additionalProperties": false,
+        });
+        assert_eq!(
+            json_schema,
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": r#"Context of the `Action::"view"` action"#,
+                "type": "object",
+                "properties": {
+                    "age": { "type": "integer" },
+                    "name": { "type": "string" },
+                    "ip": { "anyOf": [{ "type": "string" }, escaped("__extn", ip)] },
+                    "owner": { "anyOf": [uid.clone(), escaped("__entity", uid)] },
+                },
+                "required": ["age", "ip", "name"],
+                "additionalProperties": false,
+            })
+        );
+
+        let action = EntityUidInput::Text(r#"Action::"edit""#.to_string());
+        assert!(matches!(
+            context_json_schema(&schema, action),
+            ContextJsonSchemaResult::Error { .. }
+        ));
+    }
+}
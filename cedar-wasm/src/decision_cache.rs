@@ -0,0 +1,169 @@
+//! This module contains the cache of authorization answers a `CedarEngine`
+//! may keep, for UIs that check the same permissions over and over.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use cedar_policy::Decision;
+use serde_json::Value;
+
+/// The fields of a request that an answer from the cache may depend on.
+/// Requests with any other field, e.g. asking for a `trace`, are not cached.
+const KEY_FIELDS: [&str; 4] = ["principal", "action", "resource", "context"];
+
+/// What a cached answer is looked up by: the request's principal, action,
+/// resource, and context, as JSON, and a hash of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DecisionKey {
+    hash: u64,
+    request: String,
+}
+
+impl DecisionKey {
+    /// The key for `request`, or `None` if its answer is not to be cached
+    pub(crate) fn for_request(request: &str) -> Option<Self> {
+        let Ok(Value::Object(request)) = serde_json::from_str::<Value>(request) else {
+            return None;
+        };
+        if request
+            .keys()
+            .any(|field| !KEY_FIELDS.contains(&field.as_str()))
+        {
+            return None;
+        }
+        let request = Value::Array(
+            KEY_FIELDS
+                .iter()
+                .map(|field| request.get(*field).cloned().unwrap_or(Value::Null))
+                .collect(),
+        )
+        .to_string();
+        let mut hasher = DefaultHasher::new();
+        request.hash(&mut hasher);
+        Some(Self {
+            hash: hasher.finish(),
+            request,
+        })
+    }
+}
+
+/// Answers to requests, least recently used first, holding at most
+/// `capacity` of them. With no capacity, nothing is cached.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DecisionCache {
+    capacity: usize,
+    /// each key with the `result` of the successful answer to its request,
+    /// and the decision it answered
+    entries: Vec<(DecisionKey, String, Decision)>,
+}
+
+impl DecisionCache {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// The cached answer for `key`, with its decision, which becomes the most
+    /// recently used
+    pub(crate) fn get(&mut self, key: &DecisionKey) -> Option<(String, Decision)> {
+        let i = self
+            .entries
+            .iter()
+            .position(|(cached, ..)| cached.hash == key.hash && cached.request == key.request)?;
+        let entry = self.entries.remove(i);
+        let result = (entry.1.clone(), entry.2);
+        self.entries.push(entry);
+        Some(result)
+    }
+
+    /// Cache the answer `result` for `key`, which answered `decision`,
+    /// evicting the least recently used answer if the cache is full
+    pub(crate) fn insert(&mut self, key: DecisionKey, result: String, decision: Decision) {
+        if !self.is_enabled() {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, result, decision));
+    }
+
+    /// Drop every cached answer, e.g. because the policies or entities they
+    /// were answered against have changed
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(request: &str) -> DecisionKey {
+        DecisionKey::for_request(request).unwrap()
+    }
+
+    #[test]
+    fn keys_plain_requests_only() {
+        let request = r#"{ "principal": "User::\"a\"", "action": "Action::\"view\"", "resource": "Photo::\"p\"", "context": {} }"#;
+        assert_eq!(
+            key(request),
+            key(
+                r#"{ "context": {}, "resource": "Photo::\"p\"", "action": "Action::\"view\"", "principal": "User::\"a\"" }"#
+            )
+        );
+        assert!(
+            DecisionKey::for_request(r#"{ "principal": "User::\"a\"", "trace": true }"#).is_none()
+        );
+        assert!(DecisionKey::for_request("[]").is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = DecisionCache::with_capacity(2);
+        cache.insert(
+            key(r#"{ "context": { "n": 1 } }"#),
+            "one".to_string(),
+            Decision::Allow,
+        );
+        cache.insert(
+            key(r#"{ "context": { "n": 2 } }"#),
+            "two".to_string(),
+            Decision::Deny,
+        );
+        assert_eq!(
+            cache.get(&key(r#"{ "context": { "n": 1 } }"#)),
+            Some(("one".to_string(), Decision::Allow))
+        );
+        cache.insert(
+            key(r#"{ "context": { "n": 3 } }"#),
+            "three".to_string(),
+            Decision::Allow,
+        );
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key(r#"{ "context": { "n": 2 } }"#)), None);
+        assert_eq!(
+            cache.get(&key(r#"{ "context": { "n": 1 } }"#)),
+            Some(("one".to_string(), Decision::Allow))
+        );
+
+        let mut disabled = DecisionCache::default();
+        disabled.insert(
+            key(r#"{ "context": {} }"#),
+            "none".to_string(),
+            Decision::Deny,
+        );
+        assert_eq!(disabled.len(), 0);
+    }
+}
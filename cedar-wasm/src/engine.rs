@@ -0,0 +1,1861 @@
+//! This module contains `CedarEngine`, a policy decision point bundling a
+//! schema, policies, and entities that are parsed once and then used to answer
+//! any number of authorization requests, and snapshots of an engine's state.
+#[cfg(feature = "full")]
+use cedar_policy::frontend::validate::json_validate_with;
+use cedar_policy::frontend::{
+    is_authorized::{
+        json_is_authorized_with_error_mode, ErrorMode, InterfaceAuthorizationError,
+        InterfaceResponse,
+    },
+    utils::{InterfaceResult, RESPONSE_VERSION},
+};
+use cedar_policy::{
+    AuthorizationError, Authorizer, Context, Decision, Effect, EntityTypeName, EntityUid, PolicyId,
+    Request, Response,
+};
+#[cfg(feature = "full")]
+use cedar_policy_core::ast::EntityUID;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::str::FromStr;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    decision_cache::{DecisionCache, DecisionKey},
+    errors_to_js,
+    extensions::ExtensionConfig,
+    now_ms, with_cbor_context, EntityPage, EntityUidInput, EntityUidInputs, PolicySetJson,
+    SkippedEntities, SkippedEntity, TcComputationMode, WasmEntities, WasmPolicySet, WasmSchema,
+};
+
+#[derive(Tsify, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Sizes of what a `CedarEngine` holds
+pub struct EngineStats {
+    /// static and template-linked policies
+    pub policies: usize,
+    pub templates: usize,
+    pub entities: usize,
+    /// the size of the policy text and entity JSON the engine holds, a lower
+    /// bound on the heap its parsed forms occupy
+    pub estimated_heap_bytes: usize,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The answers to a batch of authorization requests, in request order
+pub struct BatchAuthorizationResults {
+    pub results: Vec<InterfaceResult>,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// When to stop answering a batch of requests before its end. The answers
+/// end with the one that stopped the batch.
+pub struct BatchOptions {
+    /// stop at the first request that is denied, or that fails
+    #[serde(default)]
+    #[tsify(optional)]
+    pub stop_on_first_deny: bool,
+    /// stop at the first request that is allowed
+    #[serde(default)]
+    #[tsify(optional)]
+    pub stop_on_first_allow: bool,
+}
+
+impl BatchOptions {
+    /// Whether the batch stops at a request answered with `decision`, or
+    /// that failed if none
+    fn stops_at(self, decision: Option<Decision>) -> bool {
+        if decision == Some(Decision::Allow) {
+            self.stop_on_first_allow
+        } else {
+            self.stop_on_first_deny
+        }
+    }
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum WhatCanIDoResult {
+    Success {
+        /// the allowed actions, as sorted Cedar text
+        actions: Vec<String>,
+        /// why the request was invalid for an action it applies to, e.g.
+        /// because the context does not match the action's; such actions are
+        /// not allowed
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[tsify(optional)]
+        request_errors: Vec<String>,
+    },
+    Error {
+        errors: Vec<String>,
+    },
+}
+
+/// A cell of a permission matrix for a request that was allowed
+const MATRIX_ALLOW: u8 = 1;
+/// A cell of a permission matrix for a request that was denied
+const MATRIX_DENY: u8 = 0;
+/// A cell of a permission matrix for a request the schema rejects, e.g. for an
+/// action that does not apply to the principal's type
+const MATRIX_INVALID: u8 = 2;
+
+/// The most times `isAuthorizedWithLoader` calls its loader for one request
+const MAX_LOADER_ROUNDS: usize = 8;
+
+/// Version of the snapshot format written by `exportState`. Snapshots of any
+/// other version are rejected by `importState`.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// The state of a `CedarEngine`, encoded as CBOR by `exportState`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineSnapshot {
+    version: u32,
+    schema: Option<serde_json::Value>,
+    policies: PolicySetJson,
+    /// entities JSON, with parents as given
+    entities: String,
+    tc_computation: TcComputationMode,
+    /// the entities skipped by `newLenient`
+    #[serde(default)]
+    skipped: Vec<SkippedEntity>,
+}
+
+/// A schema (optional), policy set, and entity store, ready to answer
+/// authorization requests
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct CedarEngine {
+    schema: Option<WasmSchema>,
+    policy_set: WasmPolicySet,
+    entities: WasmEntities,
+    /// answers to earlier requests, if enabled by `enableDecisionCache`
+    decisions: RefCell<DecisionCache>,
+    /// the extensions disabled by `setDisabledExtensions`
+    extensions: ExtensionConfig,
+    /// why the engine's policies may not be used, if they call functions of
+    /// disabled extensions
+    extension_errors: Vec<String>,
+    /// the mode set by `setErrorMode`, echoed in each answer once set
+    error_mode: Option<ErrorMode>,
+    /// the entities skipped when the engine was built with `newLenient`
+    skipped: Vec<SkippedEntity>,
+}
+
+impl CedarEngine {
+    /// Fail if the engine's policies, or `request` if given, use an extension
+    /// disabled by `setDisabledExtensions`
+    fn check_extensions(&self, request: Option<&serde_json::Value>) -> Result<(), Vec<String>> {
+        let mut errors = self.extension_errors.clone();
+        if let Some(request) = request {
+            errors.extend(self.extensions.request_errors(request));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// A failure answering `request` if it, or the engine's policies, use an
+    /// extension disabled by `setDisabledExtensions`
+    fn extension_failure(&self, request: &str) -> Option<InterfaceResult> {
+        if !self.extensions.is_restricted() {
+            return None;
+        }
+        let request = serde_json::from_str::<serde_json::Value>(request).ok();
+        self.check_extensions(request.as_ref())
+            .err()
+            .map(InterfaceResult::fail_disabled_extension)
+    }
+
+    /// The decision the engine's error mode reports in place of `decision`,
+    /// Cedar's decision for a request on `uids`, if it differs. `determined`
+    /// is whether some policy determined the decision, and `errored` are the
+    /// policies whose evaluation failed.
+    fn error_mode_decision<'a>(
+        &self,
+        decision: Decision,
+        determined: bool,
+        mut errored: impl Iterator<Item = &'a PolicyId>,
+        uids: [Option<&EntityUid>; 3],
+    ) -> Option<Decision> {
+        match (self.error_mode.unwrap_or_default(), decision) {
+            (ErrorMode::FailOpen, Decision::Deny) if !determined => errored
+                .any(|id| {
+                    self.policy_set
+                        .policy_set()
+                        .policy(id)
+                        .is_some_and(|policy| policy.effect() == Effect::Permit)
+                })
+                .then_some(Decision::Allow),
+            (ErrorMode::FailClosed, Decision::Allow) => uids
+                .into_iter()
+                .flatten()
+                .any(|uid| self.is_skipped(uid))
+                .then_some(Decision::Deny),
+            _ => None,
+        }
+    }
+
+    /// Whether the engine skipped the entity `uid` when loading its entities
+    fn is_skipped(&self, uid: &EntityUid) -> bool {
+        if self.skipped.is_empty() {
+            return false;
+        }
+        let uid = uid.to_string();
+        self.skipped
+            .iter()
+            .any(|skipped| skipped.uid.as_deref() == Some(uid.as_str()))
+    }
+
+    /// The decision of the engine's error mode for `request`, which Cedar
+    /// answered with `response`
+    fn decide(&self, request: &Request, response: &Response) -> Decision {
+        self.error_mode_decision(
+            response.decision(),
+            response.diagnostics().reason().next().is_some(),
+            response.diagnostics().errors().map(AuthorizationError::id),
+            [request.principal(), request.action(), request.resource()],
+        )
+        .unwrap_or(response.decision())
+    }
+
+    /// Answer `request` against `entities` with the decision of the engine's
+    /// error mode, and the mode echoed as `error_mode` if one is set or it
+    /// changed the decision, along with the decision if the request was
+    /// answered
+    fn answer(
+        &self,
+        request: &str,
+        entities: &WasmEntities,
+    ) -> (InterfaceResult, Option<Decision>) {
+        json_is_authorized_with_error_mode(
+            request,
+            self.policy_set.policy_set(),
+            entities.entities(),
+            self.schema.as_ref().map(WasmSchema::schema),
+            Some(&now_ms),
+            self.error_mode,
+            &|request: &Request, response: &InterfaceResponse| {
+                self.error_mode_decision(
+                    response.decision(),
+                    response.diagnostics().reason().next().is_some(),
+                    response
+                        .diagnostics()
+                        .errors()
+                        .map(InterfaceAuthorizationError::policy_id),
+                    [request.principal(), request.action(), request.resource()],
+                )
+            },
+        )
+    }
+
+    /// Answer each of `requests`, a JSON array of requests as taken by
+    /// `isAuthorized`, in order, until one the `options` stop at
+    pub fn is_authorized_batch(
+        &self,
+        requests: &str,
+        options: BatchOptions,
+    ) -> Result<Vec<InterfaceResult>, Vec<String>> {
+        let requests = serde_json::from_str::<Vec<Box<RawValue>>>(requests)
+            .map_err(|e| vec![format!("error parsing requests: {e}")])?;
+        let mut results = Vec::with_capacity(requests.len());
+        for request in &requests {
+            let (result, decision) = self.decide_request(request.get());
+            let stop = options.stops_at(decision);
+            results.push(result);
+            if stop {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// The actions declared by the engine's schema that apply to the
+    /// `principal` and `resource` of `request` and are allowed for them, with
+    /// the errors building the request for any action it applies to
+    #[cfg(feature = "full")]
+    pub fn allowed_actions(
+        &self,
+        request: &str,
+    ) -> Result<(Vec<String>, Vec<String>), Vec<String>> {
+        let Some(schema) = &self.schema else {
+            return Err(vec![
+                "cannot list actions: the engine was built without a schema".to_string(),
+            ]);
+        };
+        let request = parse_partial_request(request)?;
+        self.check_extensions(Some(&request))?;
+        let principal = request_uid(&request, "principal")?;
+        let resource = request_uid(&request, "resource")?;
+        let context = request_context(&request);
+
+        let authorizer = Authorizer::new();
+        let mut actions = Vec::new();
+        let mut request_errors = Vec::new();
+        for action in schema.actions_applying_to(
+            &principal.type_name().to_string(),
+            &resource.type_name().to_string(),
+        ) {
+            let request = EntityUid::from_str(&action)
+                .map_err(|e| e.to_string())
+                .and_then(|uid| {
+                    let context =
+                        Context::from_json_value(context.clone(), Some((schema.schema(), &uid)))
+                            .map_err(|e| format!("error parsing context: {e}"))?;
+                    Request::new(
+                        Some(principal.clone()),
+                        Some(uid),
+                        Some(resource.clone()),
+                        context,
+                        Some(schema.schema()),
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            match request {
+                Ok(request) => {
+                    let response = authorizer.is_authorized(
+                        &request,
+                        self.policy_set.policy_set(),
+                        self.entities.entities(),
+                    );
+                    if self.decide(&request, &response) == Decision::Allow {
+                        actions.push(action);
+                    }
+                }
+                Err(e) => request_errors.push(format!("{action}: {e}")),
+            }
+        }
+        Ok((actions, request_errors))
+    }
+
+    /// The decision for every combination of `principals`, `actions`, and
+    /// `resources`, with an empty context. The cell for
+    /// principal `p`, action `a`, and resource `r` is at index
+    /// `(p * actions.len() + a) * resources.len() + r`.
+    pub fn permission_matrix(
+        &self,
+        principals: &[EntityUidInput],
+        actions: &[EntityUidInput],
+        resources: &[EntityUidInput],
+    ) -> Result<Vec<u8>, Vec<String>> {
+        self.check_extensions(None)?;
+        let mut errors = Vec::new();
+        let mut parse = |uids: &[EntityUidInput], what: &str| -> Vec<EntityUid> {
+            uids.iter()
+                .filter_map(|uid| {
+                    uid.to_uid()
+                        .map_err(|e| errors.push(format!("error parsing {what} {e}")))
+                        .ok()
+                })
+                .collect()
+        };
+        let principals = parse(principals, "principal");
+        let actions = parse(actions, "action");
+        let resources = parse(resources, "resource");
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let authorizer = Authorizer::new();
+        let schema = self.schema.as_ref().map(WasmSchema::schema);
+        let mut matrix = Vec::with_capacity(principals.len() * actions.len() * resources.len());
+        for principal in &principals {
+            for action in &actions {
+                for resource in &resources {
+                    let request = Request::new(
+                        Some(principal.clone()),
+                        Some(action.clone()),
+                        Some(resource.clone()),
+                        Context::empty(),
+                        schema,
+                    );
+                    matrix.push(match request {
+                        Ok(request) => {
+                            let response = authorizer.is_authorized(
+                                &request,
+                                self.policy_set.policy_set(),
+                                self.entities.entities(),
+                            );
+                            match self.decide(&request, &response) {
+                                Decision::Allow => MATRIX_ALLOW,
+                                Decision::Deny => MATRIX_DENY,
+                            }
+                        }
+                        Err(_) => MATRIX_INVALID,
+                    });
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Up to `limit` of the entities the `principal` of `request` may perform
+    /// its `action` on, in order of type and then uid, skipping the first
+    /// `offset`, along with the number of such entities. The candidates are
+    /// the entities of the request's `resourceType`, or of the resource types
+    /// the engine's schema declares for the action if it is not given.
+    pub fn accessible_resources(
+        &self,
+        request: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, Vec<String>> {
+        self.authorized_entities(request, Enumerated::Resource, offset, limit)
+    }
+
+    /// Up to `limit` of the entities that may perform the `action` of
+    /// `request` on its `resource`, in order of type and then uid, skipping
+    /// the first `offset`, along with the number of such entities. The
+    /// candidates are the entities of the request's `principalType`, or of
+    /// the principal types the engine's schema declares for the action if it
+    /// is not given.
+    pub fn authorized_principals(
+        &self,
+        request: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, Vec<String>> {
+        self.authorized_entities(request, Enumerated::Principal, offset, limit)
+    }
+
+    /// Answer `request` with each candidate entity in turn as its `enumerated`
+    /// entity, returning a page of those that are allowed
+    fn authorized_entities(
+        &self,
+        request: &str,
+        enumerated: Enumerated,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, Vec<String>> {
+        let request = parse_partial_request(request)?;
+        self.check_extensions(Some(&request))?;
+        let fixed = request_uid(&request, enumerated.other_field())?;
+        let action = request_uid(&request, "action")?;
+        let schema = self.schema.as_ref().map(WasmSchema::schema);
+        let context = Context::from_json_value(
+            request_context(&request),
+            schema.map(|schema| (schema, &action)),
+        )
+        .map_err(|e| vec![format!("error parsing context: {e}")])?;
+        let type_field = enumerated.type_field();
+        let candidate_types = match (request.get(type_field), &self.schema) {
+            (Some(candidate_type), _) => {
+                let candidate_type = candidate_type
+                    .as_str()
+                    .ok_or_else(|| vec![format!("`{type_field}` must be a string")])?;
+                vec![EntityTypeName::from_str(candidate_type)
+                    .map_err(|e| vec![format!("error parsing `{type_field}`: {e}")])?]
+            }
+            #[cfg(feature = "full")]
+            (None, Some(schema)) => {
+                let action_uid = EntityUID::from_str(&action.to_string())
+                    .map_err(|e| vec![format!("error parsing action: {e}")])?;
+                let applies_to = schema.applies_to_action(&action_uid).ok_or_else(|| {
+                    vec![format!("action {action} is not declared by the schema")]
+                })?;
+                let types = match enumerated {
+                    Enumerated::Principal => applies_to.principal_types,
+                    Enumerated::Resource => applies_to.resource_types,
+                };
+                types
+                    .iter()
+                    .map(|ty| EntityTypeName::from_str(ty).map_err(|e| vec![e.to_string()]))
+                    .collect::<Result<_, _>>()?
+            }
+            #[cfg(not(feature = "full"))]
+            (None, Some(_)) => {
+                return Err(vec![format!(
+                    "request is missing `{type_field}`, and inferring it from the schema needs the `full` feature"
+                )])
+            }
+            (None, None) => {
+                return Err(vec![format!(
+                    "request is missing `{type_field}`, which the engine has no schema to infer"
+                )])
+            }
+        };
+
+        let authorizer = Authorizer::new();
+        let mut authorized = Vec::new();
+        for candidate in candidate_types
+            .iter()
+            .flat_map(|ty| self.entities.uids_of_type(ty))
+        {
+            let (principal, resource) = match enumerated {
+                Enumerated::Principal => (candidate, &fixed),
+                Enumerated::Resource => (&fixed, candidate),
+            };
+            let request = Request::new(
+                Some(principal.clone()),
+                Some(action.clone()),
+                Some(resource.clone()),
+                context.clone(),
+                schema,
+            )
+            .map_err(|e| vec![e.to_string()])?;
+            let response = authorizer.is_authorized(
+                &request,
+                self.policy_set.policy_set(),
+                self.entities.entities(),
+            );
+            if self.decide(&request, &response) == Decision::Allow {
+                authorized.push(candidate);
+            }
+        }
+        Ok(EntityPage {
+            total: authorized.len(),
+            uids: authorized
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(ToString::to_string)
+                .collect(),
+        })
+    }
+
+    /// Answer `request`, which must be `strict`, as
+    /// `isAuthorized` does, but against `entities` in place of the engine's
+    /// own, along with the entities the answer reports missing
+    fn authorize_against(
+        &self,
+        request: &str,
+        entities: &WasmEntities,
+    ) -> (InterfaceResult, Vec<String>) {
+        if let Some(failure) = self.extension_failure(request) {
+            return (failure, Vec::new());
+        }
+        let (answer, _) = self.answer(request, entities);
+        let missing = match &answer {
+            InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<serde_json::Value>(result)
+                    .ok()
+                    .and_then(|mut answer| {
+                        answer
+                            .get_mut("missing_entities")
+                            .map(serde_json::Value::take)
+                    })
+                    .and_then(|missing| serde_json::from_value(missing).ok())
+                    .unwrap_or_default()
+            }
+            InterfaceResult::Failure { .. } => Vec::new(),
+        };
+        (answer, missing)
+    }
+
+    /// Build an engine from policies and a schema, each in their JSON format or
+    /// Cedar syntax as they look, and entities in their JSON format. If a
+    /// schema is given, the entities are parsed according to it and requests
+    /// are validated against it.
+    pub fn build(
+        schema: Option<&str>,
+        policies: &str,
+        entities: &str,
+    ) -> Result<Self, Vec<String>> {
+        let schema = schema
+            .map(|schema| WasmSchema::parse_in_format(schema, None))
+            .transpose()?;
+        let policy_set = WasmPolicySet::parse_in_format(policies, None)?;
+        let entities = match &schema {
+            Some(schema) => {
+                WasmEntities::parse_with_schema(entities, schema.schema(), Default::default())?
+            }
+            None => WasmEntities::parse(entities, None, Default::default())?,
+        };
+        Ok(Self {
+            schema,
+            policy_set,
+            entities,
+            decisions: RefCell::default(),
+            extensions: ExtensionConfig::default(),
+            extension_errors: Vec::new(),
+            error_mode: None,
+            skipped: Vec::new(),
+        })
+    }
+
+    /// Build an engine as [`CedarEngine::build`] does, but skipping each
+    /// entity that does not parse on its own in place of failing. Requests on
+    /// a skipped entity are answered as the engine's error mode decides.
+    pub fn build_lenient(
+        schema: Option<&str>,
+        policies: &str,
+        entities: &str,
+    ) -> Result<Self, Vec<String>> {
+        let schema = schema
+            .map(|schema| WasmSchema::parse_in_format(schema, None))
+            .transpose()?;
+        let policy_set = WasmPolicySet::parse_in_format(policies, None)?;
+        let (entities, skipped) =
+            WasmEntities::parse_lenient(entities, schema.as_ref().map(WasmSchema::schema))?;
+        Ok(Self {
+            schema,
+            policy_set,
+            entities,
+            decisions: RefCell::default(),
+            extensions: ExtensionConfig::default(),
+            extension_errors: Vec::new(),
+            error_mode: None,
+            skipped,
+        })
+    }
+
+    /// Counts of the policies and entities held by the engine, with an
+    /// estimate of the heap they occupy
+    pub fn stats(&self) -> EngineStats {
+        let policy_set = self.policy_set.policy_set();
+        let policy_bytes: usize = policy_set
+            .policies()
+            .map(|policy| policy.to_string().len())
+            .chain(
+                policy_set
+                    .templates()
+                    .map(|template| template.to_string().len()),
+            )
+            .sum();
+        let entity_bytes = self
+            .entities
+            .to_json_string()
+            .map(|json| json.len())
+            .unwrap_or_default();
+        EngineStats {
+            policies: policy_set.policies().count(),
+            templates: policy_set.templates().count(),
+            entities: self.entities.size(),
+            estimated_heap_bytes: policy_bytes + entity_bytes,
+        }
+    }
+
+    /// Encode the engine's schema, policies, and entities as a binary snapshot
+    pub fn snapshot(&self) -> Result<Vec<u8>, Vec<String>> {
+        let snapshot = EngineSnapshot {
+            version: SNAPSHOT_VERSION,
+            schema: self.schema.as_ref().map(|schema| schema.json().clone()),
+            policies: self
+                .policy_set
+                .to_policy_set_json()
+                .map_err(|e| vec![format!("error exporting policies: {e}")])?,
+            entities: self
+                .entities
+                .to_json_string()
+                .map_err(|e| vec![format!("error exporting entities: {e}")])?,
+            tc_computation: self.entities.tc_computation(),
+            skipped: self.skipped.clone(),
+        };
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&snapshot, &mut bytes)
+            .map_err(|e| vec![format!("error encoding snapshot: {e}")])?;
+        Ok(bytes)
+    }
+
+    /// Restore an engine from a snapshot written by [`CedarEngine::snapshot`].
+    /// The entities are not checked against the schema again.
+    pub fn restore(bytes: &[u8]) -> Result<Self, Vec<String>> {
+        let snapshot: EngineSnapshot = ciborium::from_reader(bytes)
+            .map_err(|e| vec![format!("error decoding snapshot: {e}")])?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(vec![format!(
+                "unsupported snapshot version {}, expected {SNAPSHOT_VERSION}",
+                snapshot.version
+            )]);
+        }
+        let schema = snapshot.schema.map(WasmSchema::parse_value).transpose()?;
+        let policy_set = WasmPolicySet::from_policy_set_json(snapshot.policies)?;
+        let entities = WasmEntities::restore(
+            &snapshot.entities,
+            schema.as_ref().map(|schema| schema.schema().clone()),
+            snapshot.tc_computation,
+        )?;
+        Ok(Self {
+            schema,
+            policy_set,
+            entities,
+            decisions: RefCell::default(),
+            extensions: ExtensionConfig::default(),
+            extension_errors: Vec::new(),
+            error_mode: None,
+            skipped: snapshot.skipped,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl CedarEngine {
+    /// Build an engine from policies, entities JSON, and an optional schema,
+    /// throwing if any of them is invalid. The policies and schema may each be
+    /// in their JSON format or Cedar syntax, which is detected.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        policies: &str,
+        entities: &str,
+        schema: Option<String>,
+    ) -> Result<CedarEngine, JsError> {
+        Self::build(schema.as_deref(), policies, entities).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Build an engine as the constructor does, but skipping each entity that
+    /// does not parse on its own, e.g. because it does not conform to the
+    /// schema, in place of throwing. The entities skipped are listed by
+    /// `skippedEntities`. Under the `failClosed` error mode, the default, a
+    /// request whose principal, action, or resource was skipped is denied.
+    #[wasm_bindgen(js_name = "newLenient")]
+    pub fn new_lenient(
+        policies: &str,
+        entities: &str,
+        schema: Option<String>,
+    ) -> Result<CedarEngine, JsError> {
+        Self::build_lenient(schema.as_deref(), policies, entities)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// The entities skipped when the engine was built with `newLenient`, in
+    /// order
+    #[wasm_bindgen(js_name = "skippedEntities")]
+    pub fn skipped_entities(&self) -> SkippedEntities {
+        SkippedEntities(self.skipped.clone())
+    }
+
+    /// Build an engine from already-parsed handles, without a schema. The
+    /// handles remain usable on their own.
+    #[wasm_bindgen(js_name = "fromHandles")]
+    pub fn from_handles(policy_set: &WasmPolicySet, entities: &WasmEntities) -> CedarEngine {
+        Self {
+            schema: None,
+            policy_set: policy_set.clone(),
+            entities: entities.clone(),
+            decisions: RefCell::default(),
+            extensions: ExtensionConfig::default(),
+            extension_errors: Vec::new(),
+            error_mode: None,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Build an engine from already-parsed handles, including a schema. The
+    /// entities are expected to have been parsed with the same schema, e.g.
+    /// by `WasmEntities.fromJsonWithSchema`.
+    #[wasm_bindgen(js_name = "fromHandlesWithSchema")]
+    pub fn from_handles_with_schema(
+        schema: &WasmSchema,
+        policy_set: &WasmPolicySet,
+        entities: &WasmEntities,
+    ) -> CedarEngine {
+        Self {
+            schema: Some(schema.clone()),
+            policy_set: policy_set.clone(),
+            entities: entities.clone(),
+            decisions: RefCell::default(),
+            extensions: ExtensionConfig::default(),
+            extension_errors: Vec::new(),
+            error_mode: None,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Export the engine's schema, policies, and entities as a compact binary
+    /// snapshot, e.g. for persisting to IndexedDB
+    #[wasm_bindgen(js_name = "exportState")]
+    pub fn export_state(&self) -> Result<Vec<u8>, JsError> {
+        self.snapshot().map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Restore an engine from a snapshot produced by `exportState`, throwing
+    /// if it is invalid or was written by an incompatible version
+    #[wasm_bindgen(js_name = "importState")]
+    pub fn import_state(bytes: &[u8]) -> Result<CedarEngine, JsError> {
+        Self::restore(bytes).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Counts of the policies and entities held by the engine, with an
+    /// estimate of the heap they occupy
+    #[wasm_bindgen(js_name = "memoryStats")]
+    pub fn memory_stats(&self) -> EngineStats {
+        self.stats()
+    }
+
+    /// Answer an authorization request. The request has the fields of an
+    /// `isAuthorized` call minus the `slice` and `schema`, which come from
+    /// the engine.
+    #[wasm_bindgen(js_name = "isAuthorized")]
+    pub fn is_authorized(&self, request: &str) -> InterfaceResult {
+        self.decide_request(request).0
+    }
+
+    /// Answer `request` as `isAuthorized` does, along with the decision if
+    /// the request was answered
+    fn decide_request(&self, request: &str) -> (InterfaceResult, Option<Decision>) {
+        if let Some(failure) = self.extension_failure(request) {
+            return (failure, None);
+        }
+        let key = self
+            .decisions
+            .borrow()
+            .is_enabled()
+            .then(|| DecisionKey::for_request(request))
+            .flatten();
+        if let Some((result, decision)) = key
+            .as_ref()
+            .and_then(|key| self.decisions.borrow_mut().get(key))
+        {
+            return (
+                InterfaceResult::Success {
+                    api_version: RESPONSE_VERSION,
+                    result,
+                },
+                Some(decision),
+            );
+        }
+        let (answer, decision) = self.answer(request, &self.entities);
+        if let (Some(key), InterfaceResult::Success { result, .. }, Some(decision)) =
+            (key, &answer, decision)
+        {
+            self.decisions
+                .borrow_mut()
+                .insert(key, result.clone(), decision);
+        }
+        (answer, decision)
+    }
+
+    /// Cache the answers to up to `capacity` requests, dropping the least
+    /// recently used answer when full, and any answers cached before. Only
+    /// requests with no fields but `principal`, `action`, `resource`, and
+    /// `context` are cached, keyed by those fields as given. A `capacity` of
+    /// zero turns the cache off.
+    #[wasm_bindgen(js_name = "enableDecisionCache")]
+    pub fn enable_decision_cache(&self, capacity: usize) {
+        self.decisions
+            .replace(DecisionCache::with_capacity(capacity));
+    }
+
+    /// Drop the answers cached since `enableDecisionCache`, e.g. because
+    /// something the policies depend on outside the engine has changed
+    #[wasm_bindgen(js_name = "clearDecisionCache")]
+    pub fn clear_decision_cache(&self) {
+        self.decisions.borrow_mut().clear();
+    }
+
+    /// The number of answers in the decision cache
+    #[wasm_bindgen(js_name = "decisionCacheSize")]
+    pub fn decision_cache_size(&self) -> usize {
+        self.decisions.borrow().len()
+    }
+
+    /// Answer later requests against `policy_set` in place of the engine's
+    /// policies, dropping any cached answers
+    #[wasm_bindgen(js_name = "setPolicies")]
+    pub fn set_policies(&mut self, policy_set: &WasmPolicySet) {
+        self.policy_set = policy_set.clone();
+        self.extension_errors = self.extensions.policy_errors(policy_set.policy_set());
+        self.decisions.borrow_mut().clear();
+    }
+
+    /// Report the decision of `mode` for requests whose answer may be wrong
+    /// because evaluating a policy failed or an entity was skipped by
+    /// `newLenient`, dropping any cached answers. The mode applies to every
+    /// query, including `whatCanIDo`, `accessibleResources`, `whoCanDo`, and
+    /// `buildPermissionMatrix`. Once a mode is set, each answer to a request
+    /// echoes it as `error_mode`, with `error_mode_applied` set if the mode
+    /// changed the decision.
+    #[wasm_bindgen(js_name = "setErrorMode")]
+    pub fn set_error_mode(&mut self, mode: ErrorMode) {
+        self.error_mode = Some(mode);
+        self.decisions.borrow_mut().clear();
+    }
+
+    /// The mode set by `setErrorMode`, or `failClosed` if none was set
+    #[wasm_bindgen(js_name = "errorMode")]
+    pub fn error_mode(&self) -> ErrorMode {
+        self.error_mode.unwrap_or_default()
+    }
+
+    /// Disable the extensions named in `extensions`, e.g. `ipaddr` or
+    /// `decimal`, in place of any disabled before, throwing if one is not a
+    /// known extension. While the engine's policies call functions of a
+    /// disabled extension, or a request constructs a value of one, answering
+    /// and validating fail with the code `disabledExtension`, and the other
+    /// queries fail with the same errors.
+    #[wasm_bindgen(js_name = "setDisabledExtensions")]
+    pub fn set_disabled_extensions(&mut self, extensions: Vec<String>) -> Result<(), JsError> {
+        self.extensions =
+            ExtensionConfig::disabling(&extensions).map_err(|errors| errors_to_js(&errors))?;
+        self.extension_errors = self.extensions.policy_errors(self.policy_set.policy_set());
+        self.decisions.borrow_mut().clear();
+        Ok(())
+    }
+
+    /// The names of the extensions disabled by `setDisabledExtensions`, sorted
+    #[wasm_bindgen(js_name = "disabledExtensions")]
+    pub fn disabled_extensions(&self) -> Vec<String> {
+        self.extensions.disabled()
+    }
+
+    /// Answer later requests against `entities` in place of the engine's
+    /// entities, dropping any cached answers and the list of skipped
+    /// entities. The entities are expected to have been parsed with the
+    /// engine's schema, if it has one.
+    #[wasm_bindgen(js_name = "setEntities")]
+    pub fn set_entities(&mut self, entities: &WasmEntities) {
+        self.entities = entities.clone();
+        self.skipped.clear();
+        self.decisions.borrow_mut().clear();
+    }
+
+    /// Answer each of `requests`, a JSON array of requests as taken by
+    /// `isAuthorized`, throwing if the array does not parse. A request that
+    /// fails does not fail the others. With `stopOnFirstDeny` or
+    /// `stopOnFirstAllow` set in `options`, the requests after the first one
+    /// denied (or failing) or allowed are not answered.
+    #[wasm_bindgen(js_name = "isAuthorizedBatch")]
+    pub fn wasm_is_authorized_batch(
+        &self,
+        requests: &str,
+        options: Option<BatchOptions>,
+    ) -> Result<BatchAuthorizationResults, JsError> {
+        self.is_authorized_batch(requests, options.unwrap_or_default())
+            .map(|results| BatchAuthorizationResults { results })
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Like `isAuthorizedBatch`, but with the answers returned as a single
+    /// buffer that a Web Worker can transfer with `postMessage` rather than
+    /// clone. The buffer holds one record per request, in order: a 4-byte
+    /// little-endian length followed by that many bytes of the answer as
+    /// UTF-8 JSON.
+    #[wasm_bindgen(js_name = "isAuthorizedBatchBinary")]
+    pub fn is_authorized_batch_binary(
+        &self,
+        requests: &str,
+        options: Option<BatchOptions>,
+    ) -> Result<Vec<u8>, JsError> {
+        self.is_authorized_batch(requests, options.unwrap_or_default())
+            .and_then(|results| length_prefixed(&results))
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Like `isAuthorized`, but with the request's context encoded as CBOR,
+    /// in place of any `context` in the request
+    #[wasm_bindgen(js_name = "isAuthorizedWithCborContext")]
+    pub fn is_authorized_with_cbor_context(
+        &self,
+        request: &str,
+        context: &[u8],
+    ) -> InterfaceResult {
+        match with_cbor_context(request, context) {
+            Ok(request) => self.is_authorized(&request),
+            Err(errors) => InterfaceResult::fail_bad_request(errors),
+        }
+    }
+
+    /// Like `isAuthorized`, but fetching the entities evaluation needs that
+    /// the engine lacks. When answering the request looks up entities that
+    /// are not in the engine, `loader` is called with an array of their uids,
+    /// as Cedar strings like `User::"alice"`, and should return, or resolve
+    /// to, those it finds in the entities JSON format, as a string or array.
+    /// The request is then answered again with them, until no entity is
+    /// missing that the loader has not been asked for, or the loader has been
+    /// called 8 times. Loaded entities are used for this request only.
+    ///
+    /// Resolves to the same result as `isAuthorized` for a `strict` request,
+    /// so its answer lists the entities still missing at the end. Rejects if
+    /// the loader throws or rejects, or returns entities that do not parse.
+    #[wasm_bindgen(js_name = "isAuthorizedWithLoader")]
+    pub async fn is_authorized_with_loader(
+        &self,
+        request: String,
+        loader: js_sys::Function,
+    ) -> Result<JsValue, JsValue> {
+        let request = match strict_call(&request) {
+            Ok(request) => request,
+            Err(errors) => {
+                return Ok(serde_wasm_bindgen::to_value(
+                    &InterfaceResult::fail_bad_request(errors),
+                )?)
+            }
+        };
+        let mut loaded: Option<WasmEntities> = None;
+        let mut requested = HashSet::new();
+        for _ in 0..MAX_LOADER_ROUNDS {
+            let (answer, missing) =
+                self.authorize_against(&request, loaded.as_ref().unwrap_or(&self.entities));
+            let wanted: js_sys::Array = missing
+                .into_iter()
+                .filter(|uid| requested.insert(uid.clone()))
+                .map(JsValue::from)
+                .collect();
+            if wanted.length() == 0 {
+                return Ok(serde_wasm_bindgen::to_value(&answer)?);
+            }
+            let found = loader.call1(&JsValue::NULL, &wanted)?;
+            let found = JsFuture::from(js_sys::Promise::resolve(&found)).await?;
+            let json = match found.as_string() {
+                Some(json) => json,
+                None if found.is_null() || found.is_undefined() => continue,
+                None => String::from(js_sys::JSON::stringify(&found)?),
+            };
+            loaded
+                .get_or_insert_with(|| self.entities.clone())
+                .upsert_all(&json)
+                .map_err(|errors| errors_to_js(&errors))?;
+        }
+        let (answer, _) =
+            self.authorize_against(&request, loaded.as_ref().unwrap_or(&self.entities));
+        Ok(serde_wasm_bindgen::to_value(&answer)?)
+    }
+
+    /// The actions the engine's schema declares for the `principal` and
+    /// `resource` of `request` that are allowed for them, answering the
+    /// request for each in one call, e.g. to decide which buttons to show.
+    /// The request has the fields of an `isAuthorized` request minus the
+    /// `action`, and its `context` is used for every action. Fails if the
+    /// engine has no schema.
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = "whatCanIDo")]
+    pub fn what_can_i_do(&self, request: &str) -> WhatCanIDoResult {
+        match self.allowed_actions(request) {
+            Ok((actions, request_errors)) => WhatCanIDoResult::Success {
+                actions,
+                request_errors,
+            },
+            Err(errors) => WhatCanIDoResult::Error { errors },
+        }
+    }
+
+    /// Up to `limit` of the entities the `principal` of `request` may perform
+    /// its `action` on, in order of type and then uid, skipping the first
+    /// `offset`, along with the number of such entities, throwing if the
+    /// request is invalid. The request has the fields of an `isAuthorized`
+    /// request minus the `resource`, and an optional `resourceType` to list
+    /// entities of; without it, the entities of every resource type the
+    /// schema declares for the action are listed.
+    #[wasm_bindgen(js_name = "accessibleResources")]
+    pub fn wasm_accessible_resources(
+        &self,
+        request: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, JsError> {
+        self.accessible_resources(request, offset, limit)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Up to `limit` of the entities that may perform the `action` of
+    /// `request` on its `resource`, in order of type and then uid, skipping
+    /// the first `offset`, along with the number of such entities, throwing if
+    /// the request is invalid. The request has the fields of an
+    /// `isAuthorized` request minus the `principal`, and an optional
+    /// `principalType` to list entities of; without it, the entities of every
+    /// principal type the schema declares for the action are listed.
+    #[wasm_bindgen(js_name = "whoCanDo")]
+    pub fn who_can_do(
+        &self,
+        request: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, JsError> {
+        self.authorized_principals(request, offset, limit)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// The decision for every combination of `principals`, `actions`, and
+    /// `resources`, each as Cedar text, e.g. `User::"alice"`, or as its type
+    /// and id, with an empty context, throwing if any of them does not parse.
+    /// The matrix is
+    /// dense: the cell for principal `p`, action `a`, and resource `r` is at
+    /// index `(p * actions.length + a) * resources.length + r`, and is 1 if
+    /// the request is allowed, 0 if it is denied, and 2 if the engine's schema
+    /// rejects the request, e.g. because the action does not apply to the
+    /// principal's type.
+    #[wasm_bindgen(js_name = "buildPermissionMatrix")]
+    pub fn build_permission_matrix(
+        &self,
+        principals: EntityUidInputs,
+        actions: EntityUidInputs,
+        resources: EntityUidInputs,
+    ) -> Result<Vec<u8>, JsError> {
+        self.permission_matrix(&principals.0, &actions.0, &resources.0)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Validate the engine's policies against its schema. The call takes the
+    /// optional `validationSettings` of a `validate` call. Fails if the engine
+    /// has no schema.
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = "validate")]
+    pub fn validate(&self, input: &str) -> InterfaceResult {
+        if let Err(errors) = self.check_extensions(None) {
+            return InterfaceResult::fail_disabled_extension(errors);
+        }
+        match &self.schema {
+            Some(schema) => {
+                json_validate_with(input, schema.schema(), self.policy_set.policy_set())
+            }
+            None => InterfaceResult::fail_bad_request(vec![
+                "cannot validate: the engine was built without a schema".to_string(),
+            ]),
+        }
+    }
+}
+
+/// The entity of a request that a reverse query lists the candidates for
+#[derive(Debug, Clone, Copy)]
+enum Enumerated {
+    Principal,
+    Resource,
+}
+
+impl Enumerated {
+    /// The field of the request giving the type of the candidates
+    fn type_field(self) -> &'static str {
+        match self {
+            Self::Principal => "principalType",
+            Self::Resource => "resourceType",
+        }
+    }
+
+    /// The field of the request giving the entity that is not enumerated
+    fn other_field(self) -> &'static str {
+        match self {
+            Self::Principal => "resource",
+            Self::Resource => "principal",
+        }
+    }
+}
+
+/// Parse a request that may be missing some of the fields of a full one
+fn parse_partial_request(request: &str) -> Result<serde_json::Value, Vec<String>> {
+    serde_json::from_str(request).map_err(|e| vec![format!("error parsing request: {e}")])
+}
+
+/// The entity uid in `field` of `request`
+fn request_uid(request: &serde_json::Value, field: &str) -> Result<EntityUid, Vec<String>> {
+    let json = request
+        .get(field)
+        .cloned()
+        .ok_or_else(|| vec![format!("request is missing `{field}`")])?;
+    EntityUid::from_json(json).map_err(|e| vec![format!("error parsing {field}: {e}")])
+}
+
+/// The `context` of `request`, which is empty if not given, `null`, or the
+/// empty string
+fn request_context(request: &serde_json::Value) -> serde_json::Value {
+    match request.get("context") {
+        None | Some(serde_json::Value::Null) => serde_json::json!({}),
+        Some(serde_json::Value::String(context)) if context.is_empty() => serde_json::json!({}),
+        Some(context) => context.clone(),
+    }
+}
+
+/// The authorization call `input`, made `strict`
+fn strict_call(input: &str) -> Result<String, Vec<String>> {
+    let mut call = match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(serde_json::Value::Object(call)) => call,
+        Ok(_) => return Err(vec!["authorization call must be a JSON object".to_string()]),
+        Err(e) => return Err(vec![format!("error parsing call: {e}")]),
+    };
+    call.insert("strict".to_string(), serde_json::Value::Bool(true));
+    Ok(serde_json::Value::Object(call).to_string())
+}
+
+/// Encode `results` as consecutive records, each a 4-byte little-endian length
+/// followed by that many bytes of the result as JSON
+fn length_prefixed(results: &[InterfaceResult]) -> Result<Vec<u8>, Vec<String>> {
+    let mut bytes = Vec::new();
+    for result in results {
+        let record = serde_json::to_vec(result)
+            .map_err(|e| vec![format!("error serializing result: {e}")])?;
+        let len = u32::try_from(record.len())
+            .map_err(|_| vec!["result is too large to encode".to_string()])?;
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend(record);
+    }
+    Ok(bytes)
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cedar_policy::frontend::utils::FailureCode;
+
+    const SCHEMA: &str = r#"{ "": {
+        "entityTypes": {
+            "User": {},
+            "Photo": { "memberOfTypes": ["Album"], "shape": { "type": "Record", "attributes": { "private": { "type": "Boolean" } } } },
+            "Album": {}
+        },
+        "actions": {
+            "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+        }
+    }}"#;
+
+    const POLICIES: &str = r#"
+        permit(principal == User::"alice", action == Action::"view", resource in Album::"trip");
+        forbid(principal, action, resource) when { resource.private };
+    "#;
+
+    const ENTITIES: &str = r#"[
+        { "uid": { "type": "Photo", "id": "beach" }, "attrs": { "private": false }, "parents": [{ "type": "Album", "id": "trip" }] },
+        { "uid": { "type": "Photo", "id": "diary" }, "attrs": { "private": true }, "parents": [{ "type": "Album", "id": "trip" }] }
+    ]"#;
+
+    fn decision(result: InterfaceResult) -> String {
+        match result {
+            InterfaceResult::Success { result, .. } => {
+                let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+                answer["response"]["decision"].as_str().unwrap().to_string()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("authorization failed: {errors:?}"),
+        }
+    }
+
+    fn request(resource: &str) -> String {
+        serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Action", "id": "view" },
+            "resource": { "type": "Photo", "id": resource },
+            "context": {}
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn answers_repeated_requests() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+        assert_eq!(decision(engine.is_authorized(&request("diary"))), "Deny");
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+    }
+
+    #[test]
+    fn answers_batches() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        let requests = format!("[{}, {}, {{}}]", request("beach"), request("diary"));
+        let results = engine
+            .is_authorized_batch(&requests, BatchOptions::default())
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        let mut results = results.into_iter();
+        assert_eq!(decision(results.next().unwrap()), "Allow");
+        assert_eq!(decision(results.next().unwrap()), "Deny");
+        assert!(matches!(
+            results.next().unwrap(),
+            InterfaceResult::Failure { .. }
+        ));
+
+        let mut bytes = engine
+            .is_authorized_batch_binary(&requests, None)
+            .unwrap()
+            .into_iter();
+        let mut decisions = Vec::new();
+        while bytes.len() > 0 {
+            let len = u32::from_le_bytes(std::array::from_fn(|_| bytes.next().unwrap()));
+            let record: Vec<u8> = bytes.by_ref().take(len as usize).collect();
+            let result: InterfaceResult = serde_json::from_slice(&record).unwrap();
+            decisions
+                .push(matches!(result, InterfaceResult::Success { .. }).then(|| decision(result)));
+        }
+        assert_eq!(
+            decisions,
+            vec![Some("Allow".to_string()), Some("Deny".to_string()), None]
+        );
+        assert!(engine
+            .is_authorized_batch("{}", BatchOptions::default())
+            .is_err());
+    }
+
+    #[test]
+    fn caches_decisions_until_policies_or_entities_change() {
+        let mut engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+        assert_eq!(engine.decision_cache_size(), 0);
+
+        engine.enable_decision_cache(8);
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+        assert_eq!(decision(engine.is_authorized(&request("diary"))), "Deny");
+        assert_eq!(engine.decision_cache_size(), 2);
+
+        let mut traced: serde_json::Value = serde_json::from_str(&request("beach")).unwrap();
+        traced["trace"] = serde_json::Value::Bool(true);
+        engine.is_authorized(&traced.to_string());
+        assert_eq!(engine.decision_cache_size(), 2);
+
+        engine.set_policies(&WasmPolicySet::parse("forbid(principal, action, resource);").unwrap());
+        assert_eq!(engine.decision_cache_size(), 0);
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Deny");
+
+        engine.set_entities(&WasmEntities::parse("[]", None, Default::default()).unwrap());
+        assert_eq!(engine.decision_cache_size(), 0);
+    }
+
+    #[test]
+    fn fails_on_disabled_extensions() {
+        let policies = r#"permit(principal, action, resource) when { context.src.isLoopback() };"#;
+        let mut engine = CedarEngine::from_handles(
+            &WasmPolicySet::parse(policies).unwrap(),
+            &WasmEntities::parse(ENTITIES, None, Default::default()).unwrap(),
+        );
+        let request = |context: serde_json::Value| {
+            let mut request: serde_json::Value = serde_json::from_str(&request("beach")).unwrap();
+            request["context"] = context;
+            request.to_string()
+        };
+        let loopback =
+            serde_json::json!({ "src": { "__extn": { "fn": "ip", "arg": "127.0.0.1" } } });
+        assert_eq!(
+            decision(engine.is_authorized(&request(loopback.clone()))),
+            "Allow"
+        );
+
+        engine
+            .set_disabled_extensions(vec!["ipaddr".to_string()])
+            .unwrap();
+        assert_eq!(engine.disabled_extensions(), vec!["ipaddr".to_string()]);
+        match engine.is_authorized(&request(loopback.clone())) {
+            InterfaceResult::Failure { errors, code, .. } => {
+                assert_eq!(code, Some(FailureCode::DisabledExtension));
+                assert_eq!(
+                    errors,
+                    vec![
+                        "policy `policy0` uses `isLoopback` of the `ipaddr` extension, which is disabled",
+                        "the request uses `ip` of the `ipaddr` extension, which is disabled",
+                    ]
+                );
+            }
+            InterfaceResult::Success { .. } => panic!("expected the disabled extension to fail"),
+        }
+
+        engine.set_policies(&WasmPolicySet::parse(POLICIES).unwrap());
+        assert_eq!(
+            decision(engine.is_authorized(&request(serde_json::json!({})))),
+            "Allow"
+        );
+        assert!(matches!(
+            engine.is_authorized(&request(loopback.clone())),
+            InterfaceResult::Failure {
+                code: Some(FailureCode::DisabledExtension),
+                ..
+            }
+        ));
+
+        engine.set_disabled_extensions(Vec::new()).unwrap();
+        assert_eq!(decision(engine.is_authorized(&request(loopback))), "Allow");
+    }
+
+    #[test]
+    fn reports_decision_of_error_mode() {
+        let mut engine = CedarEngine::from_handles(
+            &WasmPolicySet::parse(
+                r#"permit(principal, action, resource) when { resource.missing };"#,
+            )
+            .unwrap(),
+            &WasmEntities::parse(ENTITIES, None, Default::default()).unwrap(),
+        );
+        let answer = |engine: &CedarEngine| match engine.is_authorized(&request("beach")) {
+            InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<serde_json::Value>(&result).unwrap()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("authorization failed: {errors:?}"),
+        };
+        let unset = answer(&engine);
+        assert_eq!(unset["response"]["decision"], "Deny");
+        assert_eq!(unset.get("error_mode"), None);
+        assert_eq!(engine.error_mode(), ErrorMode::FailClosed);
+
+        engine.set_error_mode(ErrorMode::FailOpen);
+        let open = answer(&engine);
+        assert_eq!(open["response"]["decision"], "Allow");
+        assert_eq!(open["error_mode"], "failOpen");
+        assert_eq!(open["error_mode_applied"], true);
+
+        engine.set_error_mode(ErrorMode::FailClosed);
+        let closed = answer(&engine);
+        assert_eq!(closed["response"]["decision"], "Deny");
+        assert_eq!(closed["error_mode"], "failClosed");
+        assert_eq!(closed.get("error_mode_applied"), None);
+
+        engine.set_policies(&WasmPolicySet::parse(POLICIES).unwrap());
+        engine.set_error_mode(ErrorMode::FailOpen);
+        assert_eq!(decision(engine.is_authorized(&request("diary"))), "Deny");
+    }
+
+    #[test]
+    fn fails_open_only_on_failing_permits() {
+        let mut engine = CedarEngine::from_handles(
+            &WasmPolicySet::parse(
+                r#"forbid(principal, action, resource) when { resource.missing };"#,
+            )
+            .unwrap(),
+            &WasmEntities::parse(ENTITIES, None, Default::default()).unwrap(),
+        );
+        engine.set_error_mode(ErrorMode::FailOpen);
+        let answer = match engine.is_authorized(&request("beach")) {
+            InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<serde_json::Value>(&result).unwrap()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("authorization failed: {errors:?}"),
+        };
+        assert_eq!(answer["response"]["decision"], "Deny");
+        assert_eq!(answer["error_mode"], "failOpen");
+        assert_eq!(answer.get("error_mode_applied"), None);
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn applies_error_mode_to_every_query() {
+        let policies = WasmPolicySet::parse(
+            r#"permit(principal, action, resource) when { resource.missing };"#,
+        )
+        .unwrap();
+        let mut engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        engine.set_policies(&policies);
+        let alice = [EntityUidInput::Text(r#"User::"alice""#.to_string())];
+        let view = [EntityUidInput::Text(r#"Action::"view""#.to_string())];
+        let beach = [EntityUidInput::Text(r#"Photo::"beach""#.to_string())];
+        let queries = |engine: &CedarEngine| {
+            let (actions, _) = engine
+                .allowed_actions(
+                    r#"{ "principal": { "type": "User", "id": "alice" }, "resource": { "type": "Photo", "id": "beach" } }"#,
+                )
+                .unwrap();
+            let matrix = engine.permission_matrix(&alice, &view, &beach).unwrap();
+            let resources = engine
+                .accessible_resources(
+                    r#"{ "principal": { "type": "User", "id": "alice" }, "action": { "type": "Action", "id": "view" } }"#,
+                    0,
+                    10,
+                )
+                .unwrap();
+            let principals = engine
+                .authorized_principals(
+                    r#"{ "resource": { "type": "Photo", "id": "beach" }, "action": { "type": "Action", "id": "view" }, "principalType": "User" }"#,
+                    0,
+                    10,
+                )
+                .unwrap();
+            (actions.len(), matrix, resources.total, principals.total)
+        };
+        assert_eq!(queries(&engine), (0, vec![MATRIX_DENY], 0, 0));
+
+        engine.set_error_mode(ErrorMode::FailOpen);
+        assert_eq!(queries(&engine), (1, vec![MATRIX_ALLOW], 2, 0));
+    }
+
+    #[test]
+    fn skips_entities_that_do_not_parse_when_lenient() {
+        let entities = r#"[
+            { "uid": { "type": "Photo", "id": "beach" }, "attrs": { "private": false }, "parents": [{ "type": "Album", "id": "trip" }] },
+            { "uid": { "type": "Photo", "id": "dune" }, "attrs": { "private": "no" }, "parents": [{ "type": "Album", "id": "trip" }] }
+        ]"#;
+        assert!(CedarEngine::build(Some(SCHEMA), POLICIES, entities).is_err());
+        let policies = r#"permit(principal == User::"alice", action == Action::"view", resource);"#;
+        let mut engine = CedarEngine::build_lenient(Some(SCHEMA), policies, entities).unwrap();
+        let skipped = engine.skipped_entities().0;
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].uid.as_deref(), Some(r#"Photo::"dune""#));
+        assert_eq!(skipped[0].index, 1);
+
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+        let answer = match engine.is_authorized(&request("dune")) {
+            InterfaceResult::Success { result, .. } => {
+                serde_json::from_str::<serde_json::Value>(&result).unwrap()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("authorization failed: {errors:?}"),
+        };
+        assert_eq!(answer["response"]["decision"], "Deny");
+        assert_eq!(answer["error_mode"], "failClosed");
+        assert_eq!(answer["error_mode_applied"], true);
+        let dune = [EntityUidInput::Text(r#"Photo::"dune""#.to_string())];
+        let alice = [EntityUidInput::Text(r#"User::"alice""#.to_string())];
+        let view = [EntityUidInput::Text(r#"Action::"view""#.to_string())];
+        assert_eq!(
+            engine.permission_matrix(&alice, &view, &dune),
+            Ok(vec![MATRIX_DENY])
+        );
+
+        let restored = CedarEngine::restore(&engine.snapshot().unwrap()).unwrap();
+        assert_eq!(restored.skipped_entities(), engine.skipped_entities());
+        assert_eq!(decision(restored.is_authorized(&request("dune"))), "Deny");
+
+        engine.set_error_mode(ErrorMode::FailOpen);
+        assert_eq!(decision(engine.is_authorized(&request("dune"))), "Allow");
+        assert_eq!(
+            engine.permission_matrix(&alice, &view, &dune),
+            Ok(vec![MATRIX_ALLOW])
+        );
+    }
+
+    #[test]
+    fn stops_batches_early() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        let requests = format!(
+            "[{}, {}, {}]",
+            request("beach"),
+            request("diary"),
+            request("beach")
+        );
+        let decisions = |options: BatchOptions| -> Vec<String> {
+            engine
+                .is_authorized_batch(&requests, options)
+                .unwrap()
+                .into_iter()
+                .map(decision)
+                .collect()
+        };
+        assert_eq!(
+            decisions(BatchOptions {
+                stop_on_first_deny: true,
+                ..Default::default()
+            }),
+            vec!["Allow", "Deny"]
+        );
+        assert_eq!(
+            decisions(BatchOptions {
+                stop_on_first_allow: true,
+                ..Default::default()
+            }),
+            vec!["Allow"]
+        );
+        assert_eq!(
+            decisions(BatchOptions::default()),
+            vec!["Allow", "Deny", "Allow"]
+        );
+    }
+
+    #[test]
+    fn builds_from_handles() {
+        let engine = CedarEngine::from_handles(
+            &WasmPolicySet::parse(POLICIES).unwrap(),
+            &WasmEntities::parse(ENTITIES, None, Default::default()).unwrap(),
+        );
+        assert_eq!(decision(engine.is_authorized(&request("beach"))), "Allow");
+    }
+
+    fn cbor(json: &serde_json::Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(json, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn accepts_cbor_entities_and_context() {
+        let entities: serde_json::Value = serde_json::from_str(ENTITIES).unwrap();
+        let engine = CedarEngine::from_handles(
+            &WasmPolicySet::parse(
+                r#"permit(principal, action, resource in Album::"trip") when { context.trusted };"#,
+            )
+            .unwrap(),
+            &WasmEntities::parse_cbor(&cbor(&entities), None, Default::default()).unwrap(),
+        );
+        let context = |trusted: bool| cbor(&serde_json::json!({ "trusted": trusted }));
+        assert_eq!(
+            decision(engine.is_authorized_with_cbor_context(&request("beach"), &context(true))),
+            "Allow"
+        );
+        assert_eq!(
+            decision(engine.is_authorized_with_cbor_context(&request("beach"), &context(false))),
+            "Deny"
+        );
+        assert!(matches!(
+            engine.is_authorized_with_cbor_context(&request("beach"), &[0xff]),
+            InterfaceResult::Failure { .. }
+        ));
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn lists_allowed_actions() {
+        let schema = r#"{ "": {
+            "entityTypes": { "User": {}, "Photo": {}, "Album": {} },
+            "actions": {
+                "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                "edit": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                "delete": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                "comment": { "appliesTo": {
+                    "principalTypes": ["User"],
+                    "resourceTypes": ["Photo"],
+                    "context": { "type": "Record", "attributes": { "text": { "type": "String" } } }
+                } },
+                "share": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Album"] } }
+            }
+        }}"#;
+        let policies = r#"
+            permit(principal, action in [Action::"view", Action::"edit", Action::"share", Action::"comment"], resource);
+            forbid(principal, action == Action::"edit", resource) unless { principal == User::"alice" };
+        "#;
+        let engine = CedarEngine::build(Some(schema), policies, "[]").unwrap();
+        let request = |principal: &str, context: serde_json::Value| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": principal },
+                "resource": { "type": "Photo", "id": "beach" },
+                "context": context
+            })
+            .to_string()
+        };
+
+        // the empty context does not match `comment`'s, so it is not allowed
+        let (actions, request_errors) = engine
+            .allowed_actions(&request("alice", serde_json::json!({})))
+            .unwrap();
+        assert_eq!(actions, vec![r#"Action::"edit""#, r#"Action::"view""#]);
+        assert_eq!(request_errors.len(), 1);
+        assert!(request_errors[0].starts_with(r#"Action::"comment": "#));
+
+        let (actions, request_errors) = engine
+            .allowed_actions(&request("bob", serde_json::json!({ "text": "nice" })))
+            .unwrap();
+        assert_eq!(actions, vec![r#"Action::"comment""#]);
+        assert_eq!(request_errors.len(), 3);
+
+        let without_schema = CedarEngine::build(None, policies, "[]").unwrap();
+        assert!(without_schema
+            .allowed_actions(&request("alice", serde_json::json!({})))
+            .is_err());
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn lists_accessible_resources() {
+        let photo = |id: usize, private: bool| {
+            serde_json::json!({
+                "uid": { "type": "Photo", "id": format!("p{id}") },
+                "attrs": { "private": private },
+                "parents": [{ "type": "Album", "id": "trip" }]
+            })
+        };
+        let entities = serde_json::Value::Array(
+            (1..=5)
+                .map(|id| photo(id, id == 3))
+                .chain([serde_json::json!({
+                    "uid": { "type": "Photo", "id": "other" },
+                    "attrs": { "private": false },
+                    "parents": []
+                })])
+                .collect(),
+        )
+        .to_string();
+        let request = serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Action", "id": "view" },
+            "context": {}
+        })
+        .to_string();
+        let page = |uids: &[&str], total: usize| EntityPage {
+            uids: uids.iter().map(|id| format!(r#"Photo::"{id}""#)).collect(),
+            total,
+        };
+
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, &entities).unwrap();
+        assert_eq!(
+            engine.accessible_resources(&request, 0, 10).unwrap(),
+            page(&["p1", "p2", "p4", "p5"], 4)
+        );
+        assert_eq!(
+            engine.accessible_resources(&request, 1, 2).unwrap(),
+            page(&["p2", "p4"], 4)
+        );
+
+        // without a schema, the resource type must be given
+        let engine = CedarEngine::build(None, POLICIES, &entities).unwrap();
+        assert!(engine.accessible_resources(&request, 0, 10).is_err());
+        let mut with_type: serde_json::Value = serde_json::from_str(&request).unwrap();
+        with_type["resourceType"] = "Photo".into();
+        assert_eq!(
+            engine
+                .accessible_resources(&with_type.to_string(), 3, 10)
+                .unwrap(),
+            page(&["p5"], 4)
+        );
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn lists_authorized_principals() {
+        let entities: Vec<serde_json::Value> = serde_json::from_str(ENTITIES).unwrap();
+        let entities = serde_json::Value::Array(
+            ["alice", "bob", "carol"]
+                .into_iter()
+                .map(|id| {
+                    serde_json::json!({
+                        "uid": { "type": "User", "id": id },
+                        "attrs": {},
+                        "parents": []
+                    })
+                })
+                .chain(entities)
+                .collect(),
+        )
+        .to_string();
+        let policies = format!(
+            r#"{POLICIES} permit(principal == User::"carol", action, resource == Photo::"beach");"#
+        );
+        let request = |resource: &str| {
+            serde_json::json!({
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": resource },
+                "context": {}
+            })
+        };
+        let page = |uids: &[&str], total: usize| EntityPage {
+            uids: uids.iter().map(|id| format!(r#"User::"{id}""#)).collect(),
+            total,
+        };
+
+        let engine = CedarEngine::build(Some(SCHEMA), &policies, &entities).unwrap();
+        let beach = request("beach").to_string();
+        assert_eq!(
+            engine.authorized_principals(&beach, 0, 10).unwrap(),
+            page(&["alice", "carol"], 2)
+        );
+        assert_eq!(
+            engine.authorized_principals(&beach, 1, 10).unwrap(),
+            page(&["carol"], 2)
+        );
+        assert_eq!(
+            engine
+                .authorized_principals(&request("diary").to_string(), 0, 10)
+                .unwrap(),
+            page(&[], 0)
+        );
+
+        // without a schema, the principal type must be given
+        let engine = CedarEngine::build(None, &policies, &entities).unwrap();
+        assert!(engine.authorized_principals(&beach, 0, 10).is_err());
+        let mut with_type = request("beach");
+        with_type["principalType"] = "User".into();
+        assert_eq!(
+            engine
+                .authorized_principals(&with_type.to_string(), 0, 1)
+                .unwrap(),
+            page(&["alice"], 2)
+        );
+    }
+
+    #[test]
+    fn reports_missing_entities_for_loading() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        let call = strict_call(&request("sunset")).unwrap();
+
+        let (answer, missing) = engine.authorize_against(&call, &engine.entities);
+        assert_eq!(decision(answer), "Deny");
+        assert_eq!(missing, vec![r#"Photo::"sunset""#.to_string()]);
+
+        let mut loaded = engine.entities.clone();
+        loaded
+            .upsert_all(
+                r#"[{ "uid": { "type": "Photo", "id": "sunset" }, "attrs": { "private": false }, "parents": [{ "type": "Album", "id": "trip" }] }]"#,
+            )
+            .unwrap();
+        let (answer, missing) = engine.authorize_against(&call, &loaded);
+        assert_eq!(decision(answer), "Allow");
+        assert!(missing.is_empty());
+        let sunset = EntityUid::from_str(r#"Photo::"sunset""#).unwrap();
+        assert!(engine.entities.get(&sunset).is_none());
+
+        assert!(strict_call("[]").is_err());
+    }
+
+    #[test]
+    fn builds_permission_matrix() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        let uids = |uids: &[&str]| -> Vec<EntityUidInput> {
+            uids.iter()
+                .map(|uid| EntityUidInput::Text(uid.to_string()))
+                .collect()
+        };
+        let bob = EntityUidInput::Parts {
+            entity_type: "User".to_string(),
+            id: "bob".to_string(),
+        };
+        let matrix = engine
+            .permission_matrix(
+                &[uids(&[r#"User::"alice""#]), vec![bob]].concat(),
+                &uids(&[r#"Action::"view""#]),
+                &uids(&[r#"Photo::"beach""#, r#"Photo::"diary""#, r#"Album::"trip""#]),
+            )
+            .unwrap();
+        assert_eq!(
+            matrix,
+            vec![
+                MATRIX_ALLOW,
+                MATRIX_DENY,
+                MATRIX_INVALID,
+                MATRIX_DENY,
+                MATRIX_DENY,
+                MATRIX_INVALID
+            ]
+        );
+
+        let errors = engine
+            .permission_matrix(&uids(&["alice"]), &uids(&[]), &uids(&["beach", "diary"]))
+            .unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn validates_policies_against_schema() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        match engine.validate("{}") {
+            InterfaceResult::Success { result, .. } => {
+                let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+                assert_eq!(answer["passed"], serde_json::json!(true));
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("validation failed: {errors:?}"),
+        }
+
+        let without_schema = CedarEngine::build(None, POLICIES, ENTITIES).unwrap();
+        assert!(matches!(
+            without_schema.validate("{}"),
+            InterfaceResult::Failure {
+                is_internal: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn restores_from_snapshot() {
+        let engine = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES).unwrap();
+        let restored = CedarEngine::restore(&engine.snapshot().unwrap()).unwrap();
+        assert_eq!(decision(restored.is_authorized(&request("beach"))), "Allow");
+        assert_eq!(decision(restored.is_authorized(&request("diary"))), "Deny");
+        assert_eq!(restored.policy_set.metadata(), engine.policy_set.metadata());
+        assert!(restored.schema.is_some());
+
+        assert!(CedarEngine::restore(b"not a snapshot").is_err());
+    }
+
+    #[test]
+    fn reports_stats() {
+        let stats = CedarEngine::build(Some(SCHEMA), POLICIES, ENTITIES)
+            .unwrap()
+            .stats();
+        assert_eq!(stats.policies, 2);
+        assert_eq!(stats.templates, 0);
+        // the two photos and the schema's one action
+        assert_eq!(stats.entities, 3);
+        assert!(stats.estimated_heap_bytes > 0);
+    }
+
+    #[test]
+    fn rejects_entities_not_matching_schema() {
+        let entities =
+            r#"[{ "uid": { "type": "Photo", "id": "beach" }, "attrs": {}, "parents": [] }]"#;
+        assert!(CedarEngine::build(Some(SCHEMA), POLICIES, entities).is_err());
+    }
+}
@@ -0,0 +1,1487 @@
+//! This module contains the `WasmEntities` handle, which holds a parsed
+//! entity store, with its transitive closure already computed, so that it can
+//! be reused across authorization calls and updated in place.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    str::FromStr,
+};
+
+use cedar_policy::{Entities, Entity, EntityId, EntityTypeName, EntityUid, Schema, TCComputation};
+use cedar_policy_core::transitive_closure::{find_cycle, TCNode};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{cbor_to_json, errors_to_js, WasmSchema};
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of checking entities against a schema
+pub enum ValidateEntitiesResult {
+    /// the entities conform to the schema
+    Success { entities: usize },
+    /// the entities do not parse or do not conform to the schema
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How the action entities of an entity store disagree with a schema
+pub enum ActionHierarchyDiscrepancyKind {
+    /// the schema declares the action but the entities do not have it
+    MissingFromEntities,
+    /// the entities have the action but the schema does not declare it
+    UndeclaredInSchema,
+    /// the action is a member of different action groups in each
+    GroupsDiffer,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An action on which an entity store disagrees with a schema
+pub struct ActionHierarchyDiscrepancy {
+    pub action: String,
+    pub kind: ActionHierarchyDiscrepancyKind,
+    pub message: String,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of checking the action entities of an
+/// entity store against a schema
+pub enum ActionHierarchyResult {
+    /// the action entities agree with the schema
+    Success {},
+    /// the action entities disagree with the schema on these actions, sorted
+    Invalid {
+        discrepancies: Vec<ActionHierarchyDiscrepancy>,
+    },
+    /// the entities do not parse
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `actionEntitiesFromSchema`
+pub enum ActionEntitiesResult {
+    /// the action entities, as a JSON array in the JSON format of entities
+    Success {
+        entities: String,
+    },
+    Error {
+        errors: Vec<String>,
+    },
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of checking an entity hierarchy for cycles
+pub enum EntityHierarchyResult {
+    /// the parents of the entities form no cycle
+    Success {},
+    /// the parents of the entities form a cycle, given as the uids along it,
+    /// starting and ending with the same uid
+    Invalid { cycle: Vec<String> },
+    /// the entities do not parse
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How the transitive closure of the entity hierarchy is obtained when
+/// loading entities
+pub enum TcComputationMode {
+    /// compute it while loading; `parents` may list direct parents only
+    #[default]
+    ComputeNow,
+    /// trust that `parents` already lists every ancestor, skipping the
+    /// computation entirely
+    AssumeAlreadyComputed,
+    /// check that `parents` already lists every ancestor, failing if not
+    EnforceAlreadyComputed,
+}
+
+impl From<TcComputationMode> for TCComputation {
+    fn from(mode: TcComputationMode) -> Self {
+        match mode {
+            TcComputationMode::ComputeNow => TCComputation::ComputeNow,
+            TcComputationMode::AssumeAlreadyComputed => TCComputation::AssumeAlreadyComputed,
+            TcComputationMode::EnforceAlreadyComputed => TCComputation::EnforceAlreadyComputed,
+        }
+    }
+}
+
+/// Version of the binary format written by `entitiesToBinary`
+const BINARY_VERSION: u32 = 1;
+
+/// Entities with their hierarchy flattened, encoded as CBOR by
+/// `entitiesToBinary`
+#[derive(Debug, Serialize, Deserialize)]
+struct EntitiesBinary {
+    version: u32,
+    /// entities JSON, where each entity's parents are all of its ancestors
+    entities: serde_json::Value,
+}
+
+/// A parsed entity store, held on the wasm side of the boundary. The
+/// hierarchy is computed once, when the store is constructed, and maintained
+/// as entities are added, replaced, or removed.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WasmEntities {
+    entities: Entities,
+    /// the entities with their parents as given, from which the hierarchy is
+    /// recomputed when the store changes
+    declared: HashMap<EntityUid, Entity>,
+    /// the uids in `declared`, by entity type, for listing them in order
+    by_type: HashMap<EntityTypeName, BTreeSet<EntityUid>>,
+    /// the schema the entities were parsed with, which later additions must
+    /// also conform to
+    schema: Option<Schema>,
+    tc_computation: TcComputationMode,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A page of the entities of one type
+pub struct EntityPage {
+    /// the uids of the entities in the page, as Cedar text, in order
+    pub uids: Vec<String>,
+    /// the number of entities of the type in the store
+    pub total: usize,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An entity uid, either as Cedar text, e.g. `User::"alice"`, or as its type
+/// and id, which need no escaping
+pub enum EntityUidInput {
+    Text(String),
+    Parts {
+        #[serde(rename = "type")]
+        entity_type: String,
+        id: String,
+    },
+}
+
+impl EntityUidInput {
+    /// The uid this stands for. Errors start with the text that did not parse.
+    pub fn to_uid(&self) -> Result<EntityUid, String> {
+        match self {
+            Self::Text(text) => EntityUid::from_str(text).map_err(|e| format!("`{text}`: {e}")),
+            Self::Parts { entity_type, id } => {
+                let entity_type = EntityTypeName::from_str(entity_type)
+                    .map_err(|e| format!("`{entity_type}`: {e}"))?;
+                Ok(EntityUid::from_type_name_and_id(
+                    entity_type,
+                    EntityId::new(id),
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A list of entity uids, each as Cedar text or as its type and id
+pub struct EntityUidInputs(pub Vec<EntityUidInput>);
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An entity that was skipped when loading entities leniently, because it
+/// does not parse on its own
+pub struct SkippedEntity {
+    /// the uid of the entity as Cedar text, if it parses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub uid: Option<String>,
+    /// the position of the entity in the entities given
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Tsify, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The entities skipped when loading entities leniently, in order
+pub struct SkippedEntities(pub Vec<SkippedEntity>);
+
+impl WasmEntities {
+    /// Parse entities from their JSON format, optionally informed by a JSON
+    /// schema as in `isAuthorized`
+    pub fn parse(
+        json: &str,
+        schema: Option<&str>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let schema = schema
+            .map(Schema::from_str)
+            .transpose()
+            .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        Self::load(json, schema, tc_computation)
+    }
+
+    /// Parse entities from their JSON format, informed by an already-parsed
+    /// schema
+    pub fn parse_with_schema(
+        json: &str,
+        schema: &Schema,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        Self::load(json, Some(schema.clone()), tc_computation)
+    }
+
+    /// Decode entities from their CBOR encoding, which encodes the same value
+    /// as their JSON format, optionally informed by a JSON schema
+    pub fn parse_cbor(
+        bytes: &[u8],
+        schema: Option<&str>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let schema = schema
+            .map(Schema::from_str)
+            .transpose()
+            .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        Self::load_value(cbor_to_json(bytes, "entities")?, schema, tc_computation)
+    }
+
+    /// Decode entities from their CBOR encoding, informed by an
+    /// already-parsed schema
+    pub fn parse_cbor_with_schema(
+        bytes: &[u8],
+        schema: &Schema,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        Self::load_value(
+            cbor_to_json(bytes, "entities")?,
+            Some(schema.clone()),
+            tc_computation,
+        )
+    }
+
+    /// Parse entities from their JSON format, optionally informed by an
+    /// already-parsed schema, skipping each entity that does not parse on its
+    /// own, e.g. because it does not conform to the schema, in place of
+    /// failing. Fails if the entities are not a JSON array, or if those kept
+    /// do not parse together, e.g. because two of them have the same uid.
+    pub fn parse_lenient(
+        json: &str,
+        schema: Option<&Schema>,
+    ) -> Result<(Self, Vec<SkippedEntity>), Vec<String>> {
+        let entities = match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(serde_json::Value::Array(entities)) => entities,
+            Ok(_) => return Err(vec!["entities must be a JSON array".to_string()]),
+            Err(e) => return Err(vec![format!("error parsing entities: {e}")]),
+        };
+        let mut kept = Vec::with_capacity(entities.len());
+        let mut skipped = Vec::new();
+        for (index, entity) in entities.into_iter().enumerate() {
+            match Entities::from_json_value(serde_json::Value::Array(vec![entity.clone()]), schema)
+            {
+                Ok(_) => kept.push(entity),
+                Err(e) => skipped.push(SkippedEntity {
+                    uid: entity
+                        .get("uid")
+                        .cloned()
+                        .and_then(|uid| EntityUid::from_json(uid).ok())
+                        .map(|uid| uid.to_string()),
+                    index,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        let entities = Self::load_value(
+            serde_json::Value::Array(kept),
+            schema.cloned(),
+            TcComputationMode::default(),
+        )?;
+        Ok((entities, skipped))
+    }
+
+    fn load(
+        json: &str,
+        schema: Option<Schema>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let declared = Entities::from_json_str_with_tc_computation(
+            json,
+            schema.as_ref(),
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+        Self::from_declared(by_uid(&declared), schema, tc_computation)
+    }
+
+    fn load_value(
+        json: serde_json::Value,
+        schema: Option<Schema>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let declared = Entities::from_json_value_with_tc_computation(
+            json,
+            schema.as_ref(),
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+        Self::from_declared(by_uid(&declared), schema, tc_computation)
+    }
+
+    /// Restore entities written by [`WasmEntities::to_json_string`]. They are
+    /// not checked against `schema` again, but later additions are.
+    pub fn restore(
+        json: &str,
+        schema: Option<Schema>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let declared = Entities::from_json_str_with_tc_computation(
+            json,
+            None,
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+        Self::from_declared(by_uid(&declared), schema, tc_computation)
+    }
+
+    fn from_declared(
+        declared: HashMap<EntityUid, Entity>,
+        schema: Option<Schema>,
+        tc_computation: TcComputationMode,
+    ) -> Result<Self, Vec<String>> {
+        let mut entities = Self {
+            entities: Entities::empty(),
+            by_type: by_type(&declared),
+            declared,
+            schema,
+            tc_computation,
+        };
+        entities.rebuild()?;
+        Ok(entities)
+    }
+
+    /// The entities in their JSON format, with their parents as given rather
+    /// than the computed hierarchy
+    pub fn to_json_string(&self) -> Result<String, String> {
+        let declared = Entities::from_entities_with_tc_computation(
+            self.declared.values().cloned(),
+            None,
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .map_err(|e| e.to_string())?;
+        let mut json = Vec::new();
+        declared
+            .write_to_json(&mut json)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(json).map_err(|e| e.to_string())
+    }
+
+    /// Encode the entities, with their hierarchy already flattened, in the
+    /// binary format read by [`WasmEntities::from_binary`]
+    pub fn to_binary(&self) -> Result<Vec<u8>, Vec<String>> {
+        let mut json = Vec::new();
+        self.entities
+            .write_to_json(&mut json)
+            .map_err(|e| vec![format!("error encoding entities: {e}")])?;
+        let binary = EntitiesBinary {
+            version: BINARY_VERSION,
+            entities: serde_json::from_slice(&json)
+                .map_err(|e| vec![format!("error encoding entities: {e}")])?,
+        };
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&binary, &mut bytes)
+            .map_err(|e| vec![format!("error encoding entities: {e}")])?;
+        Ok(bytes)
+    }
+
+    /// Decode entities written by [`WasmEntities::to_binary`]. Since their
+    /// hierarchy is already flattened, it is not computed again, and each
+    /// entity's parents are all of its ancestors: removing an entity later
+    /// does not drop the ancestors other entities inherited through it.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, Vec<String>> {
+        let binary: EntitiesBinary = ciborium::from_reader(bytes)
+            .map_err(|e| vec![format!("error decoding entities: {e}")])?;
+        if binary.version != BINARY_VERSION {
+            return Err(vec![format!(
+                "unsupported entities binary version {}, expected {BINARY_VERSION}",
+                binary.version
+            )]);
+        }
+        let entities = Entities::from_json_value_with_tc_computation(
+            binary.entities,
+            None,
+            TCComputation::AssumeAlreadyComputed,
+        )
+        .map_err(|e| vec![format!("error decoding entities: {e}")])?;
+        let declared = by_uid(&entities);
+        Ok(Self {
+            by_type: by_type(&declared),
+            declared,
+            entities,
+            schema: None,
+            tc_computation: TcComputationMode::ComputeNow,
+        })
+    }
+
+    /// The schema the entities were parsed with, if any
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// How the hierarchy is obtained when the store changes
+    pub fn tc_computation(&self) -> TcComputationMode {
+        self.tc_computation
+    }
+
+    /// The entities held by this handle
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// The ancestors of `uid`, sorted, or `None` if there is no such entity
+    pub fn ancestors_of(&self, uid: &EntityUid) -> Option<Vec<String>> {
+        self.entities.ancestors(uid).map(|ancestors| {
+            let mut ancestors: Vec<_> = ancestors.map(ToString::to_string).collect();
+            ancestors.sort();
+            ancestors
+        })
+    }
+
+    /// The entities with their parents as given, in no particular order
+    pub fn declared(&self) -> impl Iterator<Item = &Entity> {
+        self.declared.values()
+    }
+
+    /// The entity `uid` with its parents as given, or `None` if there is no
+    /// such entity
+    pub fn get(&self, uid: &EntityUid) -> Option<&Entity> {
+        self.declared.get(uid)
+    }
+
+    /// The uids of the entities of type `entity_type`, in order
+    pub fn uids_of_type(&self, entity_type: &EntityTypeName) -> impl Iterator<Item = &EntityUid> {
+        self.by_type.get(entity_type).into_iter().flatten()
+    }
+
+    /// Up to `limit` of the entities of type `entity_type`, in order of uid,
+    /// skipping the first `offset`
+    pub fn list(&self, entity_type: &EntityTypeName, offset: usize, limit: usize) -> EntityPage {
+        match self.by_type.get(entity_type) {
+            Some(uids) => EntityPage {
+                uids: uids
+                    .iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(ToString::to_string)
+                    .collect(),
+                total: uids.len(),
+            },
+            None => EntityPage {
+                uids: vec![],
+                total: 0,
+            },
+        }
+    }
+
+    /// Add the entities in `json`, failing if any of them is already present
+    pub fn insert_all(&mut self, json: &str) -> Result<(), Vec<String>> {
+        let added = parse_batch(json, self.schema.as_ref())?;
+        let duplicates: Vec<_> = added
+            .iter()
+            .filter(|entity| self.declared.contains_key(&entity.uid()))
+            .map(|entity| format!("duplicate entity: {}", entity.uid()))
+            .collect();
+        if !duplicates.is_empty() {
+            return Err(duplicates);
+        }
+        self.apply(added.into_iter().map(|e| (e.uid(), Some(e))).collect())
+    }
+
+    /// Add the entities in `json`, replacing any already present with the same
+    /// uid
+    pub fn upsert_all(&mut self, json: &str) -> Result<(), Vec<String>> {
+        let upserted = parse_batch(json, self.schema.as_ref())?;
+        self.apply(upserted.into_iter().map(|e| (e.uid(), Some(e))).collect())
+    }
+
+    /// Remove the entity `uid`, returning whether it was present. Entities
+    /// that had it as a parent keep that parent, but no longer inherit its
+    /// ancestors.
+    pub fn remove(&mut self, uid: &EntityUid) -> Result<bool, Vec<String>> {
+        if !self.declared.contains_key(uid) {
+            return Ok(false);
+        }
+        self.apply(vec![(uid.clone(), None)])?;
+        Ok(true)
+    }
+
+    /// Apply a set of changes, where `None` removes the entity, and recompute
+    /// the hierarchy. If that fails, the changes are rolled back.
+    fn apply(&mut self, changes: Vec<(EntityUid, Option<Entity>)>) -> Result<(), Vec<String>> {
+        let previous: Vec<_> = changes
+            .into_iter()
+            .map(|(uid, entity)| {
+                let old = self.set_declared(&uid, entity);
+                (uid, old)
+            })
+            .collect();
+        let result = self.rebuild();
+        if result.is_err() {
+            for (uid, old) in previous.into_iter().rev() {
+                self.set_declared(&uid, old);
+            }
+        }
+        result
+    }
+
+    /// Set the declared entity `uid`, where `None` removes it, keeping the
+    /// index by type up to date. Returns the entity it replaced.
+    fn set_declared(&mut self, uid: &EntityUid, entity: Option<Entity>) -> Option<Entity> {
+        let entity_type = uid.type_name().clone();
+        match entity {
+            Some(entity) => {
+                self.by_type
+                    .entry(entity_type)
+                    .or_default()
+                    .insert(uid.clone());
+                self.declared.insert(uid.clone(), entity)
+            }
+            None => {
+                if let Some(uids) = self.by_type.get_mut(&entity_type) {
+                    uids.remove(uid);
+                    if uids.is_empty() {
+                        self.by_type.remove(&entity_type);
+                    }
+                }
+                self.declared.remove(uid)
+            }
+        }
+    }
+
+    fn rebuild(&mut self) -> Result<(), Vec<String>> {
+        self.entities = Entities::from_entities_with_tc_computation(
+            self.declared.values().cloned(),
+            None,
+            self.tc_computation.into(),
+        )
+        .map_err(|e| match declared_cycle(&self.declared) {
+            Some(cycle) => vec![format!(
+                "error computing entity hierarchy: {e}; the parents form the cycle {cycle}"
+            )],
+            None => vec![format!("error computing entity hierarchy: {e}")],
+        })?;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl WasmEntities {
+    /// Parse entities from their JSON format, throwing if they are invalid.
+    /// If a schema is given, it informs the parsing as in `isAuthorized`.
+    /// The transitive closure is computed unless `tcComputation` says otherwise.
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(
+        json: &str,
+        schema: Option<String>,
+        tc_computation: Option<TcComputationMode>,
+    ) -> Result<WasmEntities, JsError> {
+        Self::parse(json, schema.as_deref(), tc_computation.unwrap_or_default())
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Parse entities from their JSON format, informed by a schema handle,
+    /// throwing if they do not conform to it
+    #[wasm_bindgen(js_name = "fromJsonWithSchema")]
+    pub fn from_json_with_schema(
+        json: &str,
+        schema: &WasmSchema,
+        tc_computation: Option<TcComputationMode>,
+    ) -> Result<WasmEntities, JsError> {
+        Self::parse_with_schema(json, schema.schema(), tc_computation.unwrap_or_default())
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Decode entities from their CBOR encoding, which encodes the same value
+    /// as their JSON format, throwing if they are invalid. The schema and
+    /// `tcComputation` are used as in `fromJson`.
+    #[wasm_bindgen(js_name = "fromCbor")]
+    pub fn from_cbor(
+        bytes: &[u8],
+        schema: Option<String>,
+        tc_computation: Option<TcComputationMode>,
+    ) -> Result<WasmEntities, JsError> {
+        Self::parse_cbor(bytes, schema.as_deref(), tc_computation.unwrap_or_default())
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Decode entities from their CBOR encoding, informed by a schema handle,
+    /// throwing if they do not conform to it
+    #[wasm_bindgen(js_name = "fromCborWithSchema")]
+    pub fn from_cbor_with_schema(
+        bytes: &[u8],
+        schema: &WasmSchema,
+        tc_computation: Option<TcComputationMode>,
+    ) -> Result<WasmEntities, JsError> {
+        Self::parse_cbor_with_schema(bytes, schema.schema(), tc_computation.unwrap_or_default())
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Number of entities in the store
+    #[wasm_bindgen(js_name = "size")]
+    pub fn size(&self) -> usize {
+        self.entities.iter().count()
+    }
+
+    /// The ancestors of the entity, or `undefined` if there is no such entity
+    #[wasm_bindgen(js_name = "ancestors")]
+    pub fn ancestors(&self, uid: EntityUidInput) -> Result<Option<Vec<String>>, JsError> {
+        let uid = parse_uid(&uid)?;
+        Ok(self.ancestors_of(&uid))
+    }
+
+    /// Add the entities in their JSON format to the store, throwing if any of
+    /// them is invalid or already present. The store is unchanged on error.
+    #[wasm_bindgen(js_name = "addEntities")]
+    pub fn add_entities(&mut self, json: &str) -> Result<(), JsError> {
+        self.insert_all(json)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Add an entity in its JSON format to the store, replacing any entity
+    /// with the same uid. An array of entities is also accepted. The store is
+    /// unchanged on error.
+    #[wasm_bindgen(js_name = "upsertEntity")]
+    pub fn upsert_entity(&mut self, json: &str) -> Result<(), JsError> {
+        let json = match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(entity @ serde_json::Value::Object(_)) => {
+                serde_json::Value::Array(vec![entity]).to_string()
+            }
+            _ => json.to_string(),
+        };
+        self.upsert_all(&json)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// The entity in its JSON format with its parents as given, or
+    /// `undefined` if there is no such entity
+    #[wasm_bindgen(js_name = "getEntity")]
+    pub fn get_entity(&self, uid: EntityUidInput) -> Result<Option<String>, JsError> {
+        let uid = parse_uid(&uid)?;
+        self.get(&uid)
+            .map(entity_to_json)
+            .transpose()
+            .map_err(|e| errors_to_js(&[e]))
+    }
+
+    /// Up to `limit` of the entities of the type written as Cedar text, e.g.
+    /// `App::User`, in order of uid, skipping the first `offset`, along with
+    /// the number of entities of that type
+    #[wasm_bindgen(js_name = "listEntities")]
+    pub fn list_entities(
+        &self,
+        entity_type: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<EntityPage, JsError> {
+        let entity_type = EntityTypeName::from_str(entity_type)
+            .map_err(|e| errors_to_js(&[format!("error parsing entity type: {e}")]))?;
+        Ok(self.list(&entity_type, offset, limit))
+    }
+
+    /// Remove the entity, returning whether it was present
+    #[wasm_bindgen(js_name = "removeEntity")]
+    pub fn remove_entity(&mut self, uid: EntityUidInput) -> Result<bool, JsError> {
+        let uid = parse_uid(&uid)?;
+        self.remove(&uid).map_err(|errors| errors_to_js(&errors))
+    }
+}
+
+/// The uid `uid` stands for, or the error to throw if it does not parse
+fn parse_uid(uid: &EntityUidInput) -> Result<EntityUid, JsError> {
+    uid.to_uid()
+        .map_err(|e| errors_to_js(&[format!("error parsing entity uid {e}")]))
+}
+
+/// Check that entities in their JSON format conform to a schema handle. The
+/// entity count includes the action entities declared by the schema.
+#[wasm_bindgen(js_name = "validateEntities")]
+pub fn validate_entities(schema: &WasmSchema, entities_str: &str) -> ValidateEntitiesResult {
+    match WasmEntities::parse_with_schema(
+        entities_str,
+        schema.schema(),
+        TcComputationMode::default(),
+    ) {
+        Ok(entities) => ValidateEntitiesResult::Success {
+            entities: entities.size(),
+        },
+        Err(errors) => ValidateEntitiesResult::Error { errors },
+    }
+}
+
+/// Check that the parents of entities in their JSON format form no cycle,
+/// reporting the uids along a cycle if they do. Unlike loading the entities,
+/// this does not compute the hierarchy, and parents that are not among the
+/// entities are ignored.
+#[wasm_bindgen(js_name = "checkEntityHierarchy")]
+pub fn check_entity_hierarchy(entities_str: &str) -> EntityHierarchyResult {
+    let entities = match parse_batch(entities_str, None) {
+        Ok(entities) => entities,
+        Err(errors) => return EntityHierarchyResult::Error { errors },
+    };
+    let nodes: HashMap<_, _> = entities
+        .into_iter()
+        .map(|entity| {
+            let (uid, _, parents) = entity.into_inner();
+            (uid.clone(), HierarchyNode { uid, parents })
+        })
+        .collect();
+    match find_cycle(&nodes) {
+        Some(cycle) => EntityHierarchyResult::Invalid {
+            cycle: cycle.iter().map(ToString::to_string).collect(),
+        },
+        None => EntityHierarchyResult::Success {},
+    }
+}
+
+/// Check that the action entities in an entity store, in their JSON format,
+/// agree with the actions a schema handle declares: that each action is in
+/// both, and that it is a member of the same action groups in both, directly
+/// or transitively. Entities that are not actions are ignored.
+#[wasm_bindgen(js_name = "checkActionHierarchy")]
+pub fn check_action_hierarchy(schema: &WasmSchema, entities_str: &str) -> ActionHierarchyResult {
+    let stored = match Entities::from_json_str(entities_str, None) {
+        Ok(entities) => entities,
+        Err(e) => {
+            return ActionHierarchyResult::Error {
+                errors: vec![format!("error parsing entities: {e}")],
+            }
+        }
+    };
+    let declared = match schema.schema().action_entities() {
+        Ok(entities) => entities,
+        Err(e) => {
+            return ActionHierarchyResult::Error {
+                errors: vec![format!("error reading schema actions: {e}")],
+            }
+        }
+    };
+    let groups = |entities: &Entities, action: &EntityUid| -> BTreeSet<String> {
+        entities
+            .ancestors(action)
+            .into_iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect()
+    };
+    let actions: BTreeSet<_> = declared
+        .iter()
+        .chain(stored.iter().filter(|entity| is_action(&entity.uid())))
+        .map(|entity| entity.uid())
+        .collect();
+    let discrepancies: Vec<_> = actions
+        .into_iter()
+        .filter_map(|action| {
+            let (kind, message) = match (declared.get(&action), stored.get(&action)) {
+                (Some(_), None) => (
+                    ActionHierarchyDiscrepancyKind::MissingFromEntities,
+                    format!("action `{action}` is declared in the schema but not in the entities"),
+                ),
+                (None, _) => (
+                    ActionHierarchyDiscrepancyKind::UndeclaredInSchema,
+                    format!("action `{action}` is in the entities but not declared in the schema"),
+                ),
+                (Some(_), Some(_)) => {
+                    let in_schema = groups(&declared, &action);
+                    let in_entities = groups(&stored, &action);
+                    if in_schema == in_entities {
+                        return None;
+                    }
+                    (
+                        ActionHierarchyDiscrepancyKind::GroupsDiffer,
+                        format!(
+                            "action `{action}` is a member of {} in the schema but of {} in the entities",
+                            describe_groups(&in_schema),
+                            describe_groups(&in_entities)
+                        ),
+                    )
+                }
+            };
+            Some(ActionHierarchyDiscrepancy {
+                action: action.to_string(),
+                kind,
+                message,
+            })
+        })
+        .collect();
+    if discrepancies.is_empty() {
+        ActionHierarchyResult::Success {}
+    } else {
+        ActionHierarchyResult::Invalid { discrepancies }
+    }
+}
+
+/// The action entities a schema handle declares, in the JSON format of
+/// entities, so that an entity store need not list them by hand. The
+/// `parents` of each action are every action group it is in, directly or not,
+/// so the entities load in any `TcComputationMode`. Actions and their parents
+/// are sorted, so that the same schema always gives the same text.
+#[wasm_bindgen(js_name = "actionEntitiesFromSchema")]
+pub fn action_entities_from_schema(schema: &WasmSchema) -> ActionEntitiesResult {
+    match action_entities_json(schema.schema()) {
+        Ok(entities) => ActionEntitiesResult::Success { entities },
+        Err(e) => ActionEntitiesResult::Error {
+            errors: vec![format!("error encoding action entities: {e}")],
+        },
+    }
+}
+
+fn action_entities_json(schema: &Schema) -> Result<String, String> {
+    let mut json = Vec::new();
+    schema
+        .action_entities()
+        .map_err(|e| e.to_string())?
+        .write_to_json(&mut json)
+        .map_err(|e| e.to_string())?;
+    let mut actions: Vec<serde_json::Value> =
+        serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    // uids, in the JSON format, sort by type and then by id
+    let key = |uid: Option<&serde_json::Value>| {
+        let part = |name| uid.and_then(|uid| uid.get(name)).map(ToString::to_string);
+        (part("type"), part("id"))
+    };
+    for action in &mut actions {
+        if let Some(serde_json::Value::Array(parents)) = action.get_mut("parents") {
+            parents.sort_by_key(|parent| key(Some(parent)));
+        }
+    }
+    actions.sort_by_key(|action| key(action.get("uid")));
+    serde_json::to_string(&actions).map_err(|e| e.to_string())
+}
+
+/// Whether `uid` is an action, i.e. its type is `Action` in some namespace
+fn is_action(uid: &EntityUid) -> bool {
+    uid.type_name().basename() == "Action"
+}
+
+fn describe_groups(groups: &BTreeSet<String>) -> String {
+    if groups.is_empty() {
+        "no action groups".to_string()
+    } else {
+        groups
+            .iter()
+            .map(|group| format!("`{group}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The uids along a cycle of the parents of `declared`, as `a` -> `b` -> `a`,
+/// if they form one. This is only looked for once computing the hierarchy
+/// has failed, since the error names just one entity on the cycle.
+fn declared_cycle(declared: &HashMap<EntityUid, Entity>) -> Option<String> {
+    let nodes: HashMap<_, _> = declared
+        .iter()
+        .map(|(uid, entity)| {
+            let (_, _, parents) = entity.clone().into_inner();
+            (
+                uid.clone(),
+                HierarchyNode {
+                    uid: uid.clone(),
+                    parents,
+                },
+            )
+        })
+        .collect();
+    find_cycle(&nodes).map(|cycle| {
+        cycle
+            .iter()
+            .map(|uid| format!("`{uid}`"))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    })
+}
+
+/// An entity as a node of the hierarchy, with edges to its parents
+struct HierarchyNode {
+    uid: EntityUid,
+    parents: HashSet<EntityUid>,
+}
+
+impl TCNode<EntityUid> for HierarchyNode {
+    fn get_key(&self) -> EntityUid {
+        self.uid.clone()
+    }
+
+    fn add_edge_to(&mut self, k: EntityUid) {
+        self.parents.insert(k);
+    }
+
+    fn out_edges(&self) -> Box<dyn Iterator<Item = &EntityUid> + '_> {
+        Box::new(self.parents.iter())
+    }
+
+    fn has_edge_to(&self, k: &EntityUid) -> bool {
+        self.parents.contains(k)
+    }
+}
+
+/// Parse a batch of entities, keeping their parents as given. Action entities
+/// from the schema, which a store parsed with it already holds, are left out.
+fn parse_batch(json: &str, schema: Option<&Schema>) -> Result<Vec<Entity>, Vec<String>> {
+    let parsed = Entities::from_json_str_with_tc_computation(
+        json,
+        schema,
+        TCComputation::AssumeAlreadyComputed,
+    )
+    .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+    let actions = match schema {
+        Some(schema) => schema
+            .action_entities()
+            .map_err(|e| vec![format!("error parsing entities: {e}")])?,
+        None => Entities::empty(),
+    };
+    Ok(parsed
+        .iter()
+        .filter(|entity| actions.get(&entity.uid()).is_none())
+        .cloned()
+        .collect())
+}
+
+/// `entity` in its JSON format
+fn entity_to_json(entity: &Entity) -> Result<String, String> {
+    let entities = Entities::from_entities_with_tc_computation(
+        [entity.clone()],
+        None,
+        TCComputation::AssumeAlreadyComputed,
+    )
+    .map_err(|e| e.to_string())?;
+    let mut json = Vec::new();
+    entities
+        .write_to_json(&mut json)
+        .map_err(|e| e.to_string())?;
+    match serde_json::from_slice::<serde_json::Value>(&json).map_err(|e| e.to_string())? {
+        serde_json::Value::Array(mut entities) if entities.len() == 1 => {
+            Ok(entities.remove(0).to_string())
+        }
+        _ => Err("error encoding entity".to_string()),
+    }
+}
+
+fn by_type(declared: &HashMap<EntityUid, Entity>) -> HashMap<EntityTypeName, BTreeSet<EntityUid>> {
+    let mut by_type: HashMap<_, BTreeSet<_>> = HashMap::new();
+    for uid in declared.keys() {
+        by_type
+            .entry(uid.type_name().clone())
+            .or_default()
+            .insert(uid.clone());
+    }
+    by_type
+}
+
+fn by_uid(entities: &Entities) -> HashMap<EntityUid, Entity> {
+    entities
+        .iter()
+        .map(|entity| (entity.uid(), entity.clone()))
+        .collect()
+}
+
+/// A load of entities in progress, fed one chunk at a time. Each chunk is
+/// parsed and checked against the schema as it arrives, and the hierarchy is
+/// computed only when the load is finished.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct EntitiesLoad {
+    declared: HashMap<EntityUid, Entity>,
+    schema: Option<Schema>,
+}
+
+impl EntitiesLoad {
+    /// Start a load, optionally informed by a schema as in `isAuthorized`
+    pub fn begin(schema: Option<Schema>) -> Self {
+        Self {
+            declared: HashMap::new(),
+            schema,
+        }
+    }
+
+    /// Add the entities in `chunk`, a JSON array of entities, failing if any
+    /// of them was already fed. The load is unchanged on error.
+    pub fn feed(&mut self, chunk: &str) -> Result<(), Vec<String>> {
+        let entities = parse_batch(chunk, self.schema.as_ref())?;
+        let duplicates: Vec<_> = entities
+            .iter()
+            .filter(|entity| self.declared.contains_key(&entity.uid()))
+            .map(|entity| format!("duplicate entity: {}", entity.uid()))
+            .collect();
+        if !duplicates.is_empty() {
+            return Err(duplicates);
+        }
+        self.declared
+            .extend(entities.into_iter().map(|entity| (entity.uid(), entity)));
+        Ok(())
+    }
+
+    /// Number of entities fed so far
+    pub fn len(&self) -> usize {
+        self.declared.len()
+    }
+
+    /// Whether no entities have been fed yet
+    pub fn is_empty(&self) -> bool {
+        self.declared.is_empty()
+    }
+
+    /// Finish the load, adding the schema's action entities and computing the
+    /// hierarchy
+    pub fn finish(self, tc_computation: TcComputationMode) -> Result<WasmEntities, Vec<String>> {
+        let mut declared = self.declared;
+        if let Some(schema) = &self.schema {
+            let actions = schema
+                .action_entities()
+                .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+            for action in actions.iter() {
+                declared
+                    .entry(action.uid())
+                    .or_insert_with(|| action.clone());
+            }
+        }
+        WasmEntities::from_declared(declared, self.schema, tc_computation)
+    }
+}
+
+/// Start loading entities in chunks. If a schema is given, each chunk is
+/// parsed according to it as in `isAuthorized`.
+#[wasm_bindgen(js_name = "beginEntitiesLoad")]
+pub fn begin_entities_load(schema: Option<String>) -> Result<EntitiesLoad, JsError> {
+    let schema = schema
+        .map(|schema| {
+            Schema::from_str(&schema)
+                .map_err(|e| errors_to_js(&[format!("error parsing schema: {e}")]))
+        })
+        .transpose()?;
+    Ok(EntitiesLoad::begin(schema))
+}
+
+/// Start loading entities in chunks, parsing each chunk according to a schema
+/// handle
+#[wasm_bindgen(js_name = "beginEntitiesLoadWithSchema")]
+pub fn begin_entities_load_with_schema(schema: &WasmSchema) -> EntitiesLoad {
+    EntitiesLoad::begin(Some(schema.schema().clone()))
+}
+
+/// Feed a chunk, a JSON array of entities, to a load, returning the number of
+/// entities fed so far. Throws if the chunk is invalid or repeats an entity,
+/// leaving the load unchanged.
+#[wasm_bindgen(js_name = "feedEntitiesChunk")]
+pub fn feed_entities_chunk(load: &mut EntitiesLoad, chunk: &str) -> Result<usize, JsError> {
+    load.feed(chunk).map_err(|errors| errors_to_js(&errors))?;
+    Ok(load.len())
+}
+
+/// Finish a load, computing the hierarchy unless `tcComputation` says
+/// otherwise. The load is consumed and cannot be fed again.
+#[wasm_bindgen(js_name = "finishEntitiesLoad")]
+pub fn finish_entities_load(
+    load: EntitiesLoad,
+    tc_computation: Option<TcComputationMode>,
+) -> Result<WasmEntities, JsError> {
+    load.finish(tc_computation.unwrap_or_default())
+        .map_err(|errors| errors_to_js(&errors))
+}
+
+/// Encode entities in a compact binary format with their hierarchy already
+/// flattened, so that `entitiesFromBinary` does not need to compute it
+#[wasm_bindgen(js_name = "entitiesToBinary")]
+pub fn entities_to_binary(entities: &WasmEntities) -> Result<Vec<u8>, JsError> {
+    entities.to_binary().map_err(|errors| errors_to_js(&errors))
+}
+
+/// Decode entities produced by `entitiesToBinary`, throwing if the bytes are
+/// invalid or were written by an incompatible version
+#[wasm_bindgen(js_name = "entitiesFromBinary")]
+pub fn entities_from_binary(bytes: &[u8]) -> Result<WasmEntities, JsError> {
+    WasmEntities::from_binary(bytes).map_err(|errors| errors_to_js(&errors))
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ENTITIES: &str = r#"[
+        { "uid": { "type": "Photo", "id": "door" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "album" }] },
+        { "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "shared" }] }
+    ]"#;
+
+    #[test]
+    fn computes_hierarchy_once() {
+        let entities = WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        assert_eq!(entities.size(), 2);
+        let door = EntityUid::from_str(r#"Photo::"door""#).unwrap();
+        assert_eq!(
+            entities.ancestors_of(&door),
+            Some(vec![
+                r#"Folder::"album""#.to_string(),
+                r#"Folder::"shared""#.to_string()
+            ])
+        );
+        let missing = EntityUid::from_str(r#"Photo::"missing""#).unwrap();
+        assert_eq!(entities.ancestors_of(&missing), None);
+    }
+
+    #[test]
+    fn honors_tc_computation_mode() {
+        let assumed =
+            WasmEntities::parse(ENTITIES, None, TcComputationMode::AssumeAlreadyComputed).unwrap();
+        let door = EntityUid::from_str(r#"Photo::"door""#).unwrap();
+        assert_eq!(
+            assumed.ancestors_of(&door),
+            Some(vec![r#"Folder::"album""#.to_string()])
+        );
+        assert!(
+            WasmEntities::parse(ENTITIES, None, TcComputationMode::EnforceAlreadyComputed).is_err()
+        );
+    }
+
+    #[test]
+    fn maintains_hierarchy_across_updates() {
+        let mut entities =
+            WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        let door = EntityUid::from_str(r#"Photo::"door""#).unwrap();
+        let album = EntityUid::from_str(r#"Folder::"album""#).unwrap();
+
+        entities
+            .insert_all(r#"[{ "uid": { "type": "Folder", "id": "shared" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "root" }] }]"#)
+            .unwrap();
+        assert_eq!(entities.size(), 3);
+        assert_eq!(
+            entities.ancestors_of(&door),
+            Some(vec![
+                r#"Folder::"album""#.to_string(),
+                r#"Folder::"root""#.to_string(),
+                r#"Folder::"shared""#.to_string()
+            ])
+        );
+
+        let errors = entities
+            .insert_all(
+                r#"[{ "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [] }]"#,
+            )
+            .unwrap_err();
+        assert_eq!(errors, vec![r#"duplicate entity: Folder::"album""#]);
+
+        entities
+            .upsert_all(
+                r#"[{ "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [] }]"#,
+            )
+            .unwrap();
+        assert_eq!(
+            entities.ancestors_of(&door),
+            Some(vec![r#"Folder::"album""#.to_string()])
+        );
+
+        assert!(entities.remove(&album).unwrap());
+        assert!(!entities.remove(&album).unwrap());
+        assert_eq!(entities.size(), 2);
+        assert_eq!(
+            entities.ancestors_of(&door),
+            Some(vec![r#"Folder::"album""#.to_string()])
+        );
+    }
+
+    #[test]
+    fn rolls_back_failed_updates() {
+        let mut entities =
+            WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        let cycle = r#"[{ "uid": { "type": "Folder", "id": "shared" }, "attrs": {}, "parents": [{ "type": "Photo", "id": "door" }] }]"#;
+        let errors = entities.insert_all(cycle).unwrap_err();
+        // the cycle may start at any of its entities
+        assert!(
+            [
+                r#"`Photo::"door"` -> `Folder::"album"` -> `Folder::"shared"` -> `Photo::"door"`"#,
+                r#"`Folder::"album"` -> `Folder::"shared"` -> `Photo::"door"` -> `Folder::"album"`"#,
+                r#"`Folder::"shared"` -> `Photo::"door"` -> `Folder::"album"` -> `Folder::"shared"`"#,
+            ]
+            .iter()
+            .any(|cycle| errors[0].ends_with(&format!("the parents form the cycle {cycle}"))),
+            "{errors:?}"
+        );
+        assert_eq!(entities.size(), 2);
+        let shared = EntityUid::from_str(r#"Folder::"shared""#).unwrap();
+        assert_eq!(entities.ancestors_of(&shared), None);
+    }
+
+    #[test]
+    fn parses_uids_as_text_or_parts() {
+        let parse = |json: serde_json::Value| {
+            serde_json::from_value::<EntityUidInput>(json)
+                .unwrap()
+                .to_uid()
+        };
+        let quoted = parse(serde_json::json!({ "type": "App::User", "id": "say \"hi\"" })).unwrap();
+        assert_eq!(
+            quoted,
+            EntityUid::from_str(r#"App::User::"say \"hi\"""#).unwrap()
+        );
+        assert_eq!(
+            parse(serde_json::json!(r#"App::User::"alice""#)).unwrap(),
+            EntityUid::from_str(r#"App::User::"alice""#).unwrap()
+        );
+        assert!(parse(serde_json::json!({ "type": "not a type", "id": "alice" })).is_err());
+        assert!(parse(serde_json::json!("alice")).is_err());
+    }
+
+    #[test]
+    fn indexes_entities_by_type() {
+        let mut entities =
+            WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        let folder = EntityTypeName::from_str("Folder").unwrap();
+        entities
+            .insert_all(
+                r#"[
+                    { "uid": { "type": "Folder", "id": "shared" }, "attrs": {}, "parents": [] },
+                    { "uid": { "type": "Folder", "id": "root" }, "attrs": {}, "parents": [] }
+                ]"#,
+            )
+            .unwrap();
+        assert_eq!(
+            entities.list(&folder, 1, 5),
+            EntityPage {
+                uids: vec![
+                    r#"Folder::"root""#.to_string(),
+                    r#"Folder::"shared""#.to_string()
+                ],
+                total: 3,
+            }
+        );
+        assert_eq!(
+            entities.list(&EntityTypeName::from_str("User").unwrap(), 0, 5),
+            EntityPage {
+                uids: vec![],
+                total: 0,
+            }
+        );
+
+        let door = EntityUid::from_str(r#"Photo::"door""#).unwrap();
+        let entity: serde_json::Value =
+            serde_json::from_str(&entity_to_json(entities.get(&door).unwrap()).unwrap()).unwrap();
+        assert_eq!(
+            entity["parents"],
+            serde_json::json!([{ "type": "Folder", "id": "album" }])
+        );
+
+        // a failed update leaves the index unchanged
+        let cycle = r#"[{ "uid": { "type": "Folder", "id": "other" }, "attrs": {}, "parents": [{ "type": "Photo", "id": "door" }] },
+            { "uid": { "type": "Folder", "id": "shared" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "other" }] }]"#;
+        assert!(entities.upsert_all(cycle).is_err());
+        assert_eq!(entities.list(&folder, 0, 5).total, 3);
+
+        entities
+            .remove(&EntityUid::from_str(r#"Folder::"root""#).unwrap())
+            .unwrap();
+        assert_eq!(entities.list(&folder, 0, 5).total, 2);
+        let photo = EntityTypeName::from_str("Photo").unwrap();
+        entities.remove(&door).unwrap();
+        assert!(!entities.by_type.contains_key(&photo));
+        assert_eq!(entities.get(&door), None);
+    }
+
+    #[test]
+    fn round_trips_through_binary() {
+        let entities = WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        let decoded = WasmEntities::from_binary(&entities.to_binary().unwrap()).unwrap();
+        assert_eq!(decoded.entities(), entities.entities());
+        assert!(WasmEntities::from_binary(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn loads_entities_in_chunks() {
+        let mut load = EntitiesLoad::begin(None);
+        load.feed(r#"[{ "uid": { "type": "Photo", "id": "door" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "album" }] }]"#)
+            .unwrap();
+        load.feed(r#"[{ "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [{ "type": "Folder", "id": "shared" }] }]"#)
+            .unwrap();
+        assert!(load
+            .feed(r#"[{ "uid": { "type": "Folder", "id": "album" }, "attrs": {}, "parents": [] }]"#)
+            .is_err());
+        assert_eq!(load.len(), 2);
+
+        let loaded = load.finish(TcComputationMode::ComputeNow).unwrap();
+        let parsed = WasmEntities::parse(ENTITIES, None, TcComputationMode::ComputeNow).unwrap();
+        assert_eq!(loaded.entities(), parsed.entities());
+    }
+
+    #[test]
+    fn validates_entities_against_schema() {
+        let schema = WasmSchema::parse(
+            r#"{ "": {
+                "entityTypes": { "User": { "shape": { "type": "Record", "attributes": { "age": { "type": "Long" } } } } },
+                "actions": {}
+            }}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            validate_entities(
+                &schema,
+                r#"[{ "uid": { "type": "User", "id": "alice" }, "attrs": { "age": 7 }, "parents": [] }]"#
+            ),
+            ValidateEntitiesResult::Success { entities: 1 }
+        ));
+        assert!(matches!(
+            validate_entities(
+                &schema,
+                r#"[{ "uid": { "type": "User", "id": "alice" }, "attrs": { "age": "seven" }, "parents": [] }]"#
+            ),
+            ValidateEntitiesResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_entities() {
+        let errors = WasmEntities::parse(
+            r#"[{ "uid": "nope" }]"#,
+            None,
+            TcComputationMode::ComputeNow,
+        )
+        .unwrap_err();
+        assert!(errors
+            .iter()
+            .all(|e| e.starts_with("error parsing entities")));
+    }
+
+    #[test]
+    fn reports_hierarchy_cycles() {
+        let cyclic = r#"[
+            { "uid": { "type": "Group", "id": "a" }, "attrs": {}, "parents": [{ "type": "Group", "id": "b" }] },
+            { "uid": { "type": "Group", "id": "b" }, "attrs": {}, "parents": [{ "type": "Group", "id": "a" }] }
+        ]"#;
+        match check_entity_hierarchy(cyclic) {
+            EntityHierarchyResult::Invalid { cycle } => {
+                assert_eq!(cycle.len(), 3);
+                assert_eq!(cycle[0], cycle[2]);
+                assert_ne!(cycle[0], cycle[1]);
+            }
+            result => panic!("expected a cycle: {result:?}"),
+        }
+        let errors = WasmEntities::parse(cyclic, None, TcComputationMode::ComputeNow).unwrap_err();
+        assert!(
+            errors[0].contains(r#"`Group::"a"` -> `Group::"b"` -> `Group::"a"`"#)
+                || errors[0].contains(r#"`Group::"b"` -> `Group::"a"` -> `Group::"b"`"#),
+            "{errors:?}"
+        );
+
+        assert!(matches!(
+            check_entity_hierarchy(ENTITIES),
+            EntityHierarchyResult::Success {}
+        ));
+        assert!(matches!(
+            check_entity_hierarchy("{"),
+            EntityHierarchyResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn checks_action_hierarchy_against_schema() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            entity User;
+            action read;
+            action view in [read];
+            action list in [read];
+            action edit;
+            "#,
+        )
+        .unwrap();
+        let entities = r#"[
+            { "uid": { "type": "Action", "id": "read" }, "attrs": {}, "parents": [] },
+            { "uid": { "type": "Action", "id": "view" }, "attrs": {}, "parents": [{ "type": "Action", "id": "read" }] },
+            { "uid": { "type": "Action", "id": "list" }, "attrs": {}, "parents": [] },
+            { "uid": { "type": "Action", "id": "delete" }, "attrs": {}, "parents": [] },
+            { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }
+        ]"#;
+        match check_action_hierarchy(&schema, entities) {
+            ActionHierarchyResult::Invalid { discrepancies } => {
+                let kinds: Vec<_> = discrepancies
+                    .iter()
+                    .map(|discrepancy| (discrepancy.action.as_str(), discrepancy.kind))
+                    .collect();
+                assert_eq!(
+                    kinds,
+                    vec![
+                        (
+                            r#"Action::"delete""#,
+                            ActionHierarchyDiscrepancyKind::UndeclaredInSchema
+                        ),
+                        (
+                            r#"Action::"edit""#,
+                            ActionHierarchyDiscrepancyKind::MissingFromEntities
+                        ),
+                        (
+                            r#"Action::"list""#,
+                            ActionHierarchyDiscrepancyKind::GroupsDiffer
+                        ),
+                    ]
+                );
+                assert_eq!(
+                    discrepancies[2].message,
+                    r#"action `Action::"list"` is a member of `Action::"read"` in the schema but of no action groups in the entities"#
+                );
+            }
+            result => panic!("expected discrepancies: {result:?}"),
+        }
+
+        let agreeing = r#"[
+            { "uid": { "type": "Action", "id": "read" }, "attrs": {}, "parents": [] },
+            { "uid": { "type": "Action", "id": "view" }, "attrs": {}, "parents": [{ "type": "Action", "id": "read" }] },
+            { "uid": { "type": "Action", "id": "list" }, "attrs": {}, "parents": [{ "type": "Action", "id": "read" }] },
+            { "uid": { "type": "Action", "id": "edit" }, "attrs": {}, "parents": [] }
+        ]"#;
+        assert!(matches!(
+            check_action_hierarchy(&schema, agreeing),
+            ActionHierarchyResult::Success {}
+        ));
+        assert!(matches!(
+            check_action_hierarchy(&schema, "{"),
+            ActionHierarchyResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn generates_action_entities_from_schema() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            entity User;
+            action all;
+            action read in [all];
+            action view in [read];
+            "#,
+        )
+        .unwrap();
+        let entities = match action_entities_from_schema(&schema) {
+            ActionEntitiesResult::Success { entities } => entities,
+            ActionEntitiesResult::Error { errors } => panic!("expected entities: {errors:?}"),
+        };
+        let json: serde_json::Value = serde_json::from_str(&entities).unwrap();
+        let action = |id: &str| serde_json::json!({ "type": "Action", "id": id });
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "uid": action("all"), "attrs": {}, "parents": [] },
+                { "uid": action("read"), "attrs": {}, "parents": [action("all")] },
+                { "uid": action("view"), "attrs": {}, "parents": [action("all"), action("read")] },
+            ])
+        );
+        // the generated entities agree with the schema they came from
+        assert!(matches!(
+            check_action_hierarchy(&schema, &entities),
+            ActionHierarchyResult::Success {}
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+//! This module contains `evaluateExpression`, which evaluates a standalone
+//! Cedar expression, e.g. a policy condition under test.
+use cedar_policy::frontend::{evaluate::json_evaluate, utils::InterfaceResult};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Evaluate the Cedar expression `expr_str`. `bindings` is a JSON object with
+/// any of `principal`, `action`, and `resource` (as `{ type, id }` objects or
+/// Cedar text) and `context`; those left out are unspecified or, for the
+/// context, empty. `entities` is entities JSON, which may be empty.
+///
+/// On success, the result holds the `value` of the expression in the JSON
+/// format of Cedar values.
+#[wasm_bindgen(js_name = "evaluateExpression")]
+pub fn evaluate_expression(expr_str: &str, bindings: &str, entities: &str) -> InterfaceResult {
+    match evaluation_call(expr_str, bindings, entities) {
+        Ok(call) => json_evaluate(&call.to_string()),
+        Err(error) => InterfaceResult::fail_bad_request(vec![error]),
+    }
+}
+
+/// The `json_evaluate` call for the arguments of `evaluateExpression`
+fn evaluation_call(expr_str: &str, bindings: &str, entities: &str) -> Result<Value, String> {
+    let mut call = match serde_json::from_str(bindings) {
+        Ok(Value::Object(bindings)) => bindings,
+        Ok(_) => return Err("error parsing bindings: expected a JSON object".to_string()),
+        Err(e) => return Err(format!("error parsing bindings: {e}")),
+    };
+    let entities: Value = if entities.trim().is_empty() {
+        Value::Array(Vec::new())
+    } else {
+        serde_json::from_str(entities).map_err(|e| format!("error parsing entities: {e}"))?
+    };
+    call.insert(
+        "expression".to_string(),
+        Value::String(expr_str.to_string()),
+    );
+    call.insert("entities".to_string(), entities);
+    Ok(Value::Object(call))
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evaluates_with_bindings() {
+        let bindings =
+            r#"{ "principal": { "type": "User", "id": "alice" }, "context": { "n": 2 } }"#;
+        let entities = r#"[{ "uid": { "type": "User", "id": "alice" }, "attrs": { "n": 40 }, "parents": [] }]"#;
+        match evaluate_expression("principal.n + context.n", bindings, entities) {
+            InterfaceResult::Success { result, .. } => {
+                let answer: Value = serde_json::from_str(&result).unwrap();
+                assert_eq!(answer.get("value"), Some(&Value::from(42)));
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("evaluation failed: {errors:?}"),
+        }
+        assert!(matches!(
+            evaluate_expression("true", "[]", ""),
+            InterfaceResult::Failure { .. }
+        ));
+    }
+}
@@ -0,0 +1,391 @@
+//! This module contains `explainDecision`, which explains an authorization
+//! decision in terms of the policies, and the clauses of those policies, that
+//! led to it.
+use std::str::FromStr;
+
+use cedar_policy::{
+    ActionConstraint, AuthorizationError, Authorizer, Context, Decision, Effect, Entities,
+    EntityUid, Policy, PolicyId, PolicySet, PrincipalConstraint, Request, ResourceConstraint,
+};
+use cedar_policy_core::{ast::PolicyID, est};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The part of a policy a clause comes from
+pub enum ClauseKind {
+    Principal,
+    Action,
+    Resource,
+    When,
+    Unless,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A clause of a policy that did not hold for the request
+pub struct UnsatisfiedClause {
+    pub kind: ClauseKind,
+    /// the clause as Cedar text, e.g. `principal in Group::"admins"` or the
+    /// condition of a `when` clause
+    pub text: String,
+    /// the error evaluating the clause, if it errored rather than not holding
+    pub error: Option<String>,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A permit policy that did not apply to the request
+pub struct UnsatisfiedPermit {
+    pub policy_id: String,
+    /// the clauses of the policy that did not hold, in policy order
+    pub clauses: Vec<UnsatisfiedClause>,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A policy that errored, and so was ignored by the decision
+pub struct PolicyErrorExplanation {
+    pub policy_id: String,
+    pub message: String,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Why a request was allowed or denied. Policy ids are sorted.
+pub struct DecisionExplanation {
+    pub decision: Decision,
+    /// the permit policies that applied, for an Allow
+    pub determining_permits: Vec<String>,
+    /// the forbid policies that applied, which caused a Deny
+    pub determining_forbids: Vec<String>,
+    /// for a Deny, the permit policies that did not apply, and why
+    pub unsatisfied_permits: Vec<UnsatisfiedPermit>,
+    pub errors: Vec<PolicyErrorExplanation>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum ExplainDecisionResult {
+    Success { explanation: DecisionExplanation },
+    Error { errors: Vec<String> },
+}
+
+/// Explain the decision for `request`, which has the `principal`, `action`,
+/// `resource`, and `context` of an `isAuthorized` call, against policy text
+/// and entities JSON.
+///
+/// For a Deny, each permit policy that did not apply is listed with every
+/// clause of it that did not hold: its scope constraints, and its `when` and
+/// `unless` conditions, each evaluated on its own.
+#[wasm_bindgen(js_name = "explainDecision")]
+pub fn explain_decision(request: &str, policies: &str, entities: &str) -> ExplainDecisionResult {
+    match explain(request, policies, entities) {
+        Ok(explanation) => ExplainDecisionResult::Success { explanation },
+        Err(errors) => ExplainDecisionResult::Error { errors },
+    }
+}
+
+fn explain(
+    request: &str,
+    policies: &str,
+    entities: &str,
+) -> Result<DecisionExplanation, Vec<String>> {
+    let request = parse_request(request)?;
+    let policies = PolicySet::from_str(policies).map_err(|e| e.errors_as_strings())?;
+    let entities = Entities::from_json_str(entities, None)
+        .map_err(|e| vec![format!("error parsing entities: {e}")])?;
+
+    let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+    let mut determining_permits = Vec::new();
+    let mut determining_forbids = Vec::new();
+    for id in response.diagnostics().reason() {
+        match policies.policy(id).map(Policy::effect) {
+            Some(Effect::Permit) => determining_permits.push(id.to_string()),
+            Some(Effect::Forbid) => determining_forbids.push(id.to_string()),
+            None => {}
+        }
+    }
+    determining_permits.sort();
+    determining_forbids.sort();
+    let mut errors: Vec<PolicyErrorExplanation> = response
+        .diagnostics()
+        .errors()
+        .map(|e| PolicyErrorExplanation {
+            policy_id: e.id().to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    errors.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+
+    let mut unsatisfied_permits = Vec::new();
+    if response.decision() == Decision::Deny {
+        for policy in policies.policies() {
+            let id = policy.id().to_string();
+            if policy.effect() == Effect::Permit && !errors.iter().any(|e| e.policy_id == id) {
+                unsatisfied_permits.push(UnsatisfiedPermit {
+                    policy_id: id,
+                    clauses: unsatisfied_clauses(policy, &request, &entities)?,
+                });
+            }
+        }
+        unsatisfied_permits.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+    }
+
+    Ok(DecisionExplanation {
+        decision: response.decision(),
+        determining_permits,
+        determining_forbids,
+        unsatisfied_permits,
+        errors,
+    })
+}
+
+fn parse_request(request: &str) -> Result<Request, Vec<String>> {
+    let request: Value =
+        serde_json::from_str(request).map_err(|e| vec![format!("error parsing request: {e}")])?;
+    let uid = |field: &str| -> Result<EntityUid, Vec<String>> {
+        let json = request
+            .get(field)
+            .cloned()
+            .ok_or_else(|| vec![format!("request is missing `{field}`")])?;
+        EntityUid::from_json(json).map_err(|e| vec![format!("error parsing {field}: {e}")])
+    };
+    let (principal, action, resource) = (uid("principal")?, uid("action")?, uid("resource")?);
+    let context = match request.get("context") {
+        Some(context) => Context::from_json_value(context.clone(), None)
+            .map_err(|e| vec![format!("error parsing context: {e}")])?,
+        None => Context::empty(),
+    };
+    Request::new(Some(principal), Some(action), Some(resource), context, None)
+        .map_err(|e| vec![e.to_string()])
+}
+
+/// The clauses of `policy` that do not hold for `request`, each evaluated as
+/// a policy of its own
+fn unsatisfied_clauses(
+    policy: &Policy,
+    request: &Request,
+    entities: &Entities,
+) -> Result<Vec<UnsatisfiedClause>, Vec<String>> {
+    let json = policy
+        .to_json()
+        .map_err(|e| vec![format!("error converting policy `{}`: {e}", policy.id())])?;
+    let mut clauses = Vec::new();
+    let scope = [
+        (
+            "principal",
+            ClauseKind::Principal,
+            principal_text(policy.principal_constraint()),
+        ),
+        (
+            "action",
+            ClauseKind::Action,
+            action_text(policy.action_constraint()),
+        ),
+        (
+            "resource",
+            ClauseKind::Resource,
+            resource_text(policy.resource_constraint()),
+        ),
+    ];
+    for (field, kind, text) in scope {
+        let Some(text) = text else { continue };
+        let mut clause_policy = unconstrained_permit();
+        if let (Some(clause_policy), Some(constraint)) =
+            (clause_policy.as_object_mut(), json.get(field))
+        {
+            clause_policy.insert(field.to_string(), constraint.clone());
+        }
+        if let Some(clause) = check_clause(clause_policy, kind, text, request, entities)? {
+            clauses.push(clause);
+        }
+    }
+    let conditions = json
+        .get("conditions")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for condition in conditions {
+        let kind = match condition.get("kind").and_then(Value::as_str) {
+            Some("unless") => ClauseKind::Unless,
+            _ => ClauseKind::When,
+        };
+        let text = condition
+            .get("body")
+            .cloned()
+            .and_then(|body| serde_json::from_value::<est::Expr>(body).ok())
+            .and_then(|body| body.try_into_ast(PolicyID::from_string("clause")).ok())
+            .map(|body| body.to_string())
+            .unwrap_or_default();
+        let mut clause_policy = unconstrained_permit();
+        if let Some(clause_policy) = clause_policy.as_object_mut() {
+            clause_policy.insert("conditions".to_string(), json!([condition]));
+        }
+        if let Some(clause) = check_clause(clause_policy, kind, text, request, entities)? {
+            clauses.push(clause);
+        }
+    }
+    Ok(clauses)
+}
+
+/// The EST of `permit(principal, action, resource);`
+fn unconstrained_permit() -> Value {
+    json!({
+        "effect": "permit",
+        "principal": { "op": "All" },
+        "action": { "op": "All" },
+        "resource": { "op": "All" },
+        "conditions": []
+    })
+}
+
+/// Evaluate the single-clause policy `clause_policy`, returning the clause if
+/// it does not hold
+fn check_clause(
+    clause_policy: Value,
+    kind: ClauseKind,
+    text: String,
+    request: &Request,
+    entities: &Entities,
+) -> Result<Option<UnsatisfiedClause>, Vec<String>> {
+    let policy = Policy::from_json(Some(PolicyId::new("clause")), clause_policy)
+        .map_err(|e| vec![format!("error checking clause `{text}`: {e}")])?;
+    let policies = PolicySet::from_policies([policy])
+        .map_err(|e| vec![format!("error checking clause `{text}`: {e}")])?;
+    let response = Authorizer::new().is_authorized(request, &policies, entities);
+    let error = response.diagnostics().errors().next().map(|e| match e {
+        AuthorizationError::PolicyEvaluationError { error, .. } => error.to_string(),
+    });
+    Ok((response.decision() == Decision::Deny).then_some(UnsatisfiedClause { kind, text, error }))
+}
+
+fn principal_text(constraint: PrincipalConstraint) -> Option<String> {
+    match constraint {
+        PrincipalConstraint::Any => None,
+        PrincipalConstraint::Eq(uid) => Some(format!("principal == {uid}")),
+        PrincipalConstraint::In(uid) => Some(format!("principal in {uid}")),
+        PrincipalConstraint::Is(ty) => Some(format!("principal is {ty}")),
+        PrincipalConstraint::IsIn(ty, uid) => Some(format!("principal is {ty} in {uid}")),
+    }
+}
+
+fn action_text(constraint: ActionConstraint) -> Option<String> {
+    match constraint {
+        ActionConstraint::Any => None,
+        ActionConstraint::Eq(uid) => Some(format!("action == {uid}")),
+        ActionConstraint::In(uids) => Some(format!(
+            "action in [{}]",
+            uids.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn resource_text(constraint: ResourceConstraint) -> Option<String> {
+    match constraint {
+        ResourceConstraint::Any => None,
+        ResourceConstraint::Eq(uid) => Some(format!("resource == {uid}")),
+        ResourceConstraint::In(uid) => Some(format!("resource in {uid}")),
+        ResourceConstraint::Is(ty) => Some(format!("resource is {ty}")),
+        ResourceConstraint::IsIn(ty, uid) => Some(format!("resource is {ty} in {uid}")),
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POLICIES: &str = r#"
+        permit(principal in Group::"editors", action == Action::"edit", resource)
+        when { resource.owner == principal }
+        unless { resource.locked };
+        permit(principal, action == Action::"view", resource);
+        forbid(principal, action, resource) when { resource.archived };
+    "#;
+
+    const ENTITIES: &str = r#"[
+        { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "editors" }] },
+        { "uid": { "type": "User", "id": "bob" }, "attrs": {}, "parents": [] },
+        { "uid": { "type": "Doc", "id": "plan" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } }, "locked": true, "archived": false }, "parents": [] },
+        { "uid": { "type": "Doc", "id": "old" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } }, "locked": false, "archived": true }, "parents": [] }
+    ]"#;
+
+    fn explanation(principal: &str, action: &str, resource: &str) -> DecisionExplanation {
+        let request = json!({
+            "principal": { "type": "User", "id": principal },
+            "action": { "type": "Action", "id": action },
+            "resource": { "type": "Doc", "id": resource },
+            "context": {}
+        });
+        match explain_decision(&request.to_string(), POLICIES, ENTITIES) {
+            ExplainDecisionResult::Success { explanation } => explanation,
+            ExplainDecisionResult::Error { errors } => panic!("explanation failed: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn explains_unsatisfied_permits() {
+        let explanation = explanation("bob", "edit", "plan");
+        assert_eq!(explanation.decision, Decision::Deny);
+        assert!(explanation.determining_forbids.is_empty());
+        assert_eq!(
+            explanation.unsatisfied_permits,
+            vec![
+                UnsatisfiedPermit {
+                    policy_id: "policy0".to_string(),
+                    clauses: vec![
+                        UnsatisfiedClause {
+                            kind: ClauseKind::Principal,
+                            text: r#"principal in Group::"editors""#.to_string(),
+                            error: None,
+                        },
+                        UnsatisfiedClause {
+                            kind: ClauseKind::When,
+                            text: "(resource[\"owner\"]) == principal".to_string(),
+                            error: None,
+                        },
+                        UnsatisfiedClause {
+                            kind: ClauseKind::Unless,
+                            text: "resource[\"locked\"]".to_string(),
+                            error: None,
+                        },
+                    ],
+                },
+                UnsatisfiedPermit {
+                    policy_id: "policy1".to_string(),
+                    clauses: vec![UnsatisfiedClause {
+                        kind: ClauseKind::Action,
+                        text: r#"action == Action::"view""#.to_string(),
+                        error: None,
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_forbids_and_allows() {
+        let denied = explanation("alice", "view", "old");
+        assert_eq!(denied.decision, Decision::Deny);
+        assert_eq!(denied.determining_forbids, vec!["policy2"]);
+
+        let allowed = explanation("alice", "view", "plan");
+        assert_eq!(allowed.decision, Decision::Allow);
+        assert_eq!(allowed.determining_permits, vec!["policy1"]);
+        assert!(allowed.unsatisfied_permits.is_empty());
+    }
+}
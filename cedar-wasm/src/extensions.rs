@@ -0,0 +1,211 @@
+//! This module contains the configuration of the Cedar extensions, e.g.
+//! `ipaddr` and `decimal`, that an engine allows policies and requests to use.
+use std::collections::{BTreeMap, BTreeSet};
+
+use cedar_policy::PolicySet;
+use cedar_policy_core::{
+    ast::Extension,
+    extensions::{decimal, ipaddr},
+};
+use serde_json::Value;
+
+/// The extensions that may be disabled
+fn known_extensions() -> [Extension; 2] {
+    [ipaddr::extension(), decimal::extension()]
+}
+
+/// The extensions disabled for an engine, with the functions they provide
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtensionConfig {
+    /// the extension providing each function of a disabled extension
+    disabled_functions: BTreeMap<String, String>,
+}
+
+impl ExtensionConfig {
+    /// Disable the extensions named in `names`, failing for any name that is
+    /// not one of the known extensions
+    pub(crate) fn disabling(names: &[String]) -> Result<Self, Vec<String>> {
+        let extensions = known_extensions();
+        let unknown = names
+            .iter()
+            .filter(|name| {
+                !extensions
+                    .iter()
+                    .any(|ext| ext.name().to_string() == **name)
+            })
+            .map(|name| {
+                format!(
+                    "unknown extension `{name}`; the extensions are {}",
+                    extensions
+                        .iter()
+                        .map(|ext| format!("`{}`", ext.name()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(unknown);
+        }
+        let disabled_functions = extensions
+            .iter()
+            .filter(|ext| names.contains(&ext.name().to_string()))
+            .flat_map(|ext| {
+                ext.funcs()
+                    .map(move |func| (func.name().to_string(), ext.name().to_string()))
+            })
+            .collect();
+        Ok(Self { disabled_functions })
+    }
+
+    /// Whether any extension is disabled
+    pub(crate) fn is_restricted(&self) -> bool {
+        !self.disabled_functions.is_empty()
+    }
+
+    /// The names of the disabled extensions, sorted
+    pub(crate) fn disabled(&self) -> Vec<String> {
+        self.disabled_functions
+            .values()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// An error for each policy and template of `policies` that calls a
+    /// function of a disabled extension
+    pub(crate) fn policy_errors(&self, policies: &PolicySet) -> Vec<String> {
+        if !self.is_restricted() {
+            return Vec::new();
+        }
+        let policies = policies
+            .policies()
+            .filter(|policy| policy.is_static())
+            .map(|policy| (policy.id().to_string(), policy.to_json().ok()))
+            .chain(
+                policies
+                    .templates()
+                    .map(|template| (template.id().to_string(), template.to_json().ok())),
+            );
+        let mut errors = Vec::new();
+        for (id, json) in policies {
+            let mut calls = BTreeSet::new();
+            if let Some(json) = json {
+                collect_calls(&json, Walk::Expr, &mut calls);
+            }
+            errors.extend(self.errors_for(&calls, &format!("policy `{id}`")));
+        }
+        errors
+    }
+
+    /// An error for each function of a disabled extension that constructs an
+    /// extension value in `request`, e.g. in its context
+    pub(crate) fn request_errors(&self, request: &Value) -> Vec<String> {
+        if !self.is_restricted() {
+            return Vec::new();
+        }
+        let mut calls = BTreeSet::new();
+        collect_calls(request, Walk::Data, &mut calls);
+        self.errors_for(&calls, "the request")
+    }
+
+    fn errors_for(&self, calls: &BTreeSet<String>, user: &str) -> Vec<String> {
+        calls
+            .iter()
+            .filter_map(|func| {
+                self.disabled_functions.get(func).map(|ext| {
+                    format!("{user} uses `{func}` of the `{ext}` extension, which is disabled")
+                })
+            })
+            .collect()
+    }
+}
+
+/// What a JSON value being searched for extension functions holds
+#[derive(Debug, Clone, Copy)]
+enum Walk {
+    /// an expression in the JSON format of policies, where a function call is
+    /// an object keyed by the function name
+    Expr,
+    /// a value in the JSON format of Cedar values, where a function call is
+    /// an `__extn` escape
+    Data,
+}
+
+/// Add to `calls` the names of the extension functions called in `json`
+fn collect_calls(json: &Value, walk: Walk, calls: &mut BTreeSet<String>) {
+    match json {
+        Value::Object(map) => {
+            if let Some(func) = map
+                .get("__extn")
+                .and_then(|extn| extn.get("fn"))
+                .and_then(Value::as_str)
+            {
+                calls.insert(func.to_string());
+            }
+            for (key, value) in map {
+                match walk {
+                    // the keys of a record literal are attribute names
+                    Walk::Expr if key == "Record" => {
+                        if let Value::Object(attrs) = value {
+                            for value in attrs.values() {
+                                collect_calls(value, walk, calls);
+                            }
+                        }
+                    }
+                    Walk::Expr if key == "Value" => collect_calls(value, Walk::Data, calls),
+                    Walk::Expr => {
+                        calls.insert(key.clone());
+                        collect_calls(value, walk, calls);
+                    }
+                    Walk::Data => collect_calls(value, walk, calls),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_calls(value, walk, calls);
+            }
+        }
+        _ => {}
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn finds_calls_of_disabled_extensions() {
+        let config = ExtensionConfig::disabling(&["ipaddr".to_string()]).unwrap();
+        assert_eq!(config.disabled(), vec!["ipaddr".to_string()]);
+        let policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.src.isInRange(ip("10.0.0.0/8")) };
+            permit(principal, action, resource) when { context.amount.lessThan(decimal("1.5")) };
+            permit(principal, action, resource) when { { ip: 1 }.ip == 1 };"#,
+        )
+        .unwrap();
+        let errors = config.policy_errors(&policies);
+        assert_eq!(
+            errors,
+            vec![
+                "policy `policy0` uses `ip` of the `ipaddr` extension, which is disabled"
+                    .to_string(),
+                "policy `policy0` uses `isInRange` of the `ipaddr` extension, which is disabled"
+                    .to_string(),
+            ]
+        );
+        let request = serde_json::json!({
+            "context": { "src": { "__extn": { "fn": "ip", "arg": "10.1.2.3" } }, "ip": "x" }
+        });
+        assert_eq!(config.request_errors(&request).len(), 1);
+        assert!(ExtensionConfig::default()
+            .policy_errors(&policies)
+            .is_empty());
+        assert!(ExtensionConfig::disabling(&["datetime".to_string()]).is_err());
+    }
+}
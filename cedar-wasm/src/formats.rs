@@ -0,0 +1,68 @@
+//! This module contains the detection of whether a schema or policy set given
+//! as text is in its JSON format or in Cedar syntax.
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The format of a schema or policy set given as text
+pub enum InputFormat {
+    /// the JSON format
+    Json,
+    /// the Cedar schema syntax, or Cedar policy text
+    Cedar,
+}
+
+/// The format of `text`, a `what` such as `schema`: `format` if it is given,
+/// and otherwise the format `text` looks like. Text starting with `{` or `[`
+/// is JSON; text that is empty or starts with a letter, `@`, or a `//`
+/// comment is Cedar syntax. Anything else is ambiguous, and fails.
+pub(crate) fn detect_format(
+    text: &str,
+    what: &str,
+    format: Option<InputFormat>,
+) -> Result<InputFormat, String> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+    let start = text.trim_start();
+    match start.chars().next() {
+        Some('{' | '[') => Ok(InputFormat::Json),
+        None | Some('@') => Ok(InputFormat::Cedar),
+        Some(c) if c.is_alphabetic() || start.starts_with("//") => Ok(InputFormat::Cedar),
+        Some(c) => Err(format!(
+            "cannot tell whether the {what} is JSON or Cedar syntax, as it starts with `{c}`; give its format as `json` or `cedar`"
+        )),
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_formats() {
+        let detect = |text: &str| detect_format(text, "schema", None);
+        assert_eq!(detect(r#" { "": {} }"#), Ok(InputFormat::Json));
+        assert_eq!(detect("[]"), Ok(InputFormat::Json));
+        assert_eq!(detect("entity User;"), Ok(InputFormat::Cedar));
+        assert_eq!(detect("// users\nentity User;"), Ok(InputFormat::Cedar));
+        assert_eq!(
+            detect("@id(\"p\") permit(principal, action, resource);"),
+            Ok(InputFormat::Cedar)
+        );
+        assert_eq!(detect("  "), Ok(InputFormat::Cedar));
+        assert_eq!(
+            detect("\"entity User;\""),
+            Err("cannot tell whether the schema is JSON or Cedar syntax, as it starts with `\"`; give its format as `json` or `cedar`".to_string())
+        );
+        assert_eq!(
+            detect_format("\"x\"", "schema", Some(InputFormat::Json)),
+            Ok(InputFormat::Json)
+        );
+    }
+}
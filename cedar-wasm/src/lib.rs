@@ -1,18 +1,250 @@
 #![forbid(unsafe_code)]
 
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
+mod annotations;
 mod authorizer;
+#[cfg(feature = "full")]
+mod context;
+mod decision_cache;
+mod engine;
+mod entities;
+mod evaluate;
+mod explain;
+mod extensions;
+mod formats;
+mod lint;
+mod memory;
+#[cfg(feature = "full")]
+mod migration;
+mod msgpack;
 mod policies_and_templates;
+mod policy_set;
+#[cfg(feature = "full")]
+mod rename;
+mod replay;
+#[cfg(feature = "full")]
+mod request;
+#[cfg(feature = "full")]
+mod samples;
+mod schema;
+#[cfg(feature = "full")]
+mod schema_checks;
+#[cfg(feature = "full")]
+mod schema_merge;
+mod shards;
+mod tenants;
+#[cfg(feature = "full")]
+mod typescript;
+#[cfg(feature = "full")]
+mod usage;
+#[cfg(feature = "full")]
 mod validator;
+mod warm_up;
 
-pub use authorizer::wasm_is_authorized;
+pub use annotations::{
+    validate_annotations, AnnotationSchema, AnnotationValidationResult, AnnotationViolation,
+    AnnotationViolationKind,
+};
+pub use authorizer::{
+    wasm_is_authorized, wasm_is_authorized_actions, wasm_is_authorized_with,
+    wasm_is_authorized_with_cbor_context, wasm_is_authorized_with_policy_set,
+    wasm_is_authorized_with_schema, wasm_is_authorized_with_shards,
+};
+pub use cedar_policy::frontend::is_authorized::ErrorMode;
+pub use cedar_policy::frontend::utils::RESPONSE_VERSION;
+#[cfg(feature = "full")]
+pub use context::{
+    build_context, context_json_schema, validate_context, BuildContextResult,
+    ContextAttributeError, ContextErrorKind, ContextJsonSchemaResult, ContextKeyError,
+    ValidateContextResult,
+};
+#[cfg(feature = "full")]
+pub use engine::WhatCanIDoResult;
+pub use engine::{BatchAuthorizationResults, BatchOptions, CedarEngine, EngineStats};
+pub use entities::{
+    action_entities_from_schema, begin_entities_load, begin_entities_load_with_schema,
+    check_action_hierarchy, check_entity_hierarchy, entities_from_binary, entities_to_binary,
+    feed_entities_chunk, finish_entities_load, validate_entities, ActionEntitiesResult,
+    ActionHierarchyDiscrepancy, ActionHierarchyDiscrepancyKind, ActionHierarchyResult,
+    EntitiesLoad, EntityHierarchyResult, EntityPage, EntityUidInput, EntityUidInputs,
+    SkippedEntities, SkippedEntity, TcComputationMode, ValidateEntitiesResult, WasmEntities,
+};
+pub use evaluate::evaluate_expression;
+pub use explain::{
+    explain_decision, ClauseKind, DecisionExplanation, ExplainDecisionResult,
+    PolicyErrorExplanation, UnsatisfiedClause, UnsatisfiedPermit,
+};
+pub use formats::InputFormat;
+pub use lint::{lint_policies, LintResult, LintRule, LintRules, LintViolation, PolicySpan};
+pub use memory::{get_memory_stats, MemoryStats};
+#[cfg(feature = "full")]
+pub use migration::{
+    check_schema_migration, EntityBreakage, PolicyBreakage, SchemaMigrationResult,
+};
+pub use msgpack::is_authorized_msgpack;
+#[cfg(feature = "full")]
+pub use msgpack::validate_msgpack;
 pub use policies_and_templates::{
-    check_parse_policy_set, policy_text_from_json, policy_text_to_json,
+    check_parse_policy_set, policies_text_from_json, policies_text_to_json, policy_text_from_json,
+    policy_text_to_json, JsonToPoliciesResult, PoliciesToJsonResult,
+};
+#[cfg(feature = "full")]
+pub use policy_set::{check_template_links, TemplateLinkCheck, TemplateLinks, TemplateLinksCheck};
+pub use policy_set::{
+    PolicyMetadata, PolicySetJson, PolicySetMetadata, PolicySetToJsonResult, TemplateLinkJson,
+    WasmPolicySet,
+};
+#[cfg(feature = "full")]
+pub use rename::{rename_namespace, NamespaceArtifacts, RenameNamespaceResult};
+pub use replay::{
+    compare_decisions, replay_requests, DecisionComparison, DecisionDifference, ReplayResults,
+};
+#[cfg(feature = "full")]
+pub use request::{validate_request, RequestConstraint, ValidateRequestResult};
+#[cfg(feature = "full")]
+pub use samples::{
+    generate_sample_entities, generate_sample_requests, SampleCounts, SampleEntitiesResult,
+    SampleRequest, SampleRequests,
 };
-pub use validator::wasm_validate;
+pub use schema::WasmSchema;
+#[cfg(feature = "full")]
+pub use schema::{
+    describe_schema, get_entity_type_shape, schema_cedar_to_json, schema_json_to_cedar,
+    ActionAppliesTo, ActionDescription, AttributeShape, EntityTypeDescription,
+    EntityTypeShapeResult, SchemaConversionResult, SchemaDescription,
+};
+#[cfg(feature = "full")]
+pub use schema_checks::{
+    validate_schema, SchemaIssue, SchemaIssueSeverity, SchemaSpan, ValidateSchemaResult,
+};
+#[cfg(feature = "full")]
+pub use schema_merge::{merge_schemas, MergeSchemasResult, SchemaConflict, SchemaDeclarationKind};
+pub use shards::WasmEntityShards;
+pub use tenants::{
+    drop_tenant, is_authorized_for_tenant, register_tenant, register_tenant_engine, tenant_ids,
+};
+#[cfg(feature = "full")]
+pub use typescript::{schema_to_typescript, TypescriptOptions};
+#[cfg(feature = "full")]
+pub use usage::{usage_report, UsageReport};
+#[cfg(feature = "full")]
+pub use validator::{
+    validate_many, validate_slot_values, validate_to_diagnostics, wasm_clear_answer_cache,
+    wasm_clear_schema_cache, wasm_validate, wasm_validate_policy_json,
+    wasm_validate_policy_set_json, wasm_validate_with, wasm_validate_with_policy_set,
+    wasm_validate_with_schema, Diagnostic, DiagnosticPosition, DiagnosticRange, DiagnosticsResult,
+    PolicySetToValidate, PolicySetsToValidate, SlotValues, ValidateManyResult,
+    ValidateSlotValuesResult,
+};
+pub use warm_up::wasm_warm_up;
+
+/// Errors from constructing a handle are thrown to JavaScript as a single
+/// `Error` whose message lists each error on its own line
+pub(crate) fn errors_to_js(errors: &[String]) -> JsError {
+    JsError::new(&errors.join("\n"))
+}
+
+/// Decode CBOR-encoded `bytes` into the JSON value they encode, describing
+/// them as `what` in errors
+pub(crate) fn cbor_to_json(bytes: &[u8], what: &str) -> Result<serde_json::Value, Vec<String>> {
+    ciborium::from_reader(bytes).map_err(|e| vec![format!("error decoding {what}: {e}")])
+}
+
+/// Replace the `context` of the authorization call `input` with the context
+/// encoded as CBOR in `context`, returning the call's JSON
+pub(crate) fn with_cbor_context(input: &str, context: &[u8]) -> Result<String, Vec<String>> {
+    let mut call = match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(serde_json::Value::Object(call)) => call,
+        Ok(_) => return Err(vec!["authorization call must be a JSON object".to_string()]),
+        Err(e) => return Err(vec![format!("error parsing call: {e}")]),
+    };
+    call.insert("context".to_string(), cbor_to_json(context, "context")?);
+    Ok(serde_json::Value::Object(call).to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// The current time in milliseconds, for timing the phases of a call
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now_ms() -> f64 {
+    performance_now()
+}
+
+/// The current time in milliseconds, for timing the phases of a call. Outside
+/// wasm, where there is no `performance.now()`, this counts from the first
+/// call on each thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now_ms() -> f64 {
+    thread_local! {
+        static START: std::time::Instant = std::time::Instant::now();
+    }
+    START.with(|start| start.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// Runs when the module is instantiated. With the `console_error_panic_hook`
+/// feature enabled, any residual panic is forwarded to the browser console
+/// with its message and location instead of an opaque `unreachable` trap.
+#[wasm_bindgen(start)]
+pub fn start() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
 
 #[wasm_bindgen(js_name = "getCedarVersion")]
 pub fn get_cedar_version() -> String {
     std::env!("CEDAR_VERSION").to_string()
 }
+
+#[wasm_bindgen(js_name = "getResponseVersion")]
+pub fn get_response_version() -> u32 {
+    RESPONSE_VERSION
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that describes which Cedar language capabilities this build of the module supports
+pub struct CedarLanguageFeatures {
+    /// the `is` operator for entity type tests
+    pub is_operator: bool,
+    /// the `ipaddr` extension
+    pub ipaddr_extension: bool,
+    /// the `decimal` extension
+    pub decimal_extension: bool,
+    /// the `datetime` extension
+    pub datetime_extension: bool,
+    /// enumerated entity types in schemas
+    pub enumerated_entity_types: bool,
+    /// partial evaluation of requests with unknowns
+    pub partial_evaluation: bool,
+    /// level-based validation bounding entity dereferencing
+    pub level_validation: bool,
+}
+
+/// Whether the Cedar build linked into the module has the extension `name`
+fn has_extension(name: &str) -> bool {
+    cedar_policy_core::extensions::Extensions::all_available()
+        .ext_names()
+        .any(|ext| ext.to_string() == name)
+}
+
+#[wasm_bindgen(js_name = "getCedarLanguageFeatures")]
+pub fn get_cedar_language_features() -> CedarLanguageFeatures {
+    CedarLanguageFeatures {
+        is_operator: true,
+        ipaddr_extension: has_extension("ipaddr"),
+        decimal_extension: has_extension("decimal"),
+        datetime_extension: has_extension("datetime"),
+        enumerated_entity_types: false,
+        partial_evaluation: cfg!(feature = "partial-eval"),
+        level_validation: false,
+    }
+}
@@ -0,0 +1,278 @@
+//! This module contains `lintPolicies`, which checks policies against a
+//! deny-list of constructs a team has chosen not to use, e.g. `unless`
+//! clauses, reporting each use with where it is in the policy text.
+use cedar_policy::frontend::utils::line_and_column;
+use cedar_policy_core::{
+    ast::{ExprKind, PolicyID},
+    parser::{cst, err::ParseErrors, parse_policyset, text_to_cst, Loc, Node},
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[derive(Tsify, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The constructs that policies must not use
+pub struct LintRules {
+    /// ban `unless` clauses
+    #[serde(default)]
+    #[tsify(optional)]
+    pub no_unless: bool,
+    /// ban `permit` policies that apply to any principal
+    #[serde(default)]
+    #[tsify(optional)]
+    pub no_wildcard_principal_in_permit: bool,
+    /// ban `permit` policies that apply to any resource
+    #[serde(default)]
+    #[tsify(optional)]
+    pub no_wildcard_resource_in_permit: bool,
+    /// the attributes whose values must not be matched with `like`
+    #[serde(default)]
+    #[tsify(optional)]
+    pub no_like_on: Vec<String>,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The rule of `LintRules` that a construct breaks
+pub enum LintRule {
+    NoUnless,
+    NoWildcardPrincipalInPermit,
+    NoWildcardResourceInPermit,
+    NoLikeOn,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A range of policy text, as byte offsets and as the line and column at each
+/// end, both counting from 1 with columns in characters; the end is exclusive
+pub struct PolicySpan {
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl PolicySpan {
+    /// The span of `text` at `loc`
+    fn new(text: &str, loc: &Loc) -> Self {
+        let (start, end) = (loc.start(), loc.end());
+        let (start_line, start_column) = line_and_column(text, start);
+        let (end_line, end_column) = line_and_column(text, end);
+        Self {
+            start,
+            end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A use of a banned construct
+pub struct LintViolation {
+    /// the id of the policy or template, as `PolicySet::from_str` gives it
+    pub policy_id: String,
+    pub rule: LintRule,
+    pub message: String,
+    pub span: PolicySpan,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `lintPolicies`
+pub enum LintResult {
+    /// the policies parse; they pass if `violations` is empty
+    Success { violations: Vec<LintViolation> },
+    /// the policies do not parse
+    Error { errors: Vec<String> },
+}
+
+/// Check policies and templates, given as Cedar text, against `rules`,
+/// reporting each use of a banned construct in the order of the text. A
+/// policy applies to any principal or resource when its scope does not
+/// constrain it at all, not even by type.
+#[wasm_bindgen(js_name = "lintPolicies")]
+pub fn lint_policies(policies_str: &str, rules: LintRules) -> LintResult {
+    if let Err(errors) = parse_policyset(policies_str) {
+        return LintResult::Error {
+            errors: errors.iter().map(ToString::to_string).collect(),
+        };
+    }
+    let cst = match text_to_cst::parse_policies(policies_str) {
+        Ok(cst) => cst,
+        Err(errors) => {
+            return LintResult::Error {
+                errors: errors.iter().map(ToString::to_string).collect(),
+            }
+        }
+    };
+    let mut violations = Vec::new();
+    for (id, policy) in cst.with_generated_policyids().into_iter().flatten() {
+        lint_policy(policies_str, &id, policy, &rules, &mut violations);
+    }
+    violations.sort_by_key(|violation| violation.span.start);
+    LintResult::Success { violations }
+}
+
+/// Add the uses of constructs `rules` bans in `policy`, with id `id`, to
+/// `violations`
+fn lint_policy(
+    text: &str,
+    id: &PolicyID,
+    policy: &Node<Option<cst::Policy>>,
+    rules: &LintRules,
+    violations: &mut Vec<LintViolation>,
+) {
+    let Some(cst) = &policy.node else {
+        return;
+    };
+    let mut violation = |rule, message, loc: &Loc| {
+        violations.push(LintViolation {
+            policy_id: id.to_string(),
+            rule,
+            message,
+            span: PolicySpan::new(text, loc),
+        })
+    };
+    if rules.no_unless {
+        for cond in cst.conds.iter().filter(
+            |cond| matches!(&cond.node, Some(cond) if cond.cond.node == Some(cst::Ident::Unless)),
+        ) {
+            violation(
+                LintRule::NoUnless,
+                "`unless` clauses are not allowed".to_string(),
+                &cond.loc,
+            );
+        }
+    }
+    if cst.effect.node == Some(cst::Ident::Permit) {
+        for variable in &cst.variables {
+            let Some(def) = &variable.node else {
+                continue;
+            };
+            let (rule, var) = match &def.variable.node {
+                Some(cst::Ident::Principal) if rules.no_wildcard_principal_in_permit => {
+                    (LintRule::NoWildcardPrincipalInPermit, "principal")
+                }
+                Some(cst::Ident::Resource) if rules.no_wildcard_resource_in_permit => {
+                    (LintRule::NoWildcardResourceInPermit, "resource")
+                }
+                _ => continue,
+            };
+            if def.entity_type.is_none() && def.ineq.is_none() {
+                violation(
+                    rule,
+                    format!("`permit` policies must constrain the {var}"),
+                    &variable.loc,
+                );
+            }
+        }
+    }
+    if !rules.no_like_on.is_empty() {
+        let Some(template) = policy.to_policy_template(id.clone(), &mut ParseErrors::new()) else {
+            return;
+        };
+        for expr in template.non_head_constraints().subexpressions() {
+            let ExprKind::Like { expr: operand, .. } = expr.expr_kind() else {
+                continue;
+            };
+            let ExprKind::GetAttr { attr, .. } = operand.expr_kind() else {
+                continue;
+            };
+            if rules
+                .no_like_on
+                .iter()
+                .any(|banned| banned == attr.as_str())
+            {
+                if let Some(loc) = expr.source_loc() {
+                    violation(
+                        LintRule::NoLikeOn,
+                        format!("`like` on the attribute `{attr}` is not allowed"),
+                        loc,
+                    );
+                }
+            }
+        }
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn violations(policies: &str, rules: LintRules) -> Vec<LintViolation> {
+        match lint_policies(policies, rules) {
+            LintResult::Success { violations } => violations,
+            result => panic!("expected the policies to parse: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_banned_constructs_with_spans() {
+        let policies = r#"permit(principal, action, resource is Photo)
+unless { principal.suspended };
+forbid(principal, action, resource)
+when { principal.email like "*@example.com" && resource.name like "*.jpg" };
+permit(principal == ?principal, action, resource);
+"#;
+        let rules = LintRules {
+            no_unless: true,
+            no_wildcard_principal_in_permit: true,
+            no_wildcard_resource_in_permit: true,
+            no_like_on: vec!["email".to_string()],
+        };
+        let found = violations(policies, rules);
+        let summary: Vec<_> = found
+            .iter()
+            .map(|violation| {
+                (
+                    violation.policy_id.as_str(),
+                    violation.rule,
+                    violation.span.start_line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("policy0", LintRule::NoWildcardPrincipalInPermit, 1),
+                ("policy0", LintRule::NoUnless, 2),
+                ("policy1", LintRule::NoLikeOn, 4),
+                ("policy2", LintRule::NoWildcardResourceInPermit, 5),
+            ]
+        );
+        let like = &found[2];
+        assert_eq!(
+            &policies[like.span.start..like.span.end],
+            r#"principal.email like "*@example.com""#
+        );
+        assert_eq!(like.span.start_column, 8);
+        assert_eq!(
+            &policies[found[1].span.start..found[1].span.end],
+            "unless { principal.suspended }"
+        );
+
+        assert!(violations(policies, LintRules::default()).is_empty());
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        assert!(matches!(
+            lint_policies("permit(principal, action", LintRules::default()),
+            LintResult::Error { .. }
+        ));
+    }
+}
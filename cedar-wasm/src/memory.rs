@@ -0,0 +1,93 @@
+//! This module reports how much memory the module instance is using, so that
+//! embedders can budget memory in constrained environments.
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::tenants::with_tenants;
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Memory held by the module instance. Handles owned by JavaScript are not
+/// included; use `CedarEngine.memoryStats` for those.
+pub struct MemoryStats {
+    /// tenants in the registry
+    pub tenants: usize,
+    /// static and template-linked policies held by registered tenants
+    pub policies: usize,
+    /// templates held by registered tenants
+    pub templates: usize,
+    /// entities held by registered tenants
+    pub entities: usize,
+    /// the size of the policy text and entity JSON held by registered
+    /// tenants, a lower bound on the heap their parsed forms occupy
+    pub estimated_heap_bytes: usize,
+    /// size of the wasm linear memory, or `undefined` when not running as wasm
+    #[tsify(optional)]
+    pub linear_memory_bytes: Option<usize>,
+}
+
+/// Report the memory held by the module instance
+#[wasm_bindgen(js_name = "getMemoryStats")]
+pub fn get_memory_stats() -> MemoryStats {
+    let mut stats = with_tenants(|tenants| {
+        tenants.values().map(|engine| engine.stats()).fold(
+            MemoryStats {
+                tenants: tenants.len(),
+                policies: 0,
+                templates: 0,
+                entities: 0,
+                estimated_heap_bytes: 0,
+                linear_memory_bytes: None,
+            },
+            |mut stats, engine| {
+                stats.policies += engine.policies;
+                stats.templates += engine.templates;
+                stats.entities += engine.entities;
+                stats.estimated_heap_bytes += engine.estimated_heap_bytes;
+                stats
+            },
+        )
+    });
+    stats.linear_memory_bytes = linear_memory_bytes();
+    stats
+}
+
+#[cfg(target_arch = "wasm32")]
+fn linear_memory_bytes() -> Option<usize> {
+    /// wasm pages are 64 KiB
+    const PAGE_SIZE: usize = 65536;
+    Some(core::arch::wasm32::memory_size::<0>() * PAGE_SIZE)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn linear_memory_bytes() -> Option<usize> {
+    None
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{tenants::register, CedarEngine};
+
+    #[test]
+    fn sums_over_tenants() {
+        let entities =
+            r#"[{ "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }]"#;
+        for tenant_id in ["acme", "globex"] {
+            let engine =
+                CedarEngine::build(None, "permit(principal, action, resource);", entities).unwrap();
+            register(tenant_id, engine);
+        }
+        let stats = get_memory_stats();
+        assert_eq!(stats.tenants, 2);
+        assert_eq!(stats.policies, 2);
+        assert_eq!(stats.templates, 0);
+        assert_eq!(stats.entities, 2);
+        assert!(stats.estimated_heap_bytes > 0);
+        assert_eq!(stats.linear_memory_bytes, None);
+    }
+}
@@ -0,0 +1,255 @@
+//! This module contains `checkSchemaMigration`, which checks existing policies
+//! and entities against a changed schema before it is rolled out, reporting
+//! each one the change breaks and why.
+use std::{collections::BTreeMap, str::FromStr};
+
+use cedar_policy::{Entities, PolicySet, ValidationMode, Validator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::WasmSchema;
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A policy or template that does not validate against the new schema
+pub struct PolicyBreakage {
+    /// the id of the policy or template, as `PolicySet::from_str` gives it
+    pub policy_id: String,
+    /// whether it validates against the old schema, i.e. whether the change
+    /// is what breaks it
+    pub passed_before: bool,
+    /// the validation errors against the new schema
+    pub errors: Vec<String>,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An entity that does not conform to the new schema
+pub struct EntityBreakage {
+    /// the uid of the entity as Cedar text
+    pub uid: String,
+    /// the position of the entity in the entities given
+    pub index: usize,
+    /// whether it conforms to the old schema, i.e. whether the change is what
+    /// breaks it
+    pub passed_before: bool,
+    pub errors: Vec<String>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `checkSchemaMigration`
+pub enum SchemaMigrationResult {
+    /// the policies and entities that the new schema rejects, in order; the
+    /// migration is safe if both are empty
+    Success {
+        policies: Vec<PolicyBreakage>,
+        entities: Vec<EntityBreakage>,
+    },
+    /// the policies or the entities do not parse
+    Error { errors: Vec<String> },
+}
+
+/// Validate policies and templates, given as Cedar text, and check entities,
+/// in their JSON format, against the schema handle `newSchema`, reporting
+/// each one it rejects, along with whether `oldSchema` accepts it. Policies
+/// are validated in strict mode, and each entity is checked on its own, so
+/// one entity that does not conform does not hide the others.
+#[wasm_bindgen(js_name = "checkSchemaMigration")]
+pub fn check_schema_migration(
+    old_schema: &WasmSchema,
+    new_schema: &WasmSchema,
+    policies_str: &str,
+    entities_str: &str,
+) -> SchemaMigrationResult {
+    let policies = match PolicySet::from_str(policies_str) {
+        Ok(policies) => policies,
+        Err(errors) => {
+            return SchemaMigrationResult::Error {
+                errors: errors.iter().map(ToString::to_string).collect(),
+            }
+        }
+    };
+    let entities = match serde_json::from_str::<Value>(entities_str) {
+        Ok(Value::Array(entities)) => entities,
+        Ok(_) => {
+            return SchemaMigrationResult::Error {
+                errors: vec!["entities must be a JSON array".to_string()],
+            }
+        }
+        Err(e) => {
+            return SchemaMigrationResult::Error {
+                errors: vec![format!("error parsing entities: {e}")],
+            }
+        }
+    };
+
+    let before = policy_errors(old_schema, &policies);
+    let policies = policy_errors(new_schema, &policies)
+        .into_iter()
+        .map(|(policy_id, errors)| PolicyBreakage {
+            passed_before: !before.contains_key(&policy_id),
+            policy_id,
+            errors,
+        })
+        .collect();
+
+    let mut breakages = Vec::new();
+    for (index, entity) in entities.into_iter().enumerate() {
+        let uid = match parse_entity(&entity, None) {
+            Ok(parsed) => parsed
+                .iter()
+                .next()
+                .map(|parsed| parsed.uid().to_string())
+                .unwrap_or_default(),
+            Err(errors) => {
+                return SchemaMigrationResult::Error {
+                    errors: errors
+                        .into_iter()
+                        .map(|e| format!("error parsing entity {index}: {e}"))
+                        .collect(),
+                }
+            }
+        };
+        if let Err(errors) = parse_entity(&entity, Some(new_schema)) {
+            breakages.push(EntityBreakage {
+                uid,
+                index,
+                passed_before: parse_entity(&entity, Some(old_schema)).is_ok(),
+                errors,
+            });
+        }
+    }
+    SchemaMigrationResult::Success {
+        policies,
+        entities: breakages,
+    }
+}
+
+/// The validation errors of each policy of `policies` that `schema` rejects,
+/// by policy id
+fn policy_errors(schema: &WasmSchema, policies: &PolicySet) -> BTreeMap<String, Vec<String>> {
+    let mut errors: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    let result =
+        Validator::new(schema.schema().clone()).validate(policies, ValidationMode::default());
+    for error in result.validation_errors() {
+        errors
+            .entry(error.location().policy_id().to_string())
+            .or_default()
+            .push(error.error_kind().to_string());
+    }
+    errors
+}
+
+/// Parse `entity` in its JSON format, checking that it conforms to `schema`
+/// if one is given
+fn parse_entity(entity: &Value, schema: Option<&WasmSchema>) -> Result<Entities, Vec<String>> {
+    Entities::from_json_value(
+        Value::Array(vec![entity.clone()]),
+        schema.map(WasmSchema::schema),
+    )
+    .map_err(|e| vec![e.to_string()])
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OLD: &str = r#"
+        entity Group;
+        entity User in [Group] { name: String, age: Long };
+        entity Photo { owner: User };
+        action view appliesTo { principal: [User], resource: [Photo] };
+    "#;
+
+    const NEW: &str = r#"
+        entity Team;
+        entity User in [Team] { name: String, age: String };
+        entity Photo { owner: User };
+        action view appliesTo { principal: [User], resource: [Photo] };
+    "#;
+
+    const POLICIES: &str = r#"
+        permit(principal, action == Action::"view", resource)
+        when { principal.age > 18 };
+        permit(principal in Group::"admins", action, resource);
+        permit(principal, action, resource) when { resource.owner == principal };
+        permit(principal, action, resource) when { principal.nickname == "x" };
+    "#;
+
+    const ENTITIES: &str = r#"[
+        { "uid": { "type": "User", "id": "alice" }, "attrs": { "name": "Alice", "age": 30 },
+          "parents": [{ "type": "Group", "id": "admins" }] },
+        { "uid": { "type": "User", "id": "bob" }, "attrs": { "name": "Bob", "age": "30" },
+          "parents": [] },
+        { "uid": { "type": "Photo", "id": "pic" }, "attrs": { "owner": { "type": "User", "id": "bob" } },
+          "parents": [] },
+        { "uid": { "type": "Group", "id": "admins" }, "attrs": {}, "parents": [] }
+    ]"#;
+
+    #[test]
+    fn reports_what_the_new_schema_breaks() {
+        let old = WasmSchema::parse_cedar(OLD).unwrap();
+        let new = WasmSchema::parse_cedar(NEW).unwrap();
+        let SchemaMigrationResult::Success { policies, entities } =
+            check_schema_migration(&old, &new, POLICIES, ENTITIES)
+        else {
+            panic!("expected the policies and entities to parse");
+        };
+        let summary: Vec<_> = policies
+            .iter()
+            .map(|breakage| (breakage.policy_id.as_str(), breakage.passed_before))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("policy0", true), ("policy1", true), ("policy3", false)]
+        );
+        assert!(policies.iter().all(|breakage| !breakage.errors.is_empty()));
+        let summary: Vec<_> = entities
+            .iter()
+            .map(|breakage| {
+                (
+                    breakage.uid.as_str(),
+                    breakage.index,
+                    breakage.passed_before,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (r#"User::"alice""#, 0, true),
+                (r#"Group::"admins""#, 3, true)
+            ]
+        );
+
+        // the same schema breaks nothing that was not already broken
+        let SchemaMigrationResult::Success { policies, entities } =
+            check_schema_migration(&old, &old, POLICIES, ENTITIES)
+        else {
+            panic!("expected the policies and entities to parse");
+        };
+        assert!(policies.iter().all(|breakage| !breakage.passed_before));
+        assert!(entities.iter().all(|breakage| !breakage.passed_before));
+    }
+
+    #[test]
+    fn reports_input_that_does_not_parse() {
+        let schema = WasmSchema::parse_cedar(OLD).unwrap();
+        assert!(matches!(
+            check_schema_migration(&schema, &schema, "permit(", "[]"),
+            SchemaMigrationResult::Error { .. }
+        ));
+        assert!(matches!(
+            check_schema_migration(&schema, &schema, "", "{}"),
+            SchemaMigrationResult::Error { .. }
+        ));
+    }
+}
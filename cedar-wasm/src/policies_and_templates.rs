@@ -25,7 +25,11 @@ pub fn policy_text_from_json(json_str: &str) -> JsonToPolicyResult {
             }
         }
     };
-    let policy = Policy::from_json(None, parsed_json);
+    policy_text_from_json_value(parsed_json)
+}
+
+fn policy_text_from_json_value(json: serde_json::Value) -> JsonToPolicyResult {
+    let policy = Policy::from_json(None, json);
     match policy {
         Ok(p) => JsonToPolicyResult::Success {
             policy_text: p.to_string(),
@@ -36,6 +40,35 @@ pub fn policy_text_from_json(json_str: &str) -> JsonToPolicyResult {
     }
 }
 
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Result of converting a batch of policies from JSON to Cedar text
+pub enum JsonToPoliciesResult {
+    /// One result per policy, in the order the policies were given
+    Success { results: Vec<JsonToPolicyResult> },
+    /// The input was not a JSON array
+    Error { errors: Vec<String> },
+}
+
+/// Convert each policy of a JSON array of policies in JSON (EST) format to
+/// Cedar text, as `policyTextFromJson` does, in a single call. A policy
+/// failing to convert does not stop the others from being converted.
+#[wasm_bindgen(js_name = "policiesFromJson")]
+pub fn policies_text_from_json(json_array: &str) -> JsonToPoliciesResult {
+    match serde_json::from_str::<Vec<serde_json::Value>>(json_array) {
+        Ok(policies) => JsonToPoliciesResult::Success {
+            results: policies
+                .into_iter()
+                .map(policy_text_from_json_value)
+                .collect(),
+        },
+        Err(e) => JsonToPoliciesResult::Error {
+            errors: vec![e.to_string()],
+        },
+    }
+}
+
 #[derive(Tsify, Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
@@ -58,6 +91,27 @@ pub fn policy_text_to_json(cedar_str: &str) -> PolicyToJsonResult {
     }
 }
 
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Result of converting a batch of policies from Cedar text to JSON
+pub struct PoliciesToJsonResult {
+    /// One result per policy, in the order the policies were given
+    pub results: Vec<PolicyToJsonResult>,
+}
+
+/// Convert each of `policies`, written as Cedar text, to JSON (EST) format
+/// as `policyTextToJson` does, in a single call. A policy failing to convert
+/// does not stop the others from being converted.
+#[wasm_bindgen(js_name = "policiesToJson")]
+pub fn policies_text_to_json(policies: Vec<String>) -> PoliciesToJsonResult {
+    PoliciesToJsonResult {
+        results: policies
+            .iter()
+            .map(|policy| policy_text_to_json(policy))
+            .collect(),
+    }
+}
+
 #[derive(Tsify, Debug, Serialize, Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 /// struct that defines the result for the syntax validation function
@@ -100,6 +154,8 @@ pub struct Template {
     parse_errors: Option<Vec<String>>,
 }
 
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
 #[cfg(test)]
 mod test {
 
@@ -152,6 +208,59 @@ mod test {
         }
     }
 
+    #[test]
+    fn converts_policies_in_bulk() {
+        let converted = policies_text_to_json(vec![
+            "permit(principal, action, resource);".to_string(),
+            "permit(2pac, action, resource);".to_string(),
+            r#"forbid(principal == User::"alice", action, resource);"#.to_string(),
+        ]);
+        assert!(matches!(
+            converted.results.as_slice(),
+            [
+                PolicyToJsonResult::Success { .. },
+                PolicyToJsonResult::Error { .. },
+                PolicyToJsonResult::Success { .. },
+            ]
+        ));
+
+        let json_array = serde_json::to_string(
+            &converted
+                .results
+                .into_iter()
+                .filter_map(|result| match result {
+                    PolicyToJsonResult::Success { policy } => Some(policy),
+                    PolicyToJsonResult::Error { .. } => None,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        match policies_text_from_json(&json_array) {
+            JsonToPoliciesResult::Success { results } => {
+                let texts: Vec<_> = results
+                    .into_iter()
+                    .map(|result| match result {
+                        JsonToPolicyResult::Success { policy_text } => policy_text,
+                        JsonToPolicyResult::Error { errors } => panic!("{errors:?}"),
+                    })
+                    .collect();
+                assert_eq!(
+                    texts,
+                    vec![
+                        "permit(principal, action, resource);",
+                        r#"forbid(principal == User::"alice", action, resource);"#,
+                    ]
+                );
+            }
+            JsonToPoliciesResult::Error { errors } => panic!("{errors:?}"),
+        }
+
+        assert!(matches!(
+            policies_text_from_json(r#"{ "effect": "permit" }"#),
+            JsonToPoliciesResult::Error { .. }
+        ));
+    }
+
     #[test]
     fn can_parse_1_policy() {
         let stringified_result = check_parse_policy_set("permit(principal, action, resource);");
@@ -0,0 +1,718 @@
+//! This module contains the `WasmPolicySet` handle, which holds a parsed
+//! policy set so that it can be passed to authorization and validation calls
+//! without re-parsing policy text on every request.
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "full")]
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+
+use cedar_policy::{EntityUid, Policy, PolicyId, PolicySet, SlotId, Template};
+use cedar_policy_core::est;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "full")]
+use crate::WasmSchema;
+use crate::{
+    errors_to_js,
+    formats::{detect_format, InputFormat},
+};
+
+/// A parsed policy set, held on the wasm side of the boundary
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WasmPolicySet {
+    policy_set: PolicySet,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// JSON format of a policy set: static policies and templates in their JSON
+/// (EST) format, keyed by id, plus the links instantiating the templates
+pub struct PolicySetJson {
+    #[serde(default)]
+    pub static_policies: BTreeMap<String, est::Policy>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, est::Policy>,
+    #[serde(default)]
+    pub template_links: Vec<TemplateLinkJson>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A link of the template `template_id`, producing the policy `new_id`
+pub struct TemplateLinkJson {
+    pub template_id: String,
+    pub new_id: String,
+    /// values for the slots, keyed by `?principal` or `?resource`
+    #[tsify(type = "Record<string, {type: string, id: string}>")]
+    pub values: BTreeMap<String, serde_json::Value>,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Links proposed for the templates of a policy set
+pub struct TemplateLinks(pub Vec<TemplateLinkJson>);
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// What is wrong with a proposed link, if anything
+pub struct TemplateLinkCheck {
+    pub template_id: String,
+    pub new_id: String,
+    /// the reasons the link cannot be made, empty if it can
+    pub errors: Vec<String>,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `checkTemplateLinks`
+pub struct TemplateLinksCheck {
+    /// whether every link can be made
+    pub passed: bool,
+    /// the check of each link, in the order given
+    pub links: Vec<TemplateLinkCheck>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `WasmPolicySet.toJson`
+pub enum PolicySetToJsonResult {
+    Success { json: PolicySetJson },
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Summary of the policies and templates held by a `WasmPolicySet`, sorted by id
+pub struct PolicySetMetadata {
+    pub policies: Vec<PolicyMetadata>,
+    pub templates: Vec<PolicyMetadata>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct PolicyMetadata {
+    pub id: String,
+    /// `permit` or `forbid`
+    pub effect: String,
+    pub annotations: BTreeMap<String, String>,
+    /// for template-linked policies, the template they were linked from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub template_id: Option<String>,
+}
+
+impl WasmPolicySet {
+    /// Parse a policy set from Cedar text. Policies are assigned ids
+    /// `policy0`, `policy1`, ... in the order they appear.
+    pub fn parse(text: &str) -> Result<Self, Vec<String>> {
+        PolicySet::from_str(text)
+            .map(Self::from)
+            .map_err(|e| e.errors_as_strings())
+    }
+
+    /// Parse a policy set in `format`, or in the format it looks like if that
+    /// is not given; see [`detect_format`]
+    pub fn parse_in_format(text: &str, format: Option<InputFormat>) -> Result<Self, Vec<String>> {
+        match detect_format(text, "policy set", format).map_err(|e| vec![e])? {
+            InputFormat::Json => Self::parse_json(text),
+            InputFormat::Cedar => Self::parse(text),
+        }
+    }
+
+    /// Parse a policy set from its JSON format; see [`PolicySetJson`]
+    pub fn parse_json(json: &str) -> Result<Self, Vec<String>> {
+        let json: PolicySetJson = serde_json::from_str(json)
+            .map_err(|e| vec![format!("error parsing policy set JSON: {e}")])?;
+        Self::from_policy_set_json(json)
+    }
+
+    /// Build a policy set from its JSON format, already deserialized
+    pub fn from_policy_set_json(json: PolicySetJson) -> Result<Self, Vec<String>> {
+        let mut policy_set = PolicySet::new();
+        let mut errors = Vec::new();
+        for (id, est) in json.templates {
+            match serde_json::to_value(est)
+                .map_err(|e| e.to_string())
+                .and_then(|est| {
+                    Template::from_json(Some(policy_id(&id)), est).map_err(|e| e.to_string())
+                })
+                .and_then(|template| policy_set.add_template(template).map_err(|e| e.to_string()))
+            {
+                Ok(()) => (),
+                Err(e) => errors.push(format!("error in template `{id}`: {e}")),
+            }
+        }
+        for (id, est) in json.static_policies {
+            match serde_json::to_value(est)
+                .map_err(|e| e.to_string())
+                .and_then(|est| {
+                    Policy::from_json(Some(policy_id(&id)), est).map_err(|e| e.to_string())
+                })
+                .and_then(|policy| policy_set.add(policy).map_err(|e| e.to_string()))
+            {
+                Ok(()) => (),
+                Err(e) => errors.push(format!("error in policy `{id}`: {e}")),
+            }
+        }
+        for link in json.template_links {
+            if let Err(e) = link_template(&mut policy_set, &link) {
+                errors.push(format!("error in link `{}`: {e}", link.new_id));
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { policy_set })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The policy set held by this handle
+    pub fn policy_set(&self) -> &PolicySet {
+        &self.policy_set
+    }
+
+    /// Add the static policy written as Cedar text under `id`, failing if the
+    /// id is already used by a policy or template
+    pub fn insert(&mut self, id: &str, text: &str) -> Result<(), Vec<String>> {
+        let policy = parse_policy(id, text)?;
+        self.policy_set.add(policy).map_err(|e| vec![e.to_string()])
+    }
+
+    /// Remove the static or template-linked policy `id`, failing if there is
+    /// no such policy
+    pub fn remove(&mut self, id: &str) -> Result<(), Vec<String>> {
+        let id = policy_id(id);
+        let result = match self.policy_set.policy(&id) {
+            Some(policy) if !policy.is_static() => self.policy_set.unlink(id).map(|_| ()),
+            _ => self.policy_set.remove_static(id).map(|_| ()),
+        };
+        result.map_err(|e| vec![e.to_string()])
+    }
+
+    /// Replace the static policy `id` with the one written as Cedar text,
+    /// failing if there is no such policy. The policy set is unchanged on
+    /// error.
+    pub fn replace(&mut self, id: &str, text: &str) -> Result<(), Vec<String>> {
+        let policy = parse_policy(id, text)?;
+        let old = self
+            .policy_set
+            .remove_static(policy_id(id))
+            .map_err(|e| vec![e.to_string()])?;
+        self.policy_set.add(policy).map_err(|e| {
+            // PANIC SAFETY: the old policy was just removed from this id, so it can be re-added
+            #[allow(clippy::expect_used)]
+            self.policy_set
+                .add(old)
+                .expect("re-adding the removed policy should succeed");
+            vec![e.to_string()]
+        })
+    }
+
+    /// The policy set in its JSON format; see [`PolicySetJson`]
+    pub fn to_policy_set_json(&self) -> Result<PolicySetJson, String> {
+        let mut json = PolicySetJson {
+            static_policies: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            template_links: Vec::new(),
+        };
+        for template in self.policy_set.templates() {
+            let est = template.to_json().map_err(|e| e.to_string())?;
+            json.templates.insert(
+                template.id().to_string(),
+                serde_json::from_value(est).map_err(|e| e.to_string())?,
+            );
+        }
+        for policy in self.policy_set.policies() {
+            match (policy.template_id(), policy.template_links()) {
+                (Some(template_id), Some(values)) => json.template_links.push(TemplateLinkJson {
+                    template_id: template_id.to_string(),
+                    new_id: policy.id().to_string(),
+                    values: values
+                        .into_iter()
+                        .map(|(slot, uid)| (slot.to_string(), entity_uid_json(&uid)))
+                        .collect(),
+                }),
+                _ => {
+                    let est = policy.to_json().map_err(|e| e.to_string())?;
+                    json.static_policies.insert(
+                        policy.id().to_string(),
+                        serde_json::from_value(est).map_err(|e| e.to_string())?,
+                    );
+                }
+            }
+        }
+        json.template_links.sort_by(|a, b| a.new_id.cmp(&b.new_id));
+        Ok(json)
+    }
+}
+
+impl From<PolicySet> for WasmPolicySet {
+    fn from(policy_set: PolicySet) -> Self {
+        Self { policy_set }
+    }
+}
+
+#[wasm_bindgen]
+impl WasmPolicySet {
+    /// Parse a policy set from Cedar text, throwing if it does not parse
+    #[wasm_bindgen(js_name = "fromText")]
+    pub fn from_text(text: &str) -> Result<WasmPolicySet, JsError> {
+        Self::parse(text).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Parse a policy set from its JSON format, throwing if it is invalid
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(json: &str) -> Result<WasmPolicySet, JsError> {
+        Self::parse_json(json).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Parse a policy set in `format`, or, if that is not given, in the format
+    /// it looks like, throwing if it is invalid or its format is ambiguous
+    #[wasm_bindgen(js_name = "parse")]
+    pub fn parse_text(text: &str, format: Option<InputFormat>) -> Result<WasmPolicySet, JsError> {
+        Self::parse_in_format(text, format).map_err(|errors| errors_to_js(&errors))
+    }
+
+    #[wasm_bindgen(js_name = "toJson")]
+    pub fn to_json(&self) -> PolicySetToJsonResult {
+        match self.to_policy_set_json() {
+            Ok(json) => PolicySetToJsonResult::Success { json },
+            Err(e) => PolicySetToJsonResult::Error { errors: vec![e] },
+        }
+    }
+
+    #[wasm_bindgen(js_name = "metadata")]
+    pub fn metadata(&self) -> PolicySetMetadata {
+        let mut policies: Vec<_> = self
+            .policy_set
+            .policies()
+            .map(|policy| PolicyMetadata {
+                id: policy.id().to_string(),
+                effect: policy.effect().to_string(),
+                annotations: collect_annotations(policy.annotations()),
+                template_id: policy.template_id().map(ToString::to_string),
+            })
+            .collect();
+        let mut templates: Vec<_> = self
+            .policy_set
+            .templates()
+            .map(|template| PolicyMetadata {
+                id: template.id().to_string(),
+                effect: template.effect().to_string(),
+                annotations: collect_annotations(template.annotations()),
+                template_id: None,
+            })
+            .collect();
+        policies.sort_by(|a, b| a.id.cmp(&b.id));
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        PolicySetMetadata {
+            policies,
+            templates,
+        }
+    }
+
+    /// Add the static policy written as Cedar text under `id`, throwing if it
+    /// does not parse or the id is already in use
+    #[wasm_bindgen(js_name = "addPolicy")]
+    pub fn add_policy(&mut self, id: &str, text: &str) -> Result<(), JsError> {
+        self.insert(id, text)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Remove the static or template-linked policy `id`, throwing if there is
+    /// no such policy
+    #[wasm_bindgen(js_name = "removePolicy")]
+    pub fn remove_policy(&mut self, id: &str) -> Result<(), JsError> {
+        self.remove(id).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Replace the static policy `id` with the one written as Cedar text,
+    /// throwing if it does not parse or there is no such policy
+    #[wasm_bindgen(js_name = "replacePolicy")]
+    pub fn replace_policy(&mut self, id: &str, text: &str) -> Result<(), JsError> {
+        self.replace(id, text)
+            .map_err(|errors| errors_to_js(&errors))
+    }
+}
+
+/// Check a set of proposed links against the templates of `policies` and a
+/// schema handle: that each link is of a template in the policy set, fills
+/// every slot of the template and no other, gives each slot an entity uid of
+/// a type the schema declares, and has an id used by no policy, template, or
+/// earlier link. Whether the template's actions apply to the linked types is
+/// left to `validateSlotValues`.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = "checkTemplateLinks")]
+pub fn check_template_links(
+    policies: &WasmPolicySet,
+    schema: &WasmSchema,
+    links: TemplateLinks,
+) -> TemplateLinksCheck {
+    let declared: HashSet<_> = schema.entity_types().into_iter().collect();
+    let mut ids: HashSet<_> = policies
+        .policy_set
+        .policies()
+        .map(|policy| policy.id().to_string())
+        .chain(
+            policies
+                .policy_set
+                .templates()
+                .map(|template| template.id().to_string()),
+        )
+        .collect();
+    let links: Vec<_> = links
+        .0
+        .into_iter()
+        .map(|link| {
+            let mut errors = Vec::new();
+            if !ids.insert(link.new_id.clone()) {
+                errors.push(format!("the id `{}` is already in use", link.new_id));
+            }
+            errors.extend(link_errors(&policies.policy_set, &declared, &link));
+            TemplateLinkCheck {
+                template_id: link.template_id,
+                new_id: link.new_id,
+                errors,
+            }
+        })
+        .collect();
+    TemplateLinksCheck {
+        passed: links.iter().all(|link| link.errors.is_empty()),
+        links,
+    }
+}
+
+/// The reasons `link` cannot be made of a template of `policy_set`, other
+/// than its id, given the entity types the schema declares
+#[cfg(feature = "full")]
+fn link_errors(
+    policy_set: &PolicySet,
+    declared: &HashSet<String>,
+    link: &TemplateLinkJson,
+) -> Vec<String> {
+    let template_id = &link.template_id;
+    let Some(template) = policy_set.template(&policy_id(template_id)) else {
+        return vec![format!("there is no template `{template_id}`")];
+    };
+    let slots: BTreeSet<_> = template.slots().map(ToString::to_string).collect();
+    let missing = slots
+        .iter()
+        .filter(|slot| !link.values.contains_key(*slot))
+        .map(|slot| format!("slot `{slot}` of template `{template_id}` is not filled"));
+    let supplied = link.values.iter().filter_map(|(slot, value)| {
+        if !slots.contains(slot) {
+            return Some(format!("template `{template_id}` has no slot `{slot}`"));
+        }
+        match EntityUid::from_json(value.clone()) {
+            Ok(uid) if !declared.contains(&uid.type_name().to_string()) => Some(format!(
+                "the value of `{slot}` is `{uid}`, but the schema does not declare the entity type `{}`",
+                uid.type_name()
+            )),
+            Ok(_) => None,
+            Err(e) => Some(format!("error parsing value of `{slot}`: {e}")),
+        }
+    });
+    missing.chain(supplied).collect()
+}
+
+fn parse_policy(id: &str, text: &str) -> Result<Policy, Vec<String>> {
+    Policy::parse(Some(id.to_string()), text).map_err(|e| e.errors_as_strings())
+}
+
+pub(crate) fn policy_id(id: &str) -> PolicyId {
+    match PolicyId::from_str(id) {
+        Ok(id) => id,
+        Err(never) => match never {},
+    }
+}
+
+fn collect_annotations<'a>(
+    annotations: impl Iterator<Item = (&'a str, &'a str)>,
+) -> BTreeMap<String, String> {
+    annotations
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn entity_uid_json(uid: &EntityUid) -> serde_json::Value {
+    serde_json::json!({
+        "type": uid.type_name().to_string(),
+        "id": AsRef::<str>::as_ref(uid.id()),
+    })
+}
+
+fn link_template(policy_set: &mut PolicySet, link: &TemplateLinkJson) -> Result<(), String> {
+    let values = link
+        .values
+        .iter()
+        .map(|(slot, value)| {
+            let slot = match slot.as_str() {
+                "?principal" => SlotId::principal(),
+                "?resource" => SlotId::resource(),
+                _ => return Err(format!("unknown slot `{slot}`")),
+            };
+            EntityUid::from_json(value.clone())
+                .map(|uid| (slot, uid))
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    policy_set
+        .link(
+            policy_id(&link.template_id),
+            policy_id(&link.new_id),
+            values,
+        )
+        .map_err(|e| e.to_string())
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POLICIES: &str = r#"
+        @id("alice can view")
+        permit(principal == User::"alice", action == Action::"view", resource);
+        forbid(principal, action, resource) when { resource.private };
+    "#;
+
+    #[test]
+    fn parses_text_and_reports_metadata() {
+        let policy_set = WasmPolicySet::parse(POLICIES).unwrap();
+        let metadata = policy_set.metadata();
+        assert_eq!(metadata.templates, vec![]);
+        assert_eq!(
+            metadata.policies,
+            vec![
+                PolicyMetadata {
+                    id: "policy0".to_string(),
+                    effect: "permit".to_string(),
+                    annotations: BTreeMap::from([("id".to_string(), "alice can view".to_string())]),
+                    template_id: None,
+                },
+                PolicyMetadata {
+                    id: "policy1".to_string(),
+                    effect: "forbid".to_string(),
+                    annotations: BTreeMap::new(),
+                    template_id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_text() {
+        let errors = WasmPolicySet::parse("permit(principal, action, resource").unwrap_err();
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn parses_in_detected_format() {
+        let policy_set = WasmPolicySet::parse_in_format(POLICIES, None).unwrap();
+        let json = serde_json::to_string(&policy_set.to_policy_set_json().unwrap()).unwrap();
+        let reparsed = WasmPolicySet::parse_in_format(&json, None).unwrap();
+        assert_eq!(reparsed.metadata(), policy_set.metadata());
+        let errors = WasmPolicySet::parse_in_format(&json, Some(InputFormat::Cedar)).unwrap_err();
+        assert!(!errors.is_empty());
+        assert_eq!(
+            WasmPolicySet::parse_in_format("1", None).unwrap_err(),
+            vec!["cannot tell whether the policy set is JSON or Cedar syntax, as it starts with `1`; give its format as `json` or `cedar`".to_string()]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let policy_set = WasmPolicySet::parse(
+            r#"
+            permit(principal == ?principal, action, resource);
+            forbid(principal, action, resource) when { resource.private };
+            "#,
+        )
+        .unwrap();
+        let mut with_link = policy_set.policy_set().clone();
+        with_link
+            .link(
+                policy_id("policy0"),
+                policy_id("alice"),
+                HashMap::from([(
+                    SlotId::principal(),
+                    EntityUid::from_str(r#"User::"alice""#).unwrap(),
+                )]),
+            )
+            .unwrap();
+        let policy_set = WasmPolicySet::from(with_link);
+
+        let result = policy_set.to_json();
+        assert!(serde_json::to_value(&result).unwrap()["success"]["json"].is_object());
+        let json = match result {
+            PolicySetToJsonResult::Success { json } => json,
+            PolicySetToJsonResult::Error { errors } => panic!("failed to convert: {errors:?}"),
+        };
+        assert_eq!(json.templates.len(), 1);
+        assert_eq!(json.static_policies.len(), 1);
+        assert_eq!(json.template_links.len(), 1);
+
+        let json = serde_json::to_string(&json).unwrap();
+        let reparsed = WasmPolicySet::parse_json(&json).unwrap();
+        assert_eq!(reparsed.metadata(), policy_set.metadata());
+        match reparsed.to_json() {
+            PolicySetToJsonResult::Success { json: rejson } => {
+                assert_eq!(serde_json::to_string(&rejson).unwrap(), json);
+            }
+            PolicySetToJsonResult::Error { errors } => panic!("failed to convert: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn edits_policies_by_id() {
+        let mut policy_set = WasmPolicySet::parse(POLICIES).unwrap();
+        policy_set
+            .insert(
+                "bob",
+                r#"permit(principal == User::"bob", action, resource);"#,
+            )
+            .unwrap();
+        assert_eq!(policy_set.metadata().policies.len(), 3);
+
+        let errors = policy_set
+            .insert("bob", "forbid(principal, action, resource);")
+            .unwrap_err();
+        assert_eq!(errors, vec!["duplicate template or policy id `bob`"]);
+        assert!(policy_set
+            .insert(
+                "template",
+                "permit(principal == ?principal, action, resource);"
+            )
+            .is_err());
+
+        policy_set
+            .replace("bob", "forbid(principal, action, resource);")
+            .unwrap();
+        let bob = policy_set.policy_set().policy(&policy_id("bob")).unwrap();
+        assert_eq!(bob.effect().to_string(), "forbid");
+        assert!(policy_set.replace("bob", "forbid(principal").is_err());
+        assert!(policy_set
+            .replace("carol", "forbid(principal, action, resource);")
+            .is_err());
+
+        policy_set.remove("bob").unwrap();
+        assert_eq!(policy_set.metadata().policies.len(), 2);
+        assert!(policy_set.remove("bob").is_err());
+    }
+
+    #[test]
+    fn reports_json_errors_per_policy() {
+        let json = r#"{
+            "staticPolicies": {
+                "bad": {
+                    "effect": "permit",
+                    "principal": { "op": "==", "slot": "?principal" },
+                    "action": { "op": "All" },
+                    "resource": { "op": "All" },
+                    "conditions": []
+                }
+            },
+            "templateLinks": [
+                { "templateId": "missing", "newId": "link", "values": {} }
+            ]
+        }"#;
+        let errors = WasmPolicySet::parse_json(json).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("error in policy `bad`"));
+        assert!(errors[1].starts_with("error in link `link`"));
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn checks_proposed_links() {
+        let policies = WasmPolicySet::parse(
+            r#"
+            permit(principal == ?principal, action, resource in ?resource);
+            permit(principal in ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let schema = WasmSchema::parse_cedar("entity User; entity Group; entity Folder;").unwrap();
+        let link = |template: &str, new_id: &str, values: serde_json::Value| TemplateLinkJson {
+            template_id: template.to_string(),
+            new_id: new_id.to_string(),
+            values: serde_json::from_value(values).unwrap(),
+        };
+        let user = serde_json::json!({ "type": "User", "id": "alice" });
+        let links = TemplateLinks(vec![
+            link(
+                "policy0",
+                "ok",
+                serde_json::json!({ "?principal": user, "?resource": { "type": "Folder", "id": "f" } }),
+            ),
+            link(
+                "policy0",
+                "missing",
+                serde_json::json!({ "?principal": user }),
+            ),
+            link(
+                "policy1",
+                "extra",
+                serde_json::json!({ "?principal": { "type": "Group", "id": "g" }, "?resource": user }),
+            ),
+            link(
+                "policy1",
+                "undeclared",
+                serde_json::json!({ "?principal": { "type": "Robot", "id": "r" } }),
+            ),
+            link(
+                "policy1",
+                "malformed",
+                serde_json::json!({ "?principal": "alice" }),
+            ),
+            link("policy1", "ok", serde_json::json!({ "?principal": user })),
+            link("policy2", "policy0", serde_json::json!({})),
+        ]);
+        let check = check_template_links(&policies, &schema, links);
+        assert!(!check.passed);
+        let errors: Vec<_> = check.links.iter().map(|link| link.errors.len()).collect();
+        assert_eq!(errors, vec![0, 1, 1, 1, 1, 1, 2]);
+        assert_eq!(
+            check.links[1].errors,
+            vec!["slot `?resource` of template `policy0` is not filled".to_string()]
+        );
+        assert_eq!(
+            check.links[2].errors,
+            vec!["template `policy1` has no slot `?resource`".to_string()]
+        );
+        assert!(check.links[3].errors[0].contains("`Robot`"));
+        assert!(check.links[4].errors[0].starts_with("error parsing value of `?principal`"));
+        assert_eq!(
+            check.links[5].errors,
+            vec!["the id `ok` is already in use".to_string()]
+        );
+        assert_eq!(
+            check.links[6].errors,
+            vec![
+                "the id `policy0` is already in use".to_string(),
+                "there is no template `policy2`".to_string()
+            ]
+        );
+
+        let valid = TemplateLinks(vec![link(
+            "policy1",
+            "link",
+            serde_json::json!({ "?principal": user }),
+        )]);
+        assert!(check_template_links(&policies, &schema, valid).passed);
+    }
+}
@@ -0,0 +1,489 @@
+//! This module contains `renameNamespace`, which moves the declarations of a
+//! namespace to another one, rewriting a schema, policies, and entities so
+//! that they keep referring to the same things.
+use std::str::FromStr;
+
+use cedar_policy::PolicySet;
+use cedar_policy_core::ast::Name;
+use cedar_policy_validator::SchemaFragment;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::formats::{detect_format, InputFormat};
+
+#[derive(Tsify, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The artifacts `renameNamespace` rewrites, any of which may be left out
+pub struct NamespaceArtifacts {
+    /// a schema, in its JSON format or the Cedar schema syntax
+    #[serde(default)]
+    #[tsify(optional)]
+    pub schema: Option<String>,
+    /// policies and templates as Cedar text
+    #[serde(default)]
+    #[tsify(optional)]
+    pub policies: Option<String>,
+    /// entities in their JSON format
+    #[serde(default)]
+    #[tsify(optional)]
+    pub entities: Option<String>,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `renameNamespace`
+pub enum RenameNamespaceResult {
+    /// the artifacts given, rewritten
+    Success { artifacts: NamespaceArtifacts },
+    /// a namespace or an artifact does not parse, or the schema already
+    /// declares the new namespace
+    Error { errors: Vec<String> },
+}
+
+/// Rename the namespace `oldNs`, e.g. `Acme`, to `newNs` in each of the
+/// artifacts given, along with the namespaces nested in it, e.g. `Acme::Hr`.
+/// Entity types and actions of the namespace are renamed wherever they are
+/// referred to, and nothing else is: entity ids, strings, and comments are
+/// left as they are, even if they contain the name of the namespace. Policies
+/// and a schema in the Cedar schema syntax keep their layout; a schema or
+/// entities in their JSON format are written out again, with object keys in
+/// sorted order.
+#[wasm_bindgen(js_name = "renameNamespace")]
+pub fn rename_namespace(
+    old_ns: &str,
+    new_ns: &str,
+    artifacts: NamespaceArtifacts,
+) -> RenameNamespaceResult {
+    let rename = match (Name::from_str(old_ns), Name::from_str(new_ns)) {
+        (Ok(old), Ok(new)) => Rename {
+            old: old.to_string(),
+            new: new.to_string(),
+        },
+        (Err(e), _) => {
+            return RenameNamespaceResult::Error {
+                errors: vec![format!("error parsing namespace `{old_ns}`: {e}")],
+            }
+        }
+        (_, Err(e)) => {
+            return RenameNamespaceResult::Error {
+                errors: vec![format!("error parsing namespace `{new_ns}`: {e}")],
+            }
+        }
+    };
+    let mut errors = Vec::new();
+    let mut renamed = NamespaceArtifacts::default();
+    if let Some(schema) = &artifacts.schema {
+        match rename.schema(schema) {
+            Ok(schema) => renamed.schema = Some(schema),
+            Err(e) => errors.push(e),
+        }
+    }
+    if let Some(policies) = &artifacts.policies {
+        match PolicySet::from_str(policies) {
+            Ok(_) => renamed.policies = Some(rename.text(policies, false)),
+            Err(e) => errors.push(format!("error parsing policies: {e}")),
+        }
+    }
+    if let Some(entities) = &artifacts.entities {
+        match serde_json::from_str(entities) {
+            Ok(json) => {
+                renamed.entities = Some(rename.entities(json).to_string());
+            }
+            Err(e) => errors.push(format!("error parsing entities: {e}")),
+        }
+    }
+    if errors.is_empty() {
+        RenameNamespaceResult::Success { artifacts: renamed }
+    } else {
+        RenameNamespaceResult::Error { errors }
+    }
+}
+
+/// A namespace and what to rename it to, both written out as Cedar text
+struct Rename {
+    old: String,
+    new: String,
+}
+
+impl Rename {
+    /// The qualified name `name`, written out as Cedar text, in the new
+    /// namespace if it is in the old one or in a namespace nested in it
+    fn name(&self, name: &str) -> Option<String> {
+        let rest = name.strip_prefix(&self.old)?.strip_prefix("::")?;
+        Some(format!("{}::{rest}", self.new))
+    }
+
+    /// The schema `text`, renamed, in the format it is in
+    fn schema(&self, text: &str) -> Result<String, String> {
+        let format = detect_format(text, "schema", None)?;
+        let fragment = match format {
+            InputFormat::Json => serde_json::from_str(text)
+                .map_err(|e| e.to_string())
+                .and_then(|json| SchemaFragment::from_json_value(json).map_err(|e| e.to_string())),
+            InputFormat::Cedar => SchemaFragment::from_str_natural(text)
+                .map(|(fragment, _)| fragment)
+                .map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("error parsing schema: {e}"))?;
+        if let Some(namespace) = fragment.0.keys().find(|namespace| {
+            namespace.as_str() == self.new
+                || namespace
+                    .strip_prefix(self.new.as_str())
+                    .is_some_and(|rest| rest.starts_with("::"))
+        }) {
+            return Err(format!(
+                "the schema already declares the namespace `{namespace}`"
+            ));
+        }
+        match format {
+            InputFormat::Json => {
+                let json = serde_json::to_value(fragment).map_err(|e| e.to_string())?;
+                serde_json::to_string_pretty(&self.schema_json(json)).map_err(|e| e.to_string())
+            }
+            InputFormat::Cedar => Ok(self.text(text, true)),
+        }
+    }
+
+    /// The JSON format of a schema, renamed: the namespaces, and every type
+    /// name, which is every string but the ids of action groups
+    fn schema_json(&self, json: Value) -> Value {
+        match json {
+            Value::Object(namespaces) => Value::Object(
+                namespaces
+                    .into_iter()
+                    .map(|(namespace, definition)| {
+                        let namespace = match self.name(&namespace) {
+                            Some(renamed) => renamed,
+                            None if namespace == self.old => self.new.clone(),
+                            None => namespace,
+                        };
+                        (namespace, self.schema_names(definition))
+                    })
+                    .collect(),
+            ),
+            json => json,
+        }
+    }
+
+    /// `json`, part of the JSON format of a schema, with every string that is
+    /// not the value of `id` renamed
+    fn schema_names(&self, json: Value) -> Value {
+        match json {
+            Value::String(name) => Value::String(self.name(&name).unwrap_or(name)),
+            Value::Array(elements) => Value::Array(
+                elements
+                    .into_iter()
+                    .map(|element| self.schema_names(element))
+                    .collect(),
+            ),
+            Value::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| match key.as_str() {
+                        "id" => (key, value),
+                        _ => (key, self.schema_names(value)),
+                    })
+                    .collect(),
+            ),
+            json => json,
+        }
+    }
+
+    /// Entities in their JSON format, renamed: the type of every uid, parent,
+    /// and entity reference, which are the strings that are the value of `type`
+    fn entities(&self, json: Value) -> Value {
+        match json {
+            Value::Array(elements) => Value::Array(
+                elements
+                    .into_iter()
+                    .map(|element| self.entities(element))
+                    .collect(),
+            ),
+            Value::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| match (key.as_str(), value) {
+                        ("type", Value::String(name)) => {
+                            let name = self.name(&name).unwrap_or(name);
+                            (key, Value::String(name))
+                        }
+                        (_, value) => (key, self.entities(value)),
+                    })
+                    .collect::<Map<_, _>>(),
+            ),
+            json => json,
+        }
+    }
+
+    /// Cedar text, renamed, as policies or, if `schema` is set, as a schema in
+    /// the Cedar schema syntax. Only the namespaces of names are rewritten:
+    /// every path but its last identifier, or, in a schema, the whole path
+    /// after `namespace`.
+    fn text(&self, text: &str, schema: bool) -> String {
+        let old: Vec<&str> = self.old.split("::").collect();
+        let tokens = tokens(text);
+        let kind = |i: usize| tokens.get(i).map(|(kind, ..)| *kind);
+        let source = |i: usize| {
+            tokens
+                .get(i)
+                .and_then(|&(_, start, end)| text.get(start..end))
+        };
+        let mut out = String::with_capacity(text.len());
+        let mut copied = 0;
+        let mut i = 0;
+        while let Some(&(token, start, _)) = tokens.get(i) {
+            // a path starts at an identifier that does not follow `::`
+            if token != Token::Ident || (i > 0 && kind(i - 1) == Some(Token::Colons)) {
+                i += 1;
+                continue;
+            }
+            let mut path = vec![i];
+            let mut next = i + 1;
+            while kind(next) == Some(Token::Colons) && kind(next + 1) == Some(Token::Ident) {
+                path.push(next + 1);
+                next += 2;
+            }
+            let declared = schema && i > 0 && source(i - 1) == Some("namespace");
+            let namespace_len = if declared { path.len() } else { path.len() - 1 };
+            let namespace = path.get(..namespace_len).unwrap_or_default();
+            if namespace.len() >= old.len()
+                && namespace
+                    .iter()
+                    .zip(&old)
+                    .all(|(&token, segment)| source(token) == Some(*segment))
+            {
+                let end = namespace
+                    .get(old.len() - 1)
+                    .and_then(|&token| tokens.get(token))
+                    .map(|&(_, _, end)| end);
+                if let (Some(end), Some(before)) = (end, text.get(copied..start)) {
+                    out.push_str(before);
+                    out.push_str(&self.new);
+                    copied = end;
+                }
+            }
+            i = next;
+        }
+        out.push_str(text.get(copied..).unwrap_or_default());
+        out
+    }
+}
+
+/// The kind of a token of Cedar text, as far as renaming needs to tell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Ident,
+    Colons,
+    /// a string, which is one token however long, or any other character
+    Other,
+}
+
+/// The tokens of Cedar text, with where each starts and ends, leaving out
+/// whitespace and comments
+fn tokens(text: &str) -> Vec<(Token, usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(&byte) = bytes.get(pos) {
+        let start = pos;
+        let kind = match byte {
+            b if b.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                while bytes.get(pos).is_some_and(|&b| b != b'\n') {
+                    pos += 1;
+                }
+                continue;
+            }
+            b'"' => {
+                pos += 1;
+                while let Some(&b) = bytes.get(pos) {
+                    pos += if b == b'\\' { 2 } else { 1 };
+                    if b == b'"' {
+                        break;
+                    }
+                }
+                Token::Other
+            }
+            b':' if bytes.get(pos + 1) == Some(&b':') => {
+                pos += 2;
+                Token::Colons
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                while bytes
+                    .get(pos)
+                    .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                {
+                    pos += 1;
+                }
+                Token::Ident
+            }
+            _ => {
+                // one character, however many bytes it takes
+                pos += text
+                    .get(pos..)
+                    .and_then(|rest| rest.chars().next())
+                    .map_or(1, char::len_utf8);
+                Token::Other
+            }
+        };
+        tokens.push((kind, start, pos.min(text.len())));
+    }
+    tokens
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn rename(artifacts: NamespaceArtifacts) -> NamespaceArtifacts {
+        match rename_namespace("Acme", "Globex", artifacts) {
+            RenameNamespaceResult::Success { artifacts } => artifacts,
+            RenameNamespaceResult::Error { errors } => panic!("expected a rename: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn renames_names_in_policies() {
+        let policies = r#"// Acme::User::"alice" may view Acme photos
+@id("Acme::view")
+permit(principal == Acme::User::"Acme::alice", action == Acme :: Action::"view", resource is Acme::Hr::Photo)
+when { resource.owner in Acme::Group::"admins" && principal.Acme == "Acme::x" && Acme2::User::"bob" == AcmeCo::User::"a" };
+"#;
+        let renamed = rename(NamespaceArtifacts {
+            policies: Some(policies.to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            renamed.policies.as_deref(),
+            Some(
+                r#"// Acme::User::"alice" may view Acme photos
+@id("Acme::view")
+permit(principal == Globex::User::"Acme::alice", action == Globex :: Action::"view", resource is Globex::Hr::Photo)
+when { resource.owner in Globex::Group::"admins" && principal.Acme == "Acme::x" && Acme2::User::"bob" == AcmeCo::User::"a" };
+"#
+            )
+        );
+        assert_eq!(renamed.schema, None);
+    }
+
+    #[test]
+    fn renames_names_in_schemas() {
+        let schema = r#"namespace Acme {
+    // users of Acme
+    entity User in [Acme::Hr::Team] { "Acme::tag": String };
+    action view appliesTo { principal: [User], resource: [Acme::Hr::Team] };
+}
+namespace Acme::Hr {
+    entity Team;
+}
+"#;
+        let renamed = rename(NamespaceArtifacts {
+            schema: Some(schema.to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            renamed.schema.as_deref(),
+            Some(
+                r#"namespace Globex {
+    // users of Acme
+    entity User in [Globex::Hr::Team] { "Acme::tag": String };
+    action view appliesTo { principal: [User], resource: [Globex::Hr::Team] };
+}
+namespace Globex::Hr {
+    entity Team;
+}
+"#
+            )
+        );
+
+        let json = json!({ "Acme": {
+            "entityTypes": { "User": { "memberOfTypes": ["Acme::Group"] }, "Group": {} },
+            "actions": {
+                "view": {
+                    "memberOf": [{ "id": "Acme::all", "type": "Acme::Action" }],
+                    "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Group"] }
+                },
+                "Acme::all": {}
+            }
+        }});
+        let renamed = rename(NamespaceArtifacts {
+            schema: Some(json.to_string()),
+            ..Default::default()
+        });
+        let renamed: Value = serde_json::from_str(&renamed.schema.unwrap_or_default()).unwrap();
+        assert_eq!(
+            renamed["Globex"]["entityTypes"]["User"]["memberOfTypes"],
+            json!(["Globex::Group"])
+        );
+        assert_eq!(
+            renamed["Globex"]["actions"]["view"]["memberOf"],
+            json!([{ "id": "Acme::all", "type": "Globex::Action" }])
+        );
+        assert!(renamed["Globex"]["actions"]["Acme::all"].is_object());
+    }
+
+    #[test]
+    fn renames_names_in_entities() {
+        let entities = json!([{
+            "uid": { "type": "Acme::User", "id": "Acme::alice" },
+            "attrs": {
+                "manager": { "__entity": { "type": "Acme::User", "id": "bob" } },
+                "team": { "type": "Acme::Hr::Team", "id": "ops" },
+                "note": "Acme::User",
+            },
+            "parents": [{ "type": "Acme::Group", "id": "admins" }, { "type": "Acme", "id": "x" }]
+        }]);
+        let renamed = rename(NamespaceArtifacts {
+            entities: Some(entities.to_string()),
+            ..Default::default()
+        });
+        let renamed: Value = serde_json::from_str(&renamed.entities.unwrap_or_default()).unwrap();
+        assert_eq!(
+            renamed,
+            json!([{
+                "uid": { "type": "Globex::User", "id": "Acme::alice" },
+                "attrs": {
+                    "manager": { "__entity": { "type": "Globex::User", "id": "bob" } },
+                    "team": { "type": "Globex::Hr::Team", "id": "ops" },
+                    "note": "Acme::User",
+                },
+                "parents": [{ "type": "Globex::Group", "id": "admins" }, { "type": "Acme", "id": "x" }]
+            }])
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(matches!(
+            rename_namespace("Acme", "not a name", NamespaceArtifacts::default()),
+            RenameNamespaceResult::Error { .. }
+        ));
+        let artifacts = NamespaceArtifacts {
+            schema: Some(
+                "namespace Acme { entity User; } namespace Globex { entity User; }".to_string(),
+            ),
+            policies: Some("permit(".to_string()),
+            entities: None,
+        };
+        match rename_namespace("Acme", "Globex", artifacts) {
+            RenameNamespaceResult::Error { errors } => {
+                assert_eq!(errors.len(), 2);
+                assert_eq!(
+                    errors.first().map(String::as_str),
+                    Some("the schema already declares the namespace `Globex`")
+                );
+            }
+            result => panic!("expected errors: {result:?}"),
+        }
+    }
+}
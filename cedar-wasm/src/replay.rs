@@ -0,0 +1,276 @@
+//! This module contains `replayRequests`, which answers a log of captured
+//! requests against a policy set, e.g. to try out a policy change, and
+//! `compareDecisions`, which finds the requests two policy sets disagree on.
+use std::collections::BTreeMap;
+
+use cedar_policy::{
+    frontend::{is_authorized::json_is_authorized_with, utils::InterfaceResult},
+    Decision,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{errors_to_js, WasmEntities, WasmPolicySet};
+
+#[derive(Tsify, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The decisions for a log of requests replayed against a policy set
+pub struct ReplayResults {
+    /// the decision for each request, in order, or `null` for a request that
+    /// could not be answered
+    pub decisions: Vec<Option<Decision>>,
+    /// the errors answering each request that could not be answered, by its
+    /// index in the log
+    pub errors: BTreeMap<usize, Vec<String>>,
+    /// how many requests were allowed
+    pub allowed: usize,
+    /// how many requests were denied
+    pub denied: usize,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A request that two policy sets decide differently
+pub struct DecisionDifference {
+    /// the index of the request in the log
+    pub index: usize,
+    /// the decision under the first policy set
+    pub decision_a: Decision,
+    /// the decision under the second policy set
+    pub decision_b: Decision,
+    /// the ids of the policies that determined the first decision, sorted
+    pub determining_a: Vec<String>,
+    /// the ids of the policies that determined the second decision, sorted
+    pub determining_b: Vec<String>,
+}
+
+#[derive(Tsify, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The requests of a log that two policy sets decide differently
+pub struct DecisionComparison {
+    /// the requests decided differently, in order
+    pub differences: Vec<DecisionDifference>,
+    /// the errors answering each request that either policy set could not
+    /// answer, by its index in the log
+    pub errors: BTreeMap<usize, Vec<String>>,
+}
+
+/// Answer each request of `requests`, a JSON array of requests as taken by
+/// `isAuthorizedWith`, against `policies` and `entities`. Other fields of the
+/// logged requests, e.g. the decision they were given when captured, are
+/// ignored. Throws only if `requests` is not a JSON array.
+#[wasm_bindgen(js_name = "replayRequests")]
+pub fn replay_requests(
+    policies: &WasmPolicySet,
+    entities: &WasmEntities,
+    requests: &str,
+) -> Result<ReplayResults, JsError> {
+    replay(policies, entities, requests).map_err(|errors| errors_to_js(&errors))
+}
+
+fn replay(
+    policies: &WasmPolicySet,
+    entities: &WasmEntities,
+    requests: &str,
+) -> Result<ReplayResults, Vec<String>> {
+    let mut results = ReplayResults::default();
+    for (index, request) in parse_requests(requests)?.iter().enumerate() {
+        match answer(policies, entities, request) {
+            Ok((decision, _)) => {
+                match decision {
+                    Decision::Allow => results.allowed += 1,
+                    Decision::Deny => results.denied += 1,
+                }
+                results.decisions.push(Some(decision));
+            }
+            Err(errors) => {
+                results.errors.insert(index, errors);
+                results.decisions.push(None);
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Answer each request of `requests`, as taken by `replayRequests`, against
+/// both `policiesA` and `policiesB`, returning only those whose decisions
+/// differ, with the policies that determined each decision. Throws only if
+/// `requests` is not a JSON array.
+#[wasm_bindgen(js_name = "compareDecisions")]
+pub fn compare_decisions(
+    policies_a: &WasmPolicySet,
+    policies_b: &WasmPolicySet,
+    entities: &WasmEntities,
+    requests: &str,
+) -> Result<DecisionComparison, JsError> {
+    compare(policies_a, policies_b, entities, requests).map_err(|errors| errors_to_js(&errors))
+}
+
+fn compare(
+    policies_a: &WasmPolicySet,
+    policies_b: &WasmPolicySet,
+    entities: &WasmEntities,
+    requests: &str,
+) -> Result<DecisionComparison, Vec<String>> {
+    let mut comparison = DecisionComparison::default();
+    for (index, request) in parse_requests(requests)?.iter().enumerate() {
+        match answer(policies_a, entities, request)
+            .and_then(|a| Ok((a, answer(policies_b, entities, request)?)))
+        {
+            Ok(((decision_a, determining_a), (decision_b, determining_b))) => {
+                if decision_a != decision_b {
+                    comparison.differences.push(DecisionDifference {
+                        index,
+                        decision_a,
+                        decision_b,
+                        determining_a,
+                        determining_b,
+                    });
+                }
+            }
+            Err(errors) => {
+                comparison.errors.insert(index, errors);
+            }
+        }
+    }
+    Ok(comparison)
+}
+
+fn parse_requests(requests: &str) -> Result<Vec<Box<RawValue>>, Vec<String>> {
+    serde_json::from_str(requests).map_err(|e| vec![format!("error parsing requests: {e}")])
+}
+
+/// The decision for `request` against `policies` and `entities`, with the ids
+/// of the policies that determined it
+fn answer(
+    policies: &WasmPolicySet,
+    entities: &WasmEntities,
+    request: &RawValue,
+) -> Result<(Decision, Vec<String>), Vec<String>> {
+    match json_is_authorized_with(
+        request.get(),
+        policies.policy_set(),
+        entities.entities(),
+        None,
+        None,
+    ) {
+        InterfaceResult::Success { result, .. } => decision(&result)
+            .ok_or_else(|| vec!["the answer to the request has no decision".to_string()]),
+        InterfaceResult::Failure { errors, .. } => Err(errors),
+    }
+}
+
+/// The decision in the answer `result` to a request, with its reason
+fn decision(result: &str) -> Option<(Decision, Vec<String>)> {
+    #[derive(Deserialize)]
+    struct Answer {
+        response: Response,
+    }
+    #[derive(Deserialize)]
+    struct Response {
+        decision: Decision,
+        diagnostics: Diagnostics,
+    }
+    #[derive(Deserialize)]
+    struct Diagnostics {
+        reason: Vec<String>,
+    }
+    let Answer { response } = serde_json::from_str(result).ok()?;
+    let mut reason = response.diagnostics.reason;
+    reason.sort();
+    Some((response.decision, reason))
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replays_requests() {
+        let policies = WasmPolicySet::parse(
+            r#"permit(principal == User::"alice", action, resource);
+            forbid(principal, action, resource) when { context.blocked };"#,
+        )
+        .unwrap();
+        let entities = WasmEntities::parse("[]", None, Default::default()).unwrap();
+        let request = |principal: &str, blocked: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": principal },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "beach" },
+                "context": { "blocked": blocked },
+                "decision": "Allow"
+            })
+        };
+        let requests = serde_json::json!([
+            request("alice", false),
+            request("alice", true),
+            request("bob", false),
+            { "principal": "not a uid", "action": { "type": "Action", "id": "view" } }
+        ]);
+        let results = replay(&policies, &entities, &requests.to_string()).unwrap();
+        assert_eq!(
+            results.decisions,
+            vec![
+                Some(Decision::Allow),
+                Some(Decision::Deny),
+                Some(Decision::Deny),
+                None
+            ]
+        );
+        assert_eq!((results.allowed, results.denied), (1, 2));
+        assert_eq!(results.errors.keys().copied().collect::<Vec<_>>(), vec![3]);
+        assert!(replay(&policies, &entities, "{}").is_err());
+    }
+
+    #[test]
+    fn compares_decisions() {
+        let policies_a =
+            WasmPolicySet::parse(r#"permit(principal == User::"alice", action, resource);"#)
+                .unwrap();
+        let policies_b = WasmPolicySet::parse(
+            r#"permit(principal == User::"alice", action, resource);
+            forbid(principal, action, resource) when { context.blocked };"#,
+        )
+        .unwrap();
+        let entities = WasmEntities::parse("[]", None, Default::default()).unwrap();
+        let request = |principal: &str, blocked: bool| {
+            serde_json::json!({
+                "principal": { "type": "User", "id": principal },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "beach" },
+                "context": { "blocked": blocked }
+            })
+        };
+        let requests = serde_json::json!([
+            request("alice", false),
+            request("alice", true),
+            request("bob", true),
+            { "principal": "not a uid", "action": { "type": "Action", "id": "view" } }
+        ]);
+        let comparison =
+            compare(&policies_a, &policies_b, &entities, &requests.to_string()).unwrap();
+        assert_eq!(comparison.differences.len(), 1);
+        assert_eq!(
+            comparison.differences.first(),
+            Some(&DecisionDifference {
+                index: 1,
+                decision_a: Decision::Allow,
+                decision_b: Decision::Deny,
+                determining_a: vec!["policy0".to_string()],
+                determining_b: vec!["policy1".to_string()],
+            })
+        );
+        assert_eq!(
+            comparison.errors.keys().copied().collect::<Vec<_>>(),
+            vec![3]
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! This module contains `validateRequest`, which checks that a request is
+//! well-formed for a schema before it is authorized, e.g. to reject malformed
+//! input at a policy enforcement point.
+use cedar_policy::{Context, ContextJsonError, EntityUid, Request};
+use cedar_policy_validator::RequestValidationError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{EntityUidInput, WasmSchema};
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The constraint of a schema that a request fails
+pub enum RequestConstraint {
+    /// the action is not declared in the schema
+    UndeclaredAction,
+    /// the principal's type is not declared in the schema
+    UndeclaredPrincipalType,
+    /// the resource's type is not declared in the schema
+    UndeclaredResourceType,
+    /// the action does not apply to the principal's type
+    InvalidPrincipalType,
+    /// the action does not apply to the resource's type
+    InvalidResourceType,
+    /// the context does not have the shape the schema declares for the action
+    InvalidContext,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of checking a request against a schema
+pub enum ValidateRequestResult {
+    /// the request conforms to the schema
+    Success {},
+    /// the request does not conform to the schema, failing `constraint`
+    Invalid {
+        constraint: RequestConstraint,
+        message: String,
+    },
+    /// the uids or the context do not parse
+    Error { errors: Vec<String> },
+}
+
+/// Check that a request conforms to a schema handle: that its action is
+/// declared and applies to the types of its principal and resource, and that
+/// its context, in its JSON format, has the shape declared for the action.
+/// Context values may leave `__entity` and `__extn` escapes implicit, as in
+/// `isAuthorized` with a schema.
+#[wasm_bindgen(js_name = "validateRequest")]
+pub fn validate_request(
+    schema: &WasmSchema,
+    principal: EntityUidInput,
+    action: EntityUidInput,
+    resource: EntityUidInput,
+    context: &str,
+) -> ValidateRequestResult {
+    match check_request(schema, &principal, &action, &resource, context) {
+        Ok(Ok(())) => ValidateRequestResult::Success {},
+        Ok(Err((constraint, message))) => ValidateRequestResult::Invalid {
+            constraint,
+            message,
+        },
+        Err(errors) => ValidateRequestResult::Error { errors },
+    }
+}
+
+/// The constraint the request fails, if any, with a message describing how.
+/// Errors are for input that does not parse.
+fn check_request(
+    schema: &WasmSchema,
+    principal: &EntityUidInput,
+    action: &EntityUidInput,
+    resource: &EntityUidInput,
+    context: &str,
+) -> Result<Result<(), (RequestConstraint, String)>, Vec<String>> {
+    let uid = |uid: &EntityUidInput, what: &str| {
+        uid.to_uid()
+            .map_err(|e| format!("error parsing {what} uid {e}"))
+    };
+    let uids = [
+        uid(principal, "principal"),
+        uid(action, "action"),
+        uid(resource, "resource"),
+    ];
+    let errors = uids
+        .iter()
+        .filter_map(|uid| uid.as_ref().err().cloned())
+        .collect::<Vec<_>>();
+    let [Ok(principal), Ok(action), Ok(resource)] = uids else {
+        return Err(errors);
+    };
+    let context = match serde_json::from_str::<Value>(context) {
+        Ok(context @ Value::Object(_)) => context,
+        Ok(_) => return Err(vec!["context must be a JSON object".to_string()]),
+        Err(e) => return Err(vec![format!("error parsing context: {e}")]),
+    };
+    let context = match Context::from_json_value(context, Some((schema.schema(), &action))) {
+        Ok(context) => context,
+        Err(e @ ContextJsonError::MissingAction { .. }) => {
+            return Ok(Err((RequestConstraint::UndeclaredAction, e.to_string())))
+        }
+        Err(e) => return Ok(Err((RequestConstraint::InvalidContext, e.to_string()))),
+    };
+    Ok(request_constraint(
+        principal, action, resource, context, schema,
+    ))
+}
+
+fn request_constraint(
+    principal: EntityUid,
+    action: EntityUid,
+    resource: EntityUid,
+    context: Context,
+    schema: &WasmSchema,
+) -> Result<(), (RequestConstraint, String)> {
+    Request::new(
+        Some(principal),
+        Some(action),
+        Some(resource),
+        context,
+        Some(schema.schema()),
+    )
+    .map(|_| ())
+    .map_err(|e| {
+        let constraint = match &e {
+            RequestValidationError::UndeclaredAction { .. } => RequestConstraint::UndeclaredAction,
+            RequestValidationError::UndeclaredPrincipalType { .. } => {
+                RequestConstraint::UndeclaredPrincipalType
+            }
+            RequestValidationError::UndeclaredResourceType { .. } => {
+                RequestConstraint::UndeclaredResourceType
+            }
+            RequestValidationError::InvalidPrincipalType { .. } => {
+                RequestConstraint::InvalidPrincipalType
+            }
+            RequestValidationError::InvalidResourceType { .. } => {
+                RequestConstraint::InvalidResourceType
+            }
+            RequestValidationError::InvalidContext { .. }
+            | RequestValidationError::TypeOfContext(_) => RequestConstraint::InvalidContext,
+        };
+        (constraint, e.to_string())
+    })
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        entity User;
+        entity Photo;
+        entity Group;
+        action view appliesTo {
+            principal: [User],
+            resource: [Photo],
+            context: { mfa: Bool }
+        };
+    "#;
+
+    fn check(
+        principal: &str,
+        action: &str,
+        resource: &str,
+        context: &str,
+    ) -> ValidateRequestResult {
+        let schema = WasmSchema::parse_in_format(SCHEMA, None).unwrap();
+        let uid = |text: &str| EntityUidInput::Text(text.to_string());
+        validate_request(&schema, uid(principal), uid(action), uid(resource), context)
+    }
+
+    fn constraint(result: ValidateRequestResult) -> RequestConstraint {
+        match result {
+            ValidateRequestResult::Invalid { constraint, .. } => constraint,
+            result => panic!("expected the request to be invalid: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn validates_requests_against_schema() {
+        let (user, view, photo) = (r#"User::"alice""#, r#"Action::"view""#, r#"Photo::"beach""#);
+        let mfa = r#"{ "mfa": true }"#;
+        assert!(matches!(
+            check(user, view, photo, mfa),
+            ValidateRequestResult::Success {}
+        ));
+        assert_eq!(
+            constraint(check(user, r#"Action::"edit""#, photo, mfa)),
+            RequestConstraint::UndeclaredAction
+        );
+        assert_eq!(
+            constraint(check(r#"Robot::"r2""#, view, photo, mfa)),
+            RequestConstraint::UndeclaredPrincipalType
+        );
+        assert_eq!(
+            constraint(check(user, view, r#"Video::"clip""#, mfa)),
+            RequestConstraint::UndeclaredResourceType
+        );
+        assert_eq!(
+            constraint(check(r#"Group::"staff""#, view, photo, mfa)),
+            RequestConstraint::InvalidPrincipalType
+        );
+        assert_eq!(
+            constraint(check(user, view, r#"Group::"staff""#, mfa)),
+            RequestConstraint::InvalidResourceType
+        );
+        assert_eq!(
+            constraint(check(user, view, photo, r#"{ "mfa": "yes" }"#)),
+            RequestConstraint::InvalidContext
+        );
+        assert_eq!(
+            constraint(check(user, view, photo, "{}")),
+            RequestConstraint::InvalidContext
+        );
+    }
+
+    #[test]
+    fn reports_input_that_does_not_parse() {
+        match check("alice", r#"Action::"view""#, "Photo", "[]") {
+            ValidateRequestResult::Error { errors } => assert_eq!(errors.len(), 2),
+            result => panic!("expected errors: {result:?}"),
+        }
+        match check(
+            r#"User::"alice""#,
+            r#"Action::"view""#,
+            r#"Photo::"beach""#,
+            "[]",
+        ) {
+            ValidateRequestResult::Error { errors } => {
+                assert_eq!(errors, vec!["context must be a JSON object".to_string()]);
+            }
+            result => panic!("expected errors: {result:?}"),
+        }
+    }
+}
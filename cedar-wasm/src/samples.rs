@@ -0,0 +1,494 @@
+//! This module contains `generateSampleEntities` and `generateSampleRequests`,
+//! which make up entities and requests that conform to a schema, e.g. to seed
+//! a playground or a test without writing fixtures by hand.
+use std::collections::BTreeMap;
+
+use cedar_policy_core::ast::{EntityType, EntityUID, Name};
+use cedar_policy_validator::{
+    types::{AttributeType, EntityRecordKind, Primitive, Type},
+    ValidatorEntityType,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{describe_schema, EntityUidInput, WasmSchema};
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The number of entities to make up of each entity type, keyed by the fully
+/// qualified name of the type
+pub struct SampleCounts(pub BTreeMap<String, usize>);
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `generateSampleEntities`
+pub enum SampleEntitiesResult {
+    /// the entities, as a JSON array in the JSON format of entities
+    Success { entities: String },
+    /// a count is for an entity type the schema does not declare
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A request that conforms to a schema
+pub struct SampleRequest {
+    pub principal: EntityUidInput,
+    pub action: EntityUidInput,
+    pub resource: EntityUidInput,
+    /// the context, in its JSON format with explicit escapes
+    #[tsify(type = "Record<string, any>")]
+    pub context: Value,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The requests `generateSampleRequests` makes up
+pub struct SampleRequests(pub Vec<SampleRequest>);
+
+/// Make up `countsPerType` entities of each entity type, with attributes of
+/// the types the schema handle declares and parents of the types it allows
+/// them to be members of directly, none of them forming a cycle. The same schema,
+/// counts, and `seed` always give the same entities. Entity ids are the
+/// lowercased type name and a number, e.g. `user-0`. Entity references and
+/// extension values are written with `__entity` and `__extn` escapes, so the
+/// entities load with or without the schema. A reference to a type with no
+/// entities made up refers to an entity that does not exist.
+#[wasm_bindgen(js_name = "generateSampleEntities")]
+pub fn generate_sample_entities(
+    schema: &WasmSchema,
+    counts_per_type: SampleCounts,
+    seed: u32,
+) -> SampleEntitiesResult {
+    let validator_schema = schema.validator_schema();
+    let mut types: Vec<(&Name, &ValidatorEntityType)> = validator_schema.entity_types().collect();
+    types.sort_by_key(|(name, _)| name.to_string());
+    let errors: Vec<_> = counts_per_type
+        .0
+        .keys()
+        .filter(|ty| !types.iter().any(|(name, _)| &&name.to_string() == ty))
+        .map(|ty| format!("the schema does not declare the entity type `{ty}`"))
+        .collect();
+    if !errors.is_empty() {
+        return SampleEntitiesResult::Error { errors };
+    }
+    let counts: BTreeMap<_, _> = types
+        .iter()
+        .map(|(name, _)| {
+            let count = counts_per_type
+                .0
+                .get(&name.to_string())
+                .copied()
+                .unwrap_or(0);
+            ((*name).clone(), count)
+        })
+        .collect();
+    // the types each type is declared a member of, not their ancestors
+    let member_of: BTreeMap<_, _> = describe_schema(schema)
+        .entity_types
+        .into_iter()
+        .map(|ty| (ty.name, ty.parents))
+        .collect();
+    let mut generator = Generator {
+        rng: SplitMix64(u64::from(seed)),
+        counts: &counts,
+    };
+    let mut entities = Vec::new();
+    for (name, entity_type) in &types {
+        let parent_types = member_of.get(&name.to_string());
+        for index in 0..counts.get(*name).copied().unwrap_or(0) {
+            let attrs = generator.record(entity_type.attributes());
+            let parents: Vec<_> = types
+                .iter()
+                .filter(|(parent, _)| {
+                    parent_types.is_some_and(|parents| parents.contains(&parent.to_string()))
+                        && may_be_parent(&types, parent, name)
+                })
+                .filter_map(|(parent, _)| {
+                    // a parent of the same type comes before the entity
+                    let candidates = if parent == name {
+                        index
+                    } else {
+                        counts.get(*parent).copied().unwrap_or(0)
+                    };
+                    (candidates > 0 && generator.rng.below(2) == 0)
+                        .then(|| uid_json(parent, generator.rng.below(candidates)))
+                })
+                .collect();
+            entities.push(json!({
+                "uid": uid_json(name, index),
+                "attrs": attrs,
+                "parents": parents,
+            }));
+        }
+    }
+    match serde_json::to_string(&entities) {
+        Ok(entities) => SampleEntitiesResult::Success { entities },
+        Err(e) => SampleEntitiesResult::Error {
+            errors: vec![e.to_string()],
+        },
+    }
+}
+
+/// Make up a request for each action of a schema handle with each principal
+/// type and resource type it applies to, in the order of the actions and then
+/// of the types. The principal and resource are the first entities
+/// `generateSampleEntities` makes up of their types, e.g. `user-0`, and the
+/// context has the attributes the action requires and no others, each with
+/// the least value of its type: `0`, `""`, `false`, an empty set, the first
+/// entity of the first type it may be, or `0.0.0.0` or `0.0` for extension
+/// types. An action that applies to no principal type or to no resource type
+/// has no requests.
+#[wasm_bindgen(js_name = "generateSampleRequests")]
+pub fn generate_sample_requests(schema: &WasmSchema) -> SampleRequests {
+    let validator_schema = schema.validator_schema();
+    let mut requests = Vec::new();
+    for action in schema.actions() {
+        let Ok(action) = action.parse::<EntityUID>() else {
+            continue;
+        };
+        let Some(action_id) = validator_schema.get_action_id(&action) else {
+            continue;
+        };
+        let specified = |types: &mut dyn Iterator<Item = &EntityType>| {
+            let mut names: Vec<_> = types
+                .filter_map(|ty| match ty {
+                    EntityType::Specified(name) => Some(name.clone()),
+                    EntityType::Unspecified => None,
+                })
+                .collect();
+            names.sort_by_key(ToString::to_string);
+            names
+        };
+        let principal_types = specified(&mut action_id.applies_to_principals());
+        let resource_types = specified(&mut action_id.applies_to_resources());
+        let context = minimal_value(&action_id.context_type());
+        let id: &str = action.eid().as_ref();
+        for principal in &principal_types {
+            for resource in &resource_types {
+                requests.push(SampleRequest {
+                    principal: uid_input(principal, 0),
+                    action: EntityUidInput::Parts {
+                        entity_type: action.entity_type().to_string(),
+                        id: id.to_string(),
+                    },
+                    resource: uid_input(resource, 0),
+                    context: context.clone(),
+                });
+            }
+        }
+    }
+    SampleRequests(requests)
+}
+
+/// The least value of type `ty`, as `generateSampleRequests` describes it
+fn minimal_value(ty: &Type) -> Value {
+    match ty {
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => json!(0),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        } => json!(""),
+        Type::True => json!(true),
+        Type::False
+        | Type::Primitive {
+            primitive_type: Primitive::Bool,
+        } => json!(false),
+        Type::Set { .. } => json!([]),
+        Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. }) => Value::Object(
+            attrs
+                .iter()
+                .filter(|(_, attribute)| attribute.is_required)
+                .map(|(name, attribute)| (name.to_string(), minimal_value(&attribute.attr_type)))
+                .collect(),
+        ),
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+            match lub.iter().min_by_key(|name| name.to_string()) {
+                Some(ty) => json!({ "__entity": uid_json(ty, 0) }),
+                None => Value::Null,
+            }
+        }
+        Type::ExtensionType { name } => match name.to_string().as_str() {
+            "ipaddr" => json!({ "__extn": { "fn": "ip", "arg": "0.0.0.0" } }),
+            "decimal" => json!({ "__extn": { "fn": "decimal", "arg": "0.0" } }),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+/// Whether entities of type `parent` may be made parents of entities of type
+/// `child` of `types`, which allow it. Where each type may also be an
+/// ancestor of the other, only the type with the greater name may be the
+/// parent, so that no entities form a cycle; a type that allows itself is
+/// handled by the caller.
+fn may_be_parent(types: &[(&Name, &ValidatorEntityType)], parent: &Name, child: &Name) -> bool {
+    let mutual = types
+        .iter()
+        .any(|(name, ty)| *name == child && ty.descendants.contains(parent));
+    parent == child || !mutual || parent.to_string() > child.to_string()
+}
+
+/// The uid of the `index`th entity made up of type `ty`
+fn uid_input(ty: &Name, index: usize) -> EntityUidInput {
+    EntityUidInput::Parts {
+        entity_type: ty.to_string(),
+        id: format!("{}-{index}", ty.basename().to_string().to_lowercase()),
+    }
+}
+
+/// The JSON format of the uid of the `index`th entity made up of type `ty`
+fn uid_json(ty: &Name, index: usize) -> Value {
+    serde_json::to_value(uid_input(ty, index)).unwrap_or(Value::Null)
+}
+
+/// Makes up attribute values
+struct Generator<'a> {
+    rng: SplitMix64,
+    /// the number of entities made up of each entity type
+    counts: &'a BTreeMap<Name, usize>,
+}
+
+impl Generator<'_> {
+    /// A record with `attributes`, each optional one present half the time
+    fn record<'b, N: AsRef<str> + 'b>(
+        &mut self,
+        attributes: impl Iterator<Item = (&'b N, &'b AttributeType)>,
+    ) -> Value {
+        let mut record = Map::new();
+        for (name, attribute) in attributes {
+            if attribute.is_required || self.rng.below(2) == 0 {
+                let value = self.value(name.as_ref(), &attribute.attr_type);
+                record.insert(name.as_ref().to_string(), value);
+            }
+        }
+        Value::Object(record)
+    }
+
+    /// A value of type `ty` for the attribute `attr`
+    fn value(&mut self, attr: &str, ty: &Type) -> Value {
+        match ty {
+            Type::Primitive {
+                primitive_type: Primitive::Long,
+            } => json!(self.rng.below(100)),
+            Type::Primitive {
+                primitive_type: Primitive::String,
+            } => json!(format!("{attr}-{}", self.rng.below(100))),
+            Type::True => json!(true),
+            Type::False => json!(false),
+            Type::Primitive {
+                primitive_type: Primitive::Bool,
+            } => json!(self.rng.below(2) == 0),
+            Type::Set { element_type } => {
+                let elements = match element_type {
+                    Some(element_type) => (0..self.rng.below(4))
+                        .map(|_| self.value(attr, element_type))
+                        .collect(),
+                    None => Vec::new(),
+                };
+                Value::Array(elements)
+            }
+            Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. }) => {
+                self.record(attrs.iter())
+            }
+            Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+                let types: Vec<_> = lub.iter().collect();
+                let Some(ty) = types.get(self.rng.below(types.len())) else {
+                    return Value::Null;
+                };
+                let count = self.counts.get(*ty).copied().unwrap_or(0);
+                json!({ "__entity": uid_json(ty, self.rng.below(count.max(1))) })
+            }
+            Type::ExtensionType { name } => {
+                let (constructor, arg) = match name.to_string().as_str() {
+                    "ipaddr" => (
+                        "ip",
+                        format!("10.0.{}.{}", self.rng.below(256), self.rng.below(256)),
+                    ),
+                    "decimal" => (
+                        "decimal",
+                        format!("{}.{:02}", self.rng.below(100), self.rng.below(100)),
+                    ),
+                    _ => return Value::Null,
+                };
+                json!({ "__extn": { "fn": constructor, "arg": arg } })
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+/// The SplitMix64 generator: small, fast, and the same on every platform,
+/// which is all that made-up entities need
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `bound`, or 0 if `bound` is 0
+    fn below(&mut self, bound: usize) -> usize {
+        match u64::try_from(bound) {
+            Ok(bound) if bound > 0 => usize::try_from(self.next() % bound).unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        validate_entities, validate_request, ValidateEntitiesResult, ValidateRequestResult,
+    };
+
+    const SCHEMA: &str = r#"
+        namespace App {
+            type Address = { city: String, zip?: String };
+            entity Org;
+            entity Group in [Group, Org];
+            entity User in [Group] {
+                name: String,
+                age?: Long,
+                admin: Bool,
+                home: Address,
+                tags: Set<String>,
+                manager: User,
+                ip: ipaddr,
+                score: decimal,
+            };
+            action view appliesTo {
+                principal: [User],
+                resource: [Group, Org],
+                context: {
+                    source: ipaddr,
+                    reason?: String,
+                    approver: { user: User, level: Long },
+                    tags: Set<String>,
+                }
+            };
+            action audit appliesTo { principal: [User], resource: [Org] };
+            action ping;
+        }
+    "#;
+
+    fn generate(counts: &[(&str, usize)], seed: u32) -> String {
+        let schema = WasmSchema::parse_cedar(SCHEMA).unwrap();
+        let counts = SampleCounts(
+            counts
+                .iter()
+                .map(|(ty, count)| (ty.to_string(), *count))
+                .collect(),
+        );
+        match generate_sample_entities(&schema, counts, seed) {
+            SampleEntitiesResult::Success { entities } => entities,
+            SampleEntitiesResult::Error { errors } => panic!("expected entities: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn generates_entities_conforming_to_schema() {
+        let counts = [("App::User", 20), ("App::Group", 10), ("App::Org", 2)];
+        let schema = WasmSchema::parse_cedar(SCHEMA).unwrap();
+        for seed in 0..5 {
+            let entities = generate(&counts, seed);
+            let json: Vec<Value> = serde_json::from_str(&entities).unwrap();
+            assert_eq!(json.len(), 32);
+            // users are members of groups directly, never of organizations
+            for user in json.iter().filter(|e| e["uid"]["type"] == "App::User") {
+                for parent in user["parents"].as_array().unwrap() {
+                    assert_eq!(parent["type"], "App::Group");
+                }
+            }
+            // the entities conform to the schema and form no cycle
+            assert!(
+                matches!(
+                    validate_entities(&schema, &entities),
+                    ValidateEntitiesResult::Success { .. }
+                ),
+                "{entities}"
+            );
+        }
+        assert_eq!(generate(&counts, 7), generate(&counts, 7));
+        assert_ne!(generate(&counts, 7), generate(&counts, 8));
+    }
+
+    #[test]
+    fn rejects_undeclared_types() {
+        let schema = WasmSchema::parse_cedar(SCHEMA).unwrap();
+        let counts = SampleCounts(BTreeMap::from([("App::Robot".to_string(), 1)]));
+        assert!(matches!(
+            generate_sample_entities(&schema, counts, 0),
+            SampleEntitiesResult::Error { .. }
+        ));
+        assert_eq!(generate(&[], 0), "[]");
+    }
+
+    #[test]
+    fn generates_requests_conforming_to_schema() {
+        let schema = WasmSchema::parse_cedar(SCHEMA).unwrap();
+        let SampleRequests(requests) = generate_sample_requests(&schema);
+        let summary: Vec<_> = requests
+            .iter()
+            .map(|request| {
+                serde_json::to_value([&request.principal, &request.action, &request.resource])
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                json!([
+                    { "type": "App::User", "id": "user-0" },
+                    { "type": "App::Action", "id": "audit" },
+                    { "type": "App::Org", "id": "org-0" },
+                ]),
+                json!([
+                    { "type": "App::User", "id": "user-0" },
+                    { "type": "App::Action", "id": "view" },
+                    { "type": "App::Group", "id": "group-0" },
+                ]),
+                json!([
+                    { "type": "App::User", "id": "user-0" },
+                    { "type": "App::Action", "id": "view" },
+                    { "type": "App::Org", "id": "org-0" },
+                ]),
+            ]
+        );
+        assert_eq!(
+            requests[1].context,
+            json!({
+                "source": { "__extn": { "fn": "ip", "arg": "0.0.0.0" } },
+                "approver": {
+                    "user": { "__entity": { "type": "App::User", "id": "user-0" } },
+                    "level": 0,
+                },
+                "tags": [],
+            })
+        );
+        for request in requests {
+            assert!(matches!(
+                validate_request(
+                    &schema,
+                    request.principal,
+                    request.action,
+                    request.resource,
+                    &request.context.to_string(),
+                ),
+                ValidateRequestResult::Success {}
+            ));
+        }
+    }
+}
@@ -0,0 +1,758 @@
+//! This module contains the `WasmSchema` handle, which holds a parsed schema
+//! so that it can be reused across validation, entity parsing, and
+//! schema-aware authorization without re-parsing the schema JSON.
+#[cfg(feature = "full")]
+use std::str::FromStr;
+
+use cedar_policy::Schema;
+#[cfg(feature = "full")]
+use cedar_policy_core::{
+    ast::{Eid, EntityUID, Name},
+    extensions::Extensions,
+};
+#[cfg(feature = "full")]
+use cedar_policy_validator::{
+    types::{AttributeType, EntityRecordKind, Primitive, Type},
+    SchemaFragment, ValidatorSchema,
+};
+#[cfg(feature = "full")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "full")]
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    errors_to_js,
+    formats::{detect_format, InputFormat},
+};
+
+/// A parsed schema, held on the wasm side of the boundary
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WasmSchema {
+    schema: Schema,
+    /// the same schema, kept for introspection
+    #[cfg(feature = "full")]
+    validator_schema: ValidatorSchema,
+    /// the JSON the schema was parsed from, kept for snapshots
+    json: serde_json::Value,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The principal and resource types an action applies to
+pub struct ActionAppliesTo {
+    pub principal_types: Vec<String>,
+    pub resource_types: Vec<String>,
+}
+
+impl WasmSchema {
+    /// Parse a schema from its JSON format
+    pub fn parse(json: &str) -> Result<Self, Vec<String>> {
+        let json: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        Self::parse_value(json)
+    }
+
+    /// Parse a schema from the Cedar schema syntax, e.g.
+    /// `entity User; action view appliesTo { ... };`
+    pub fn parse_cedar(src: &str) -> Result<Self, Vec<String>> {
+        let (fragment, _) = cedar_policy::SchemaFragment::from_str_natural(src)
+            .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        let json = fragment
+            .to_json_value()
+            .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        Self::parse_value(json)
+    }
+
+    /// Parse a schema in `format`, or in the format it looks like if that is
+    /// not given; see [`detect_format`]
+    pub fn parse_in_format(text: &str, format: Option<InputFormat>) -> Result<Self, Vec<String>> {
+        match detect_format(text, "schema", format).map_err(|e| vec![e])? {
+            InputFormat::Json => Self::parse(text),
+            InputFormat::Cedar => Self::parse_cedar(text),
+        }
+    }
+
+    /// Parse a schema from its JSON format, already deserialized
+    pub fn parse_value(json: serde_json::Value) -> Result<Self, Vec<String>> {
+        #[cfg(feature = "full")]
+        let validator_schema =
+            ValidatorSchema::from_json_value(json.clone(), Extensions::all_available())
+                .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        let schema = Schema::from_json_value(json.clone())
+            .map_err(|e| vec![format!("error parsing schema: {e}")])?;
+        Ok(Self {
+            schema,
+            #[cfg(feature = "full")]
+            validator_schema,
+            json,
+        })
+    }
+
+    /// The JSON this schema was parsed from
+    pub fn json(&self) -> &serde_json::Value {
+        &self.json
+    }
+
+    /// The schema held by this handle
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The same schema, as the validator holds it
+    #[cfg(feature = "full")]
+    pub(crate) fn validator_schema(&self) -> &ValidatorSchema {
+        &self.validator_schema
+    }
+
+    /// The principal and resource types `action` applies to, or `None` if the
+    /// schema does not declare it
+    #[cfg(feature = "full")]
+    pub fn applies_to_action(&self, action: &EntityUID) -> Option<ActionAppliesTo> {
+        self.validator_schema.get_action_id(action).map(|action| {
+            let mut principal_types: Vec<_> = action
+                .applies_to_principals()
+                .map(ToString::to_string)
+                .collect();
+            let mut resource_types: Vec<_> = action
+                .applies_to_resources()
+                .map(ToString::to_string)
+                .collect();
+            principal_types.sort();
+            resource_types.sort();
+            ActionAppliesTo {
+                principal_types,
+                resource_types,
+            }
+        })
+    }
+
+    /// The actions declared by the schema that apply to principals of type
+    /// `principal_type` and resources of type `resource_type`, as sorted Cedar
+    /// text
+    #[cfg(feature = "full")]
+    pub fn actions_applying_to(&self, principal_type: &str, resource_type: &str) -> Vec<String> {
+        let mut actions: Vec<_> = self
+            .validator_schema
+            .action_entities()
+            .map(|entities| {
+                entities
+                    .iter()
+                    .filter(|entity| {
+                        self.validator_schema
+                            .get_action_id(entity.uid())
+                            .is_some_and(|action| {
+                                action
+                                    .applies_to_principals()
+                                    .any(|ty| ty.to_string() == principal_type)
+                                    && action
+                                        .applies_to_resources()
+                                        .any(|ty| ty.to_string() == resource_type)
+                            })
+                    })
+                    .map(|entity| entity.uid().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        actions.sort();
+        actions
+    }
+}
+
+#[wasm_bindgen]
+impl WasmSchema {
+    /// Parse a schema from its JSON format, throwing if it is invalid
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(json: &str) -> Result<WasmSchema, JsError> {
+        Self::parse(json).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Parse a schema from the Cedar schema syntax, throwing if it is invalid
+    #[wasm_bindgen(js_name = "fromCedar")]
+    pub fn from_cedar(src: &str) -> Result<WasmSchema, JsError> {
+        Self::parse_cedar(src).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Parse a schema in `format`, or, if that is not given, in the format it
+    /// looks like, throwing if it is invalid or its format is ambiguous
+    #[wasm_bindgen(js_name = "parse")]
+    pub fn parse_text(text: &str, format: Option<InputFormat>) -> Result<WasmSchema, JsError> {
+        Self::parse_in_format(text, format).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// The entity types declared by the schema, fully qualified and sorted
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = "entityTypes")]
+    pub fn entity_types(&self) -> Vec<String> {
+        let mut entity_types: Vec<_> = self
+            .validator_schema
+            .entity_types()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        entity_types.sort();
+        entity_types
+    }
+
+    /// The actions declared by the schema, as sorted Cedar text
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = "actions")]
+    pub fn actions(&self) -> Vec<String> {
+        let mut actions: Vec<_> = self
+            .validator_schema
+            .action_entities()
+            .map(|entities| entities.iter().map(|e| e.uid().to_string()).collect())
+            .unwrap_or_default();
+        actions.sort();
+        actions
+    }
+
+    /// The principal and resource types the action written as Cedar text,
+    /// e.g. `Action::"view"`, applies to, or `undefined` if it is not declared
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = "appliesTo")]
+    pub fn applies_to(&self, action: &str) -> Result<Option<ActionAppliesTo>, JsError> {
+        let action = EntityUID::from_str(action)
+            .map_err(|e| errors_to_js(&[format!("error parsing action: {e}")]))?;
+        Ok(self.applies_to_action(&action))
+    }
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// What a schema declares, e.g. to fill the choices of a policy builder
+pub struct SchemaDescription {
+    /// the namespaces of the schema, sorted, with `""` for the empty namespace
+    pub namespaces: Vec<String>,
+    /// the entity types of the schema, sorted by name
+    pub entity_types: Vec<EntityTypeDescription>,
+    /// the actions of the schema, sorted by uid
+    pub actions: Vec<ActionDescription>,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An entity type a schema declares
+pub struct EntityTypeDescription {
+    /// the fully qualified name of the entity type
+    pub name: String,
+    pub namespace: String,
+    /// the fully qualified names of the types the schema declares entities of
+    /// this type may be members of, sorted
+    pub parents: Vec<String>,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An action a schema declares
+pub struct ActionDescription {
+    /// the action as Cedar text, e.g. `App::Action::"view"`
+    pub uid: String,
+    pub namespace: String,
+    /// the action groups the schema declares the action a member of, as
+    /// sorted Cedar text
+    pub parents: Vec<String>,
+    pub applies_to: ActionAppliesTo,
+}
+
+/// Describe the namespaces, entity types, and actions a schema handle
+/// declares. Parents are the ones declared directly, by `memberOfTypes` and
+/// `memberOf`, not their ancestors.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = "describeSchema")]
+pub fn describe_schema(schema: &WasmSchema) -> SchemaDescription {
+    let Ok(fragment) = SchemaFragment::from_json_value(schema.json.clone()) else {
+        return SchemaDescription::default();
+    };
+    let mut description = SchemaDescription::default();
+    for (namespace, definition) in &fragment.0 {
+        description.namespaces.push(namespace.to_string());
+        for (name, entity_type) in &definition.entity_types {
+            let mut parents: Vec<_> = entity_type
+                .member_of_types
+                .iter()
+                .map(|parent| qualify(namespace, parent))
+                .collect();
+            parents.sort();
+            description.entity_types.push(EntityTypeDescription {
+                name: qualify(namespace, name),
+                namespace: namespace.to_string(),
+                parents,
+            });
+        }
+        for (id, action) in &definition.actions {
+            let Some(uid) = action_uid(namespace, None, id) else {
+                continue;
+            };
+            let mut parents: Vec<_> = action
+                .member_of
+                .iter()
+                .flatten()
+                .filter_map(|parent| action_uid(namespace, parent.ty.as_deref(), &parent.id))
+                .map(|parent| parent.to_string())
+                .collect();
+            parents.sort();
+            description.actions.push(ActionDescription {
+                applies_to: schema.applies_to_action(&uid).unwrap_or_default(),
+                uid: uid.to_string(),
+                namespace: namespace.to_string(),
+                parents,
+            });
+        }
+    }
+    description.namespaces.sort();
+    description.entity_types.sort_by(|a, b| a.name.cmp(&b.name));
+    description.actions.sort_by(|a, b| a.uid.cmp(&b.uid));
+    description
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An attribute of an entity type or of a record, with its type resolved
+pub struct AttributeShape {
+    pub name: String,
+    /// the type of the attribute as written in a schema, with common types
+    /// resolved, e.g. `Long`, `Set<String>`, `App::User`, `ipaddr`, or
+    /// `Record`
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub required: bool,
+    /// the attributes of a `Record`, or of the elements of a set of records,
+    /// sorted by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[tsify(optional)]
+    pub attributes: Vec<AttributeShape>,
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `getEntityTypeShape`
+pub enum EntityTypeShapeResult {
+    /// the attributes of the entity type, sorted by name
+    Success { attributes: Vec<AttributeShape> },
+    /// the entity type does not parse or is not declared
+    Error { errors: Vec<String> },
+}
+
+/// The attributes of the entity type `entityType`, fully qualified, as a
+/// schema handle declares them, with common types resolved, down to the
+/// attributes of nested records
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = "getEntityTypeShape")]
+pub fn get_entity_type_shape(schema: &WasmSchema, entity_type: &str) -> EntityTypeShapeResult {
+    let name = match Name::from_str(entity_type) {
+        Ok(name) => name,
+        Err(e) => {
+            return EntityTypeShapeResult::Error {
+                errors: vec![format!("error parsing entity type: {e}")],
+            }
+        }
+    };
+    match schema.validator_schema.get_entity_type(&name) {
+        Some(declared) => EntityTypeShapeResult::Success {
+            attributes: attribute_shapes(declared.attributes()),
+        },
+        None => EntityTypeShapeResult::Error {
+            errors: vec![format!(
+                "the schema does not declare the entity type `{entity_type}`"
+            )],
+        },
+    }
+}
+
+#[cfg(feature = "full")]
+fn attribute_shapes<'a, N: std::fmt::Display + 'a>(
+    attributes: impl Iterator<Item = (&'a N, &'a AttributeType)>,
+) -> Vec<AttributeShape> {
+    attributes
+        .map(|(name, attribute)| {
+            let (ty, attributes) = type_shape(&attribute.attr_type);
+            AttributeShape {
+                name: name.to_string(),
+                ty,
+                required: attribute.is_required,
+                attributes,
+            }
+        })
+        .collect()
+}
+
+/// `ty` as written in a schema, with the attributes of the records in it
+#[cfg(feature = "full")]
+fn type_shape(ty: &Type) -> (String, Vec<AttributeShape>) {
+    match ty {
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => ("Long".to_string(), Vec::new()),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        } => ("String".to_string(), Vec::new()),
+        Type::Primitive {
+            primitive_type: Primitive::Bool,
+        }
+        | Type::True
+        | Type::False => ("Bool".to_string(), Vec::new()),
+        Type::Set {
+            element_type: Some(element_type),
+        } => {
+            let (element, attributes) = type_shape(element_type);
+            (format!("Set<{element}>"), attributes)
+        }
+        Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. }) => {
+            ("Record".to_string(), attribute_shapes(attrs.iter()))
+        }
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => (
+            lub.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Vec::new(),
+        ),
+        Type::ExtensionType { name } => (name.to_string(), Vec::new()),
+        _ => (ty.to_string(), Vec::new()),
+    }
+}
+
+/// `name`, declared in `namespace`, fully qualified
+#[cfg(feature = "full")]
+fn qualify(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() || name.contains("::") {
+        name.to_string()
+    } else {
+        format!("{namespace}::{name}")
+    }
+}
+
+/// The uid of the action `id` of type `ty`, `Action` if not given, declared
+/// in `namespace`
+#[cfg(feature = "full")]
+fn action_uid(namespace: &str, ty: Option<&str>, id: &str) -> Option<EntityUID> {
+    let ty = Name::from_str(&qualify(namespace, ty.unwrap_or("Action"))).ok()?;
+    Some(EntityUID::from_components(ty, Eid::new(id)))
+}
+
+#[cfg(feature = "full")]
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of converting a schema from one format to
+/// the other
+pub enum SchemaConversionResult {
+    /// the schema in the other format
+    Success { schema: String },
+    /// the schema does not parse, or cannot be written in the other format
+    Error { errors: Vec<String> },
+}
+
+/// Convert a schema from its JSON format to the Cedar schema syntax. The
+/// schema is not checked beyond parsing, so a schema fragment that refers to
+/// types declared elsewhere converts too.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = "schemaJsonToCedar")]
+pub fn schema_json_to_cedar(schema_json: &str) -> SchemaConversionResult {
+    let converted = serde_json::from_str(schema_json)
+        .map_err(|e| format!("error parsing schema: {e}"))
+        .and_then(|json| {
+            SchemaFragment::from_json_value(json).map_err(|e| format!("error parsing schema: {e}"))
+        })
+        .and_then(|fragment| {
+            fragment
+                .as_natural_schema()
+                .map_err(|e| format!("error converting schema: {e}"))
+        });
+    match converted {
+        Ok(schema) => SchemaConversionResult::Success { schema },
+        Err(e) => SchemaConversionResult::Error { errors: vec![e] },
+    }
+}
+
+/// Convert a schema from the Cedar schema syntax to its JSON format, as
+/// pretty-printed JSON text. As with `schemaJsonToCedar`, the schema is not
+/// checked beyond parsing.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = "schemaCedarToJson")]
+pub fn schema_cedar_to_json(schema_text: &str) -> SchemaConversionResult {
+    let converted = SchemaFragment::from_str_natural(schema_text)
+        .map_err(|e| format!("error parsing schema: {e}"))
+        .and_then(|(fragment, _)| {
+            serde_json::to_string_pretty(&fragment)
+                .map_err(|e| format!("error converting schema: {e}"))
+        });
+    match converted {
+        Ok(schema) => SchemaConversionResult::Success { schema },
+        Err(e) => SchemaConversionResult::Error { errors: vec![e] },
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "full")]
+    const SCHEMA: &str = r#"{ "App": {
+        "entityTypes": { "User": {}, "Photo": {}, "Group": {} },
+        "actions": {
+            "view": { "appliesTo": { "principalTypes": ["User", "Group"], "resourceTypes": ["Photo"] } },
+            "edit": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+        }
+    }}"#;
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn introspects_schema() {
+        let schema = WasmSchema::parse(SCHEMA).unwrap();
+        assert_eq!(
+            schema.entity_types(),
+            vec!["App::Group", "App::Photo", "App::User"]
+        );
+        assert_eq!(
+            schema.actions(),
+            vec![r#"App::Action::"edit""#, r#"App::Action::"view""#]
+        );
+        let view = EntityUID::from_str(r#"App::Action::"view""#).unwrap();
+        assert_eq!(
+            schema.applies_to_action(&view),
+            Some(ActionAppliesTo {
+                principal_types: vec!["App::Group".to_string(), "App::User".to_string()],
+                resource_types: vec!["App::Photo".to_string()],
+            })
+        );
+        let missing = EntityUID::from_str(r#"App::Action::"delete""#).unwrap();
+        assert_eq!(schema.applies_to_action(&missing), None);
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn parses_schema_in_cedar_syntax() {
+        let schema = WasmSchema::parse_in_format(
+            "namespace App {
+                entity Group;
+                entity User in [Group];
+                entity Photo;
+                action view appliesTo { principal: [User, Group], resource: [Photo] };
+                action edit appliesTo { principal: [User], resource: [Photo] };
+            }",
+            None,
+        )
+        .unwrap();
+        let json = WasmSchema::parse_in_format(SCHEMA, None).unwrap();
+        assert_eq!(schema.entity_types(), json.entity_types());
+        assert_eq!(schema.actions(), json.actions());
+        let errors = WasmSchema::parse_cedar("entity User").unwrap_err();
+        assert!(errors.iter().all(|e| e.starts_with("error parsing schema")));
+    }
+
+    #[test]
+    fn rejects_invalid_schema() {
+        let errors = WasmSchema::parse(r#"{ "": { "entityTypes": {} } }"#).unwrap_err();
+        assert!(errors.iter().all(|e| e.starts_with("error parsing schema")));
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn converts_schema_between_formats() {
+        let json = r#"{ "App": {
+            "commonTypes": { "Address": { "type": "Record", "attributes": {
+                "city": { "type": "String" },
+                "zip": { "type": "String", "required": false }
+            } } },
+            "entityTypes": {
+                "User": { "memberOfTypes": ["Group"], "shape": { "type": "Record", "attributes": {
+                    "home": { "type": "Address" },
+                    "tags": { "type": "Set", "element": { "type": "String" } }
+                } } },
+                "Group": {},
+                "Photo": {}
+            },
+            "actions": {
+                "read": {},
+                "view": {
+                    "memberOf": [{ "id": "read" }],
+                    "appliesTo": {
+                        "principalTypes": ["User"],
+                        "resourceTypes": ["Photo"],
+                        "context": { "type": "Record", "attributes": { "mfa": { "type": "Boolean" } } }
+                    }
+                }
+            }
+        }}"#;
+        let text = match schema_json_to_cedar(json) {
+            SchemaConversionResult::Success { schema } => schema,
+            SchemaConversionResult::Error { errors } => panic!("failed to convert: {errors:?}"),
+        };
+        assert!(text.contains("namespace App"));
+        let round_trip = match schema_cedar_to_json(&text) {
+            SchemaConversionResult::Success { schema } => schema,
+            SchemaConversionResult::Error { errors } => panic!("failed to convert: {errors:?}"),
+        };
+        let parsed = |json: &str| WasmSchema::parse(json).unwrap();
+        assert_eq!(
+            parsed(&round_trip).entity_types(),
+            parsed(json).entity_types()
+        );
+        assert_eq!(parsed(&round_trip).actions(), parsed(json).actions());
+        let view = EntityUID::from_str(r#"App::Action::"view""#).unwrap();
+        assert_eq!(
+            parsed(&round_trip).applies_to_action(&view),
+            parsed(json).applies_to_action(&view)
+        );
+
+        for result in [
+            schema_json_to_cedar("{"),
+            schema_cedar_to_json("entity User"),
+        ] {
+            match result {
+                SchemaConversionResult::Error { errors } => {
+                    assert!(errors[0].starts_with("error parsing schema"));
+                }
+                result => panic!("expected an error: {result:?}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn describes_schema() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            entity Admin;
+            namespace Org { entity Admin; }
+            namespace App {
+                entity Group;
+                entity User in [Group, Org::Admin];
+                entity Photo;
+                action read;
+                action view in [read] appliesTo { principal: [User], resource: [Photo] };
+            }
+            "#,
+        )
+        .unwrap();
+        let description = describe_schema(&schema);
+        assert_eq!(
+            description.namespaces,
+            vec!["".to_string(), "App".to_string(), "Org".to_string()]
+        );
+        let entity_types: Vec<_> = description
+            .entity_types
+            .iter()
+            .map(|ty| (ty.name.as_str(), ty.namespace.as_str(), ty.parents.clone()))
+            .collect();
+        assert_eq!(
+            entity_types,
+            vec![
+                ("Admin", "", vec![]),
+                ("App::Group", "App", vec![]),
+                ("App::Photo", "App", vec![]),
+                (
+                    "App::User",
+                    "App",
+                    vec!["App::Group".to_string(), "Org::Admin".to_string()]
+                ),
+                ("Org::Admin", "Org", vec![]),
+            ]
+        );
+        assert_eq!(
+            description.actions,
+            vec![
+                ActionDescription {
+                    uid: r#"App::Action::"read""#.to_string(),
+                    namespace: "App".to_string(),
+                    parents: vec![],
+                    applies_to: ActionAppliesTo::default(),
+                },
+                ActionDescription {
+                    uid: r#"App::Action::"view""#.to_string(),
+                    namespace: "App".to_string(),
+                    parents: vec![r#"App::Action::"read""#.to_string()],
+                    applies_to: ActionAppliesTo {
+                        principal_types: vec!["App::User".to_string()],
+                        resource_types: vec!["App::Photo".to_string()],
+                    },
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn resolves_entity_type_shape() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            namespace App {
+                type Address = { city: String, zip?: String };
+                entity Group;
+                entity User {
+                    name: String,
+                    age?: Long,
+                    home: Address,
+                    offices: Set<Address>,
+                    manager: User,
+                    ip: ipaddr,
+                };
+            }
+            "#,
+        )
+        .unwrap();
+        let attributes = match get_entity_type_shape(&schema, "App::User") {
+            EntityTypeShapeResult::Success { attributes } => attributes,
+            EntityTypeShapeResult::Error { errors } => panic!("expected a shape: {errors:?}"),
+        };
+        let address = vec![
+            AttributeShape {
+                name: "city".to_string(),
+                ty: "String".to_string(),
+                required: true,
+                attributes: vec![],
+            },
+            AttributeShape {
+                name: "zip".to_string(),
+                ty: "String".to_string(),
+                required: false,
+                attributes: vec![],
+            },
+        ];
+        let shape = |name: &str, ty: &str, required: bool, attributes: Vec<AttributeShape>| {
+            AttributeShape {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                required,
+                attributes,
+            }
+        };
+        assert_eq!(
+            attributes,
+            vec![
+                shape("age", "Long", false, vec![]),
+                shape("home", "Record", true, address.clone()),
+                shape("ip", "ipaddr", true, vec![]),
+                shape("manager", "App::User", true, vec![]),
+                shape("name", "String", true, vec![]),
+                shape("offices", "Set<Record>", true, address),
+            ]
+        );
+        assert!(matches!(
+            get_entity_type_shape(&schema, "App::Group"),
+            EntityTypeShapeResult::Success { attributes } if attributes.is_empty()
+        ));
+        for missing in ["User", "App::"] {
+            assert!(matches!(
+                get_entity_type_shape(&schema, missing),
+                EntityTypeShapeResult::Error { .. }
+            ));
+        }
+    }
+}
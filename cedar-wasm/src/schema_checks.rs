@@ -0,0 +1,654 @@
+//! This module contains `validateSchema`, which checks that a schema is not
+//! only well-formed but also consistent, e.g. that the entity types it refers
+//! to are declared, reporting each issue with where it is in the schema.
+use std::collections::HashSet;
+
+use cedar_policy::frontend::utils::line_and_column;
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_validator::{SchemaFragment, ValidatorSchema};
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::formats::{detect_format, InputFormat};
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How serious an issue with a schema is
+pub enum SchemaIssueSeverity {
+    /// the schema cannot be used until the issue is fixed
+    Error,
+    /// the schema can be used, but likely does not mean what it says
+    Warning,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// An issue with a schema
+pub struct SchemaIssue {
+    pub severity: SchemaIssueSeverity,
+    /// the kind of issue, e.g. `undeclaredEntityType`, `emptyAppliesTo`, or
+    /// `parseError`
+    pub code: String,
+    pub message: String,
+    /// the JSON Pointer to where the issue is in the JSON format of the
+    /// schema, e.g. `/App/entityTypes/User/memberOfTypes/0`, or the empty
+    /// string for the whole schema and for parse errors
+    pub path: String,
+    /// the range of the schema text at `path`, if the schema was given in the
+    /// JSON format, or where a parse error is, if the parser reports it
+    #[tsify(optional)]
+    pub span: Option<SchemaSpan>,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A range of schema text, as byte offsets and as the line and column at each
+/// end, both counting from 1 with columns in characters; the end is exclusive
+pub struct SchemaSpan {
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SchemaSpan {
+    /// The span of `text` from the byte offset `start` to `end`
+    fn new(text: &str, start: usize, end: usize) -> Self {
+        let (start_line, start_column) = line_and_column(text, start);
+        let (end_line, end_column) = line_and_column(text, end);
+        Self {
+            start,
+            end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `validateSchema`
+pub enum ValidateSchemaResult {
+    /// the schema is well-formed; it can be used if none of `issues` is an
+    /// error
+    Success { issues: Vec<SchemaIssue> },
+    /// the schema is not well-formed JSON or Cedar schema syntax; each of
+    /// `issues` is a `parseError`
+    ParseFailed { issues: Vec<SchemaIssue> },
+    /// the format of the schema could not be told from its text
+    Error { errors: Vec<String> },
+}
+
+/// Check a schema, in `format`, or, if that is not given, in the format it
+/// looks like. Beyond parsing it, this reports entity types, actions, and
+/// common types that are used but not declared, common types used in the
+/// declaration of other common types, extension types that do not exist, and
+/// actions that apply to no principal or resource types.
+#[wasm_bindgen(js_name = "validateSchema")]
+pub fn validate_schema(text: &str, format: Option<InputFormat>) -> ValidateSchemaResult {
+    let (json, is_json) = match parse_schema(text, format) {
+        Ok(parsed) => parsed,
+        Err(result) => return result,
+    };
+    let mut checker = Checker::new(&json);
+    checker.check_schema(&json);
+    let mut issues = checker.issues;
+    if !issues
+        .iter()
+        .any(|issue| issue.severity == SchemaIssueSeverity::Error)
+    {
+        if let Err(e) = ValidatorSchema::from_json_value(json, Extensions::all_available()) {
+            issues.push(issue(
+                SchemaIssueSeverity::Error,
+                "invalidSchema",
+                e.to_string(),
+                &[],
+            ));
+        }
+    }
+    issues.sort_by(|a, b| a.path.cmp(&b.path));
+    if is_json {
+        for issue in &mut issues {
+            issue.span =
+                json_span(text, &issue.path).map(|(start, end)| SchemaSpan::new(text, start, end));
+        }
+    }
+    ValidateSchemaResult::Success { issues }
+}
+
+/// The JSON format of the schema `text`, and whether `text` was in it, or the
+/// result to give if it does not parse
+fn parse_schema(
+    text: &str,
+    format: Option<InputFormat>,
+) -> Result<(Value, bool), ValidateSchemaResult> {
+    let format = detect_format(text, "schema", format)
+        .map_err(|e| ValidateSchemaResult::Error { errors: vec![e] })?;
+    let failed = |issues| ValidateSchemaResult::ParseFailed { issues };
+    match format {
+        InputFormat::Json => {
+            let json: Value = serde_json::from_str(text).map_err(|e| {
+                // serde counts lines from 1 and columns from 1, in bytes
+                let line_start = text
+                    .split_inclusive('\n')
+                    .take(e.line().saturating_sub(1))
+                    .map(str::len)
+                    .sum::<usize>();
+                let offset = (line_start + e.column().saturating_sub(1)).min(text.len());
+                failed(vec![parse_issue(text, &e, Some((offset, offset)))])
+            })?;
+            SchemaFragment::from_json_value(json.clone())
+                .map_err(|e| failed(vec![parse_issue(text, &e, None)]))?;
+            Ok((json, true))
+        }
+        InputFormat::Cedar => {
+            let (fragment, _) = SchemaFragment::from_str_natural(text).map_err(|e| {
+                // the first error is `e` itself, and the others are related to it
+                let errors =
+                    std::iter::once(&e as &dyn Diagnostic).chain(e.related().into_iter().flatten());
+                failed(
+                    errors
+                        .map(|error| {
+                            let span = error
+                                .labels()
+                                .and_then(|mut labels| labels.next())
+                                .map(|label| (label.offset(), label.offset() + label.len()));
+                            parse_issue(text, error, span)
+                        })
+                        .collect(),
+                )
+            })?;
+            let json = serde_json::to_value(fragment)
+                .map_err(|e| failed(vec![parse_issue(text, &e, None)]))?;
+            Ok((json, false))
+        }
+    }
+}
+
+/// A parse error in the schema `text`, at the byte offsets `span`, if known
+fn parse_issue(
+    text: &str,
+    error: &dyn std::fmt::Display,
+    span: Option<(usize, usize)>,
+) -> SchemaIssue {
+    SchemaIssue {
+        severity: SchemaIssueSeverity::Error,
+        code: "parseError".to_string(),
+        message: format!("error parsing schema: {error}"),
+        path: String::new(),
+        span: span.map(|(start, end)| SchemaSpan::new(text, start, end)),
+    }
+}
+
+/// An issue at the JSON Pointer made of `path`, with no span yet
+fn issue(severity: SchemaIssueSeverity, code: &str, message: String, path: &[&str]) -> SchemaIssue {
+    SchemaIssue {
+        severity,
+        code: code.to_string(),
+        message,
+        path: path
+            .iter()
+            .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+            .collect(),
+        span: None,
+    }
+}
+
+/// `name` as it is declared: qualified by `namespace` unless it is already
+/// qualified
+fn qualify(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() || name.contains("::") {
+        name.to_string()
+    } else {
+        format!("{namespace}::{name}")
+    }
+}
+
+/// The members of the object at `key` of `value`, if there is one
+fn members<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    value
+        .get(key)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+/// The checks of a schema in its JSON format
+struct Checker {
+    entity_types: HashSet<String>,
+    /// each action, as its type and id
+    actions: HashSet<(String, String)>,
+    common_types: HashSet<String>,
+    extension_types: HashSet<String>,
+    issues: Vec<SchemaIssue>,
+}
+
+impl Checker {
+    /// The checker for `schema`, knowing what it declares
+    fn new(schema: &Value) -> Self {
+        let mut checker = Self {
+            entity_types: HashSet::new(),
+            actions: HashSet::new(),
+            common_types: HashSet::new(),
+            extension_types: Extensions::all_available()
+                .ext_names()
+                .map(ToString::to_string)
+                .collect(),
+            issues: Vec::new(),
+        };
+        for (namespace, definition) in schema.as_object().into_iter().flatten() {
+            let qualified = |name: &String| qualify(namespace, name);
+            checker
+                .entity_types
+                .extend(members(definition, "entityTypes").map(|(name, _)| qualified(name)));
+            checker
+                .common_types
+                .extend(members(definition, "commonTypes").map(|(name, _)| qualified(name)));
+            let action_type = qualify(namespace, "Action");
+            checker.actions.extend(
+                members(definition, "actions").map(|(id, _)| (action_type.clone(), id.clone())),
+            );
+        }
+        checker
+    }
+
+    fn check_schema(&mut self, schema: &Value) {
+        for (namespace, definition) in schema.as_object().into_iter().flatten() {
+            for (name, ty) in members(definition, "commonTypes") {
+                self.check_type(namespace, ty, true, &[namespace, "commonTypes", name]);
+            }
+            for (name, entity_type) in members(definition, "entityTypes") {
+                let path = [namespace.as_str(), "entityTypes", name];
+                self.check_entity_types(namespace, entity_type, "memberOfTypes", &path);
+                if let Some(shape) = entity_type.get("shape") {
+                    self.check_type(namespace, shape, false, &[&path[..], &["shape"]].concat());
+                }
+            }
+            for (id, action) in members(definition, "actions") {
+                self.check_action(namespace, id, action);
+            }
+        }
+    }
+
+    fn check_action(&mut self, namespace: &str, id: &str, action: &Value) {
+        let path = [namespace, "actions", id];
+        let member_of = action.get("memberOf").and_then(Value::as_array);
+        for (i, parent) in member_of.into_iter().flatten().enumerate() {
+            let Some(parent_id) = parent.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            let parent_type = qualify(
+                namespace,
+                parent
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Action"),
+            );
+            if !self
+                .actions
+                .contains(&(parent_type.clone(), parent_id.to_string()))
+            {
+                self.issues.push(issue(
+                    SchemaIssueSeverity::Error,
+                    "undeclaredAction",
+                    format!("action `{parent_type}::\"{parent_id}\"` is not declared"),
+                    &[&path[..], &["memberOf", &i.to_string()]].concat(),
+                ));
+            }
+        }
+        let Some(applies_to) = action.get("appliesTo").filter(|spec| !spec.is_null()) else {
+            return;
+        };
+        let path = [&path[..], &["appliesTo"]].concat();
+        for (key, what) in [
+            ("principalTypes", "principal"),
+            ("resourceTypes", "resource"),
+        ] {
+            if applies_to
+                .get(key)
+                .and_then(Value::as_array)
+                .is_some_and(Vec::is_empty)
+            {
+                self.issues.push(issue(
+                    SchemaIssueSeverity::Warning,
+                    "emptyAppliesTo",
+                    format!(
+                        "action `{id}` applies to no {what} types, so no request can be for it"
+                    ),
+                    &[&path[..], &[key]].concat(),
+                ));
+            }
+            self.check_entity_types(namespace, applies_to, key, &path);
+        }
+        if let Some(context) = applies_to.get("context") {
+            self.check_type(
+                namespace,
+                context,
+                false,
+                &[&path[..], &["context"]].concat(),
+            );
+        }
+    }
+
+    /// Check that the entity types listed at `key` of `value` are declared
+    fn check_entity_types(&mut self, namespace: &str, value: &Value, key: &str, path: &[&str]) {
+        let names = value.get(key).and_then(Value::as_array);
+        for (i, name) in names.into_iter().flatten().enumerate() {
+            if let Some(name) = name.as_str() {
+                self.check_entity_type(namespace, name, &[path, &[key, &i.to_string()]].concat());
+            }
+        }
+    }
+
+    fn check_entity_type(&mut self, namespace: &str, name: &str, path: &[&str]) {
+        let qualified = qualify(namespace, name);
+        if !self.entity_types.contains(&qualified) {
+            self.issues.push(issue(
+                SchemaIssueSeverity::Error,
+                "undeclaredEntityType",
+                format!("entity type `{qualified}` is not declared"),
+                path,
+            ));
+        }
+    }
+
+    /// Check the type `ty`, which is part of the declaration of a common type
+    /// if `in_common_type` is set
+    fn check_type(&mut self, namespace: &str, ty: &Value, in_common_type: bool, path: &[&str]) {
+        let name = ty.get("name").and_then(Value::as_str);
+        match ty.get("type").and_then(Value::as_str) {
+            None | Some("String" | "Long" | "Boolean") => (),
+            Some("Set") => {
+                if let Some(element) = ty.get("element") {
+                    self.check_type(
+                        namespace,
+                        element,
+                        in_common_type,
+                        &[path, &["element"]].concat(),
+                    );
+                }
+            }
+            Some("Record") => {
+                for (attr, attr_ty) in members(ty, "attributes") {
+                    self.check_type(
+                        namespace,
+                        attr_ty,
+                        in_common_type,
+                        &[path, &["attributes", attr]].concat(),
+                    );
+                }
+            }
+            Some("Entity") => {
+                if let Some(name) = name {
+                    self.check_entity_type(namespace, name, &[path, &["name"]].concat());
+                }
+            }
+            Some("Extension") => {
+                if let Some(name) = name.filter(|name| !self.extension_types.contains(*name)) {
+                    self.issues.push(issue(
+                        SchemaIssueSeverity::Warning,
+                        "unknownExtensionType",
+                        format!(
+                            "there is no extension type `{name}`, so no value can have this type"
+                        ),
+                        &[path, &["name"]].concat(),
+                    ));
+                }
+            }
+            Some(common_type) => {
+                let path = [path, &["type"]].concat();
+                if in_common_type {
+                    self.issues.push(issue(
+                        SchemaIssueSeverity::Error,
+                        "unresolvableCommonType",
+                        format!("common type `{common_type}` cannot be used in the declaration of another common type"),
+                        &path,
+                    ));
+                } else if !self.common_types.contains(&qualify(namespace, common_type)) {
+                    self.issues.push(issue(
+                        SchemaIssueSeverity::Error,
+                        "unresolvableCommonType",
+                        format!("common type `{common_type}` is not declared"),
+                        &path,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// The byte offsets of the start and end of the value at the JSON Pointer
+/// `path` in the JSON `text`
+fn json_span(text: &str, path: &str) -> Option<(usize, usize)> {
+    let segments: Vec<String> = path
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let bytes = text.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+    for segment in &segments {
+        pos = match bytes.get(pos)? {
+            b'{' => {
+                let mut pos = skip_whitespace(bytes, pos + 1);
+                loop {
+                    let key_end = skip_value(bytes, pos)?;
+                    let key: String = serde_json::from_str(text.get(pos..key_end)?).ok()?;
+                    let colon = skip_whitespace(bytes, key_end);
+                    let value = skip_whitespace(bytes, colon + 1);
+                    if &key == segment {
+                        break value;
+                    }
+                    pos = skip_whitespace(bytes, skip_value(bytes, value)?);
+                    if bytes.get(pos) != Some(&b',') {
+                        return None;
+                    }
+                    pos = skip_whitespace(bytes, pos + 1);
+                }
+            }
+            b'[' => {
+                let index: usize = segment.parse().ok()?;
+                let mut pos = skip_whitespace(bytes, pos + 1);
+                for _ in 0..index {
+                    pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+                    if bytes.get(pos) != Some(&b',') {
+                        return None;
+                    }
+                    pos = skip_whitespace(bytes, pos + 1);
+                }
+                pos
+            }
+            _ => return None,
+        };
+    }
+    Some((pos, skip_value(bytes, pos)?))
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// The position just after the JSON value starting at `pos`
+fn skip_value(bytes: &[u8], pos: usize) -> Option<usize> {
+    match bytes.get(pos)? {
+        b'"' => {
+            let mut pos = pos + 1;
+            loop {
+                match bytes.get(pos)? {
+                    b'"' => return Some(pos + 1),
+                    b'\\' => pos += 2,
+                    _ => pos += 1,
+                }
+            }
+        }
+        open @ (b'{' | b'[') => {
+            let close = if *open == b'{' { b'}' } else { b']' };
+            let mut pos = skip_whitespace(bytes, pos + 1);
+            while bytes.get(pos)? != &close {
+                pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+                if matches!(bytes.get(pos)?, b',' | b':') {
+                    pos = skip_whitespace(bytes, pos + 1);
+                }
+            }
+            Some(pos + 1)
+        }
+        _ => {
+            let mut pos = pos;
+            while bytes.get(pos).is_some_and(|b| {
+                !matches!(b, b',' | b'}' | b']' | b':') && !b.is_ascii_whitespace()
+            }) {
+                pos += 1;
+            }
+            Some(pos)
+        }
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn issues(result: ValidateSchemaResult) -> Vec<SchemaIssue> {
+        match result {
+            ValidateSchemaResult::Success { issues } => issues,
+            result => panic!("failed to parse schema: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_schema_issues_with_spans() {
+        let schema = r#"{ "App": {
+            "commonTypes": {
+                "Address": { "type": "Record", "attributes": { "city": { "type": "City" } } }
+            },
+            "entityTypes": {
+                "User": {
+                    "memberOfTypes": ["Group"],
+                    "shape": { "type": "Record", "attributes": {
+                        "ip": { "type": "Extension", "name": "ipaddress" },
+                        "home": { "type": "Location" }
+                    } }
+                },
+                "Photo": {}
+            },
+            "actions": {
+                "view": {
+                    "memberOf": [{ "id": "read" }],
+                    "appliesTo": { "principalTypes": [], "resourceTypes": ["Photo"] }
+                }
+            }
+        }}"#;
+        let issues = issues(validate_schema(schema, None));
+        let found: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.path.as_str(), issue.code.as_str(), issue.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "/App/actions/view/appliesTo/principalTypes",
+                    "emptyAppliesTo",
+                    SchemaIssueSeverity::Warning
+                ),
+                (
+                    "/App/actions/view/memberOf/0",
+                    "undeclaredAction",
+                    SchemaIssueSeverity::Error
+                ),
+                (
+                    "/App/commonTypes/Address/attributes/city/type",
+                    "unresolvableCommonType",
+                    SchemaIssueSeverity::Error
+                ),
+                (
+                    "/App/entityTypes/User/memberOfTypes/0",
+                    "undeclaredEntityType",
+                    SchemaIssueSeverity::Error
+                ),
+                (
+                    "/App/entityTypes/User/shape/attributes/home/type",
+                    "unresolvableCommonType",
+                    SchemaIssueSeverity::Error
+                ),
+                (
+                    "/App/entityTypes/User/shape/attributes/ip/name",
+                    "unknownExtensionType",
+                    SchemaIssueSeverity::Warning
+                ),
+            ]
+        );
+        let text = |issue: &SchemaIssue| {
+            let span = issue.span.unwrap();
+            &schema[span.start..span.end]
+        };
+        assert_eq!(text(&issues[0]), "[]");
+        assert_eq!(text(&issues[1]), r#"{ "id": "read" }"#);
+        assert_eq!(text(&issues[3]), r#""Group""#);
+        assert_eq!(text(&issues[5]), r#""ipaddress""#);
+    }
+
+    #[test]
+    fn reports_issues_in_cedar_syntax_without_spans() {
+        let issues = issues(validate_schema(
+            "entity User in [Group]; entity Photo; action view appliesTo { principal: [User], resource: [Photo] };",
+            None,
+        ));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "undeclaredEntityType");
+        assert_eq!(issues[0].span, None);
+
+        let clean = r#"{ "": { "entityTypes": { "User": {} }, "actions": {} } }"#;
+        assert!(self::issues(validate_schema(clean, None)).is_empty());
+        let cycle = r#"{ "": { "entityTypes": {}, "actions": {
+            "a": { "memberOf": [{ "id": "b" }] },
+            "b": { "memberOf": [{ "id": "a" }] }
+        } } }"#;
+        let issues = self::issues(validate_schema(cycle, None));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "invalidSchema");
+    }
+
+    #[test]
+    fn reports_parse_errors_with_positions() {
+        let parse_errors = |text: &str| match validate_schema(text, None) {
+            ValidateSchemaResult::ParseFailed { issues } => issues,
+            result => panic!("expected parse errors: {result:?}"),
+        };
+        let schema = "entity User;\nentity Photo in [User] {\n  owner: User,,\n};";
+        let issues = parse_errors(schema);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "parseError");
+        assert_eq!(issues[0].severity, SchemaIssueSeverity::Error);
+        let span = issues[0].span.unwrap();
+        assert_eq!(&schema[span.start..span.end], ",");
+        assert_eq!((span.start_line, span.start_column), (3, 15));
+        assert_eq!((span.end_line, span.end_column), (3, 16));
+
+        let issues = parse_errors("entity User;\nentity User;");
+        assert!(issues[0].message.contains("Duplicate declarations"));
+        assert_eq!(issues[0].span.unwrap().start_line, 1);
+
+        let issues = parse_errors("{ \"\": \n  { \"entityTypes\": ");
+        let span = issues[0].span.unwrap();
+        assert_eq!((span.start_line, span.end_line), (2, 2));
+        let issues = parse_errors(r#"{ "": { "entityTypes": [] } }"#);
+        assert_eq!(issues[0].span, None);
+    }
+}
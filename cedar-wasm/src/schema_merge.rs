@@ -0,0 +1,268 @@
+//! This module contains `mergeSchemas`, which combines schema fragments, e.g.
+//! one per team, into one schema, reporting the declarations that fragments
+//! disagree on.
+use std::collections::BTreeMap;
+
+use cedar_policy_validator::SchemaFragment;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    formats::{detect_format, InputFormat},
+    WasmSchema,
+};
+
+/// The sections of a namespace in the JSON format of a schema, with the kind
+/// of declaration each holds
+const SECTIONS: [(&str, SchemaDeclarationKind); 3] = [
+    ("commonTypes", SchemaDeclarationKind::CommonType),
+    ("entityTypes", SchemaDeclarationKind::EntityType),
+    ("actions", SchemaDeclarationKind::Action),
+];
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The kind of a declaration in a schema
+pub enum SchemaDeclarationKind {
+    CommonType,
+    EntityType,
+    Action,
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A declaration that schema fragments give different definitions of
+pub struct SchemaConflict {
+    /// the namespace of the declaration, `""` for the empty namespace
+    pub namespace: String,
+    pub kind: SchemaDeclarationKind,
+    pub name: String,
+    /// the indices of the fragments declaring it, in order
+    pub fragments: Vec<usize>,
+    pub message: String,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `mergeSchemas`
+pub enum MergeSchemasResult {
+    /// the merged schema, in its JSON format, as pretty-printed JSON text
+    Success { schema: String },
+    /// the fragments declare some of the same names differently
+    Conflicts { conflicts: Vec<SchemaConflict> },
+    /// a fragment does not parse, or the merged schema is not valid
+    Error { errors: Vec<String> },
+}
+
+/// Merge schema fragments, each in the JSON format or the Cedar schema syntax,
+/// whichever it looks like, into one schema. Fragments may declare the same
+/// namespace, and may repeat a declaration as long as they define it the same
+/// way; a declaration defined differently by two fragments is a conflict.
+/// The merged schema must be valid on its own, e.g. declare every entity type
+/// it refers to.
+#[wasm_bindgen(js_name = "mergeSchemas")]
+pub fn merge_schemas(fragments: Vec<String>) -> MergeSchemasResult {
+    let mut parsed = Vec::new();
+    let mut errors = Vec::new();
+    for (index, text) in fragments.iter().enumerate() {
+        match parse_fragment(text) {
+            Ok(json) => parsed.push(json),
+            Err(e) => errors.push(format!("error parsing schema fragment {index}: {e}")),
+        }
+    }
+    if !errors.is_empty() {
+        return MergeSchemasResult::Error { errors };
+    }
+    let (merged, conflicts) = merge(parsed);
+    if !conflicts.is_empty() {
+        return MergeSchemasResult::Conflicts { conflicts };
+    }
+    let merged = Value::Object(merged);
+    if let Err(errors) = WasmSchema::parse_value(merged.clone()) {
+        return MergeSchemasResult::Error { errors };
+    }
+    match serde_json::to_string_pretty(&merged) {
+        Ok(schema) => MergeSchemasResult::Success { schema },
+        Err(e) => MergeSchemasResult::Error {
+            errors: vec![e.to_string()],
+        },
+    }
+}
+
+/// The JSON format of the schema fragment `text`, written out the same way
+/// whichever format the fragment is in, so that declarations can be compared
+fn parse_fragment(text: &str) -> Result<Map<String, Value>, String> {
+    let fragment = match detect_format(text, "schema fragment", None)? {
+        InputFormat::Json => {
+            let json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+            SchemaFragment::from_json_value(json).map_err(|e| e.to_string())?
+        }
+        InputFormat::Cedar => {
+            let (fragment, _) =
+                SchemaFragment::from_str_natural(text).map_err(|e| e.to_string())?;
+            fragment
+        }
+    };
+    match serde_json::to_value(fragment).map_err(|e| e.to_string())? {
+        Value::Object(namespaces) => Ok(namespaces),
+        _ => Err("a schema must be a JSON object".to_string()),
+    }
+}
+
+/// Merge the namespaces of `fragments`, in their JSON format, declaration by
+/// declaration, with the declarations they define differently
+fn merge(fragments: Vec<Map<String, Value>>) -> (Map<String, Value>, Vec<SchemaConflict>) {
+    let mut merged = Map::new();
+    // the fragments declaring each declaration, keyed by namespace, section,
+    // and name, with whether any two of them disagree
+    let mut declared: BTreeMap<(String, usize, String), (Vec<usize>, bool)> = BTreeMap::new();
+    for (index, fragment) in fragments.into_iter().enumerate() {
+        for (namespace, definition) in fragment {
+            let Value::Object(mut definition) = definition else {
+                continue;
+            };
+            let target = merged
+                .entry(namespace.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            let Value::Object(target) = target else {
+                continue;
+            };
+            for (section, (key, _)) in SECTIONS.iter().enumerate() {
+                let Some(Value::Object(declarations)) = definition.remove(*key) else {
+                    continue;
+                };
+                let target = target
+                    .entry(key.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                let Value::Object(target) = target else {
+                    continue;
+                };
+                for (name, declaration) in declarations {
+                    let (indices, conflicting) = declared
+                        .entry((namespace.clone(), section, name.clone()))
+                        .or_default();
+                    indices.push(index);
+                    match target.get(&name) {
+                        Some(existing) => *conflicting |= existing != &declaration,
+                        None => {
+                            target.insert(name, declaration);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let conflicts = declared
+        .into_iter()
+        .filter(|(_, (_, conflicting))| *conflicting)
+        .filter_map(|((namespace, section, name), (fragments, _))| {
+            let (_, kind) = SECTIONS.get(section)?;
+            let what = match kind {
+                SchemaDeclarationKind::CommonType => "common type",
+                SchemaDeclarationKind::EntityType => "entity type",
+                SchemaDeclarationKind::Action => "action",
+            };
+            let qualified = if namespace.is_empty() {
+                name.clone()
+            } else {
+                format!("{namespace}::{name}")
+            };
+            let message = format!(
+                "fragments {} define the {what} `{qualified}` differently",
+                fragments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            Some(SchemaConflict {
+                namespace,
+                kind: *kind,
+                name,
+                fragments,
+                message,
+            })
+        })
+        .collect();
+    (merged, conflicts)
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merges_fragments_across_namespaces() {
+        let photos = r#"
+            namespace App {
+                entity User;
+                entity Photo;
+                action view appliesTo { principal: [User], resource: [Photo] };
+            }
+        "#;
+        let albums = r#"{ "App": {
+            "entityTypes": { "User": {}, "Album": {} },
+            "actions": {
+                "list": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Album"] } }
+            }
+        }, "Admin": { "entityTypes": { "Operator": {} }, "actions": {} } }"#;
+        let schema = match merge_schemas(vec![photos.to_string(), albums.to_string()]) {
+            MergeSchemasResult::Success { schema } => WasmSchema::parse(&schema).unwrap(),
+            result => panic!("expected the fragments to merge: {result:?}"),
+        };
+        assert_eq!(
+            schema.entity_types(),
+            vec!["Admin::Operator", "App::Album", "App::Photo", "App::User"]
+        );
+        assert_eq!(
+            schema.actions(),
+            vec![r#"App::Action::"list""#, r#"App::Action::"view""#]
+        );
+    }
+
+    #[test]
+    fn reports_conflicting_declarations() {
+        let fragments = vec![
+            "entity User; entity Group; action view;".to_string(),
+            "entity User in [Group]; entity Group; action view;".to_string(),
+            r#"{ "": { "entityTypes": { "User": { "memberOfTypes": ["Group"] } }, "actions": {} } }"#
+                .to_string(),
+        ];
+        match merge_schemas(fragments) {
+            MergeSchemasResult::Conflicts { conflicts } => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].kind, SchemaDeclarationKind::EntityType);
+                assert_eq!(conflicts[0].name, "User");
+                assert_eq!(conflicts[0].fragments, vec![0, 1, 2]);
+                assert_eq!(
+                    conflicts[0].message,
+                    "fragments 0, 1, 2 define the entity type `User` differently"
+                );
+            }
+            result => panic!("expected conflicts: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_fragments_that_do_not_parse_or_merge() {
+        match merge_schemas(vec!["entity User;".to_string(), "entity".to_string()]) {
+            MergeSchemasResult::Error { errors } => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].starts_with("error parsing schema fragment 1"));
+            }
+            result => panic!("expected an error: {result:?}"),
+        }
+        // each fragment parses, but the merged schema refers to an undeclared type
+        assert!(matches!(
+            merge_schemas(vec!["entity User in [Group];".to_string()]),
+            MergeSchemasResult::Error { .. }
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+//! This module contains `WasmEntityShards`, a set of entity stores that are
+//! authorized against as one, so that each can be loaded and refreshed
+//! independently of the others.
+use std::{collections::HashSet, rc::Rc};
+
+use cedar_policy::{Entities, Entity};
+use wasm_bindgen::prelude::*;
+
+use crate::{errors_to_js, WasmEntities};
+
+/// Named entity stores, viewed as one store when authorizing. The shards are
+/// merged into one store when they change, rather than on each authorization,
+/// with the hierarchy computed across them from the parents of each entity as
+/// given.
+///
+/// An entity in an earlier shard shadows any entity with the same uid in a
+/// later one.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct WasmEntityShards {
+    /// the entities of each shard with their parents as given, in the order
+    /// the shards were first set
+    shards: Vec<(String, Rc<Vec<Entity>>)>,
+    /// the shards merged into one store
+    merged: Entities,
+}
+
+impl WasmEntityShards {
+    /// A view of the shards as one store
+    pub fn merged(&self) -> &Entities {
+        &self.merged
+    }
+
+    /// Set the shard `name` to `entities`, with their parents as given,
+    /// returning whether it replaced a shard of that name, or fail, leaving
+    /// the shards as they were, if the shards cannot be merged
+    pub fn set(&mut self, name: &str, entities: Vec<Entity>) -> Result<bool, Vec<String>> {
+        let entities = Rc::new(entities);
+        let mut shards = self.shards.clone();
+        let replaced = match shards.iter_mut().find(|(shard, _)| shard == name) {
+            Some((_, shard)) => {
+                *shard = entities;
+                true
+            }
+            None => {
+                shards.push((name.to_string(), entities));
+                false
+            }
+        };
+        self.replace(shards)?;
+        Ok(replaced)
+    }
+
+    /// Remove the shard `name`, returning whether there was one, or fail as
+    /// [`WasmEntityShards::set`] does
+    pub fn remove(&mut self, name: &str) -> Result<bool, Vec<String>> {
+        let mut shards = self.shards.clone();
+        shards.retain(|(shard, _)| shard != name);
+        let removed = shards.len() != self.shards.len();
+        self.replace(shards)?;
+        Ok(removed)
+    }
+
+    /// Replace the shards with `shards`, or fail, leaving them as they were,
+    /// if their hierarchy across them has a cycle
+    fn replace(&mut self, shards: Vec<(String, Rc<Vec<Entity>>)>) -> Result<(), Vec<String>> {
+        let mut seen = HashSet::new();
+        let entities = shards
+            .iter()
+            .flat_map(|(_, entities)| entities.iter())
+            .filter(|entity| seen.insert(entity.uid()))
+            .cloned();
+        self.merged = Entities::from_entities(entities, None)
+            .map_err(|e| vec![format!("error merging shards: {e}")])?;
+        self.shards = shards;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl WasmEntityShards {
+    /// An empty set of shards
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmEntityShards {
+        Self::default()
+    }
+
+    /// Set the shard `name` to the current contents of `entities`, returning
+    /// whether it replaced a shard of that name. A replaced shard keeps its
+    /// place in the order; a new one is placed last. Later changes to
+    /// `entities` are not seen until it is set again. Throws, leaving the
+    /// shards as they were, if the parents of the entities of all the shards
+    /// form a cycle.
+    #[wasm_bindgen(js_name = "setShard")]
+    pub fn set_shard(&mut self, name: &str, entities: &WasmEntities) -> Result<bool, JsError> {
+        self.set(name, entities.declared().cloned().collect())
+            .map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// Remove the shard `name`, returning whether there was one. Throws, as
+    /// `setShard` does, if an entity the shard shadowed forms a cycle.
+    #[wasm_bindgen(js_name = "removeShard")]
+    pub fn remove_shard(&mut self, name: &str) -> Result<bool, JsError> {
+        self.remove(name).map_err(|errors| errors_to_js(&errors))
+    }
+
+    /// The names of the shards, in order
+    #[wasm_bindgen(js_name = "shardNames")]
+    pub fn shard_names(&self) -> Vec<String> {
+        self.shards.iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use cedar_policy::EntityUid;
+
+    use super::*;
+
+    fn entities(json: serde_json::Value) -> Vec<Entity> {
+        WasmEntities::parse(&json.to_string(), None, Default::default())
+            .unwrap()
+            .declared()
+            .cloned()
+            .collect()
+    }
+
+    fn has(entities: &Entities, uid: &str) -> bool {
+        entities.get(&EntityUid::from_str(uid).unwrap()).is_some()
+    }
+
+    #[test]
+    fn merges_and_refreshes_shards() {
+        let mut shards = WasmEntityShards::new();
+        assert_eq!(
+            shards.set(
+                "users",
+                entities(serde_json::json!([
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }
+                ]))
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            shards.set(
+                "photos",
+                entities(serde_json::json!([
+                    { "uid": { "type": "Photo", "id": "beach" }, "attrs": {}, "parents": [] }
+                ]))
+            ),
+            Ok(false)
+        );
+        let merged = shards.merged();
+        assert!(has(merged, r#"User::"alice""#));
+        assert!(has(merged, r#"Photo::"beach""#));
+
+        assert_eq!(
+            shards.set(
+                "users",
+                entities(serde_json::json!([
+                    { "uid": { "type": "User", "id": "bob" }, "attrs": {}, "parents": [] }
+                ]))
+            ),
+            Ok(true)
+        );
+        assert_eq!(shards.shard_names(), vec!["users", "photos"]);
+        let merged = shards.merged();
+        assert!(!has(merged, r#"User::"alice""#));
+        assert!(has(merged, r#"User::"bob""#));
+        assert!(has(merged, r#"Photo::"beach""#));
+
+        assert_eq!(shards.remove("photos"), Ok(true));
+        assert_eq!(shards.remove("photos"), Ok(false));
+        assert!(!has(shards.merged(), r#"Photo::"beach""#));
+    }
+
+    #[test]
+    fn computes_hierarchy_across_shards() {
+        let mut shards = WasmEntityShards::new();
+        shards
+            .set(
+                "users",
+                entities(serde_json::json!([
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": {},
+                      "parents": [{ "type": "Group", "id": "staff" }] }
+                ])),
+            )
+            .unwrap();
+        let groups = entities(serde_json::json!([
+            { "uid": { "type": "Group", "id": "staff" }, "attrs": {},
+              "parents": [{ "type": "Group", "id": "everyone" }] }
+        ]));
+        assert_eq!(shards.set("groups", groups), Ok(false));
+        let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+        let everyone = EntityUid::from_str(r#"Group::"everyone""#).unwrap();
+        assert!(shards
+            .merged()
+            .ancestors(&alice)
+            .unwrap()
+            .any(|ancestor| *ancestor == everyone));
+
+        // a cycle across the shards is rejected
+        let groups = entities(serde_json::json!([
+            { "uid": { "type": "Group", "id": "staff" }, "attrs": {},
+              "parents": [{ "type": "User", "id": "alice" }] }
+        ]));
+        let errors = shards.set("groups", groups).unwrap_err();
+        assert!(errors[0].starts_with("error merging shards"), "{errors:?}");
+        assert!(shards
+            .merged()
+            .ancestors(&alice)
+            .unwrap()
+            .any(|ancestor| *ancestor == everyone));
+    }
+}
@@ -0,0 +1,158 @@
+//! This module contains a registry of tenants kept inside the module instance.
+//! Each tenant has its own policies, entities, and optional schema, so that a
+//! single instance can answer requests for many isolated tenants.
+use std::{cell::RefCell, collections::HashMap};
+
+use cedar_policy::frontend::utils::InterfaceResult;
+use wasm_bindgen::prelude::*;
+
+use crate::{errors_to_js, CedarEngine};
+
+thread_local! {
+    /// wasm is single-threaded, so this is the one registry of the instance
+    static TENANTS: RefCell<HashMap<String, CedarEngine>> = RefCell::new(HashMap::new());
+}
+
+/// Register `engine` under `tenant_id`, returning whether it replaced a tenant
+/// already registered under that id
+pub fn register(tenant_id: &str, engine: CedarEngine) -> bool {
+    TENANTS.with(|tenants| {
+        tenants
+            .borrow_mut()
+            .insert(tenant_id.to_string(), engine)
+            .is_some()
+    })
+}
+
+/// Run `f` on the registered tenants, keyed by tenant id
+pub fn with_tenants<R>(f: impl FnOnce(&HashMap<String, CedarEngine>) -> R) -> R {
+    TENANTS.with(|tenants| f(&tenants.borrow()))
+}
+
+/// Register a tenant from policy text, entities JSON, and an optional schema
+/// JSON, as in the `CedarEngine` constructor. Registering an id that is
+/// already in use replaces that tenant; the return value says whether it did.
+#[wasm_bindgen(js_name = "registerTenant")]
+pub fn register_tenant(
+    tenant_id: &str,
+    policies: &str,
+    entities: &str,
+    schema: Option<String>,
+) -> Result<bool, JsError> {
+    let engine = CedarEngine::build(schema.as_deref(), policies, entities)
+        .map_err(|errors| errors_to_js(&errors))?;
+    Ok(register(tenant_id, engine))
+}
+
+/// Register an already-built engine as a tenant, replacing any tenant
+/// registered under the same id. The engine remains usable on its own.
+#[wasm_bindgen(js_name = "registerTenantEngine")]
+pub fn register_tenant_engine(tenant_id: &str, engine: &CedarEngine) -> bool {
+    register(tenant_id, engine.clone())
+}
+
+/// Drop the tenant `tenant_id`, returning whether it was registered
+#[wasm_bindgen(js_name = "dropTenant")]
+pub fn drop_tenant(tenant_id: &str) -> bool {
+    TENANTS.with(|tenants| tenants.borrow_mut().remove(tenant_id).is_some())
+}
+
+/// The ids of the registered tenants, sorted
+#[wasm_bindgen(js_name = "tenantIds")]
+pub fn tenant_ids() -> Vec<String> {
+    TENANTS.with(|tenants| {
+        let mut ids: Vec<_> = tenants.borrow().keys().cloned().collect();
+        ids.sort();
+        ids
+    })
+}
+
+/// Answer an authorization request against the tenant `tenant_id`, as
+/// `CedarEngine.isAuthorized` does. Fails if no such tenant is registered.
+#[wasm_bindgen(js_name = "isAuthorizedForTenant")]
+pub fn is_authorized_for_tenant(tenant_id: &str, request: &str) -> InterfaceResult {
+    TENANTS.with(|tenants| match tenants.borrow().get(tenant_id) {
+        Some(engine) => engine.is_authorized(request),
+        None => InterfaceResult::fail_bad_request(vec![format!(
+            "no tenant is registered with id `{tenant_id}`"
+        )]),
+    })
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ENTITIES: &str = r#"[
+        { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }
+    ]"#;
+
+    fn request() -> String {
+        serde_json::json!({
+            "principal": { "type": "User", "id": "alice" },
+            "action": { "type": "Action", "id": "view" },
+            "resource": { "type": "Photo", "id": "beach" },
+            "context": {}
+        })
+        .to_string()
+    }
+
+    fn decision(result: InterfaceResult) -> String {
+        match result {
+            InterfaceResult::Success { result, .. } => {
+                let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+                answer["response"]["decision"].as_str().unwrap().to_string()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("authorization failed: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn isolates_tenants() {
+        assert!(!register_tenant_or_panic(
+            "acme",
+            "permit(principal, action, resource);"
+        ));
+        assert!(!register_tenant_or_panic(
+            "globex",
+            "forbid(principal, action, resource);"
+        ));
+        assert_eq!(tenant_ids(), vec!["acme", "globex"]);
+        assert_eq!(
+            decision(is_authorized_for_tenant("acme", &request())),
+            "Allow"
+        );
+        assert_eq!(
+            decision(is_authorized_for_tenant("globex", &request())),
+            "Deny"
+        );
+
+        assert!(register_tenant_or_panic(
+            "acme",
+            "forbid(principal, action, resource);"
+        ));
+        assert_eq!(
+            decision(is_authorized_for_tenant("acme", &request())),
+            "Deny"
+        );
+
+        assert!(drop_tenant("acme"));
+        assert!(!drop_tenant("acme"));
+        assert!(matches!(
+            is_authorized_for_tenant("acme", &request()),
+            InterfaceResult::Failure {
+                is_internal: false,
+                ..
+            }
+        ));
+    }
+
+    fn register_tenant_or_panic(tenant_id: &str, policies: &str) -> bool {
+        match CedarEngine::build(None, policies, ENTITIES) {
+            Ok(engine) => register(tenant_id, engine),
+            Err(errors) => panic!("failed to build engine: {errors:?}"),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! This module contains `schemaToTypescript`, which writes TypeScript
+//! declarations for the attributes of the entity types and the contexts of
+//! the actions of a schema, so that application code building entities and
+//! contexts is checked against the schema.
+use std::fmt::Write;
+
+use cedar_policy_core::ast::{EntityType, EntityUID, Name};
+use cedar_policy_validator::types::{AttributeType, EntityRecordKind, OpenTag, Primitive, Type};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::WasmSchema;
+
+#[derive(Tsify, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// How `schemaToTypescript` writes its declarations
+pub struct TypescriptOptions {
+    /// write `export` before each declaration
+    #[serde(default)]
+    #[tsify(optional)]
+    pub export: bool,
+    /// a prefix for the name of each interface, e.g. `Cedar`
+    #[serde(default)]
+    #[tsify(optional)]
+    pub prefix: String,
+}
+
+/// Write a TypeScript interface for the attributes of each entity type and
+/// for the context of each action of a schema handle, matching the JSON
+/// format of entities and contexts with implicit escapes: an entity
+/// reference is its type and id, and an extension value is its string. The
+/// interface for `App::User` is `AppUser`, and the one for the context of
+/// `App::Action::"view photo"` is `AppViewPhotoContext`.
+#[wasm_bindgen(js_name = "schemaToTypescript")]
+pub fn schema_to_typescript(schema: &WasmSchema, options: TypescriptOptions) -> String {
+    let validator_schema = schema.validator_schema();
+    let mut entity_types: Vec<_> = validator_schema.entity_types().collect();
+    entity_types.sort_by_key(|(name, _)| name.to_string());
+    let mut out = String::new();
+    for (name, entity_type) in entity_types {
+        declare(
+            &mut out,
+            &options,
+            &interface_name(&options, name, None),
+            &format!("Attributes of the `{name}` entity type"),
+            &record_type(entity_type.attributes(), false, 0),
+        );
+    }
+    for action in schema.actions() {
+        let Ok(uid) = action.parse::<EntityUID>() else {
+            continue;
+        };
+        let EntityType::Specified(ty) = uid.entity_type() else {
+            continue;
+        };
+        let Some(Type::EntityOrRecord(EntityRecordKind::Record {
+            attrs,
+            open_attributes,
+        })) = validator_schema.context_type(&uid)
+        else {
+            continue;
+        };
+        declare(
+            &mut out,
+            &options,
+            &interface_name(&options, ty, Some(uid.eid().as_ref())),
+            &format!("Context of the `{action}` action"),
+            &record_type(attrs.iter(), open_attributes == OpenTag::OpenAttributes, 0),
+        );
+    }
+    out
+}
+
+/// Write the interface `name`, with the doc comment `doc`, to `out`
+fn declare(out: &mut String, options: &TypescriptOptions, name: &str, doc: &str, body: &str) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let export = if options.export { "export " } else { "" };
+    // writing to a `String` does not fail
+    let _ = writeln!(out, "/** {doc} */\n{export}interface {name} {body}");
+}
+
+/// The name of the interface for the entity type `ty`, or for the context of
+/// the action `action` of the action type `ty`: its namespace and name or
+/// action id in PascalCase, with the prefix of `options`
+fn interface_name(options: &TypescriptOptions, ty: &Name, action: Option<&str>) -> String {
+    let mut name = options.prefix.clone();
+    let namespace = ty.namespace();
+    let words = namespace.split("::").chain(match action {
+        Some(action) => vec![action, "Context"],
+        None => vec![ty.basename().as_ref()],
+    });
+    for word in words.flat_map(|word| word.split(|c: char| !c.is_ascii_alphanumeric())) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// A TypeScript object type with `attributes`, in the order given, indented
+/// `depth` levels
+fn record_type<'a, N: AsRef<str> + 'a>(
+    attributes: impl Iterator<Item = (&'a N, &'a AttributeType)>,
+    open: bool,
+    depth: usize,
+) -> String {
+    let indent = "  ".repeat(depth + 1);
+    let mut body = String::from("{\n");
+    for (name, attribute) in attributes {
+        let name = name.as_ref();
+        let key = if is_identifier(name) {
+            name.to_string()
+        } else {
+            serde_json::Value::from(name).to_string()
+        };
+        let optional = if attribute.is_required { "" } else { "?" };
+        let _ = writeln!(
+            body,
+            "{indent}{key}{optional}: {};",
+            typescript_type(&attribute.attr_type, depth + 1)
+        );
+    }
+    if open {
+        let _ = writeln!(body, "{indent}[attribute: string]: unknown;");
+    }
+    if body == "{\n" {
+        return "{}".to_string();
+    }
+    body.push_str(&"  ".repeat(depth));
+    body.push('}');
+    body
+}
+
+/// `ty` as a TypeScript type, indented `depth` levels if it is a record
+fn typescript_type(ty: &Type, depth: usize) -> String {
+    match ty {
+        Type::Primitive {
+            primitive_type: Primitive::Long,
+        } => "number".to_string(),
+        Type::Primitive {
+            primitive_type: Primitive::String,
+        }
+        | Type::ExtensionType { .. } => "string".to_string(),
+        Type::Primitive {
+            primitive_type: Primitive::Bool,
+        }
+        | Type::True
+        | Type::False => "boolean".to_string(),
+        Type::Set {
+            element_type: Some(element_type),
+        } => match typescript_type(element_type, depth) {
+            element if element.contains(' ') => format!("Array<{element}>"),
+            element => format!("{element}[]"),
+        },
+        Type::EntityOrRecord(EntityRecordKind::Record {
+            attrs,
+            open_attributes,
+        }) => record_type(
+            attrs.iter(),
+            *open_attributes == OpenTag::OpenAttributes,
+            depth,
+        ),
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+            let types = lub
+                .iter()
+                .map(|name| serde_json::Value::from(name.to_string()).to_string())
+                .collect::<Vec<_>>()
+                .join(" | ");
+            format!("{{ type: {types}; id: string }}")
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Whether `name` can be written as a property name without quotes
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writes_interfaces_for_entities_and_contexts() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            namespace App {
+                type Address = { city: String, "zip code"?: String };
+                entity Group;
+                entity User in [Group] {
+                    name: String,
+                    age?: Long,
+                    home: Address,
+                    tags: Set<String>,
+                    manager: User,
+                    ip: ipaddr,
+                };
+                action "view photo" appliesTo {
+                    principal: [User],
+                    resource: [Group],
+                    context: { mfa: Bool, homes: Set<Address> }
+                };
+            }
+            "#,
+        )
+        .unwrap();
+        let options = TypescriptOptions {
+            export: true,
+            prefix: String::new(),
+        };
+        assert_eq!(
+            schema_to_typescript(&schema, options),
+            r#"/** Attributes of the `App::Group` entity type */
+export interface AppGroup {}
+
+/** Attributes of the `App::User` entity type */
+export interface AppUser {
+  age?: number;
+  home: {
+    city: string;
+    "zip code"?: string;
+  };
+  ip: string;
+  manager: { type: "App::User"; id: string };
+  name: string;
+  tags: string[];
+}
+
+/** Context of the `App::Action::"view photo"` action */
+export interface AppViewPhotoContext {
+  homes: Array<{
+    city: string;
+    "zip code"?: string;
+  }>;
+  mfa: boolean;
+}
+"#
+        );
+
+        let options = TypescriptOptions {
+            export: false,
+            prefix: "Cedar".to_string(),
+        };
+        assert!(schema_to_typescript(&schema, options).starts_with(
+            "/** Attributes of the `App::Group` entity type */\ninterface CedarAppGroup {}"
+        ));
+    }
+}
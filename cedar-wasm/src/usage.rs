@@ -0,0 +1,276 @@
+//! This module contains `usageReport`, which cross-references a schema with a
+//! policy set to find what the schema declares that no policy uses, e.g. to
+//! prune a schema that has grown over time.
+use std::collections::{BTreeMap, BTreeSet};
+
+use cedar_policy_core::{
+    ast::{EntityType, Expr, ExprKind, PolicyID, Template, Var},
+    est,
+};
+use cedar_policy_validator::{
+    typecheck::{PolicyCheck, Typechecker},
+    types::{EntityRecordKind, Type},
+    ValidationMode,
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{errors_to_js, WasmPolicySet, WasmSchema};
+
+#[derive(Tsify, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// What a schema declares that no policy of a policy set uses
+pub struct UsageReport {
+    /// the entity types that no policy applies to or refers to, sorted
+    pub unused_entity_types: Vec<String>,
+    /// the actions that no policy applies to, sorted
+    pub unused_actions: Vec<String>,
+    /// the context attributes of each action that no policy applying to the
+    /// action reads, for the actions that have any
+    pub unused_context_attributes: BTreeMap<String, Vec<String>>,
+}
+
+/// What the policies of a policy set use
+#[derive(Debug, Default)]
+struct Usage {
+    entity_types: BTreeSet<String>,
+    actions: BTreeSet<String>,
+    /// the context attributes read by the policies applying to each action
+    context_attributes: BTreeMap<String, BTreeSet<String>>,
+    /// the actions some policy applying to which uses the context as a whole
+    whole_context: BTreeSet<String>,
+}
+
+/// Report the entity types, actions, and context attributes `schema` declares
+/// that no policy in `policies` uses. A policy uses the actions and the
+/// principal and resource types it applies to under the schema, the entity
+/// types of its expressions, and the context attributes it reads. Policies
+/// should validate against the schema first: a policy with type errors is
+/// taken to use only what its scope applies to.
+#[wasm_bindgen(js_name = "usageReport")]
+pub fn usage_report(schema: &WasmSchema, policies: &WasmPolicySet) -> Result<UsageReport, JsError> {
+    let usage = usage(schema, policies).map_err(|errors| errors_to_js(&errors))?;
+    let unused_entity_types = schema
+        .entity_types()
+        .into_iter()
+        .filter(|ty| !usage.entity_types.contains(ty))
+        .collect();
+    let unused_actions = schema
+        .actions()
+        .into_iter()
+        .filter(|action| !usage.actions.contains(action))
+        .collect();
+    let validator_schema = schema.validator_schema();
+    let unused_context_attributes = validator_schema
+        .action_entities()
+        .map(|entities| {
+            entities
+                .iter()
+                .filter_map(|entity| {
+                    let action = entity.uid().to_string();
+                    if usage.whole_context.contains(&action) {
+                        return None;
+                    }
+                    let Some(Type::EntityOrRecord(EntityRecordKind::Record { attrs, .. })) =
+                        validator_schema.context_type(entity.uid())
+                    else {
+                        return None;
+                    };
+                    let read = usage.context_attributes.get(&action);
+                    let mut unused: Vec<_> = attrs
+                        .keys()
+                        .filter(|attr| !read.is_some_and(|read| read.contains(attr.as_str())))
+                        .map(ToString::to_string)
+                        .collect();
+                    unused.sort();
+                    (!unused.is_empty()).then_some((action, unused))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(UsageReport {
+        unused_entity_types,
+        unused_actions,
+        unused_context_attributes,
+    })
+}
+
+/// What the static policies, templates, and template links of `policies` use
+/// under `schema`
+fn usage(schema: &WasmSchema, policies: &WasmPolicySet) -> Result<Usage, Vec<String>> {
+    let policy_set = policies.policy_set();
+    let mut templates = Vec::new();
+    for policy in policy_set.policies() {
+        match policy.template_links() {
+            Some(_) => continue,
+            None => templates.push(core_template(policy.id().as_ref(), policy.to_json())?),
+        }
+    }
+    for template in policy_set.templates() {
+        templates.push(core_template(template.id().as_ref(), template.to_json())?);
+    }
+
+    let mut usage = Usage::default();
+    for link_value in policy_set
+        .policies()
+        .filter_map(|policy| policy.template_links())
+        .flat_map(|links| links.into_values())
+    {
+        usage
+            .entity_types
+            .insert(link_value.type_name().to_string());
+    }
+    let typechecker = Typechecker::new(schema.validator_schema(), ValidationMode::default());
+    for template in &templates {
+        for (env, check) in typechecker.typecheck_by_request_env(template) {
+            let condition = match check {
+                PolicyCheck::Success(condition) => Some(condition),
+                PolicyCheck::Fail(_) => None,
+                PolicyCheck::Irrelevant(_) => continue,
+            };
+            let action = env.action_entity_uid().map(ToString::to_string);
+            for ty in [env.principal_entity_type(), env.resource_entity_type()]
+                .into_iter()
+                .flatten()
+            {
+                if let EntityType::Specified(name) = ty {
+                    usage.entity_types.insert(name.to_string());
+                }
+            }
+            if let Some(action) = &action {
+                usage.actions.insert(action.clone());
+            }
+            if let Some(condition) = condition {
+                record_expr_usage(&condition, action.as_deref(), &mut usage);
+            }
+        }
+    }
+    Ok(usage)
+}
+
+/// The core AST of the static policy or template `id`, given its JSON format
+fn core_template<E: std::fmt::Display>(
+    id: &str,
+    json: Result<serde_json::Value, E>,
+) -> Result<Template, Vec<String>> {
+    let error = |e: &dyn std::fmt::Display| vec![format!("error reading policy `{id}`: {e}")];
+    let json = json.map_err(|e| error(&e))?;
+    serde_json::from_value::<est::Policy>(json)
+        .map_err(|e| error(&e))?
+        .try_into_ast_template(Some(PolicyID::from_string(id)))
+        .map_err(|e| error(&e))
+}
+
+/// Record the entity types of the expressions in `condition`, a typechecked
+/// policy condition, and the context attributes it reads for `action`
+fn record_expr_usage(condition: &Expr<Option<Type>>, action: Option<&str>, usage: &mut Usage) {
+    let mut context_reads = 0;
+    let mut context_uses = 0;
+    for expr in condition.subexpressions() {
+        if let Some(ty) = expr.data() {
+            record_type_usage(ty, usage);
+        }
+        match expr.expr_kind() {
+            ExprKind::Var(Var::Context) => context_uses += 1,
+            ExprKind::GetAttr { expr, attr } | ExprKind::HasAttr { expr, attr }
+                if matches!(expr.expr_kind(), ExprKind::Var(Var::Context)) =>
+            {
+                context_reads += 1;
+                if let Some(action) = action {
+                    usage
+                        .context_attributes
+                        .entry(action.to_string())
+                        .or_default()
+                        .insert(attr.to_string());
+                }
+            }
+            _ => (),
+        }
+    }
+    // every read of an attribute is also a use of `context`, so any other use
+    // is of the context as a whole, e.g. `context == {}`
+    if context_uses > context_reads {
+        if let Some(action) = action {
+            usage.whole_context.insert(action.to_string());
+        }
+    }
+}
+
+fn record_type_usage(ty: &Type, usage: &mut Usage) {
+    match ty {
+        Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+            usage
+                .entity_types
+                .extend(lub.iter().map(ToString::to_string));
+        }
+        Type::Set {
+            element_type: Some(element_type),
+        } => record_type_usage(element_type, usage),
+        _ => (),
+    }
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_unused_schema_declarations() {
+        let schema = WasmSchema::parse_cedar(
+            r#"
+            entity User { manager: Manager };
+            entity Manager;
+            entity Photo;
+            entity Album;
+            entity Video;
+            action view appliesTo {
+                principal: [User],
+                resource: [Photo, Album],
+                context: { mfa: Bool, ip: String, reason: String }
+            };
+            action edit appliesTo {
+                principal: [User],
+                resource: [Photo],
+                context: { mfa: Bool, ticket: String }
+            };
+            action stream appliesTo { principal: [User], resource: [Video] };
+            action share appliesTo {
+                principal: [User],
+                resource: [Photo],
+                context: { note: String }
+            };
+            "#,
+        )
+        .unwrap();
+        let policies = WasmPolicySet::parse(
+            r#"
+            permit(principal, action == Action::"view", resource is Photo)
+            when { context.mfa && principal.manager == Manager::"m" };
+            permit(principal, action == Action::"edit", resource)
+            when { context has ticket };
+            permit(principal, action == Action::"share", resource)
+            when { context == { "note": "ok" } };
+            "#,
+        )
+        .unwrap();
+        let report = usage_report(&schema, &policies).unwrap();
+        assert_eq!(
+            report,
+            UsageReport {
+                unused_entity_types: vec!["Album".to_string(), "Video".to_string()],
+                unused_actions: vec![r#"Action::"stream""#.to_string()],
+                unused_context_attributes: BTreeMap::from([
+                    (r#"Action::"edit""#.to_string(), vec!["mfa".to_string()]),
+                    (
+                        r#"Action::"view""#.to_string(),
+                        vec!["ip".to_string(), "reason".to_string()]
+                    ),
+                ]),
+            }
+        );
+    }
+}
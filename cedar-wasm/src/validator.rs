@@ -1,7 +1,695 @@
-use cedar_policy::frontend::{utils::InterfaceResult, validate::json_validate};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use cedar_policy::{
+    frontend::{
+        utils::{InterfaceResult, PolicySpecification},
+        validate::{
+            clear_answer_cache, clear_schema_cache, json_validate_policy_json, json_validate_with,
+            json_validate_with_clock, json_validate_with_policy_set, json_validate_with_schema,
+        },
+    },
+    PolicySet, SlotId, SourceLocation, Template, TypeErrorKind, ValidationErrorKind,
+    ValidationMode, ValidationWarningKind, Validator,
+};
+use cedar_policy_core::{jsonvalue::JsonValueWithNoDuplicateKeys, parser::text_to_cst};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
+use crate::{
+    formats::{detect_format, InputFormat},
+    now_ms,
+    policy_set::policy_id,
+    EntityUidInput, WasmPolicySet, WasmSchema,
+};
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `validateToDiagnostics`
+pub enum DiagnosticsResult {
+    /// the diagnostics for the policies, which are empty if they validate
+    Success { diagnostics: Vec<Diagnostic> },
+    /// the schema could not be parsed
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A parse error, validation error, or validation warning, shaped like the
+/// `Diagnostic` of the Language Server Protocol
+pub struct Diagnostic {
+    /// the range of the policy text the diagnostic applies to
+    pub range: DiagnosticRange,
+    /// `1` for an error or `2` for a warning, as LSP numbers severities
+    pub severity: u8,
+    /// the kind of issue, e.g. `parseError` or `unrecognizedEntityType`
+    pub code: String,
+    pub message: String,
+    /// always `cedar`
+    pub source: String,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A range of policy text, as in LSP; the end is exclusive
+pub struct DiagnosticRange {
+    pub start: DiagnosticPosition,
+    pub end: DiagnosticPosition,
+}
+
+#[derive(Tsify, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A position in policy text, as in LSP: the line counts from 0, and the
+/// character is in UTF-16 code units from the start of the line
+pub struct DiagnosticPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// A policy set to validate in a `validateMany` call
+pub struct PolicySetToValidate {
+    /// the id the answer for the policy set is keyed by, e.g. a tenant's id
+    pub id: String,
+    /// the policies, in any of the forms `validate` accepts
+    pub policies: PolicySpecification,
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// The policy sets of a `validateMany` call
+pub struct PolicySetsToValidate(pub Vec<PolicySetToValidate>);
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `validateMany`
+pub enum ValidateManyResult {
+    /// the answer for each policy set, by id, as `validate` gives it
+    Success {
+        results: BTreeMap<String, InterfaceResult>,
+    },
+    /// the schema could not be parsed, or two policy sets have the same id
+    Error { errors: Vec<String> },
+}
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// Values proposed for the slots of a template, keyed by `?principal` or
+/// `?resource`, each as Cedar text or as its type and id
+pub struct SlotValues(pub BTreeMap<String, EntityUidInput>);
+
+#[derive(Tsify, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+/// struct that defines the result of `validateSlotValues`
+pub enum ValidateSlotValuesResult {
+    /// a policy linked with the values would pass validation of its scope
+    Success {},
+    /// the schema does not permit the values in the slots of the template
+    Invalid { errors: Vec<String> },
+    /// the template or the values do not parse, or the values do not fill the
+    /// slots of the template
+    Error { errors: Vec<String> },
+}
+
+/// Validate policies against a schema, given in the JSON format or as a string
+/// in the Cedar schema syntax. If the call sets `includeTimings`, the response
+/// includes `timings` measured with `performance.now()`; otherwise the answer
+/// is cached, as described for `clearAnswerCache`.
 #[wasm_bindgen(js_name = "validate")]
 pub fn wasm_validate(input: &str) -> InterfaceResult {
-    json_validate(input)
+    json_validate_with_clock(input, &now_ms)
+}
+
+/// Validate policies given in their JSON (EST) format, as produced by
+/// `policyTextToJson`, against a schema. The schema is in `schemaFormat`, or,
+/// if that is not given, in the format it looks like. Policies are assigned
+/// ids `policy0`, `policy1`, ... in array order.
+#[wasm_bindgen(js_name = "validatePolicyJson")]
+pub fn wasm_validate_policy_json(
+    schema_str: &str,
+    policies_str: &str,
+    schema_format: Option<InputFormat>,
+) -> InterfaceResult {
+    let schema = match schema_value(schema_str, schema_format) {
+        Ok(schema) => schema,
+        Err(e) => return InterfaceResult::fail_internally(e),
+    };
+    let policies = match serde_json::from_str::<JsonValueWithNoDuplicateKeys>(policies_str) {
+        Ok(policies) => serde_json::Value::from(policies),
+        Err(e) => return InterfaceResult::fail_internally(format!("error parsing policies: {e}")),
+    };
+    let call = serde_json::json!({ "schema": schema, "policies": policies });
+    json_validate_policy_json(&call.to_string())
+}
+
+/// Validate a policy set given in its JSON format, as produced by
+/// `WasmPolicySet.toJson`, against a schema: its static policies, its
+/// templates, with their slots typed by the scope constraints they appear in,
+/// and each policy linked from a template, reported under the id of the link.
+/// The schema is in `schemaFormat`, or, if that is not given, in the format it
+/// looks like.
+#[wasm_bindgen(js_name = "validatePolicySetJson")]
+pub fn wasm_validate_policy_set_json(
+    schema_str: &str,
+    policy_set_str: &str,
+    schema_format: Option<InputFormat>,
+) -> InterfaceResult {
+    let schema = match schema_value(schema_str, schema_format) {
+        Ok(schema) => schema,
+        Err(e) => return InterfaceResult::fail_internally(e),
+    };
+    match WasmPolicySet::parse_json(policy_set_str) {
+        Ok(policy_set) => json_validate_with_policy_set(
+            &serde_json::json!({ "schema": schema }).to_string(),
+            policy_set.policy_set(),
+        ),
+        Err(errors) => InterfaceResult::fail_bad_request(errors),
+    }
+}
+
+/// The schema `schema_str` as it is given in a validation call: its JSON, or,
+/// in the Cedar schema syntax, a string
+fn schema_value(
+    schema_str: &str,
+    schema_format: Option<InputFormat>,
+) -> Result<serde_json::Value, String> {
+    match detect_format(schema_str, "schema", schema_format)? {
+        InputFormat::Json => serde_json::from_str::<JsonValueWithNoDuplicateKeys>(schema_str)
+            .map(serde_json::Value::from)
+            .map_err(|e| format!("error parsing schema: {e}")),
+        // a schema given as a string is in the Cedar schema syntax
+        InputFormat::Cedar => Ok(serde_json::Value::String(schema_str.to_string())),
+    }
+}
+
+/// Like `validate`, but for a policy set parsed ahead of time. The call takes
+/// the `validationSettings` and `schema` only.
+#[wasm_bindgen(js_name = "validateWithPolicySet")]
+pub fn wasm_validate_with_policy_set(policy_set: &WasmPolicySet, input: &str) -> InterfaceResult {
+    json_validate_with_policy_set(input, policy_set.policy_set())
+}
+
+/// Like `validate`, but against a schema parsed ahead of time. The call takes
+/// the `validationSettings` and `policySet` only.
+#[wasm_bindgen(js_name = "validateWithSchema")]
+pub fn wasm_validate_with_schema(schema: &WasmSchema, input: &str) -> InterfaceResult {
+    json_validate_with_schema(input, schema.schema())
+}
+
+/// Like `validate`, but for a schema and policy set both parsed ahead of time.
+/// The call takes the `validationSettings` only.
+#[wasm_bindgen(js_name = "validateWith")]
+pub fn wasm_validate_with(
+    schema: &WasmSchema,
+    policy_set: &WasmPolicySet,
+    input: &str,
+) -> InterfaceResult {
+    json_validate_with(input, schema.schema(), policy_set.policy_set())
+}
+
+/// Validate each of many policy sets against one schema, e.g. the policy sets
+/// of the tenants of a multi-tenant service, parsing the schema only once. The
+/// schema is in `schemaFormat`, or, if that is not given, in the format it
+/// looks like. Each policy set is validated in strict mode.
+#[wasm_bindgen(js_name = "validateMany")]
+pub fn validate_many(
+    schema_str: &str,
+    policy_sets: PolicySetsToValidate,
+    schema_format: Option<InputFormat>,
+) -> ValidateManyResult {
+    let schema = match WasmSchema::parse_in_format(schema_str, schema_format) {
+        Ok(schema) => schema,
+        Err(errors) => return ValidateManyResult::Error { errors },
+    };
+    let mut results = BTreeMap::new();
+    for PolicySetToValidate { id, policies } in policy_sets.0 {
+        if results.contains_key(&id) {
+            return ValidateManyResult::Error {
+                errors: vec![format!("more than one policy set has the id `{id}`")],
+            };
+        }
+        let call = serde_json::json!({ "policySet": policies });
+        let result = json_validate_with_schema(&call.to_string(), schema.schema());
+        results.insert(id, result);
+    }
+    ValidateManyResult::Success { results }
+}
+
+/// Validate policies, given as Cedar text, against a schema, reporting each
+/// parse error, validation error, and validation warning as an LSP
+/// `Diagnostic` that an editor can show as is. Issues found in a policy
+/// without a more precise location cover the whole policy. The schema is in
+/// `schemaFormat`, or, if that is not given, in the format it looks like.
+#[wasm_bindgen(js_name = "validateToDiagnostics")]
+pub fn validate_to_diagnostics(
+    schema_str: &str,
+    policies_str: &str,
+    schema_format: Option<InputFormat>,
+) -> DiagnosticsResult {
+    match WasmSchema::parse_in_format(schema_str, schema_format) {
+        Ok(schema) => DiagnosticsResult::Success {
+            diagnostics: diagnostics(&schema, policies_str),
+        },
+        Err(errors) => DiagnosticsResult::Error { errors },
+    }
+}
+
+fn diagnostics(schema: &WasmSchema, text: &str) -> Vec<Diagnostic> {
+    let diagnostic =
+        |start: usize, end: usize, severity: u8, code: &str, message: String| Diagnostic {
+            range: DiagnosticRange {
+                start: position(text, start),
+                end: position(text, end),
+            },
+            severity,
+            code: code.to_string(),
+            message,
+            source: "cedar".to_string(),
+        };
+    let policies = match PolicySet::from_str(text) {
+        Ok(policies) => policies,
+        Err(errors) => {
+            return errors
+                .iter()
+                .map(|error| {
+                    let (start, end) =
+                        error.primary_source_span().map_or((0, text.len()), |span| {
+                            (span.offset(), span.offset() + span.len())
+                        });
+                    diagnostic(start, end, 1, "parseError", error.to_string())
+                })
+                .collect();
+        }
+    };
+    // the ranges of the policies, by id, for issues without a location
+    let policy_ranges: HashMap<String, (usize, usize)> = text_to_cst::parse_policies(text)
+        .ok()
+        .and_then(|cst| {
+            Some(
+                cst.with_generated_policyids()?
+                    .map(|(id, policy)| (id.to_string(), (policy.loc.start(), policy.loc.end())))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+    let range = |location: &SourceLocation| {
+        location
+            .range_start()
+            .zip(location.range_end())
+            .or_else(|| {
+                policy_ranges
+                    .get(&location.policy_id().to_string())
+                    .copied()
+            })
+            .unwrap_or((0, 0))
+    };
+    let result =
+        Validator::new(schema.schema().clone()).validate(&policies, ValidationMode::default());
+    let errors = result.validation_errors().map(|error| {
+        let (start, end) = range(error.location());
+        let (severity, code) = match error.error_kind() {
+            // a policy that can never apply is dead, not broken
+            ValidationErrorKind::TypeError(TypeErrorKind::ImpossiblePolicy) => {
+                (2, "impossiblePolicy")
+            }
+            kind => (1, error_code(kind)),
+        };
+        diagnostic(start, end, severity, code, error.error_kind().to_string())
+    });
+    let warnings = result.validation_warnings().map(|warning| {
+        let (start, end) = range(warning.location());
+        let code = match warning.warning_kind() {
+            ValidationWarningKind::MixedScriptString(_) => "mixedScriptString",
+            ValidationWarningKind::BidiCharsInString(_) => "bidiCharsInString",
+            ValidationWarningKind::BidiCharsInIdentifier(_) => "bidiCharsInIdentifier",
+            ValidationWarningKind::MixedScriptIdentifier(_) => "mixedScriptIdentifier",
+            ValidationWarningKind::ConfusableIdentifier(_) => "confusableIdentifier",
+            _ => "validationWarning",
+        };
+        diagnostic(start, end, 2, code, warning.warning_kind().to_string())
+    });
+    errors.chain(warnings).collect()
+}
+
+/// The diagnostic code of a validation error
+fn error_code(kind: &ValidationErrorKind) -> &'static str {
+    match kind {
+        ValidationErrorKind::UnrecognizedEntityType(_) => "unrecognizedEntityType",
+        ValidationErrorKind::UnrecognizedActionId(_) => "unrecognizedActionId",
+        ValidationErrorKind::InvalidActionApplication(_) => "invalidActionApplication",
+        ValidationErrorKind::TypeError(_) => "typeError",
+        ValidationErrorKind::UnspecifiedEntity(_) => "unspecifiedEntity",
+        _ => "validationError",
+    }
+}
+
+/// The LSP position of the byte `offset` into `text`
+fn position(text: &str, offset: usize) -> DiagnosticPosition {
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    DiagnosticPosition {
+        line: before.matches('\n').count(),
+        character: before
+            .get(line_start..)
+            .map_or(0, |line| line.encode_utf16().count()),
+    }
+}
+
+/// Check that linking the template written as Cedar text in `templateText`
+/// with `slotValues` gives a policy whose scope the schema permits: that the
+/// entity types of the values are declared, and that the actions of the
+/// template apply to them. Issues with the template itself are not reported.
+#[wasm_bindgen(js_name = "validateSlotValues")]
+pub fn validate_slot_values(
+    schema: &WasmSchema,
+    template_text: &str,
+    slot_values: SlotValues,
+) -> ValidateSlotValuesResult {
+    match slot_value_errors(schema, template_text, &slot_values) {
+        Ok(errors) if errors.is_empty() => ValidateSlotValuesResult::Success {},
+        Ok(errors) => ValidateSlotValuesResult::Invalid { errors },
+        Err(errors) => ValidateSlotValuesResult::Error { errors },
+    }
+}
+
+/// The validation errors of the template `template_text` linked with
+/// `slot_values`. Errors are for input that does not parse or link.
+fn slot_value_errors(
+    schema: &WasmSchema,
+    template_text: &str,
+    slot_values: &SlotValues,
+) -> Result<Vec<String>, Vec<String>> {
+    let template = Template::parse(Some("template".to_string()), template_text)
+        .map_err(|e| e.errors_as_strings())?;
+    let mut values = HashMap::new();
+    let mut errors = Vec::new();
+    for (slot, uid) in &slot_values.0 {
+        let slot_id = match slot.as_str() {
+            "?principal" => SlotId::principal(),
+            "?resource" => SlotId::resource(),
+            _ => {
+                errors.push(format!(
+                    "unknown slot `{slot}`; the slots are `?principal` and `?resource`"
+                ));
+                continue;
+            }
+        };
+        match uid.to_uid() {
+            Ok(uid) => {
+                values.insert(slot_id, uid);
+            }
+            Err(e) => errors.push(format!("error parsing value of `{slot}` {e}")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut policies = PolicySet::new();
+    policies
+        .add_template(template)
+        .map_err(|e| vec![e.to_string()])?;
+    let link = policy_id("link");
+    policies
+        .link(policy_id("template"), link.clone(), values)
+        .map_err(|e| vec![e.to_string()])?;
+    let result =
+        Validator::new(schema.schema().clone()).validate(&policies, ValidationMode::default());
+    Ok(result
+        .validation_errors()
+        .filter(|error| error.location().policy_id() == &link)
+        .map(|error| error.error_kind().to_string())
+        .collect())
+}
+
+/// Drop the schemas cached by `validate`, `validatePolicyJson`, and
+/// `validateWithPolicySet`. These calls cache the schemas they parse, keyed by
+/// a hash of the schema JSON, so that repeated calls with the same schema skip
+/// parsing it.
+#[wasm_bindgen(js_name = "clearSchemaCache")]
+pub fn wasm_clear_schema_cache() {
+    clear_schema_cache();
+}
+
+/// Drop the answers cached by `validate`. A `validate` call that does not ask
+/// for timings caches its answer, keyed by hashes of its schema and policy
+/// set and by its settings, so that repeating it, e.g. on every autosave of
+/// unchanged content, answers at once.
+#[wasm_bindgen(js_name = "clearAnswerCache")]
+pub fn wasm_clear_answer_cache() {
+    clear_answer_cache();
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic, clippy::indexing_slicing)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::policies_and_templates::{policy_text_to_json, PolicyToJsonResult};
+
+    const SCHEMA: &str = r#"{ "": {
+        "entityTypes": { "User": {}, "Photo": {} },
+        "actions": {
+            "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } }
+        }
+    }}"#;
+
+    fn policy_json(text: &str) -> serde_json::Value {
+        match policy_text_to_json(text) {
+            PolicyToJsonResult::Success { policy } => serde_json::to_value(policy).unwrap(),
+            PolicyToJsonResult::Error { errors } => panic!("failed to convert policy: {errors:?}"),
+        }
+    }
+
+    fn errors(result: InterfaceResult) -> Vec<serde_json::Value> {
+        match result {
+            InterfaceResult::Success { result, .. } => {
+                let answer: serde_json::Value = serde_json::from_str(&result).unwrap();
+                answer["errors"].as_array().unwrap().clone()
+            }
+            InterfaceResult::Failure { errors, .. } => panic!("validation failed: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn validates_policy_json() {
+        let policies = serde_json::json!([
+            policy_json(
+                r#"permit(principal == User::"alice", action == Action::"view", resource);"#
+            ),
+            policy_json(r#"permit(principal == Photo::"p", action == Action::"view", resource);"#),
+        ]);
+        let errors = errors(wasm_validate_policy_json(
+            SCHEMA,
+            &policies.to_string(),
+            None,
+        ));
+        assert!(!errors.is_empty());
+        assert!(
+            errors.iter().all(|error| error["policyId"] == "policy1"),
+            "unexpected errors: {errors:?}"
+        );
+    }
+
+    #[test]
+    fn validates_many_policy_sets() {
+        let policy_set = |id: &str, policies: &str| PolicySetToValidate {
+            id: id.to_string(),
+            policies: PolicySpecification::Concatenated(policies.to_string()),
+        };
+        let ok = r#"permit(principal == User::"alice", action == Action::"view", resource);"#;
+        let bad = r#"permit(principal == Photo::"p", action == Action::"view", resource);"#;
+        let results = match validate_many(
+            SCHEMA,
+            PolicySetsToValidate(vec![
+                policy_set("tenant-a", ok),
+                policy_set("tenant-b", bad),
+                policy_set("tenant-c", "permit("),
+            ]),
+            None,
+        ) {
+            ValidateManyResult::Success { results } => results,
+            ValidateManyResult::Error { errors } => panic!("failed to validate: {errors:?}"),
+        };
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            vec!["tenant-a", "tenant-b", "tenant-c"]
+        );
+        let mut results = results.into_values();
+        assert!(errors(results.next().unwrap()).is_empty());
+        assert!(!errors(results.next().unwrap()).is_empty());
+        assert!(matches!(
+            results.next(),
+            Some(InterfaceResult::Failure { .. })
+        ));
+
+        assert!(matches!(
+            validate_many(
+                SCHEMA,
+                PolicySetsToValidate(vec![policy_set("a", ok), policy_set("a", ok)]),
+                None,
+            ),
+            ValidateManyResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn validates_to_diagnostics() {
+        let policies = "permit(principal, action, resource == Album::\"a\");\npermit(principal, action, resource)\n  when { \"résumé\" && true };";
+        let diagnostics = match validate_to_diagnostics(SCHEMA, policies, None) {
+            DiagnosticsResult::Success { diagnostics } => diagnostics,
+            DiagnosticsResult::Error { errors } => panic!("failed to validate: {errors:?}"),
+        };
+        let position = |line, character| DiagnosticPosition { line, character };
+        let found = |code: &str| {
+            diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.code == code)
+                .unwrap_or_else(|| panic!("no `{code}` in {diagnostics:?}"))
+        };
+        // the scope has no location of its own, so the whole policy is given
+        let album = found("unrecognizedEntityType");
+        assert_eq!(
+            album.range,
+            DiagnosticRange {
+                start: position(0, 0),
+                end: position(0, 50),
+            }
+        );
+        assert_eq!((album.severity, album.source.as_str()), (1, "cedar"));
+        // `"résumé"` is 8 UTF-16 code units but 10 bytes
+        assert_eq!(
+            found("typeError").range,
+            DiagnosticRange {
+                start: position(2, 9),
+                end: position(2, 17),
+            }
+        );
+
+        let parse_error =
+            match validate_to_diagnostics(SCHEMA, "permit(principal,\n  act, resource);", None) {
+                DiagnosticsResult::Success { diagnostics } => diagnostics,
+                DiagnosticsResult::Error { errors } => panic!("failed to validate: {errors:?}"),
+            };
+        assert!(
+            parse_error
+                .iter()
+                .all(|diagnostic| diagnostic.code == "parseError"
+                    && diagnostic.range.start.line == 1),
+            "unexpected diagnostics: {parse_error:?}"
+        );
+        assert!(matches!(
+            validate_to_diagnostics("{", policies, None),
+            DiagnosticsResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn validates_templates_and_links() {
+        let policy_set = serde_json::json!({
+            "staticPolicies": {
+                "static": policy_json(r#"permit(principal == User::"alice", action == Action::"view", resource);"#),
+            },
+            "templates": {
+                "viewers": policy_json(r#"permit(principal == ?principal, action == Action::"view", resource);"#),
+                "owners": policy_json(r#"permit(principal, action == Action::"view", resource in ?resource) when { principal.owner };"#),
+            },
+            "templateLinks": [
+                { "templateId": "viewers", "newId": "alice", "values": { "?principal": { "type": "User", "id": "alice" } } },
+                { "templateId": "viewers", "newId": "beach", "values": { "?principal": { "type": "Photo", "id": "beach" } } },
+            ],
+        });
+        let errors = errors(wasm_validate_policy_set_json(
+            SCHEMA,
+            &policy_set.to_string(),
+            None,
+        ));
+        let mut ids: Vec<_> = errors
+            .iter()
+            .map(|error| error["policyId"].as_str().unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(
+            ids,
+            vec!["beach", "owners"],
+            "unexpected errors: {errors:?}"
+        );
+
+        let result = wasm_validate_policy_set_json(
+            SCHEMA,
+            r#"{ "templateLinks": [{ "templateId": "missing", "newId": "x", "values": {} }] }"#,
+            None,
+        );
+        assert!(matches!(
+            result,
+            InterfaceResult::Failure {
+                is_internal: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn validates_slot_values() {
+        let schema = WasmSchema::parse(SCHEMA).unwrap();
+        let template =
+            r#"permit(principal == ?principal, action == Action::"view", resource in ?resource);"#;
+        let check = |principal: &str, resource: &str| {
+            let values = SlotValues(BTreeMap::from([
+                (
+                    "?principal".to_string(),
+                    EntityUidInput::Text(principal.to_string()),
+                ),
+                (
+                    "?resource".to_string(),
+                    EntityUidInput::Text(resource.to_string()),
+                ),
+            ]));
+            validate_slot_values(&schema, template, values)
+        };
+        assert!(matches!(
+            check(r#"User::"alice""#, r#"Photo::"beach""#),
+            ValidateSlotValuesResult::Success {}
+        ));
+        match check(r#"Photo::"door""#, r#"Photo::"beach""#) {
+            ValidateSlotValuesResult::Invalid { errors } => assert_eq!(errors.len(), 1),
+            result => panic!("expected invalid slot values: {result:?}"),
+        }
+        match check(r#"Robot::"r2""#, r#"Photo::"beach""#) {
+            ValidateSlotValuesResult::Invalid { errors } => assert!(
+                errors
+                    .iter()
+                    .any(|error| error.contains("unrecognized entity type `Robot`")),
+                "unexpected errors: {errors:?}"
+            ),
+            result => panic!("expected invalid slot values: {result:?}"),
+        }
+        let values = SlotValues(BTreeMap::from([(
+            "?principal".to_string(),
+            EntityUidInput::Text(r#"User::"alice""#.to_string()),
+        )]));
+        assert!(matches!(
+            validate_slot_values(&schema, template, values),
+            ValidateSlotValuesResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_policy_json() {
+        let result = wasm_validate_policy_json(SCHEMA, r#"[{ "effect": "permit" }]"#, None);
+        assert!(matches!(
+            result,
+            InterfaceResult::Failure {
+                is_internal: false,
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,124 @@
+//! This module contains `warmUp`, which initializes the module's lazily-built
+//! state ahead of the first real call.
+use cedar_policy::frontend::{is_authorized::json_is_authorized, utils::InterfaceResult};
+#[cfg(feature = "full")]
+use cedar_policy::{PolicySet, Schema, ValidationMode, Validator};
+use wasm_bindgen::prelude::*;
+
+use crate::now_ms;
+
+/// Policies touching the parser, both extensions, and the common operators
+const POLICIES: &str = r#"
+    permit(principal == User::"alice", action == Action::"view", resource in Album::"trip")
+    when {
+        resource.owner == principal &&
+        resource has tags && resource.tags.contains("beach") &&
+        resource.name like "*.jpg" &&
+        context.ip.isInRange(ip("10.0.0.0/8")) &&
+        context.score.lessThan(decimal("0.75"))
+    };
+"#;
+
+/// A call that reaches every condition of `POLICIES`, so that evaluation
+/// exercises them all
+fn call() -> serde_json::Value {
+    serde_json::json!({
+        "principal": { "type": "User", "id": "alice" },
+        "action": { "type": "Action", "id": "view" },
+        "resource": { "type": "Photo", "id": "beach" },
+        "context": {
+            "ip": { "__extn": { "fn": "ip", "arg": "10.1.2.3" } },
+            "score": { "__extn": { "fn": "decimal", "arg": "0.5" } }
+        },
+        "slice": {
+            "policies": { "warmUp": POLICIES },
+            "entities": [
+                {
+                    "uid": { "type": "Photo", "id": "beach" },
+                    "attrs": {
+                        "owner": { "__entity": { "type": "User", "id": "alice" } },
+                        "tags": ["beach", "sunset"],
+                        "name": "beach.jpg"
+                    },
+                    "parents": [{ "type": "Album", "id": "trip" }]
+                }
+            ]
+        }
+    })
+}
+
+#[cfg(feature = "full")]
+const SCHEMA: &str = r#"{ "": {
+    "entityTypes": {
+        "User": {},
+        "Album": {},
+        "Photo": {
+            "memberOfTypes": ["Album"],
+            "shape": { "type": "Record", "attributes": {
+                "owner": { "type": "Entity", "name": "User" },
+                "tags": { "type": "Set", "element": { "type": "String" }, "required": false },
+                "name": { "type": "String" }
+            } }
+        }
+    },
+    "actions": {
+        "view": { "appliesTo": {
+            "principalTypes": ["User"],
+            "resourceTypes": ["Photo"],
+            "context": { "type": "Record", "attributes": {
+                "ip": { "type": "Extension", "name": "ipaddr" },
+                "score": { "type": "Extension", "name": "decimal" }
+            } }
+        } }
+    }
+}}"#;
+
+/// Authorize (and, with the `full` feature, validate) a built-in call, failing
+/// if it is not answered as expected
+fn warm_up() -> Result<(), String> {
+    match json_is_authorized(&call().to_string()) {
+        InterfaceResult::Success { result, .. } if result.contains(r#""decision":"Allow""#) => {}
+        result => {
+            return Err(format!(
+                "unexpected warm-up authorization result: {result:?}"
+            ))
+        }
+    }
+    #[cfg(feature = "full")]
+    {
+        let schema: Schema = SCHEMA
+            .parse()
+            .map_err(|e| format!("error parsing warm-up schema: {e}"))?;
+        let policies: PolicySet = POLICIES
+            .parse()
+            .map_err(|e| format!("error parsing warm-up policies: {e}"))?;
+        let result = Validator::new(schema).validate(&policies, ValidationMode::default());
+        if !result.validation_passed() {
+            return Err("warm-up policies failed validation".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Initialize the parser, extension functions, and other state that is
+/// otherwise built lazily on first use, so that latency-sensitive callers can
+/// pay for it at startup rather than on their first request. Returns the
+/// milliseconds spent; calls after the first are cheap.
+#[wasm_bindgen(js_name = "warmUp")]
+pub fn wasm_warm_up() -> Result<f64, JsError> {
+    let start = now_ms();
+    warm_up().map_err(|e| JsError::new(&e))?;
+    Ok(now_ms() - start)
+}
+
+// PANIC SAFETY unit tests
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn warm_up_call_is_answered() {
+        warm_up().unwrap();
+    }
+}